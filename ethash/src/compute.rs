@@ -25,9 +25,13 @@ use std::mem;
 use std::ptr;
 use sha3;
 use std::slice;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
 use std::io::{self, Read, Write};
 use std::fs::{self, File};
+use std::cmp::{min, max};
+use std::sync::Arc;
+use std::thread;
 
 use parking_lot::Mutex;
 
@@ -46,6 +50,8 @@ const NODE_BYTES: usize = 64;
 const MIX_WORDS: usize = ETHASH_MIX_BYTES / 4;
 const MIX_NODES: usize = MIX_WORDS / NODE_WORDS;
 const FNV_PRIME: u32 = 0x01000193;
+/// Cache files are looked up for epochs below this one when pruning.
+const MAX_CACHE_FILE_EPOCH: u64 = 4096;
 
 /// Computation result
 pub struct ProofOfWork {
@@ -105,17 +111,15 @@ impl Light {
 		light_compute(self, header_hash, nonce)
 	}
 
-	pub fn file_path(seed_hash: H256) -> PathBuf {
-		let mut home = ::std::env::home_dir().unwrap();
-		home.push(".ethash");
-		home.push("light");
-		home.push(to_hex(&seed_hash));
-		home
+	/// Path of the cache file for the given seed hash within `dir`.
+	pub fn file_path(dir: &Path, seed_hash: H256) -> PathBuf {
+		dir.join(to_hex(&seed_hash))
 	}
 
-	pub fn from_file(block_number: u64) -> io::Result<Light> {
+	/// Load the cache for the epoch of `block_number` from `dir`.
+	pub fn from_file(dir: &Path, block_number: u64) -> io::Result<Light> {
 		let seed_compute = SeedHashCompute::new();
-		let path = Light::file_path(seed_compute.get_seedhash(block_number));
+		let path = Light::file_path(dir, seed_compute.get_seedhash(block_number));
 		let mut file = File::open(path)?;
 
 		let cache_size = get_cache_size(block_number);
@@ -134,28 +138,90 @@ impl Light {
 		})
 	}
 
-	pub fn to_file(&self) -> io::Result<PathBuf> {
-		let seed_compute = self.seed_compute.lock();
-		let path = Light::file_path(seed_compute.get_seedhash(self.block_number));
+	/// Write the cache to `dir`, returning the path of the written file.
+	pub fn to_file(&self, dir: &Path) -> io::Result<PathBuf> {
+		let path = Light::file_path(dir, self.seed_compute.lock().get_seedhash(self.block_number));
 
-		if self.block_number >= ETHASH_EPOCH_LENGTH * 2 {
-			let deprecated = Light::file_path(
-				seed_compute.get_seedhash(self.block_number - ETHASH_EPOCH_LENGTH * 2));
-
-			if deprecated.exists() {
-				debug!(target: "ethash", "removing: {:?}", &deprecated);
-				fs::remove_file(deprecated)?;
-			}
-		}
-
-		fs::create_dir_all(path.parent().unwrap())?;
+		fs::create_dir_all(dir)?;
 		let mut file = File::create(&path)?;
 
 		let cache_size = self.cache.len() * NODE_BYTES;
 		let buf = unsafe { slice::from_raw_parts(self.cache.as_ptr() as *const u8, cache_size) };
-		file.write(buf)?;
+		file.write_all(buf)?;
 		Ok(path)
 	}
+
+	/// Remove cache files in `dir` which do not belong to the `epochs` most recent epochs
+	/// found in `dir`, so that verifying old blocks doesn't remove caches of newer epochs.
+	/// Only files named after the seed hash of an epoch and of that epoch's cache size are touched.
+	pub fn prune_files(dir: &Path, epochs: usize) -> io::Result<()> {
+		let mut caches = HashMap::new();
+		for entry in fs::read_dir(dir)? {
+			let entry = entry?;
+			let name = match entry.file_name().into_string() {
+				Ok(name) => name,
+				Err(_) => continue,
+			};
+			// only touch files which look like caches.
+			if name.len() == 64 && name.bytes().all(|b| CHARS.contains(&b)) {
+				caches.insert(name, entry.path());
+			}
+		}
+
+		// find the epoch of each cache, files of unknown epochs are left alone.
+		let mut cache_epochs = Vec::new();
+		let mut seedhash = [0u8; 32];
+		for epoch in 0..MAX_CACHE_FILE_EPOCH {
+			if caches.is_empty() {
+				break;
+			}
+			if let Some(path) = caches.remove(&to_hex(&seedhash)) {
+				let cache_size = get_cache_size(epoch * ETHASH_EPOCH_LENGTH) as u64;
+				if fs::metadata(&path).map(|m| m.len() == cache_size).unwrap_or(false) {
+					cache_epochs.push((epoch, path));
+				}
+			}
+			seedhash = SeedHashCompute::resume_compute_seedhash(seedhash, epoch, epoch + 1);
+		}
+
+		let newest_epoch = cache_epochs.iter().map(|&(epoch, _)| epoch).max().unwrap_or(0);
+		let oldest_epoch = (newest_epoch + 1).saturating_sub(max(epochs, 1) as u64);
+		let stale = cache_epochs.into_iter()
+			.filter(|&(epoch, _)| epoch < oldest_epoch)
+			.map(|(_, path)| path);
+		for path in stale {
+			debug!(target: "ethash", "removing: {:?}", path);
+			fs::remove_file(path)?;
+		}
+		Ok(())
+	}
+}
+
+/// Full dataset (DAG) structure, used for mining.
+pub struct Full {
+	block_number: u64,
+	dataset: Vec<Node>,
+}
+
+impl Full {
+	/// Generate the full dataset for a given block number, splitting the work across `threads`.
+	pub fn new(block_number: u64, threads: usize) -> Full {
+		full_new(Arc::new(light_new(block_number)), threads)
+	}
+
+	/// Block number the dataset was generated for.
+	pub fn block_number(&self) -> u64 {
+		self.block_number
+	}
+
+	/// Calculate the boundary data using the full dataset
+	/// `header_hash` - The header hash to pack into the mix
+	/// `nonce` - The nonce to pack into the mix
+	pub fn compute(&self, header_hash: &H256, nonce: u64) -> ProofOfWork {
+		let full_size = self.dataset.len() * NODE_BYTES;
+		let dataset: &[Node] = &self.dataset;
+		hash_compute(full_size, header_hash, nonce, |index| unsafe { dataset.get_unchecked(index as usize).clone() })
+	}
 }
 
 pub struct SeedHashCompute {
@@ -263,10 +329,11 @@ pub fn quick_get_difficulty(header_hash: &H256, nonce: u64, mix_hash: &H256) ->
 /// `nonce` - The nonce to pack into the mix
 pub fn light_compute(light: &Light, header_hash: &H256, nonce: u64) -> ProofOfWork {
 	let full_size = get_data_size(light.block_number);
-	hash_compute(light, full_size, header_hash, nonce)
+	let cache: &[Node] = &light.cache;  // deref once for better performance
+	hash_compute(full_size, header_hash, nonce, |index| calculate_dag_item(index, cache))
 }
 
-fn hash_compute(light: &Light, full_size: usize, header_hash: &H256, nonce: u64) -> ProofOfWork {
+fn hash_compute<F>(full_size: usize, header_hash: &H256, nonce: u64, lookup: F) -> ProofOfWork where F: Fn(u32) -> Node {
 	if full_size % MIX_WORDS != 0 {
 		panic!("Unaligned full size");
 	}
@@ -285,12 +352,11 @@ fn hash_compute(light: &Light, full_size: usize, header_hash: &H256, nonce: u64)
 
 		let page_size = 4 * MIX_WORDS;
 		let num_full_pages = (full_size / page_size) as u32;
-		let cache: &[Node] = &light.cache;  // deref once for better performance
 
 		for i in 0..(ETHASH_ACCESSES as u32) {
 			let index = fnv_hash(f_mix.get_unchecked(0).as_words().get_unchecked(0) ^ i, *mix.get_unchecked(0).as_words().get_unchecked((i as usize) % MIX_WORDS)) % num_full_pages;
 			for n in 0..MIX_NODES {
-				let tmp_node = calculate_dag_item(index * MIX_NODES as u32 + n as u32, cache);
+				let tmp_node = lookup(index * MIX_NODES as u32 + n as u32);
 				for w in 0..NODE_WORDS {
 					*mix.get_unchecked_mut(n).as_words_mut().get_unchecked_mut(w) = fnv_hash(*mix.get_unchecked(n).as_words().get_unchecked(w), *tmp_node.as_words().get_unchecked(w));
 				}
@@ -379,6 +445,48 @@ fn light_new(block_number: u64) -> Light {
 	}
 }
 
+/// Generate the full dataset from a light cache, splitting the work across `threads`.
+pub fn full_new(light: Arc<Light>, threads: usize) -> Full {
+	let full_size = get_data_size(light.block_number);
+	if full_size % NODE_BYTES != 0 {
+		panic!("Unaligned full size");
+	}
+	let num_nodes = full_size / NODE_BYTES;
+	let threads = max(threads, 1);
+	let chunk = (num_nodes + threads - 1) / threads;
+
+	let mut dataset: Vec<Node> = Vec::with_capacity(num_nodes);
+	// The nodes are written through the raw pointer into the spare capacity; the length is
+	// only set once every thread has finished successfully.
+	let base = dataset.as_mut_ptr() as usize;
+
+	let handles: Vec<_> = (0..threads).map(|t| {
+		let light = light.clone();
+		thread::spawn(move || {
+			let nodes = base as *mut Node;
+			let cache: &[Node] = &light.cache;
+			for i in (t * chunk)..min((t + 1) * chunk, num_nodes) {
+				// SAFETY: `i < num_nodes` is within the capacity and the index ranges of the threads are
+				// disjoint. The buffer outlives the threads, since `dataset` is neither moved nor
+				// reallocated before the joins.
+				unsafe { ptr::write(nodes.offset(i as isize), calculate_dag_item(i as u32, cache)) };
+			}
+		})
+	}).collect();
+	// join all the threads before panicking, so that none of them outlives `dataset`.
+	let results: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+	if results.iter().any(|result| result.is_err()) {
+		panic!("DAG generation thread panicked");
+	}
+	// SAFETY: all threads were joined successfully, so every node in `0..num_nodes` was written.
+	unsafe { dataset.set_len(num_nodes) };
+
+	Full {
+		block_number: light.block_number,
+		dataset: dataset,
+	}
+}
+
 static CHARS: &'static [u8] = b"0123456789abcdef";
 fn to_hex(bytes: &[u8]) -> String {
 	let mut v = Vec::with_capacity(bytes.len() * 2);
@@ -472,15 +580,35 @@ fn test_seed_compute_after_newer() {
 
 #[test]
 fn test_drop_old_data() {
-	let first = Light::new(0).to_file().unwrap();
-
-	let second = Light::new(ETHASH_EPOCH_LENGTH).to_file().unwrap();
+	let dir = ::std::env::temp_dir().join("ethash-test-drop-old-data");
+	let _ = fs::remove_dir_all(&dir);
+	let first = Light::new(0).to_file(&dir).unwrap();
+	let second = Light::new(ETHASH_EPOCH_LENGTH).to_file(&dir).unwrap();
+	Light::prune_files(&dir, 2).unwrap();
 	assert!(fs::metadata(&first).is_ok());
+	assert!(fs::metadata(&second).is_ok());
+
+	let third = Light::new(ETHASH_EPOCH_LENGTH * 2).to_file(&dir).unwrap();
+	Light::prune_files(&dir, 2).unwrap();
+	assert!(fs::metadata(&first).is_err());
+	assert!(fs::metadata(&second).is_ok());
+	assert!(fs::metadata(&third).is_ok());
 
-	let _ = Light::new(ETHASH_EPOCH_LENGTH * 2).to_file();
+	// regenerating an old cache keeps the newer ones.
+	let first = Light::new(0).to_file(&dir).unwrap();
+	Light::prune_files(&dir, 2).unwrap();
 	assert!(fs::metadata(&first).is_err());
 	assert!(fs::metadata(&second).is_ok());
+	assert!(fs::metadata(&third).is_ok());
 
-	let _ = Light::new(ETHASH_EPOCH_LENGTH * 3).to_file();
+	Light::prune_files(&dir, 1).unwrap();
 	assert!(fs::metadata(&second).is_err());
+	assert!(fs::metadata(&third).is_ok());
+
+	// files which merely look like caches are kept.
+	let other = dir.join(to_hex(&[0xffu8; 32]));
+	File::create(&other).unwrap();
+	Light::prune_files(&dir, 1).unwrap();
+	assert!(fs::metadata(&other).is_ok());
+	fs::remove_dir_all(&dir).unwrap();
 }
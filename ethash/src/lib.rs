@@ -25,88 +25,194 @@ extern crate log;
 mod compute;
 
 use std::mem;
+use std::thread;
+use std::path::PathBuf;
 use compute::Light;
-pub use compute::{ETHASH_EPOCH_LENGTH, H256, ProofOfWork, SeedHashCompute, quick_get_difficulty, slow_get_seedhash};
+pub use compute::{ETHASH_EPOCH_LENGTH, H256, Full, ProofOfWork, SeedHashCompute, quick_get_difficulty, slow_get_seedhash};
 
 use std::sync::Arc;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+
+/// Number of blocks before an epoch boundary at which the cache for the next epoch is generated.
+const PREGENERATE_BLOCKS: u64 = 1000;
+
+/// Light cache storage options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheOptions {
+	/// Directory where light caches are stored.
+	pub path: PathBuf,
+	/// Number of most recent epochs for which caches are kept on disk.
+	pub epochs: usize,
+}
+
+impl Default for CacheOptions {
+	fn default() -> Self {
+		let mut path = ::std::env::home_dir().unwrap_or_else(::std::env::temp_dir);
+		path.push(".ethash");
+		path.push("light");
+		CacheOptions {
+			path: path,
+			epochs: 3,
+		}
+	}
+}
 
 struct LightCache {
 	recent_epoch: Option<u64>,
 	recent: Option<Arc<Light>>,
 	prev_epoch: Option<u64>,
 	prev: Option<Arc<Light>>,
+	/// Cache generated ahead of the next epoch boundary.
+	next_epoch: Option<u64>,
+	next: Option<Arc<Light>>,
+	generating: bool,
 }
 
 /// Light/Full cache manager.
 pub struct EthashManager {
-	cache: Mutex<LightCache>,
+	options: RwLock<CacheOptions>,
+	cache: Arc<Mutex<LightCache>>,
+}
+
+/// Load the cache for `epoch` from disk or generate and store it, removing stale files.
+fn load_or_generate(options: &CacheOptions, epoch: u64) -> Light {
+	let block_number = epoch * ETHASH_EPOCH_LENGTH;
+	match Light::from_file(&options.path, block_number) {
+		Ok(light) => light,
+		Err(e) => {
+			debug!("Light cache file not found for {}:{}", block_number, e);
+			let light = Light::new(block_number);
+			if let Err(e) = light.to_file(&options.path) {
+				warn!("Light cache file write error: {}", e);
+			}
+			if let Err(e) = Light::prune_files(&options.path, options.epochs) {
+				warn!("Light cache cleanup error: {}", e);
+			}
+			light
+		}
+	}
 }
 
 impl EthashManager {
 	/// Create a new new instance of ethash manager
 	pub fn new() -> EthashManager {
+		EthashManager::with_options(CacheOptions::default())
+	}
+
+	/// Create a new instance of ethash manager storing caches according to `options`.
+	pub fn with_options(options: CacheOptions) -> EthashManager {
 		EthashManager {
-			cache: Mutex::new(LightCache {
+			options: RwLock::new(options),
+			cache: Arc::new(Mutex::new(LightCache {
 				recent_epoch: None,
 				recent: None,
 				prev_epoch: None,
 				prev: None,
-			}),
+				next_epoch: None,
+				next: None,
+				generating: false,
+			})),
 		}
 	}
 
+	/// Change where and how many caches are stored. Affects caches loaded from now on.
+	pub fn set_options(&self, options: CacheOptions) {
+		*self.options.write() = options;
+	}
+
 	/// Calculate the light client data
 	/// `block_number` - Block number to check
 	/// `light` - The light client handler
 	/// `header_hash` - The header hash to pack into the mix
 	/// `nonce` - The nonce to pack into the mix
 	pub fn compute_light(&self, block_number: u64, header_hash: &H256, nonce: u64) -> ProofOfWork {
+		let light = self.light(block_number);
+		self.pregenerate(block_number);
+		light.compute(header_hash, nonce)
+	}
+
+	/// Generate the full dataset for the epoch of `block_number` from the managed light cache,
+	/// splitting the work across `threads`.
+	pub fn full(&self, block_number: u64, threads: usize) -> Full {
+		compute::full_new(self.light(block_number), threads)
+	}
+
+	/// Light cache for the epoch of `block_number`.
+	fn light(&self, block_number: u64) -> Arc<Light> {
 		let epoch = block_number / ETHASH_EPOCH_LENGTH;
-		let light = {
-			let mut lights = self.cache.lock();
-			let light = match lights.recent_epoch.clone() {
-				Some(ref e) if *e == epoch => lights.recent.clone(),
-				_ => match lights.prev_epoch.clone() {
-					Some(e) if e == epoch => {
-						// don't swap if recent is newer.
-						if lights.recent_epoch > lights.prev_epoch {
-							None
-						} else {
-							// swap
-							let t = lights.prev_epoch;
-							lights.prev_epoch = lights.recent_epoch;
-							lights.recent_epoch = t;
-							let t = lights.prev.clone();
-							lights.prev = lights.recent.clone();
-							lights.recent = t;
-							lights.recent.clone()
-						}
+		let mut lights = self.cache.lock();
+		let light = match lights.recent_epoch.clone() {
+			Some(ref e) if *e == epoch => lights.recent.clone(),
+			_ => match lights.prev_epoch.clone() {
+				Some(e) if e == epoch => {
+					// don't swap if recent is newer.
+					if lights.recent_epoch > lights.prev_epoch {
+						None
+					} else {
+						// swap
+						let t = lights.prev_epoch;
+						lights.prev_epoch = lights.recent_epoch;
+						lights.recent_epoch = t;
+						let t = lights.prev.clone();
+						lights.prev = lights.recent.clone();
+						lights.recent = t;
+						lights.recent.clone()
 					}
-					_ => None,
-				},
-			};
-			match light {
-				None => {
-					let light = match Light::from_file(block_number) {
-						Ok(light) => Arc::new(light),
-						Err(e) => {
-							debug!("Light cache file not found for {}:{}", block_number, e);
-							let light = Light::new(block_number);
-							if let Err(e) = light.to_file() {
-								warn!("Light cache file write error: {}", e);
-							}
-							Arc::new(light)
-						}
-					};
-					lights.prev_epoch = mem::replace(&mut lights.recent_epoch, Some(epoch));
-					lights.prev = mem::replace(&mut lights.recent, Some(light.clone()));
-					light
 				}
-				Some(light) => light,
-			}
+				_ => None,
+			},
 		};
-		light.compute(header_hash, nonce)
+		match light {
+			None => {
+				let light = match lights.next_epoch.clone() {
+					Some(e) if e == epoch => {
+						lights.next_epoch = None;
+						lights.next.take().expect("next is set together with next_epoch; qed")
+					},
+					_ => Arc::new(load_or_generate(&self.options.read(), epoch)),
+				};
+				lights.prev_epoch = mem::replace(&mut lights.recent_epoch, Some(epoch));
+				lights.prev = mem::replace(&mut lights.recent, Some(light.clone()));
+				light
+			}
+			Some(light) => light,
+		}
+	}
+
+	/// Generate the cache for the next epoch in the background when the boundary is near.
+	fn pregenerate(&self, block_number: u64) {
+		let next_epoch = block_number / ETHASH_EPOCH_LENGTH + 1;
+		if next_epoch * ETHASH_EPOCH_LENGTH - block_number > PREGENERATE_BLOCKS {
+			return;
+		}
+
+		{
+			let mut lights = self.cache.lock();
+			let known = lights.generating
+				|| lights.next_epoch == Some(next_epoch)
+				|| lights.recent_epoch == Some(next_epoch)
+				|| lights.prev_epoch == Some(next_epoch);
+			if known {
+				return;
+			}
+			lights.generating = true;
+		}
+
+		let cache = self.cache.clone();
+		let options = self.options.read().clone();
+		let spawned = thread::Builder::new().name("ethash cache".into()).spawn(move || {
+			debug!("Pre-generating light cache for epoch {}", next_epoch);
+			let light = Arc::new(load_or_generate(&options, next_epoch));
+			let mut lights = cache.lock();
+			lights.next_epoch = Some(next_epoch);
+			lights.next = Some(light);
+			lights.generating = false;
+		});
+
+		if let Err(e) = spawned {
+			warn!("Error spawning light cache generation thread: {}", e);
+			self.cache.lock().generating = false;
+		}
 	}
 }
 
@@ -125,3 +231,23 @@ fn test_lru() {
 	assert_eq!(ethash.cache.lock().recent_epoch.unwrap(), 2);
 	assert_eq!(ethash.cache.lock().prev_epoch.unwrap(), 0);
 }
+
+#[test]
+fn test_pregenerate_next_epoch() {
+	let dir = ::std::env::temp_dir().join("ethash-test-pregenerate-next-epoch");
+	let _ = ::std::fs::remove_dir_all(&dir);
+	let ethash = EthashManager::with_options(CacheOptions { path: dir.clone(), epochs: 3 });
+	let hash = [0u8; 32];
+	ethash.compute_light(ETHASH_EPOCH_LENGTH - 1, &hash, 1);
+	while ethash.cache.lock().next_epoch.is_none() {
+		thread::sleep(::std::time::Duration::from_millis(50));
+	}
+	assert_eq!(ethash.cache.lock().next_epoch, Some(1));
+
+	// crossing the boundary uses the pre-generated cache.
+	ethash.compute_light(ETHASH_EPOCH_LENGTH, &hash, 1);
+	assert_eq!(ethash.cache.lock().recent_epoch, Some(1));
+	assert_eq!(ethash.cache.lock().prev_epoch, Some(0));
+	assert!(ethash.cache.lock().next.is_none());
+	::std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -16,7 +16,7 @@
 
 //! I/O and event context generalizations.
 
-use network::{NetworkContext, PeerId, NodeId};
+use network::{NetworkContext, PeerId, NodeId, ReputationChange};
 
 use super::{Announcement, LightProtocol, ReqId};
 use super::error::Error;
@@ -39,6 +39,9 @@ pub trait IoContext {
 	/// Disable a peer -- this is a disconnect + a time-out.
 	fn disable_peer(&self, peer: PeerId);
 
+	/// Adjust a peer's reputation.
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange);
+
 	/// Get a peer's protocol version.
 	fn protocol_version(&self, peer: PeerId) -> Option<u8>;
 
@@ -70,6 +73,10 @@ impl<'a> IoContext for NetworkContext<'a> {
 		NetworkContext::disable_peer(self, peer);
 	}
 
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		NetworkContext::note_reputation(self, peer, change);
+	}
+
 	fn protocol_version(&self, peer: PeerId) -> Option<u8> {
 		self.protocol_version(self.subprotocol_name(), peer)
 	}
@@ -101,6 +108,9 @@ pub trait BasicContext {
 
 	/// Disable a peer.
 	fn disable_peer(&self, peer: PeerId);
+
+	/// Adjust a peer's reputation.
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange);
 }
 
 /// Context for a protocol event which has a peer ID attached.
@@ -141,6 +151,10 @@ impl<'a> BasicContext for TickCtx<'a> {
 	fn disable_peer(&self, peer: PeerId) {
 		self.io.disable_peer(peer);
 	}
+
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		self.io.note_reputation(peer, change);
+	}
 }
 
 /// Concrete implementation of `EventContext` over the light protocol struct and
@@ -174,6 +188,10 @@ impl<'a> BasicContext for Ctx<'a> {
 	fn disable_peer(&self, peer: PeerId) {
 		self.io.disable_peer(peer);
 	}
+
+	fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		self.io.note_reputation(peer, change);
+	}
 }
 
 impl<'a> EventContext for Ctx<'a> {
@@ -24,8 +24,8 @@ use std::fmt;
 
 /// Levels of punishment.
 ///
-/// Currently just encompasses two different kinds of disconnect and
-/// no punishment, but this is where reputation systems might come into play.
+/// Encompasses two different kinds of disconnect and no punishment.
+/// Disabling a peer also lowers its reputation.
 // In ascending order
 #[derive(Debug, PartialEq, Eq)]
 pub enum Punishment {
@@ -21,7 +21,7 @@
 use ethcore::transaction::UnverifiedTransaction;
 
 use io::TimerToken;
-use network::{NetworkProtocolHandler, NetworkContext, PeerId};
+use network::{NetworkProtocolHandler, NetworkContext, PeerId, ReputationChange};
 use rlp::{RlpStream, UntrustedRlp};
 use util::hash::H256;
 use util::{DBValue, Mutex, RwLock, U256};
@@ -407,7 +407,8 @@ impl LightProtocol {
 	//   - check whether peer exists
 	//   - check whether request was made
 	//   - check whether request kinds match
-	fn pre_verify_response(&self, peer: &PeerId, raw: &UntrustedRlp) -> Result<IdGuard, Error> {
+	//   - note the response latency
+	fn pre_verify_response(&self, peer: &PeerId, io: &IoContext, raw: &UntrustedRlp) -> Result<IdGuard, Error> {
 		let req_id = ReqId(raw.val_at(0)?);
 		let cur_credits: U256 = raw.val_at(1)?;
 
@@ -417,7 +418,12 @@ impl LightProtocol {
 		let res = match peers.get(peer) {
 			Some(peer_info) => {
 				let mut peer_info = peer_info.lock();
-				let req_info = peer_info.pending_requests.remove(&req_id, SteadyTime::now());
+				let now = SteadyTime::now();
+				if let Some(sent_at) = peer_info.pending_requests.sent_at(&req_id) {
+					let latency_ms = (now - sent_at).num_milliseconds();
+					io.note_reputation(*peer, ReputationChange::Latency(::std::cmp::max(latency_ms, 0) as u64));
+				}
+				let req_info = peer_info.pending_requests.remove(&req_id, now);
 				let cumulative_cost = peer_info.pending_requests.cumulative_cost();
 				let flow_info = peer_info.remote_flow.as_mut();
 
@@ -488,6 +494,7 @@ impl LightProtocol {
 			for slowpoke in slowpokes {
 				debug!(target: "pip", "Peer {} handshake timed out", slowpoke);
 				pending.remove(&slowpoke);
+				io.note_reputation(slowpoke, ReputationChange::Timeout);
 				io.disconnect_peer(slowpoke);
 			}
 		}
@@ -497,6 +504,7 @@ impl LightProtocol {
 			for (peer_id, peer) in self.peers.read().iter() {
 				if peer.lock().pending_requests.check_timeout(now) {
 					debug!(target: "pip", "Peer {} request timeout", peer_id);
+					io.note_reputation(*peer_id, ReputationChange::Timeout);
 					io.disconnect_peer(*peer_id);
 				}
 			}
@@ -783,7 +791,7 @@ impl LightProtocol {
 	// handle a packet with responses.
 	fn response(&self, peer: &PeerId, io: &IoContext, raw: UntrustedRlp) -> Result<(), Error> {
 		let (req_id, responses) = {
-			let id_guard = self.pre_verify_response(peer, &raw)?;
+			let id_guard = self.pre_verify_response(peer, io, &raw)?;
 			let responses: Vec<Response> = raw.list_at(2)?;
 			(id_guard.defuse(), responses)
 		};
@@ -832,6 +840,7 @@ fn punish(peer: PeerId, io: &IoContext, e: Error) {
 		}
 		Punishment::Disable => {
 			debug!(target: "pip", "Disabling peer {}: {}", peer, e);
+			io.note_reputation(peer, ReputationChange::BadBlock);
 			io.disable_peer(peer)
 		}
 	}
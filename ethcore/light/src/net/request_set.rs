@@ -33,7 +33,7 @@ use time::{Duration, SteadyTime};
 
 // Request set entry: requests + cost.
 #[derive(Debug)]
-struct Entry(Requests, U256);
+struct Entry(Requests, U256, SteadyTime);
 
 /// Request set.
 #[derive(Debug)]
@@ -64,7 +64,7 @@ impl RequestSet {
 		self.cumulative_cost = self.cumulative_cost + cost;
 
 		self.ids.insert(req_id, counter);
-		self.reqs.insert(counter, Entry(req, cost));
+		self.reqs.insert(counter, Entry(req, cost, now));
 
 		if self.reqs.keys().next().map_or(true, |x| *x == counter) {
 			self.base = Some(now);
@@ -80,7 +80,7 @@ impl RequestSet {
 			None => return None,
 		};
 
-		let Entry(req, cost) = self.reqs.remove(&id).expect("entry in `ids` implies entry in `reqs`; qed");
+		let Entry(req, cost, _) = self.reqs.remove(&id).expect("entry in `ids` implies entry in `reqs`; qed");
 
 		match self.reqs.keys().next() {
 			Some(k) if *k > id => self.base = Some(now),
@@ -92,6 +92,11 @@ impl RequestSet {
 		Some(req)
 	}
 
+	/// Time at which the given set of requests was sent.
+	pub fn sent_at(&self, req_id: &ReqId) -> Option<SteadyTime> {
+		self.ids.get(req_id).and_then(|id| self.reqs.get(id)).map(|entry| entry.2)
+	}
+
 	/// Check for timeout against the given time. Returns true if
 	/// has timed out, false otherwise.
 	pub fn check_timeout(&self, now: SteadyTime) -> bool {
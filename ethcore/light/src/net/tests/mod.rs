@@ -22,7 +22,7 @@ use ethcore::client::{EachBlockWith, TestBlockChainClient};
 use ethcore::ids::BlockId;
 use ethcore::transaction::{Action, PendingTransaction};
 use ethcore::encoded;
-use network::{PeerId, NodeId};
+use network::{PeerId, NodeId, ReputationChange};
 
 use net::request_credits::FlowParams;
 use net::context::IoContext;
@@ -82,6 +82,8 @@ impl IoContext for Expect {
 		assert_eq!(self, &Expect::Punish(peer));
 	}
 
+	fn note_reputation(&self, _peer: PeerId, _change: ReputationChange) { }
+
 	fn protocol_version(&self, _peer: PeerId) -> Option<u8> {
 		Some(super::MAX_PROTOCOL_VERSION)
 	}
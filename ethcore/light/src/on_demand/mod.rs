@@ -564,7 +564,7 @@ mod tests {
 	use net::{Announcement, BasicContext, ReqId, Error as LesError};
 	use request::Requests;
 
-	use network::{PeerId, NodeId};
+	use network::{PeerId, NodeId, ReputationChange};
 	use time::Duration;
 	use util::{H256, Mutex};
 
@@ -578,6 +578,7 @@ mod tests {
 		fn make_announcement(&self, _: Announcement) { }
 		fn disconnect_peer(&self, _: PeerId) { }
 		fn disable_peer(&self, _: PeerId) { }
+		fn note_reputation(&self, _: PeerId, _: ReputationChange) { }
 	}
 
 	#[test]
@@ -26,7 +26,15 @@ extern crate ethcore_util as util;
 mod registry;
 mod service_transaction;
 mod secretstore_acl_storage;
+mod block_reward;
+mod transact_permission;
+mod peer_set;
+mod private_contract;
 
 pub use self::registry::Registry;
 pub use self::service_transaction::ServiceTransactionChecker;
 pub use self::secretstore_acl_storage::SecretStoreAclStorage;
+pub use self::block_reward::BlockReward;
+pub use self::transact_permission::TransactPermission;
+pub use self::peer_set::PeerSet;
+pub use self::private_contract::PrivateContract;
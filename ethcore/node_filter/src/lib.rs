@@ -0,0 +1,135 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Smart contract based node filter.
+
+extern crate ethcore;
+extern crate ethcore_util as util;
+extern crate ethcore_network as network;
+extern crate native_contracts;
+extern crate futures;
+extern crate lru_cache;
+#[cfg(test)] extern crate ethcore_io as io;
+#[macro_use] extern crate log;
+
+use std::sync::Weak;
+
+use ethcore::client::{BlockChainClient, BlockId};
+use futures::{future, Future};
+use lru_cache::LruCache;
+use native_contracts::PeerSet as Contract;
+use network::{NodeId, ConnectionFilter, ConnectionDirection};
+use util::{Address, H256, Mutex};
+
+const MAX_CACHE_SIZE: usize = 4096;
+
+/// Connection filter that uses a contract to manage permissions.
+pub struct NodeFilter {
+	contract: Contract,
+	contract_address: Address,
+	client: Weak<BlockChainClient>,
+	permission_cache: Mutex<LruCache<(H256, NodeId, NodeId), bool>>,
+}
+
+impl NodeFilter {
+	/// Create a new instance. Accepts a contract address.
+	pub fn new(client: Weak<BlockChainClient>, contract_address: Address) -> NodeFilter {
+		NodeFilter {
+			contract: Contract::new(contract_address),
+			contract_address: contract_address,
+			client: client,
+			permission_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
+		}
+	}
+}
+
+impl ConnectionFilter for NodeFilter {
+	fn connection_allowed(&self, own_id: &NodeId, connecting_id: &NodeId, _direction: ConnectionDirection) -> bool {
+		let client = match self.client.upgrade() {
+			Some(client) => client,
+			None => return false,
+		};
+
+		// Permissions only depend on the contract state, so the cache is keyed by its storage root.
+		let best_hash = client.chain_info().best_block_hash;
+		let key = client.storage_root(&self.contract_address, BlockId::Hash(best_hash))
+			.map(|root| (root, own_id.clone(), connecting_id.clone()));
+		if let Some(ref key) = key {
+			if let Some(allowed) = self.permission_cache.lock().get_mut(key) {
+				return *allowed;
+			}
+		}
+
+		let own_low = H256::from_slice(&own_id[0..32]);
+		let own_high = H256::from_slice(&own_id[32..64]);
+		let id_low = H256::from_slice(&connecting_id[0..32]);
+		let id_high = H256::from_slice(&connecting_id[32..64]);
+		let allowed = self.contract.connection_allowed(
+			|addr, data| future::done(client.call_contract(BlockId::Hash(best_hash), addr, data)),
+			own_low,
+			own_high,
+			id_low,
+			id_high,
+		).wait().unwrap_or_else(|e| {
+			debug!(target: "network", "Error calling peer set contract: {}", e);
+			false
+		});
+		trace!(target: "network", "Connection to {:?} allowed: {}", connecting_id, allowed);
+
+		if let Some(key) = key {
+			self.permission_cache.lock().insert(key, allowed);
+		}
+		allowed
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::str::FromStr;
+	use ethcore::spec::Spec;
+	use ethcore::client::{BlockChainClient, Client, ClientConfig};
+	use ethcore::miner::Miner;
+	use network::{ConnectionDirection, ConnectionFilter, NodeId};
+	use io::IoChannel;
+	use super::NodeFilter;
+
+	#[test]
+	fn node_filter() {
+		// The contract allows connections to nodes whose id starts with 32 bytes of `0x11`.
+		let spec = Spec::load(&include_bytes!("../res/node_filter.json")[..]).unwrap();
+		let client: Arc<BlockChainClient> = Client::new(
+			ClientConfig::default(),
+			&spec,
+			Arc::new(::util::kvdb::in_memory(::ethcore::db::NUM_COLUMNS.unwrap_or(0))),
+			Arc::new(Miner::with_spec(&spec)),
+			IoChannel::disconnected(),
+		).unwrap();
+		let contract = spec.params.node_permission_contract.unwrap();
+		let filter = NodeFilter::new(Arc::downgrade(&client), contract);
+
+		let self_id = NodeId::from_str("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+		let allowed = NodeId::from_str("11111111111111111111111111111111111111111111111111111111111111110000000000000000000000000000000000000000000000000000000000000002").unwrap();
+		let denied = NodeId::from_str("22222222222222222222222222222222222222222222222222222222222222220000000000000000000000000000000000000000000000000000000000000003").unwrap();
+
+		assert!(filter.connection_allowed(&self_id, &allowed, ConnectionDirection::Inbound));
+		assert!(filter.connection_allowed(&self_id, &allowed, ConnectionDirection::Outbound));
+		assert!(!filter.connection_allowed(&self_id, &denied, ConnectionDirection::Inbound));
+		// Cached result is used for the second check.
+		assert!(!filter.connection_allowed(&self_id, &denied, ConnectionDirection::Outbound));
+	}
+}
@@ -0,0 +1,179 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Encryption of private contract code and state.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+use ethcrypto::{aes, ecies};
+use ethkey::{self, KeyPair};
+use fetch::{Fetch, Client as FetchClient};
+use rustc_serialize::hex::{FromHex, ToHex};
+use util::{Address, Bytes, H128, H256, Hashable, Mutex};
+use error::Error;
+
+/// Length of the initialisation vector appended to encrypted data.
+const INIT_VEC_LEN: usize = 16;
+
+/// Encrypts and decrypts the data of private contracts.
+pub trait Encryptor: Send + Sync + 'static {
+	/// Encrypt data of the given contract.
+	fn encrypt(&self, contract_address: &Address, initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error>;
+
+	/// Decrypt data of the given contract.
+	fn decrypt(&self, contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error>;
+}
+
+/// Id of the secret store document holding the key of a private contract.
+pub fn contract_key_id(contract_address: &Address) -> H256 {
+	contract_address.sha3()
+}
+
+/// Configuration of the secret store encryptor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptorConfig {
+	/// Base URL of the key server's HTTP interface.
+	pub base_url: String,
+	/// Key pair used to request and decrypt document keys.
+	pub key_pair: KeyPair,
+}
+
+/// Key of a private contract.
+enum ContractKey {
+	/// The key is being retrieved from the key server.
+	Pending,
+	/// The AES key.
+	Ready(Bytes),
+}
+
+/// Encryptor using document keys stored by the secret store key servers.
+///
+/// A key for every private contract has to be generated beforehand with the key server's
+/// `POST /<key id>/<signature>/<threshold>` request, where the key id is `keccak(contract address)`.
+///
+/// Keys are retrieved on a separate thread, so that the IO threads are never blocked by the
+/// key server; until then encrypting or decrypting fails with `Error::KeyNotReady`.
+pub struct SecretStoreEncryptor {
+	config: EncryptorConfig,
+	client: FetchClient,
+	keys: Arc<Mutex<HashMap<Address, ContractKey>>>,
+}
+
+impl SecretStoreEncryptor {
+	/// Create a new encryptor.
+	pub fn new(config: EncryptorConfig) -> Result<Self, Error> {
+		let client = FetchClient::new().map_err(|e| Error::Encrypt(format!("Error creating fetch client: {:?}", e)))?;
+		Ok(SecretStoreEncryptor {
+			config: config,
+			client: client,
+			keys: Arc::new(Mutex::new(HashMap::new())),
+		})
+	}
+
+	/// Get the AES key of the contract. If it's not cached, it is requested from the key
+	/// server in the background and `Error::KeyNotReady` is returned.
+	fn key(&self, contract_address: &Address) -> Result<Bytes, Error> {
+		match self.keys.lock().get(contract_address) {
+			Some(&ContractKey::Ready(ref key)) => return Ok(key.clone()),
+			Some(&ContractKey::Pending) => return Err(Error::KeyNotReady),
+			None => {},
+		}
+
+		let key_id = contract_key_id(contract_address);
+		let signature = ethkey::sign(self.config.key_pair.secret(), &key_id)?;
+		let url = format!("{}/{}/{}", self.config.base_url.trim_right_matches('/'), key_id.hex(), signature[..].to_hex());
+
+		self.keys.lock().insert(*contract_address, ContractKey::Pending);
+		let client = self.client.clone();
+		let key_pair = self.config.key_pair.clone();
+		let keys = self.keys.clone();
+		let address = *contract_address;
+		let spawned = thread::Builder::new().name("private-tx-key".into()).spawn(move || {
+			match fetch_key(&client, &url, &key_pair) {
+				Ok(key) => {
+					keys.lock().insert(address, ContractKey::Ready(key));
+				},
+				Err(e) => {
+					warn!(target: "privatetx", "Error retrieving the key of private contract {}: {}", address, e);
+					keys.lock().remove(&address);
+				},
+			}
+		});
+
+		if let Err(e) = spawned {
+			self.keys.lock().remove(contract_address);
+			return Err(Error::Encrypt(format!("Error starting key retrieval: {}", e)));
+		}
+		Err(Error::KeyNotReady)
+	}
+}
+
+/// Request a document key from the key server and derive the AES key from it.
+fn fetch_key(client: &FetchClient, url: &str, key_pair: &KeyPair) -> Result<Bytes, Error> {
+	let mut response = client.fetch_sync(url)
+		.map_err(|e| Error::Encrypt(format!("Error requesting document key: {:?}", e)))?;
+	if !response.is_success() {
+		return Err(Error::Encrypt(format!("Key server responded with {}", response.status())));
+	}
+
+	let mut body = String::new();
+	response.read_to_string(&mut body)
+		.map_err(|e| Error::Encrypt(format!("Error reading document key: {}", e)))?;
+	let encrypted_key = body.trim().trim_matches('"').from_hex()
+		.map_err(|e| Error::Encrypt(format!("Invalid document key: {}", e)))?;
+	let document_key = ecies::decrypt_single_message(key_pair.secret(), &encrypted_key)
+		.map_err(|e| Error::Encrypt(format!("Error decrypting document key: {}", e)))?;
+
+	Ok(document_key.sha3()[..INIT_VEC_LEN].to_vec())
+}
+
+impl Encryptor for SecretStoreEncryptor {
+	fn encrypt(&self, contract_address: &Address, initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error> {
+		let key = self.key(contract_address)?;
+		let mut cypher = vec![0u8; plain_data.len() + INIT_VEC_LEN];
+		aes::encrypt(&key, initialisation_vector, plain_data, &mut cypher[..plain_data.len()]);
+		cypher[plain_data.len()..].copy_from_slice(initialisation_vector);
+		Ok(cypher)
+	}
+
+	fn decrypt(&self, contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error> {
+		if cypher.len() < INIT_VEC_LEN {
+			return Err(Error::Encrypt("Encrypted data is too short".into()));
+		}
+
+		let key = self.key(contract_address)?;
+		let data_len = cypher.len() - INIT_VEC_LEN;
+		let mut plain_data = vec![0u8; data_len];
+		aes::decrypt(&key, &cypher[data_len..], &cypher[..data_len], &mut plain_data);
+		Ok(plain_data)
+	}
+}
+
+/// Encryptor which leaves the data as is. Only useful for development and tests.
+pub struct NoopEncryptor;
+
+impl Encryptor for NoopEncryptor {
+	fn encrypt(&self, _contract_address: &Address, _initialisation_vector: &H128, plain_data: &[u8]) -> Result<Bytes, Error> {
+		Ok(plain_data.to_vec())
+	}
+
+	fn decrypt(&self, _contract_address: &Address, cypher: &[u8]) -> Result<Bytes, Error> {
+		Ok(cypher.to_vec())
+	}
+}
@@ -0,0 +1,99 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::fmt;
+use ethcore::error::{Error as EthcoreError, CallError};
+use ethkey::Error as KeyError;
+use rlp::DecoderError;
+
+/// Errors which can occur while handling private transactions.
+#[derive(Debug)]
+pub enum Error {
+	/// Private transactions can only call existing contracts.
+	BadTransactionType,
+	/// The contract state is not available at the requested block.
+	StatePruned,
+	/// Calling the private contract or executing the transaction failed.
+	Call(String),
+	/// Encrypting or decrypting private data failed.
+	Encrypt(String),
+	/// The key of the private contract is still being retrieved.
+	KeyNotReady,
+	/// No account configured for signing public transactions.
+	SignerAccountNotSet,
+	/// None of the configured passwords unlocks the account.
+	PasswordNotFound,
+	/// The signature does not belong to any of the contract's validators.
+	NotAValidator,
+	/// The private transaction is not known to this node.
+	PrivateTransactionNotFound,
+	/// Too many messages received from peers are awaiting import.
+	QueueIsFull,
+	/// Private data could not be decoded.
+	Decoder(DecoderError),
+	/// Key errors.
+	Key(KeyError),
+	/// Ethcore errors.
+	Ethcore(EthcoreError),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::BadTransactionType => write!(f, "Private transaction must call an existing contract"),
+			Error::StatePruned => write!(f, "State of the private contract is not available"),
+			Error::Call(ref msg) => write!(f, "Private contract call failed: {}", msg),
+			Error::Encrypt(ref msg) => write!(f, "Encryption failed: {}", msg),
+			Error::KeyNotReady => write!(f, "Key of the private contract is being retrieved, try again later"),
+			Error::SignerAccountNotSet => write!(f, "Account for signing public transactions is not set"),
+			Error::PasswordNotFound => write!(f, "No valid password for the account"),
+			Error::NotAValidator => write!(f, "Signer is not a validator of the private contract"),
+			Error::PrivateTransactionNotFound => write!(f, "Private transaction is not known"),
+			Error::QueueIsFull => write!(f, "Private transactions queue is full"),
+			Error::Decoder(ref err) => write!(f, "Invalid private data: {}", err),
+			Error::Key(ref err) => write!(f, "{}", err),
+			Error::Ethcore(ref err) => write!(f, "{}", err),
+		}
+	}
+}
+
+impl From<DecoderError> for Error {
+	fn from(err: DecoderError) -> Self {
+		Error::Decoder(err)
+	}
+}
+
+impl From<KeyError> for Error {
+	fn from(err: KeyError) -> Self {
+		Error::Key(err)
+	}
+}
+
+impl From<EthcoreError> for Error {
+	fn from(err: EthcoreError) -> Self {
+		Error::Ethcore(err)
+	}
+}
+
+impl From<CallError> for Error {
+	fn from(err: CallError) -> Self {
+		match err {
+			CallError::StatePruned => Error::StatePruned,
+			err => Error::Call(format!("{:?}", err)),
+		}
+	}
+}
@@ -0,0 +1,628 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Private transactions: transactions on contracts whose code and state are kept
+//! encrypted on the public chain and are only executed by the contract's validators.
+//!
+//! The originator executes the transaction locally and sends it, encrypted, to the
+//! validators. Each validator executes it as well and signs the hash of the resulting
+//! encrypted state. Once all validators have signed, the originator commits the new
+//! state with a public transaction to the contract.
+
+extern crate ethcore;
+extern crate ethcore_io as io;
+extern crate ethcore_util as util;
+extern crate ethcrypto;
+extern crate ethkey;
+extern crate fetch;
+extern crate futures;
+extern crate native_contracts;
+extern crate rlp;
+extern crate rustc_serialize;
+
+#[macro_use]
+extern crate log;
+
+mod encryptor;
+mod error;
+mod messages;
+
+pub use encryptor::{Encryptor, SecretStoreEncryptor, NoopEncryptor, EncryptorConfig, contract_key_id};
+pub use error::Error;
+pub use messages::{PrivateTransaction, SignedPrivateTransaction, state_hash};
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::mem;
+use std::sync::{Arc, Weak};
+
+use ethcore::account_provider::AccountProvider;
+use ethcore::client::{Client, BlockChainClient, BlockId, ChainNotify};
+use ethcore::executed::Executed;
+use ethcore::miner::MinerService;
+use ethcore::service::ClientIoMessage;
+use ethcore::transaction::{Action, SignedTransaction, Transaction, UnverifiedTransaction};
+use ethkey::{Signature, public_to_address};
+use futures::{future, Future};
+use io::{IoChannel, IoContext, IoHandler, TimerToken};
+use native_contracts::PrivateContract;
+use rlp::{UntrustedRlp, RlpStream};
+use util::{Address, Bytes, H128, H256, U256, Hashable, Mutex, RwLock};
+
+/// Configuration of the private transactions provider.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ProviderConfig {
+	/// Accounts of this node which validate private transactions.
+	pub validator_accounts: Vec<Address>,
+	/// Account signing the public transactions which commit private state.
+	pub signer_account: Option<Address>,
+	/// Passwords unlocking the accounts above.
+	pub passwords: Vec<String>,
+}
+
+/// Receipt of a private transaction sent to the validators.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Receipt {
+	/// Hash of the private transaction.
+	pub hash: H256,
+	/// Address of the private contract.
+	pub contract_address: Address,
+	/// Output of the local execution.
+	pub output: Bytes,
+}
+
+/// Maximal number of messages received from peers and awaiting import.
+const MAX_QUEUED_MESSAGES: usize = 1024;
+/// Timer retrying the import of messages waiting for a contract key.
+const RETRY_DEFERRED_TIMER: TimerToken = 0;
+/// Interval between the retries, in milliseconds.
+const RETRY_DEFERRED_INTERVAL_MS: u64 = 1000;
+/// Number of retries before a message waiting for a contract key is dropped.
+const MAX_DEFERRED_RETRIES: usize = 30;
+
+/// Message received from a peer, imported on the client's IO service.
+enum QueuedMessage {
+	/// RLP of an encrypted private transaction.
+	Private(Bytes),
+	/// RLP of a validator's signature.
+	Signed(Bytes),
+}
+
+/// A private transaction originated here, awaiting the validators' signatures.
+struct SigningDesc {
+	original_transaction: SignedTransaction,
+	validators: Vec<Address>,
+	signatures: HashMap<Address, Signature>,
+	state: Bytes,
+	state_nonce: U256,
+}
+
+/// Manages private transactions: local execution, validation and committing the
+/// resulting state on chain.
+pub struct Provider {
+	encryptor: Box<Encryptor>,
+	config: ProviderConfig,
+	client: Arc<Client>,
+	accounts: Arc<AccountProvider>,
+	notify: RwLock<Vec<Weak<ChainNotify>>>,
+	awaiting_signatures: Mutex<HashMap<H256, SigningDesc>>,
+	queue: Mutex<VecDeque<QueuedMessage>>,
+	/// Messages waiting for the key of their contract, with the number of retries so far.
+	deferred: Mutex<VecDeque<(QueuedMessage, usize)>>,
+	channel: Mutex<IoChannel<ClientIoMessage>>,
+}
+
+impl Provider {
+	/// Create a new provider. Messages received from peers are imported when `channel`
+	/// delivers `ClientIoMessage::NewPrivateTransaction` to the provider's IO handler.
+	pub fn new(
+		client: Arc<Client>,
+		accounts: Arc<AccountProvider>,
+		encryptor: Box<Encryptor>,
+		config: ProviderConfig,
+		channel: IoChannel<ClientIoMessage>,
+	) -> Self {
+		Provider {
+			encryptor: encryptor,
+			config: config,
+			client: client,
+			accounts: accounts,
+			notify: RwLock::new(Vec::new()),
+			awaiting_signatures: Mutex::new(HashMap::new()),
+			queue: Mutex::new(VecDeque::new()),
+			deferred: Mutex::new(VecDeque::new()),
+			channel: Mutex::new(channel),
+		}
+	}
+
+	/// Adds an actor to be notified about private transaction messages to broadcast.
+	pub fn add_notify(&self, target: Arc<ChainNotify>) {
+		self.notify.write().push(Arc::downgrade(&target));
+	}
+
+	fn notify<F>(&self, f: F) where F: Fn(&ChainNotify) {
+		for np in self.notify.read().iter() {
+			if let Some(n) = np.upgrade() {
+				f(&*n);
+			}
+		}
+	}
+
+	/// Execute a transaction on a private contract and send it to the contract's validators.
+	pub fn create_private_transaction(&self, transaction: SignedTransaction) -> Result<Receipt, Error> {
+		let contract = match transaction.action {
+			Action::Call(ref address) => *address,
+			Action::Create => return Err(Error::BadTransactionType),
+		};
+
+		let block = BlockId::Latest;
+		let validators = self.validators(&contract, block.clone())?;
+		let state_nonce = self.contract_nonce(&contract, block.clone())?;
+		let (executed, state) = self.execute_private(&transaction, block)?;
+
+		let encrypted = self.encryptor.encrypt(&contract, &transaction_iv(&transaction), &::rlp::encode(&transaction).to_vec())?;
+		let private = PrivateTransaction {
+			encrypted: encrypted,
+			contract: contract,
+		};
+		let private_hash = private.hash();
+		trace!(target: "privatetx", "Created private transaction {} for contract {}", private_hash, contract);
+
+		let own_signatures = self.sign_state(&validators, &state, state_nonce)?;
+		self.awaiting_signatures.lock().insert(private_hash, SigningDesc {
+			original_transaction: transaction,
+			validators: validators,
+			signatures: HashMap::new(),
+			state: state,
+			state_nonce: state_nonce,
+		});
+
+		self.notify(|notify| notify.broadcast_private_transaction(::rlp::encode(&private).to_vec()));
+		for signature in own_signatures {
+			self.add_signature(&private_hash, signature)?;
+		}
+
+		Ok(Receipt {
+			hash: private_hash,
+			contract_address: contract,
+			output: executed.output,
+		})
+	}
+
+	/// Queue a private transaction received from a peer for import on the IO service.
+	/// Only checks the message format. Returns the hash of the private transaction.
+	pub fn queue_private_transaction(&self, rlp: &[u8]) -> Result<H256, Error> {
+		let private: PrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		self.queue_message(QueuedMessage::Private(rlp.to_vec()))?;
+		Ok(private.hash())
+	}
+
+	/// Queue a validator's signature received from a peer for import on the IO service.
+	/// Only checks the message format. Returns the hash of the message.
+	pub fn queue_signed_private_transaction(&self, rlp: &[u8]) -> Result<H256, Error> {
+		let signed: SignedPrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		self.queue_message(QueuedMessage::Signed(rlp.to_vec()))?;
+		Ok(signed.hash())
+	}
+
+	fn queue_message(&self, message: QueuedMessage) -> Result<(), Error> {
+		{
+			let mut queue = self.queue.lock();
+			if queue.len() >= MAX_QUEUED_MESSAGES {
+				return Err(Error::QueueIsFull);
+			}
+			queue.push_back(message);
+		}
+
+		if let Err(e) = self.channel.lock().send(ClientIoMessage::NewPrivateTransaction) {
+			debug!(target: "privatetx", "Error sending private transaction IO message: {:?}", e);
+		}
+		Ok(())
+	}
+
+	/// Import all queued messages. Messages which are imported successfully are relayed
+	/// to the peers, so that they reach nodes not directly connected to the sender.
+	pub fn import_queued_messages(&self) {
+		let queued = mem::replace(&mut *self.queue.lock(), VecDeque::new());
+		for message in queued {
+			self.import_message(message, 0);
+		}
+	}
+
+	/// Retry the import of messages which were waiting for the key of their contract.
+	pub fn import_deferred_messages(&self) {
+		let deferred = mem::replace(&mut *self.deferred.lock(), VecDeque::new());
+		for (message, retries) in deferred {
+			self.import_message(message, retries + 1);
+		}
+	}
+
+	fn import_message(&self, message: QueuedMessage, retries: usize) {
+		let result = match message {
+			QueuedMessage::Private(ref rlp) => self.import_private_transaction(rlp).map(|hash| {
+				trace!(target: "privatetx", "Imported private transaction {}", hash);
+				self.notify(|notify| notify.broadcast_private_transaction(rlp.clone()));
+			}),
+			QueuedMessage::Signed(ref rlp) => self.import_signed_private_transaction(rlp).map(|hash| {
+				trace!(target: "privatetx", "Imported signed private transaction {}", hash);
+				self.notify(|notify| notify.broadcast_signed_private_transaction(rlp.clone()));
+			}),
+		};
+
+		match result {
+			Ok(()) => {},
+			Err(Error::KeyNotReady) if retries < MAX_DEFERRED_RETRIES => {
+				let mut deferred = self.deferred.lock();
+				if deferred.len() < MAX_QUEUED_MESSAGES {
+					deferred.push_back((message, retries));
+				}
+			},
+			Err(e) => debug!(target: "privatetx", "Error importing private transaction message: {}", e),
+		}
+	}
+
+	/// Import a private transaction received from a peer. If this node validates the
+	/// contract, the transaction is executed and the resulting state signed.
+	pub fn import_private_transaction(&self, rlp: &[u8]) -> Result<H256, Error> {
+		let private: PrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		let private_hash = private.hash();
+
+		let block = BlockId::Latest;
+		let validators = self.validators(&private.contract, block.clone())?;
+		if !self.config.validator_accounts.iter().any(|account| validators.contains(account)) {
+			trace!(target: "privatetx", "Not a validator of contract {}, ignoring {}", private.contract, private_hash);
+			return Ok(private_hash);
+		}
+
+		let transaction_rlp = self.encryptor.decrypt(&private.contract, &private.encrypted)?;
+		let transaction: UnverifiedTransaction = UntrustedRlp::new(&transaction_rlp).as_val()?;
+		let transaction = SignedTransaction::new(transaction)?;
+		if transaction.action != Action::Call(private.contract) {
+			return Err(Error::BadTransactionType);
+		}
+
+		let state_nonce = self.contract_nonce(&private.contract, block.clone())?;
+		let (_, state) = self.execute_private(&transaction, block)?;
+		for signature in self.sign_state(&validators, &state, state_nonce)? {
+			let signed = SignedPrivateTransaction {
+				private_transaction_hash: private_hash,
+				signature: signature,
+			};
+			trace!(target: "privatetx", "Signed private transaction {}", private_hash);
+			self.notify(|notify| notify.broadcast_signed_private_transaction(::rlp::encode(&signed).to_vec()));
+		}
+
+		Ok(private_hash)
+	}
+
+	/// Import a validator's signature received from a peer. Once all validators of a private
+	/// transaction originated here have signed, the new state is committed on chain.
+	pub fn import_signed_private_transaction(&self, rlp: &[u8]) -> Result<H256, Error> {
+		let signed: SignedPrivateTransaction = UntrustedRlp::new(rlp).as_val()?;
+		if !self.awaiting_signatures.lock().contains_key(&signed.private_transaction_hash) {
+			trace!(target: "privatetx", "Ignoring signature of unknown private transaction {}", signed.private_transaction_hash);
+			return Ok(signed.hash());
+		}
+
+		self.add_signature(&signed.private_transaction_hash, signed.signature.clone())?;
+		Ok(signed.hash())
+	}
+
+	/// Execute a transaction on a private contract without changing anything.
+	pub fn private_call(&self, block: BlockId, transaction: &SignedTransaction) -> Result<Executed, Error> {
+		self.execute_private(transaction, block).map(|(executed, _)| executed)
+	}
+
+	fn add_signature(&self, private_hash: &H256, signature: Signature) -> Result<(), Error> {
+		let mut awaiting = self.awaiting_signatures.lock();
+		let complete = {
+			let desc = awaiting.get_mut(private_hash).ok_or(Error::PrivateTransactionNotFound)?;
+			let hash = state_hash(&desc.state, desc.state_nonce);
+			let signer = public_to_address(&ethkey::recover(&signature, &hash)?);
+			if !desc.validators.contains(&signer) {
+				return Err(Error::NotAValidator);
+			}
+
+			desc.signatures.insert(signer, signature);
+			desc.signatures.len() == desc.validators.len()
+		};
+
+		if complete {
+			let desc = awaiting.remove(private_hash).expect("presence checked above; qed");
+			debug!(target: "privatetx", "All validators signed private transaction {}", private_hash);
+			self.commit_state(desc)?;
+		}
+
+		Ok(())
+	}
+
+	/// Send the public transaction setting the validated state of a private contract.
+	fn commit_state(&self, desc: SigningDesc) -> Result<(), Error> {
+		let contract = match desc.original_transaction.action {
+			Action::Call(ref address) => PrivateContract::new(*address),
+			Action::Create => return Err(Error::BadTransactionType),
+		};
+
+		let mut v = Vec::new();
+		let mut r = Vec::new();
+		let mut s = Vec::new();
+		for signature in desc.validators.iter().filter_map(|validator| desc.signatures.get(validator)) {
+			v.push(signature.v() + 27);
+			r.push(H256::from_slice(signature.r()));
+			s.push(H256::from_slice(signature.s()));
+		}
+
+		let original = &desc.original_transaction;
+		contract.set_state(
+			|address, data| future::done(self.transact_public(address, data, original)
+				.map(|_| Vec::new())
+				.map_err(|e| e.to_string())),
+			desc.state.clone(),
+			v,
+			r,
+			s,
+		).wait().map_err(Error::Call)
+	}
+
+	fn transact_public(&self, address: Address, data: Bytes, original: &SignedTransaction) -> Result<(), Error> {
+		let signer = self.config.signer_account.ok_or(Error::SignerAccountNotSet)?;
+		let miner = self.client.miner();
+		let nonce = miner.last_nonce(&signer)
+			.map(|nonce| nonce + U256::one())
+			.unwrap_or_else(|| self.client.latest_nonce(&signer));
+
+		let transaction = Transaction {
+			nonce: nonce,
+			action: Action::Call(address),
+			gas: original.gas,
+			gas_price: original.gas_price,
+			value: U256::zero(),
+			data: data,
+		};
+		let network_id = self.client.signing_network_id();
+		let signature = self.sign(signer, transaction.hash(network_id))?;
+		let signed = SignedTransaction::new(transaction.with_signature(signature, network_id))?;
+		let hash = signed.hash();
+		miner.import_own_transaction(&*self.client, signed.into())?;
+		debug!(target: "privatetx", "Committed private state with public transaction {}", hash);
+		Ok(())
+	}
+
+	/// Sign the state hash with every account of this node validating the contract.
+	fn sign_state(&self, validators: &[Address], state: &[u8], state_nonce: U256) -> Result<Vec<Signature>, Error> {
+		let hash = state_hash(state, state_nonce);
+		self.config.validator_accounts.iter()
+			.filter(|account| validators.contains(account))
+			.map(|account| self.sign(*account, hash))
+			.collect()
+	}
+
+	fn sign(&self, account: Address, hash: H256) -> Result<Signature, Error> {
+		if let Ok(signature) = self.accounts.sign(account, None, hash) {
+			return Ok(signature);
+		}
+
+		self.config.passwords.iter()
+			.filter_map(|password| self.accounts.sign(account, Some(password.clone()), hash).ok())
+			.next()
+			.ok_or(Error::PasswordNotFound)
+	}
+
+	/// Execute the transaction with the contract's decrypted code and state.
+	/// Returns the result and the encrypted state after execution.
+	fn execute_private(&self, transaction: &SignedTransaction, block: BlockId) -> Result<(Executed, Bytes), Error> {
+		let contract_address = match transaction.action {
+			Action::Call(ref address) => *address,
+			Action::Create => return Err(Error::BadTransactionType),
+		};
+
+		let contract = PrivateContract::new(contract_address);
+		let encrypted_code = contract.code(|address, data| self.call(block.clone(), address, data)).wait().map_err(Error::Call)?;
+		let encrypted_state = contract.state(|address, data| self.call(block.clone(), address, data)).wait().map_err(Error::Call)?;
+
+		let code = self.encryptor.decrypt(&contract_address, &encrypted_code)?;
+		let storage = match encrypted_state.is_empty() {
+			true => BTreeMap::new(),
+			false => decode_storage(&self.encryptor.decrypt(&contract_address, &encrypted_state)?)?,
+		};
+
+		let (executed, storage) = self.client.call_with_contract_state(transaction, block, &contract_address, code, storage)?;
+		let state = self.encryptor.encrypt(&contract_address, &state_iv(transaction), &encode_storage(&storage))?;
+		Ok((executed, state))
+	}
+
+	fn validators(&self, contract: &Address, block: BlockId) -> Result<Vec<Address>, Error> {
+		PrivateContract::new(*contract)
+			.get_validators(|address, data| self.call(block.clone(), address, data))
+			.wait()
+			.map_err(Error::Call)
+	}
+
+	fn contract_nonce(&self, contract: &Address, block: BlockId) -> Result<U256, Error> {
+		PrivateContract::new(*contract)
+			.nonce(|address, data| self.call(block.clone(), address, data))
+			.wait()
+			.map_err(Error::Call)
+	}
+
+	fn call(&self, block: BlockId, address: Address, data: Bytes) -> future::FutureResult<Bytes, String> {
+		future::done(self.client.call_contract(block, address, data))
+	}
+}
+
+impl IoHandler<ClientIoMessage> for Provider {
+	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
+		if let Err(e) = io.register_timer(RETRY_DEFERRED_TIMER, RETRY_DEFERRED_INTERVAL_MS) {
+			warn!(target: "privatetx", "Error registering private transactions timer: {:?}", e);
+		}
+	}
+
+	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
+		if timer == RETRY_DEFERRED_TIMER {
+			self.import_deferred_messages();
+		}
+	}
+
+	fn message(&self, _io: &IoContext<ClientIoMessage>, net_message: &ClientIoMessage) {
+		if let ClientIoMessage::NewPrivateTransaction = *net_message {
+			self.import_queued_messages();
+		}
+	}
+}
+
+/// Initialisation vector for the encrypted original transaction. Derived from its hash,
+/// so that all parties produce the same private transaction hash.
+fn transaction_iv(transaction: &SignedTransaction) -> H128 {
+	H128::from_slice(&transaction.hash()[..16])
+}
+
+/// Initialisation vector for the encrypted state. Derived from the transaction hash,
+/// so that all validators produce the same encrypted state.
+fn state_iv(transaction: &SignedTransaction) -> H128 {
+	H128::from_slice(&transaction.hash().sha3()[..16])
+}
+
+fn encode_storage(storage: &BTreeMap<H256, H256>) -> Bytes {
+	let mut stream = RlpStream::new_list(storage.len());
+	for (key, value) in storage {
+		stream.begin_list(2).append(key).append(value);
+	}
+	stream.out()
+}
+
+fn decode_storage(rlp: &[u8]) -> Result<BTreeMap<H256, H256>, Error> {
+	let rlp = UntrustedRlp::new(rlp);
+	let mut storage = BTreeMap::new();
+	for item in rlp.iter() {
+		storage.insert(item.val_at(0)?, item.val_at(1)?);
+	}
+	Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use std::sync::Arc;
+	use ethcore::account_provider::AccountProvider;
+	use ethcore::client::{BlockId, Client, ClientConfig, ChainNotify};
+	use ethcore::miner::{Miner, MinerService};
+	use ethcore::spec::Spec;
+	use ethcore::transaction::{Action, Transaction};
+	use ethkey::Secret;
+	use futures::Future;
+	use io::IoChannel;
+	use native_contracts::PrivateContract;
+	use util::{Address, Bytes, H256, U256, Hashable, Mutex};
+	use super::{Provider, ProviderConfig, NoopEncryptor, encode_storage, decode_storage};
+
+	#[derive(Default)]
+	struct TestNotify {
+		private: Mutex<Vec<Bytes>>,
+		signed: Mutex<Vec<Bytes>>,
+	}
+
+	impl ChainNotify for TestNotify {
+		fn broadcast_private_transaction(&self, message: Vec<u8>) {
+			self.private.lock().push(message);
+		}
+
+		fn broadcast_signed_private_transaction(&self, message: Vec<u8>) {
+			self.signed.lock().push(message);
+		}
+	}
+
+	#[test]
+	fn should_commit_state_signed_by_validators() {
+		// The contract at 0x0a keeps the nonce at 0x00, its only validator (secret "1".sha3()) at 0x01,
+		// the private code from 0x10 and the private state from 0x20. `setState` doesn't check signatures.
+		// The private code stores the first call argument at 0x00.
+		let spec = Spec::load(&include_bytes!("../res/private_contract.json")[..]).unwrap();
+		let client = Client::new(
+			ClientConfig::default(),
+			&spec,
+			Arc::new(::util::kvdb::in_memory(::ethcore::db::NUM_COLUMNS.unwrap_or(0))),
+			Arc::new(Miner::with_spec(&spec)),
+			IoChannel::disconnected(),
+		).unwrap();
+		let contract = Address::from(0x0a);
+
+		let accounts = Arc::new(AccountProvider::transient_provider());
+		let validator = accounts.insert_account(Secret::from_slice(&"1".sha3()).unwrap(), "").unwrap();
+		let signer = accounts.insert_account(Secret::from_slice(&"2".sha3()).unwrap(), "").unwrap();
+		let provider = |config| Arc::new(Provider::new(client.clone(), accounts.clone(), Box::new(NoopEncryptor), config, IoChannel::disconnected()));
+		let originator = provider(ProviderConfig {
+			validator_accounts: Vec::new(),
+			signer_account: Some(signer),
+			passwords: vec!["".into()],
+		});
+		let validating = provider(ProviderConfig {
+			validator_accounts: vec![validator],
+			signer_account: None,
+			passwords: vec!["".into()],
+		});
+		let originator_notify = Arc::new(TestNotify::default());
+		let validating_notify = Arc::new(TestNotify::default());
+		originator.add_notify(originator_notify.clone());
+		validating.add_notify(validating_notify.clone());
+
+		// create
+		let value = H256::from(42);
+		let transaction = Transaction {
+			nonce: U256::zero(),
+			action: Action::Call(contract),
+			gas: U256::from(1_000_000),
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: value.to_vec(),
+		}.sign(&Secret::from_slice(&"3".sha3()).unwrap(), None);
+		let receipt = originator.create_private_transaction(transaction).unwrap();
+		assert_eq!(receipt.contract_address, contract);
+		assert_eq!(originator_notify.private.lock().len(), 1);
+		assert!(originator_notify.signed.lock().is_empty());
+
+		// sign
+		let private = originator_notify.private.lock()[0].clone();
+		assert_eq!(validating.import_private_transaction(&private).unwrap(), receipt.hash);
+		assert_eq!(validating_notify.signed.lock().len(), 1);
+
+		// import
+		let signed = validating_notify.signed.lock()[0].clone();
+		originator.import_signed_private_transaction(&signed).unwrap();
+		assert!(originator.awaiting_signatures.lock().is_empty());
+		client.miner().update_sealing(&*client);
+		client.flush_queue();
+
+		// verify state
+		let mut expected = BTreeMap::new();
+		expected.insert(H256::zero(), value);
+		let state = PrivateContract::new(contract)
+			.state(|address, data| originator.call(BlockId::Latest, address, data))
+			.wait()
+			.unwrap();
+		assert_eq!(decode_storage(&state).unwrap(), expected);
+		assert_eq!(originator.contract_nonce(&contract, BlockId::Latest).unwrap(), U256::one());
+	}
+
+	#[test]
+	fn storage_rlp_roundtrip() {
+		let mut storage = BTreeMap::new();
+		storage.insert(1.into(), 2.into());
+		storage.insert(3.into(), 4.into());
+
+		assert_eq!(decode_storage(&encode_storage(&storage)).unwrap(), storage);
+		assert!(decode_storage(&encode_storage(&BTreeMap::new())).unwrap().is_empty());
+	}
+}
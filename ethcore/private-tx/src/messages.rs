@@ -0,0 +1,142 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Messages exchanged between the originator and the validators of a private transaction.
+
+use ethkey::Signature;
+use rlp::{UntrustedRlp, RlpStream, Encodable, Decodable, DecoderError};
+use util::{Address, Bytes, H256, U256, Uint, Hashable};
+
+/// A transaction on a private contract, encrypted with the contract's key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateTransaction {
+	/// Encrypted RLP of the original signed transaction.
+	pub encrypted: Bytes,
+	/// Address of the private contract.
+	pub contract: Address,
+}
+
+impl PrivateTransaction {
+	/// Hash identifying the private transaction.
+	pub fn hash(&self) -> H256 {
+		::rlp::encode(self).to_vec().sha3()
+	}
+}
+
+impl Encodable for PrivateTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2);
+		s.append(&self.encrypted);
+		s.append(&self.contract);
+	}
+}
+
+impl Decodable for PrivateTransaction {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 2 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		Ok(PrivateTransaction {
+			encrypted: rlp.val_at(0)?,
+			contract: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// A validator's signature of the state produced by a private transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedPrivateTransaction {
+	/// Hash of the private transaction that was executed.
+	pub private_transaction_hash: H256,
+	/// Signature of the resulting state hash.
+	pub signature: Signature,
+}
+
+impl SignedPrivateTransaction {
+	/// Hash identifying the signed message.
+	pub fn hash(&self) -> H256 {
+		::rlp::encode(self).to_vec().sha3()
+	}
+}
+
+impl Encodable for SignedPrivateTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(4);
+		s.append(&self.private_transaction_hash);
+		s.append(&self.signature.v());
+		s.append(&H256::from_slice(self.signature.r()));
+		s.append(&H256::from_slice(self.signature.s()));
+	}
+}
+
+impl Decodable for SignedPrivateTransaction {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		if rlp.item_count()? != 4 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		let v: u8 = rlp.val_at(1)?;
+		let r: H256 = rlp.val_at(2)?;
+		let s: H256 = rlp.val_at(3)?;
+		Ok(SignedPrivateTransaction {
+			private_transaction_hash: rlp.val_at(0)?,
+			signature: Signature::from_rsv(&r, &s, v),
+		})
+	}
+}
+
+/// Hash of a private contract state which validators sign and the contract verifies.
+/// The contract nonce is included so signatures can't be replayed.
+pub fn state_hash(state: &[u8], nonce: U256) -> H256 {
+	let mut buf = state.to_vec();
+	let mut nonce_buf = [0u8; 32];
+	nonce.to_big_endian(&mut nonce_buf);
+	buf.extend_from_slice(&nonce_buf);
+	buf.sha3()
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Brain, Generator, sign};
+	use rlp::{encode, decode};
+	use super::{PrivateTransaction, SignedPrivateTransaction};
+
+	#[test]
+	fn private_transaction_rlp_roundtrip() {
+		let tx = PrivateTransaction {
+			encrypted: vec![1, 2, 3],
+			contract: 5.into(),
+		};
+
+		let decoded: PrivateTransaction = decode(&encode(&tx));
+		assert_eq!(decoded, tx);
+		assert_eq!(decoded.hash(), tx.hash());
+	}
+
+	#[test]
+	fn signed_private_transaction_rlp_roundtrip() {
+		let keypair = Brain::new("validator".into()).generate().unwrap();
+		let signed = SignedPrivateTransaction {
+			private_transaction_hash: 10.into(),
+			signature: sign(keypair.secret(), &11.into()).unwrap(),
+		};
+
+		let decoded: SignedPrivateTransaction = decode(&encode(&signed));
+		assert_eq!(decoded, signed);
+	}
+}
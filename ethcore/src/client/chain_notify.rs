@@ -46,6 +46,12 @@ pub trait ChainNotify : Send + Sync {
 	/// fires when chain broadcasts a message
 	fn broadcast(&self, _data: Vec<u8>) {}
 
+	/// fires when a private transaction has to be sent to the validators of a private contract
+	fn broadcast_private_transaction(&self, _message: Vec<u8>) {}
+
+	/// fires when a validator's signature of a private transaction has to be sent to its originator
+	fn broadcast_signed_private_transaction(&self, _message: Vec<u8>) {}
+
 	/// fires when new transactions are received from a peer
 	fn transactions_received(&self,
 		_hashes: Vec<H256>,
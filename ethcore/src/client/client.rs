@@ -41,7 +41,7 @@ use client::{
 	ChainNotify, PruningInfo,
 };
 use encoded;
-use engines::Engine;
+use engines::{Engine, MaliciousEvidence};
 use env_info::EnvInfo;
 use env_info::LastHashes;
 use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Error as EthcoreError};
@@ -52,6 +52,7 @@ use futures::{future, Future};
 use header::BlockNumber;
 use io::*;
 use log_entry::LocalizedLogEntry;
+use metrics::{Collector, Metric, MetricType};
 use miner::{Miner, MinerService, TransactionImportResult};
 use native_contracts::Registry;
 use rand::OsRng;
@@ -288,6 +289,11 @@ impl Client {
 		&*self.engine
 	}
 
+	/// Returns a handle to the client database.
+	pub fn db(&self) -> Arc<KeyValueDB> {
+		self.db.read().clone()
+	}
+
 	fn notify<F>(&self, f: F) where F: Fn(&ChainNotify) {
 		for np in self.notify.read().iter() {
 			if let Some(n) = np.upgrade() {
@@ -365,24 +371,35 @@ impl Client {
 		let engine = &*self.engine;
 		let header = &block.header;
 
-		let chain = self.chain.read();
-		// Check the block isn't so old we won't be able to enact it.
-		let best_block_number = chain.best_block_number();
-		if best_block_number >= self.history && header.number() <= best_block_number - self.history {
-			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
-			return Err(());
-		}
+		let chain_has_parent = {
+			let chain = self.chain.read();
+			// Check the block isn't so old we won't be able to enact it.
+			let best_block_number = chain.best_block_number();
+			if best_block_number >= self.history && header.number() <= best_block_number - self.history {
+				warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
+				return Err(());
+			}
+
+			// Verify Block Family
+			let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
+			if let Err(e) = verify_family_result {
+				warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+				return Err(());
+			};
 
-		// Verify Block Family
-		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
-		if let Err(e) = verify_family_result {
-			warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			// Check if Parent is in chain
+			chain.block_header(header.parent_hash())
 		};
 
-		// Check if Parent is in chain
-		let chain_has_parent = chain.block_header(header.parent_hash());
 		if let Some(parent) = chain_has_parent {
+			// Check transaction permissions; the chain lock must not be held as this calls into the chain.
+			for t in &block.transactions {
+				if let Err(e) = engine.verify_transaction_family(t, header.parent_hash(), self) {
+					warn!(target: "client", "Block import failed for #{} ({})\nTransaction {} is not permitted: {:?}", header.number(), header.hash(), t.hash(), e);
+					return Err(());
+				}
+			}
+
 			// Enact Verified Block
 			let last_hashes = self.build_last_hashes(header.parent_hash().clone());
 			let db = self.state_db.lock().boxed_clone_canon(header.parent_hash());
@@ -713,6 +730,50 @@ impl Client {
 		}
 	}
 
+	/// Execute a transaction on top of the given block's state, with the account at `contract`
+	/// replaced by one holding the given code and storage. Used to run contracts whose code and
+	/// state are not kept on chain. Nothing is committed: the contract's storage after execution
+	/// is returned alongside the result.
+	pub fn call_with_contract_state(
+		&self,
+		t: &SignedTransaction,
+		block: BlockId,
+		contract: &Address,
+		code: Bytes,
+		storage: BTreeMap<H256, H256>,
+	) -> Result<(Executed, BTreeMap<H256, H256>), CallError> {
+		let mut env_info = self.env_info(block.clone()).ok_or(CallError::StatePruned)?;
+		env_info.gas_limit = U256::max_value();
+
+		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		let contract_balance = state.balance(contract).map_err(|_| CallError::StateCorrupt)?;
+		state.new_contract(contract, contract_balance, U256::zero());
+		state.init_code(contract, code).map_err(|_| CallError::StateCorrupt)?;
+		for (key, value) in storage {
+			state.set_storage(contract, key, value).map_err(|_| CallError::StateCorrupt)?;
+		}
+
+		let sender = t.sender();
+		let balance = state.balance(&sender).map_err(|_| CallError::StateCorrupt)?;
+		let needed_balance = t.value + t.gas * t.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
+				.map_err(|_| CallError::StateCorrupt)?;
+		}
+		let options = TransactOptions { tracing: false, vm_tracing: false, check_nonce: false };
+		let executed = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options)?;
+
+		let storage = state.to_pod().get().get(contract)
+			.map(|account| account.storage.iter()
+				.filter(|&(_, value)| !value.is_zero())
+				.map(|(key, value)| (*key, *value))
+				.collect())
+			.unwrap_or_else(BTreeMap::new);
+
+		Ok((executed, storage))
+	}
+
 	/// Get a copy of the best block's state.
 	pub fn state(&self) -> State<StateDB> {
 		let header = self.best_block_header();
@@ -1426,6 +1487,18 @@ impl BlockChainClient for Client {
 		self.miner.ready_transactions(number, timestamp)
 	}
 
+	fn queued_transactions(&self) -> Vec<PendingTransaction> {
+		self.miner.pending_transactions()
+	}
+
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.miner.queued_transaction(hash)
+	}
+
+	fn fork_transitions(&self) -> Vec<BlockNumber> {
+		self.engine.fork_transitions()
+	}
+
 	fn queue_consensus_message(&self, message: Bytes) {
 		let channel = self.io_channel.lock().clone();
 		if let Err(e) = channel.send(ClientIoMessage::NewMessage(message)) {
@@ -1508,6 +1581,10 @@ impl MiningBlockChainClient for Client {
 		self.engine.schedule(&self.latest_env_info())
 	}
 
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence> {
+		self.engine.malicious_evidence()
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.engine;
 		let chain = self.chain.read();
@@ -1647,6 +1724,33 @@ impl ::client::ProvingBlockChainClient for Client {
 	}
 }
 
+impl Collector for Client {
+	fn collect(&self) -> Vec<Metric> {
+		let report = self.report();
+		let queue = self.queue_info();
+		let cache = self.blockchain_cache_info();
+
+		vec![
+			Metric::counter("parity_blocks_imported_total", "Blocks imported since startup.", report.blocks_imported as f64),
+			Metric::counter("parity_transactions_applied_total", "Transactions applied since startup.", report.transactions_applied as f64),
+			Metric::counter("parity_gas_processed_total", "Gas processed since startup.", report.gas_processed.low_u64() as f64),
+			Metric::gauge("parity_best_block", "Number of the best block.", self.chain_info().best_block_number as f64),
+			Metric::new("parity_block_queue_size", "Blocks in the verification queue by stage.", MetricType::Gauge)
+				.with_sample(&[("stage", "unverified")], queue.unverified_queue_size as f64)
+				.with_sample(&[("stage", "verifying")], queue.verifying_queue_size as f64)
+				.with_sample(&[("stage", "verified")], queue.verified_queue_size as f64),
+			Metric::new("parity_cache_bytes", "Memory used by caches in bytes.", MetricType::Gauge)
+				.with_sample(&[("cache", "blocks")], cache.blocks as f64)
+				.with_sample(&[("cache", "block_details")], cache.block_details as f64)
+				.with_sample(&[("cache", "transaction_addresses")], cache.transaction_addresses as f64)
+				.with_sample(&[("cache", "blocks_blooms")], cache.blocks_blooms as f64)
+				.with_sample(&[("cache", "block_receipts")], cache.block_receipts as f64)
+				.with_sample(&[("cache", "state_db")], report.state_db_mem as f64)
+				.with_sample(&[("cache", "block_queue")], queue.mem_used as f64),
+		]
+	}
+}
+
 impl Drop for Client {
 	fn drop(&mut self) {
 		self.engine.stop();
@@ -23,6 +23,7 @@ pub use trace::Config as TraceConfig;
 pub use evm::VMType;
 
 use verification::{VerifierType, QueueConfig};
+use snapshot::SnapshotConfiguration;
 use util::{journaldb, CompactionProfile};
 
 /// Client state db compaction profile
@@ -137,6 +138,8 @@ pub struct ClientConfig {
 	pub history_mem: usize,
 	/// Check seal valididity on block import
 	pub check_seal: bool,
+	/// Retention and export of periodic snapshots.
+	pub snapshot: SnapshotConfiguration,
 }
 
 #[cfg(test)]
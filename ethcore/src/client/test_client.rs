@@ -49,6 +49,7 @@ use error::CallError;
 use trace::LocalizedTrace;
 use state_db::StateDB;
 use encoded;
+use engines::MaliciousEvidence;
 
 /// Test client.
 pub struct TestBlockChainClient {
@@ -356,6 +357,10 @@ impl MiningBlockChainClient for TestBlockChainClient {
 		Schedule::new_post_eip150(24576, true, true, true)
 	}
 
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence> {
+		self.spec.engine.malicious_evidence()
+	}
+
 	fn prepare_open_block(&self, author: Address, gas_range_target: (U256, U256), extra_data: Bytes) -> OpenBlock {
 		let engine = &*self.spec.engine;
 		let genesis_header = self.spec.genesis_header();
@@ -716,6 +721,18 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(info.best_block_number, info.best_block_timestamp)
 	}
 
+	fn queued_transactions(&self) -> Vec<PendingTransaction> {
+		self.miner.pending_transactions()
+	}
+
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.miner.queued_transaction(hash)
+	}
+
+	fn fork_transitions(&self) -> Vec<BlockNumber> {
+		self.spec.fork_transitions()
+	}
+
 	fn signing_network_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -42,6 +42,7 @@ use types::block_status::BlockStatus;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
 use encoded;
+use engines::MaliciousEvidence;
 
 #[ipc(client_ident="RemoteClient")]
 /// Blockchain database client. Owns and manages a blockchain and a block queue.
@@ -212,6 +213,15 @@ pub trait BlockChainClient : Sync + Send {
 	/// List all transactions that are allowed into the next block.
 	fn ready_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// List all transactions in the queue, including ones not yet ready.
+	fn queued_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get a transaction from the queue by hash.
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction>;
+
+	/// Blocks where consensus rules of the chain change.
+	fn fork_transitions(&self) -> Vec<BlockNumber>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -296,6 +306,9 @@ pub trait MiningBlockChainClient: BlockChainClient {
 
 	/// Returns latest schedule.
 	fn latest_schedule(&self) -> Schedule;
+
+	/// Returns evidence of validator misbehaviour detected by the engine.
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence>;
 }
 
 /// Client facilities used by internally sealing Engines.
@@ -21,12 +21,13 @@ use std::sync::Weak;
 use std::time::{UNIX_EPOCH, Duration};
 use util::*;
 use ethkey::{verify_address, Signature};
-use rlp::{UntrustedRlp, encode};
+use rlp::{UntrustedRlp, RlpStream, encode};
 use account_provider::AccountProvider;
 use block::*;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use engines::{Engine, Seal, EngineError};
-use header::Header;
+use header::{Header, BlockNumber};
 use error::{Error, TransactionError, BlockError};
 use evm::Schedule;
 use ethjson;
@@ -35,9 +36,14 @@ use env_info::EnvInfo;
 use builtin::Builtin;
 use transaction::UnverifiedTransaction;
 use client::{Client, EngineClient};
-use state::CleanupMode;
+use trace::trace::RewardType;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, new_validator_set};
+use super::block_reward::{BlockRewardContract, RewardKind, apply_block_rewards};
+use super::evidence::{EvidenceStore, MaliciousEvidence};
+
+/// Number of past steps for which signed proposals are remembered to detect double proposals.
+const MAX_TRACKED_STEPS: usize = 128;
 
 /// `AuthorityRound` params.
 #[derive(Debug, PartialEq)]
@@ -48,6 +54,8 @@ pub struct AuthorityRoundParams {
 	pub step_duration: Duration,
 	/// Block reward.
 	pub block_reward: U256,
+	/// Block reward contract address, overrides `block_reward` if present.
+	pub block_reward_contract_address: Option<Address>,
 	/// Namereg contract address.
 	pub registrar: Address,
 	/// Starting step,
@@ -67,6 +75,7 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			step_duration: Duration::from_secs(p.step_duration.into()),
 			validators: p.validators,
 			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_contract_address: p.block_reward_contract_address.map(Into::into),
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
 			start_step: p.start_step.map(Into::into),
 			validate_score_transition: p.validate_score_transition.map_or(0, Into::into),
@@ -79,8 +88,10 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 /// mainnet chains in the Olympic, Frontier and Homestead eras.
 pub struct AuthorityRound {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	gas_limit_bound_divisor: U256,
 	block_reward: U256,
+	block_reward_contract: Option<BlockRewardContract>,
 	registrar: Address,
 	step_duration: Duration,
 	builtins: BTreeMap<Address, Builtin>,
@@ -90,6 +101,10 @@ pub struct AuthorityRound {
 	client: RwLock<Option<Weak<EngineClient>>>,
 	signer: EngineSigner,
 	validators: Box<ValidatorSet>,
+	/// Headers signed by each proposer at recent steps.
+	step_proposals: RwLock<BTreeMap<(usize, Address), Header>>,
+	/// Evidence of detected validator misbehaviour.
+	evidence: EvidenceStore,
 	/// Is this Engine just for testing (prevents step calibration).
 	calibrate_step: bool,
 	validate_score_transition: u64,
@@ -121,9 +136,11 @@ impl AuthorityRound {
 		let initial_step = our_params.start_step.unwrap_or_else(|| (unix_now().as_secs() / our_params.step_duration.as_secs())) as usize;
 		let engine = Arc::new(
 			AuthorityRound {
+				tx_filter: TransactionFilter::from_params(&params),
 				params: params,
 				gas_limit_bound_divisor: our_params.gas_limit_bound_divisor,
 				block_reward: our_params.block_reward,
+				block_reward_contract: our_params.block_reward_contract_address.map(BlockRewardContract::new),
 				registrar: our_params.registrar,
 				step_duration: our_params.step_duration,
 				builtins: builtins,
@@ -133,6 +150,8 @@ impl AuthorityRound {
 				client: RwLock::new(None),
 				signer: Default::default(),
 				validators: new_validator_set(our_params.validators),
+				step_proposals: RwLock::new(BTreeMap::new()),
+				evidence: Default::default(),
 				calibrate_step: our_params.start_step.is_none(),
 				validate_score_transition: our_params.validate_score_transition,
 				eip155_transition: our_params.eip155_transition,
@@ -161,6 +180,36 @@ impl AuthorityRound {
 		}
 	}
 
+	/// Remember the header signed at the given step and return a different one if the proposer already signed it.
+	fn conflicting_proposal(&self, step: usize, header: &Header) -> Option<Header> {
+		let mut proposals = self.step_proposals.write();
+		if let Some(previous) = proposals.get(&(step, *header.author())) {
+			if previous.bare_hash() == header.bare_hash() {
+				return None;
+			}
+			return Some(previous.clone());
+		}
+		proposals.insert((step, *header.author()), header.clone());
+		let oldest = step.saturating_sub(MAX_TRACKED_STEPS);
+		if proposals.keys().next().map_or(false, |&(s, _)| s < oldest) {
+			let recent = proposals.split_off(&(oldest, Address::default()));
+			*proposals = recent;
+		}
+		None
+	}
+
+	/// Report the author of two headers signed for the same step.
+	fn report_double_proposal(&self, first: &Header, second: &Header) {
+		let mut proof = RlpStream::new_list(2);
+		proof.append(first).append(second);
+		let evidence = MaliciousEvidence {
+			validator: *second.author(),
+			block_number: second.number(),
+			proof: proof.out(),
+		};
+		self.evidence.report(&*self.validators, evidence);
+	}
+
 	fn step_proposer(&self, bh: &H256, step: usize) -> Address {
 		self.validators.get(bh, step)
 	}
@@ -218,6 +267,13 @@ impl Engine for AuthorityRound {
 	fn seal_fields(&self) -> usize { 2 }
 
 	fn params(&self) -> &CommonParams { &self.params }
+	fn transaction_filter(&self) -> Option<&TransactionFilter> { self.tx_filter.as_ref() }
+
+	fn transitions(&self) -> Vec<BlockNumber> {
+		let mut transitions = vec![self.validate_score_transition, self.eip155_transition];
+		transitions.extend(self.validators.transitions());
+		transitions
+	}
 
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.registrar.hex()] }
 
@@ -288,12 +344,13 @@ impl Engine for AuthorityRound {
 
 	/// Apply the block reward on finalisation of the block.
 	fn on_close_block(&self, block: &mut ExecutedBlock) {
-		let fields = block.fields_mut();
-		// Bestow block reward
-		let res = fields.state.add_balance(fields.header.author(), &self.block_reward, CleanupMode::NoEmpty)
-			.map_err(::error::Error::from)
-			.and_then(|_| fields.state.commit());
-		// Commit state so that we can actually figure out the state root.
+		let author = block.header().author().clone();
+		// Bestow block reward, either the fixed one or as decided by the reward contract.
+		let res = match self.block_reward_contract {
+			Some(ref contract) => contract.reward(&[(author, RewardKind::Author)], block, self)
+				.and_then(|rewards| apply_block_rewards(&rewards, RewardType::External, block)),
+			None => apply_block_rewards(&[(author, self.block_reward)], RewardType::Block, block),
+		};
 		if let Err(e) = res {
 			warn!("Encountered error on closing block: {}", e);
 		}
@@ -334,6 +391,11 @@ impl Engine for AuthorityRound {
 				trace!(target: "engine", "verify_block_unordered: bad proposer for step: {}", step);
 				Err(EngineError::NotProposer(Mismatch { expected: correct_proposer, found: header.author().clone() }))?
 			}
+			// Siblings may arrive in any order, so the block is still accepted to avoid forking.
+			if let Some(previous) = self.conflicting_proposal(step, header) {
+				trace!(target: "engine", "Proposer {} signed different blocks for step {}.", header.author(), step);
+				self.report_double_proposal(&previous, header);
+			}
 		}
 
 		// Do not calculate difficulty for genesis blocks.
@@ -345,7 +407,7 @@ impl Engine for AuthorityRound {
 		let parent_step = header_step(parent)?;
 		if step == parent_step {
 			trace!(target: "engine", "Multiple blocks proposed for step {}.", step);
-			self.validators.report_malicious(header.author());
+			self.report_double_proposal(parent, header);
 			Err(EngineError::DoubleVote(header.author().clone()))?;
 		}
 
@@ -382,6 +444,10 @@ impl Engine for AuthorityRound {
 	fn sign(&self, hash: H256) -> Result<Signature, Error> {
 		self.signer.sign(hash).map_err(Into::into)
 	}
+
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence> {
+		self.evidence.all()
+	}
 }
 
 #[cfg(test)]
@@ -391,12 +457,13 @@ mod tests {
 	use header::Header;
 	use error::{Error, BlockError};
 	use ethkey::Secret;
-	use rlp::encode;
+	use rlp::{encode, UntrustedRlp};
 	use block::*;
 	use tests::helpers::*;
 	use account_provider::AccountProvider;
 	use spec::Spec;
 	use engines::Seal;
+	use trace::trace::{Action, RewardType};
 
 	#[test]
 	fn has_valid_metadata() {
@@ -500,6 +567,70 @@ mod tests {
 		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
 	}
 
+	#[test]
+	fn detects_double_proposal_for_step() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(Secret::from_slice(&"0".sha3()).unwrap(), "0").unwrap();
+		let mut parent_header: Header = Header::default();
+		parent_header.set_seal(vec![encode(&0usize).to_vec()]);
+		parent_header.set_gas_limit(U256::from_str("222222").unwrap());
+		let mut header: Header = Header::default();
+		header.set_number(1);
+		header.set_gas_limit(U256::from_str("222222").unwrap());
+		header.set_author(addr);
+
+		let engine = Spec::new_test_round().engine;
+
+		let signature = tap.sign(addr, Some("0".into()), header.bare_hash()).unwrap();
+		header.set_seal(vec![encode(&1usize).to_vec(), encode(&(&*signature as &[u8])).to_vec()]);
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+		// Verifying the same block again is fine.
+		assert!(engine.verify_block_family(&header, &parent_header, None).is_ok());
+		assert!(engine.malicious_evidence().is_empty());
+
+		// A different block signed by the same proposer for the same step
+		// is still accepted, but reported.
+		let mut other = header.clone();
+		other.set_gas_limit(U256::from_str("222223").unwrap());
+		let signature = tap.sign(addr, Some("0".into()), other.bare_hash()).unwrap();
+		other.set_seal(vec![encode(&1usize).to_vec(), encode(&(&*signature as &[u8])).to_vec()]);
+		assert!(engine.verify_block_family(&other, &parent_header, None).is_ok());
+
+		let evidence = engine.malicious_evidence();
+		assert_eq!(evidence.len(), 1);
+		assert_eq!(evidence[0].validator, addr);
+		assert_eq!(evidence[0].block_number, 1);
+		let proof = UntrustedRlp::new(&evidence[0].proof);
+		assert_eq!(proof.val_at::<Header>(0).unwrap(), header);
+		assert_eq!(proof.val_at::<Header>(1).unwrap(), other);
+	}
+
+	#[test]
+	fn block_reward_contract_decides_rewards() {
+		let spec = Spec::new_test_round_block_reward_contract();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let author = Address::from(0x11);
+		let b = OpenBlock::new(engine, Default::default(), true, db, &genesis_header, last_hashes, author, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+
+		// The test contract ignores the benefactors and rewards 0x99 with 1000 wei.
+		assert_eq!(b.block().fields().state.balance(&Address::from(0x99)).unwrap(), 1000.into());
+		assert_eq!(b.block().fields().state.balance(&author).unwrap(), 0.into());
+		let traces = b.block().traces().clone().unwrap();
+		assert_eq!(traces.len(), 1);
+		match traces[0][0].action {
+			Action::Reward(ref reward) => {
+				assert_eq!(reward.author, Address::from(0x99));
+				assert_eq!(reward.value, 1000.into());
+				assert_eq!(reward.reward_type, RewardType::External);
+			},
+			_ => panic!("expected a reward trace"),
+		}
+	}
+
 	#[test]
 	fn rejects_future_block() {
 		let tap = AccountProvider::transient_provider();
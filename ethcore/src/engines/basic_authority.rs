@@ -23,12 +23,13 @@ use account_provider::AccountProvider;
 use block::*;
 use builtin::Builtin;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use engines::{Engine, Seal};
 use env_info::EnvInfo;
 use error::{BlockError, Error};
 use evm::Schedule;
 use ethjson;
-use header::Header;
+use header::{Header, BlockNumber};
 use client::Client;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, new_validator_set};
@@ -55,6 +56,7 @@ impl From<ethjson::spec::BasicAuthorityParams> for BasicAuthorityParams {
 /// mainnet chains in the Olympic, Frontier and Homestead eras.
 pub struct BasicAuthority {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	gas_limit_bound_divisor: U256,
 	builtins: BTreeMap<Address, Builtin>,
 	signer: EngineSigner,
@@ -65,6 +67,7 @@ impl BasicAuthority {
 	/// Create a new instance of BasicAuthority engine
 	pub fn new(params: CommonParams, our_params: BasicAuthorityParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		BasicAuthority {
+			tx_filter: TransactionFilter::from_params(&params),
 			params: params,
 			gas_limit_bound_divisor: our_params.gas_limit_bound_divisor,
 			builtins: builtins,
@@ -81,8 +84,11 @@ impl Engine for BasicAuthority {
 	fn seal_fields(&self) -> usize { 1 }
 
 	fn params(&self) -> &CommonParams { &self.params }
+	fn transaction_filter(&self) -> Option<&TransactionFilter> { self.tx_filter.as_ref() }
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
 
+	fn transitions(&self) -> Vec<BlockNumber> { self.validators.transitions() }
+
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { map!["signature".to_owned() => "TODO".to_owned()] }
 
@@ -0,0 +1,149 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block rewards: applying them to a block's state and querying a block reward contract.
+
+use std::cell::RefCell;
+use futures::{future, Future};
+use native_contracts::BlockReward as Contract;
+use util::{Address, U256};
+use block::ExecutedBlock;
+use engines::{Engine, EngineError};
+use error::Error;
+use state::CleanupMode;
+use trace::FlatTrace;
+use trace::trace::{Action, Res, Reward, RewardType};
+
+/// Gas budget of the system call made to the block reward contract.
+const REWARD_CONTRACT_GAS: u64 = 50_000_000;
+
+/// The kind of benefactor passed to the block reward contract.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RewardKind {
+	/// Reward attributed to the block author.
+	Author = 0,
+	/// Reward attributed to the author of an included uncle.
+	Uncle = 1,
+}
+
+/// A client for the block reward contract.
+///
+/// The contract should have the following interface:
+/// [{"constant":false,"inputs":[{"name":"benefactors","type":"address[]"},{"name":"kind","type":"uint16[]"}],"name":"reward","outputs":[{"name":"","type":"address[]"},{"name":"","type":"uint256[]"}],"payable":false,"type":"function"}]
+pub struct BlockRewardContract {
+	contract: Contract,
+}
+
+impl BlockRewardContract {
+	/// Create a new block reward contract client targeting the given address.
+	pub fn new(address: Address) -> Self {
+		BlockRewardContract {
+			contract: Contract::new(address),
+		}
+	}
+
+	/// Address of the block reward contract.
+	pub fn address(&self) -> Address {
+		self.contract.address
+	}
+
+	/// Call the block reward contract with the given benefactors and return the reward allocation.
+	/// The call is executed within `block`'s state as a system transaction with a fixed gas budget.
+	pub fn reward(&self, benefactors: &[(Address, RewardKind)], block: &mut ExecutedBlock, engine: &Engine) -> Result<Vec<(Address, U256)>, Error> {
+		let env_info = block.env_info();
+		let state = RefCell::new(block.fields_mut().state);
+		let call = |address, data| future::done(
+			state.borrow_mut()
+				.system_call(&env_info, engine, address, REWARD_CONTRACT_GAS.into(), data)
+				.map_err(|e| format!("{}", e))
+		);
+
+		let (addresses, kinds): (Vec<_>, Vec<_>) = benefactors.iter()
+			.map(|&(address, kind)| (address, kind as u16))
+			.unzip();
+
+		let (beneficiaries, values) = self.contract.reward(call, addresses, kinds)
+			.wait()
+			.map_err(EngineError::FailedSystemCall)?;
+
+		if beneficiaries.len() != values.len() {
+			return Err(EngineError::FailedSystemCall(
+				"Block reward contract returned arrays of different lengths.".into()
+			).into());
+		}
+
+		Ok(beneficiaries.into_iter().zip(values.into_iter()).collect())
+	}
+}
+
+/// Add the given rewards to the beneficiaries' balances and commit the state.
+/// When tracing is enabled every reward is recorded as a reward trace of the given type.
+pub fn apply_block_rewards(rewards: &[(Address, U256)], reward_type: RewardType, block: &mut ExecutedBlock) -> Result<(), Error> {
+	let fields = block.fields_mut();
+	for &(ref beneficiary, ref value) in rewards {
+		fields.state.add_balance(beneficiary, value, CleanupMode::NoEmpty)?;
+	}
+
+	if let Some(ref mut traces) = *fields.traces {
+		traces.push(rewards.iter().map(|&(ref beneficiary, ref value)| FlatTrace {
+			action: Action::Reward(Reward {
+				author: beneficiary.clone(),
+				value: value.clone(),
+				reward_type: reward_type,
+			}),
+			result: Res::None,
+			subtraces: 0,
+			trace_address: Default::default(),
+		}).collect());
+	}
+
+	fields.state.commit()?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use block::*;
+	use spec::Spec;
+	use tests::helpers::*;
+	use trace::trace::{Action, RewardType};
+	use super::apply_block_rewards;
+
+	#[test]
+	fn applies_rewards_and_records_traces() {
+		let spec = Spec::new_test_round();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let mut b = OpenBlock::new(engine, Default::default(), true, db, &genesis_header, last_hashes, Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+
+		let beneficiary = Address::from(0x42);
+		apply_block_rewards(&[(beneficiary, 10.into())], RewardType::External, b.block_mut()).unwrap();
+
+		assert_eq!(b.block().fields().state.balance(&beneficiary).unwrap(), 10.into());
+		let traces = b.block().traces().clone().unwrap();
+		assert_eq!(traces.len(), 1);
+		match traces[0][0].action {
+			Action::Reward(ref reward) => {
+				assert_eq!(reward.author, beneficiary);
+				assert_eq!(reward.reward_type, RewardType::External);
+			},
+			_ => panic!("expected a reward trace"),
+		}
+	}
+}
@@ -0,0 +1,101 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Evidence of malicious validator behaviour, e.g. signing two different blocks or votes for the same step.
+
+use util::*;
+use header::BlockNumber;
+use super::validator_set::ValidatorSet;
+
+/// Maximal number of evidence entries kept in memory.
+const MAX_EVIDENCE: usize = 128;
+
+/// Proof of a validator misbehaving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaliciousEvidence {
+	/// Misbehaving validator.
+	pub validator: Address,
+	/// Block number at which the misbehaviour happened.
+	pub block_number: BlockNumber,
+	/// RLP-encoded proof: the offending signed header or a list of two conflicting signed items.
+	pub proof: Bytes,
+}
+
+/// Keeps track of the most recent evidence and forwards it to the validator set.
+#[derive(Default)]
+pub struct EvidenceStore {
+	evidence: RwLock<VecDeque<MaliciousEvidence>>,
+}
+
+impl EvidenceStore {
+	/// Record the evidence and report the validator, unless it was already reported for this block.
+	pub fn report(&self, validators: &ValidatorSet, evidence: MaliciousEvidence) {
+		{
+			let mut guard = self.evidence.write();
+			if guard.iter().any(|e| e.validator == evidence.validator && e.block_number == evidence.block_number) {
+				trace!(target: "engine", "Validator {} already reported for block {}.", evidence.validator, evidence.block_number);
+				return;
+			}
+			if guard.len() == MAX_EVIDENCE {
+				guard.pop_front();
+			}
+			guard.push_back(evidence.clone());
+		}
+		warn!(target: "engine", "Detected malicious behaviour of validator {} at block {}.", evidence.validator, evidence.block_number);
+		validators.report_malicious(&evidence.validator, evidence.block_number, evidence.proof);
+	}
+
+	/// All evidence currently held.
+	pub fn all(&self) -> Vec<MaliciousEvidence> {
+		self.evidence.read().iter().cloned().collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use engines::validator_set::ValidatorSet;
+	use header::BlockNumber;
+	use super::{EvidenceStore, MaliciousEvidence};
+
+	#[derive(Default)]
+	struct CountingSet {
+		reports: Mutex<Vec<(Address, BlockNumber)>>,
+	}
+
+	impl ValidatorSet for CountingSet {
+		fn contains(&self, _bh: &H256, _address: &Address) -> bool { true }
+		fn get(&self, _bh: &H256, _nonce: usize) -> Address { Address::default() }
+		fn count(&self, _bh: &H256) -> usize { 1 }
+		fn report_malicious(&self, validator: &Address, block: BlockNumber, _proof: Bytes) {
+			self.reports.lock().push((*validator, block));
+		}
+	}
+
+	#[test]
+	fn reports_each_offence_once() {
+		let store = EvidenceStore::default();
+		let set = CountingSet::default();
+		let evidence = MaliciousEvidence { validator: 1.into(), block_number: 5, proof: vec![0xc0] };
+		store.report(&set, evidence.clone());
+		store.report(&set, evidence.clone());
+		store.report(&set, MaliciousEvidence { block_number: 6, ..evidence.clone() });
+
+		assert_eq!(*set.reports.lock(), vec![(1.into(), 5), (1.into(), 6)]);
+		assert_eq!(store.all().len(), 2);
+		assert_eq!(store.all()[0], evidence);
+	}
+}
@@ -20,12 +20,14 @@ use builtin::Builtin;
 use engines::{Engine, Seal};
 use env_info::EnvInfo;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use evm::Schedule;
 use block::ExecutedBlock;
 
 /// An engine which does not provide any consensus mechanism, just seals blocks internally.
 pub struct InstantSeal {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	registrar: Address,
 	builtins: BTreeMap<Address, Builtin>,
 }
@@ -34,6 +36,7 @@ impl InstantSeal {
 	/// Returns new instance of InstantSeal with default VM Factory
 	pub fn new(params: CommonParams, registrar: Address, builtins: BTreeMap<Address, Builtin>) -> Self {
 		InstantSeal {
+			tx_filter: TransactionFilter::from_params(&params),
 			params: params,
 			registrar: registrar,
 			builtins: builtins,
@@ -50,6 +53,10 @@ impl Engine for InstantSeal {
 		&self.params
 	}
 
+	fn transaction_filter(&self) -> Option<&TransactionFilter> {
+		self.tx_filter.as_ref()
+	}
+
 	fn additional_params(&self) -> HashMap<String, String> {
 		hash_map!["registrar".to_owned() => self.registrar.hex()]
 	}
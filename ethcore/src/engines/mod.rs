@@ -25,12 +25,15 @@ mod authority_round;
 mod tendermint;
 mod validator_set;
 mod signer;
+mod evidence;
+pub mod block_reward;
 
 pub use self::null_engine::NullEngine;
 pub use self::instant_seal::InstantSeal;
 pub use self::basic_authority::BasicAuthority;
 pub use self::authority_round::AuthorityRound;
 pub use self::tendermint::Tendermint;
+pub use self::evidence::MaliciousEvidence;
 
 use std::sync::Weak;
 use util::*;
@@ -44,7 +47,12 @@ use spec::CommonParams;
 use evm::Schedule;
 use header::Header;
 use transaction::{UnverifiedTransaction, SignedTransaction};
-use client::Client;
+use tx_filter::TransactionFilter;
+use client::{Client, MiningBlockChainClient};
+use ethash::{CacheOptions as EthashCacheOptions, EthashManager};
+
+/// Address on whose behalf engine-level system calls (e.g. to the block reward contract) are made.
+pub const SYSTEM_ADDRESS: Address = H160([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
 
 /// Voting errors.
 #[derive(Debug)]
@@ -59,6 +67,8 @@ pub enum EngineError {
 	UnexpectedMessage,
 	/// Seal field has an unexpected size.
 	BadSealFieldSize(OutOfBounds<usize>),
+	/// A system call (e.g. to the block reward contract) failed.
+	FailedSystemCall(String),
 }
 
 impl fmt::Display for EngineError {
@@ -70,6 +80,7 @@ impl fmt::Display for EngineError {
 			NotAuthorized(ref address) => format!("Signer {} is not authorized.", address),
 			UnexpectedMessage => "This Engine should not be fed messages.".into(),
 			BadSealFieldSize(ref oob) => format!("Seal field has an unexpected length: {}", oob),
+			FailedSystemCall(ref msg) => format!("Failed to make system call: {}", msg),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
@@ -107,6 +118,25 @@ pub trait Engine : Sync + Send {
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
+	/// Transaction permission filter, if the chain spec names a permission contract.
+	fn transaction_filter(&self) -> Option<&TransactionFilter> { None }
+
+	/// Block numbers where engine-specific consensus rules change.
+	fn transitions(&self) -> Vec<::header::BlockNumber> { Vec::new() }
+
+	/// All blocks where consensus rules change, sorted and without duplicates.
+	/// Transitions active from genesis or never activated are omitted.
+	fn fork_transitions(&self) -> Vec<::header::BlockNumber> {
+		// specs disable a transition with 0x7fffffffffffff, 0x7fffffffffffffff or u64::max_value()
+		let mut transitions: Vec<_> = self.params().transitions().into_iter()
+			.chain(self.transitions())
+			.filter(|n| *n != 0 && *n < 0x7fffffffffffff)
+			.collect();
+		transitions.sort();
+		transitions.dedup();
+		transitions
+	}
+
 	/// Get the EVM schedule for the given `env_info`.
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule;
 
@@ -172,6 +202,15 @@ pub trait Engine : Sync + Send {
 		SignedTransaction::new(t)
 	}
 
+	/// Verify a transaction against the state of the chain on top of `parent_hash`.
+	/// Runs after `verify_transaction`; by default consults the transaction permission contract.
+	fn verify_transaction_family(&self, t: &SignedTransaction, parent_hash: &H256, client: &MiningBlockChainClient) -> Result<(), Error> {
+		match self.transaction_filter() {
+			Some(filter) if !filter.transaction_allowed(parent_hash, t, client) => Err(TransactionError::NotAllowed.into()),
+			_ => Ok(()),
+		}
+	}
+
 	/// The network ID that transactions should be signed with.
 	fn signing_network_id(&self, _env_info: &EnvInfo) -> Option<u64> {
 		Some(self.params().chain_id)
@@ -224,4 +263,14 @@ pub trait Engine : Sync + Send {
 
 	/// Stops any services that the may hold the Engine and makes it safe to drop.
 	fn stop(&self) {}
+
+	/// Evidence of validator misbehaviour detected by the engine so far.
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence> { Vec::new() }
+
+	/// Configure on-disk storage of proof-of-work caches. Ignored by engines without proof-of-work.
+	fn set_pow_cache_options(&self, _options: EthashCacheOptions) {}
+
+	/// Proof-of-work cache manager verifying seals, to be shared with the internal miner.
+	/// `None` for engines without proof-of-work.
+	fn pow_cache(&self) -> Option<Arc<EthashManager>> { None }
 }
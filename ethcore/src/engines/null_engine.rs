@@ -19,12 +19,14 @@ use util::Address;
 use builtin::Builtin;
 use engines::Engine;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use evm::Schedule;
 use env_info::EnvInfo;
 
 /// An engine which does not provide any consensus mechanism and does not seal blocks.
 pub struct NullEngine {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	builtins: BTreeMap<Address, Builtin>,
 }
 
@@ -32,6 +34,7 @@ impl NullEngine {
 	/// Returns new instance of NullEngine with default VM Factory
 	pub fn new(params: CommonParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		NullEngine{
+			tx_filter: TransactionFilter::from_params(&params),
 			params: params,
 			builtins: builtins,
 		}
@@ -53,6 +56,10 @@ impl Engine for NullEngine {
 		&self.params
 	}
 
+	fn transaction_filter(&self) -> Option<&TransactionFilter> {
+		self.tx_filter.as_ref()
+	}
+
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
 		&self.builtins
 	}
@@ -24,30 +24,35 @@
 
 mod message;
 mod params;
+mod wal;
 
 use std::sync::Weak;
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use util::*;
 use client::{Client, EngineClient};
 use error::{Error, BlockError};
-use header::Header;
+use header::{Header, BlockNumber};
 use builtin::Builtin;
 use env_info::EnvInfo;
-use rlp::UntrustedRlp;
+use rlp::{UntrustedRlp, encode};
 use ethkey::{recover, public_to_address, Signature};
 use account_provider::AccountProvider;
 use block::*;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use engines::{Engine, Seal, EngineError};
 use evm::Schedule;
-use state::CleanupMode;
 use io::IoService;
+use trace::trace::RewardType;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, new_validator_set};
+use super::block_reward::{BlockRewardContract, RewardKind, apply_block_rewards};
 use super::transition::TransitionHandler;
 use super::vote_collector::VoteCollector;
+use super::evidence::{EvidenceStore, MaliciousEvidence};
 use self::message::*;
 use self::params::TendermintParams;
+use self::wal::{Wal, ConsensusState, SigningCheck};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum Step {
@@ -73,11 +78,13 @@ pub type BlockHash = H256;
 /// Engine using `Tendermint` consensus algorithm, suitable for EVM chain.
 pub struct Tendermint {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	gas_limit_bound_divisor: U256,
 	builtins: BTreeMap<Address, Builtin>,
 	step_service: IoService<Step>,
 	client: RwLock<Option<Weak<EngineClient>>>,
 	block_reward: U256,
+	block_reward_contract: Option<BlockRewardContract>,
 	registrar: Address,
 	/// Blockchain height.
 	height: AtomicUsize,
@@ -101,6 +108,10 @@ pub struct Tendermint {
 	last_proposed: RwLock<H256>,
 	/// Set used to determine the current validators.
 	validators: Box<ValidatorSet>,
+	/// Evidence of detected validator misbehaviour.
+	evidence: EvidenceStore,
+	/// Log of the consensus state and own signed messages, replayed on restart.
+	wal: Wal,
 }
 
 impl Tendermint {
@@ -108,12 +119,14 @@ impl Tendermint {
 	pub fn new(params: CommonParams, our_params: TendermintParams, builtins: BTreeMap<Address, Builtin>) -> Result<Arc<Self>, Error> {
 		let engine = Arc::new(
 			Tendermint {
+				tx_filter: TransactionFilter::from_params(&params),
 				params: params,
 				gas_limit_bound_divisor: our_params.gas_limit_bound_divisor,
 				builtins: builtins,
 				client: RwLock::new(None),
 				step_service: IoService::<Step>::start()?,
 				block_reward: our_params.block_reward,
+				block_reward_contract: our_params.block_reward_contract_address.map(BlockRewardContract::new),
 				registrar: our_params.registrar,
 				height: AtomicUsize::new(1),
 				view: AtomicUsize::new(0),
@@ -126,6 +139,8 @@ impl Tendermint {
 				proposal_parent: Default::default(),
 				last_proposed: Default::default(),
 				validators: new_validator_set(our_params.validators),
+				evidence: Default::default(),
+				wal: Default::default(),
 			});
 		let handler = TransitionHandler::new(Arc::downgrade(&engine) as Weak<Engine>, Box::new(our_params.timeouts));
 		engine.step_service.register_handler(Arc::new(handler))?;
@@ -156,27 +171,75 @@ impl Tendermint {
 		}
 	}
 
+	fn consensus_state(&self) -> ConsensusState {
+		ConsensusState {
+			height: self.height.load(AtomicOrdering::SeqCst),
+			view: self.view.load(AtomicOrdering::SeqCst),
+			step: *self.step.read(),
+			last_lock: self.last_lock.load(AtomicOrdering::SeqCst),
+			lock_change: self.lock_change.read().clone(),
+			proposal: self.proposal.read().clone(),
+		}
+	}
+
+	/// Write the current consensus state to the log.
+	fn persist_state(&self) {
+		self.wal.persist(&self.consensus_state());
+	}
+
+	/// Restore the logged consensus state if it is still for the current height.
+	fn restore_state(&self, state: ConsensusState) {
+		let height = self.height.load(AtomicOrdering::SeqCst);
+		if state.height != height {
+			debug!(target: "engine", "Discarding consensus log for height {}, now at {}.", state.height, height);
+			self.wal.new_height(height);
+			return;
+		}
+		debug!(target: "engine", "Restoring consensus state at height {} view {}.", state.height, state.view);
+		self.view.store(state.view, AtomicOrdering::SeqCst);
+		self.last_lock.store(state.last_lock, AtomicOrdering::SeqCst);
+		*self.step.write() = match state.step {
+			Step::Commit => Step::Propose,
+			step => step,
+		};
+		*self.lock_change.write() = state.lock_change;
+		*self.proposal.write() = state.proposal;
+	}
+
 	fn generate_message(&self, block_hash: Option<BlockHash>) -> Option<Bytes> {
 		let h = self.height.load(AtomicOrdering::SeqCst);
 		let r = self.view.load(AtomicOrdering::SeqCst);
 		let s = *self.step.read();
-		let vote_info = message_info_rlp(&VoteStep::new(h, r, s), block_hash);
-		match self.signer.sign(vote_info.sha3()).map(Into::into) {
-			Ok(signature) => {
-				let message_rlp = message_full_rlp(&signature, &vote_info);
-				let message = ConsensusMessage::new(signature, h, r, s, block_hash);
-				let validator = self.signer.address();
-				self.votes.vote(message.clone(), &validator);
-				debug!(target: "engine", "Generated {:?} as {}.", message, validator);
-				self.handle_valid_message(&message);
-
-				Some(message_rlp)
+		let vote_step = VoteStep::new(h, r, s);
+		let message = match self.wal.check_signing(&vote_step, &block_hash) {
+			SigningCheck::Fresh => {
+				let vote_info = message_info_rlp(&vote_step, block_hash);
+				match self.signer.sign(vote_info.sha3()).map(Into::into) {
+					Ok(signature) => ConsensusMessage::new(signature, h, r, s, block_hash),
+					Err(e) => {
+						trace!(target: "engine", "Could not sign the message {}", e);
+						return None;
+					},
+				}
 			},
-			Err(e) => {
-				trace!(target: "engine", "Could not sign the message {}", e);
-				None
+			SigningCheck::AlreadySigned(message) => {
+				debug!(target: "engine", "Reusing previously signed {:?}.", message);
+				message
 			},
-		}
+			SigningCheck::Conflicting(message) => {
+				warn!(target: "engine", "Refusing to sign a message conflicting with previously signed {:?}.", message);
+				return None;
+			},
+		};
+		// Log the message before it leaves the node.
+		self.wal.record_signed(message.clone());
+		self.persist_state();
+		let validator = self.signer.address();
+		self.votes.vote(message.clone(), &validator);
+		debug!(target: "engine", "Generated {:?} as {}.", message, validator);
+		self.handle_valid_message(&message);
+
+		Some(message_full_rlp(&message.signature, &message_info_rlp(&message.vote_step, message.block_hash)))
 	}
 
 	fn generate_and_broadcast_message(&self, block_hash: Option<BlockHash>) {
@@ -200,6 +263,7 @@ impl Tendermint {
 		self.view.store(0, AtomicOrdering::SeqCst);
 		*self.lock_change.write() = None;
 		*self.proposal.write() = None;
+		self.wal.new_height(new_height);
 	}
 
 	/// Use via step_service to transition steps.
@@ -208,6 +272,7 @@ impl Tendermint {
 			warn!(target: "engine", "Could not proceed to step {}.", io_err)
 		}
 		*self.step.write() = step;
+		self.persist_state();
 		match step {
 			Step::Propose => {
 				self.update_sealing()
@@ -385,6 +450,9 @@ impl Engine for Tendermint {
 	fn seal_fields(&self) -> usize { 3 }
 
 	fn params(&self) -> &CommonParams { &self.params }
+	fn transaction_filter(&self) -> Option<&TransactionFilter> { self.tx_filter.as_ref() }
+
+	fn transitions(&self) -> Vec<BlockNumber> { self.validators.transitions() }
 
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.registrar.hex()] }
 
@@ -441,16 +509,24 @@ impl Engine for Tendermint {
 		let height = header.number() as Height;
 		let view = self.view.load(AtomicOrdering::SeqCst);
 		let bh = Some(header.bare_hash());
-		let vote_info = message_info_rlp(&VoteStep::new(height, view, Step::Propose), bh.clone());
+		let vote_step = VoteStep::new(height, view, Step::Propose);
+		if let SigningCheck::Conflicting(message) = self.wal.check_signing(&vote_step, &bh) {
+			warn!(target: "engine", "generate_seal: Refusing to propose, already proposed {:?}.", message);
+			return Seal::None;
+		}
+		let vote_info = message_info_rlp(&vote_step, bh.clone());
 		if let Ok(signature) = self.signer.sign(vote_info.sha3()).map(Into::into) {
 			// Insert Propose vote.
 			debug!(target: "engine", "Submitting proposal {} at height {} view {}.", header.bare_hash(), height, view);
-			self.votes.vote(ConsensusMessage::new(signature, height, view, Step::Propose, bh), author);
+			let proposal = ConsensusMessage::new(signature, height, view, Step::Propose, bh);
+			self.wal.record_signed(proposal.clone());
+			self.votes.vote(proposal, author);
 			// Remember the owned block.
 			*self.last_proposed.write() = header.bare_hash();
 			// Remember proposal for later seal submission.
 			*self.proposal.write() = bh;
 			*self.proposal_parent.write() = header.parent_hash().clone();
+			self.persist_state();
 			Seal::Proposal(vec![
 				::rlp::encode(&view).to_vec(),
 				::rlp::encode(&signature).to_vec(),
@@ -471,8 +547,13 @@ impl Engine for Tendermint {
 				return Err(EngineError::NotAuthorized(sender).into());
 			}
 			self.broadcast_message(rlp.as_raw().to_vec());
-			if self.votes.vote(message.clone(), &sender).is_some() {
-				self.validators.report_malicious(&sender);
+			if let Some(double) = self.votes.vote(message.clone(), &sender) {
+				let evidence = MaliciousEvidence {
+					validator: *double.author,
+					block_number: message.vote_step.height as BlockNumber,
+					proof: encode(&double).to_vec(),
+				};
+				self.evidence.report(&*self.validators, evidence);
 				return Err(EngineError::DoubleVote(sender).into());
 			}
 			trace!(target: "engine", "Handling a valid {:?} from {}.", message, sender);
@@ -483,12 +564,13 @@ impl Engine for Tendermint {
 
 	/// Apply the block reward on finalisation of the block.
 	fn on_close_block(&self, block: &mut ExecutedBlock) {
-		let fields = block.fields_mut();
-		// Bestow block reward
-		let res = fields.state.add_balance(fields.header.author(), &self.block_reward, CleanupMode::NoEmpty)
-			.map_err(::error::Error::from)
-			.and_then(|_| fields.state.commit());
-		// Commit state so that we can actually figure out the state root.
+		let author = block.header().author().clone();
+		// Bestow block reward, either the fixed one or as decided by the reward contract.
+		let res = match self.block_reward_contract {
+			Some(ref contract) => contract.reward(&[(author, RewardKind::Author)], block, self)
+				.and_then(|rewards| apply_block_rewards(&rewards, RewardType::External, block)),
+			None => apply_block_rewards(&[(author, self.block_reward)], RewardType::Block, block),
+		};
 		if let Err(e) = res {
 			warn!("Encountered error on closing block: {}", e);
 		}
@@ -560,7 +642,12 @@ impl Engine for Tendermint {
 		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
 		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
 		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
-			self.validators.report_malicious(header.author());
+			let evidence = MaliciousEvidence {
+				validator: *header.author(),
+				block_number: header.number(),
+				proof: encode(header).to_vec(),
+			};
+			self.evidence.report(&*self.validators, evidence);
 			return Err(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() }).into());
 		}
 
@@ -646,10 +733,17 @@ impl Engine for Tendermint {
 		use client::BlockChainClient;
 		if let Some(c) = client.upgrade() {
 			self.height.store(c.chain_info().best_block_number as usize + 1, AtomicOrdering::SeqCst);
+			if let Some(state) = self.wal.open(c.db()) {
+				self.restore_state(state);
+			}
 		}
 		*self.client.write() = Some(client.clone());
 		self.validators.register_contract(client);
 	}
+
+	fn malicious_evidence(&self) -> Vec<MaliciousEvidence> {
+		self.evidence.all()
+	}
 }
 
 #[cfg(test)]
@@ -878,6 +972,35 @@ mod tests {
 		assert!(spec.engine.is_proposal(sealed.header()));
 	}
 
+	#[test]
+	fn records_double_vote_evidence() {
+		let (spec, tap) = setup();
+		let engine = spec.engine.clone();
+
+		let v0 = insert_and_unlock(&tap, "0");
+
+		let h = 1;
+		let r = 0;
+		vote(engine.as_ref(), |mh| tap.sign(v0, None, mh).map(H520::from), h, r, Step::Prevote, Some("0".sha3()));
+
+		// Same validator votes for a different block in the same step.
+		let mi = message_info_rlp(&VoteStep::new(h, r, Step::Prevote), Some("1".sha3()));
+		let m = message_full_rlp(&tap.sign(v0, None, mi.sha3()).unwrap().into(), &mi);
+		match engine.handle_message(&m) {
+			Err(Error::Engine(EngineError::DoubleVote(address))) => assert_eq!(address, v0),
+			_ => panic!(),
+		}
+
+		let evidence = engine.malicious_evidence();
+		assert_eq!(evidence.len(), 1);
+		assert_eq!(evidence[0].validator, v0);
+		assert_eq!(evidence[0].block_number, h as BlockNumber);
+		let proof = UntrustedRlp::new(&evidence[0].proof);
+		assert_eq!(proof.item_count().unwrap(), 2);
+		assert_eq!(proof.at(1).unwrap().as_raw(), &m[..]);
+		engine.stop();
+	}
+
 	#[test]
 	fn relays_messages() {
 		let (spec, tap) = setup();
@@ -33,6 +33,8 @@ pub struct TendermintParams {
 	pub timeouts: TendermintTimeouts,
 	/// Block reward.
 	pub block_reward: U256,
+	/// Block reward contract address, overrides `block_reward` if present.
+	pub block_reward_contract_address: Option<Address>,
 	/// Namereg contract address.
 	pub registrar: Address,
 }
@@ -90,6 +92,7 @@ impl From<ethjson::spec::TendermintParams> for TendermintParams {
 				commit: p.timeout_commit.map_or(dt.commit, to_duration),
 			},
 			block_reward: p.block_reward.map_or_else(U256::zero, Into::into),
+			block_reward_contract_address: p.block_reward_contract_address.map(Into::into),
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
 		}
 	}
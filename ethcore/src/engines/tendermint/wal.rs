@@ -0,0 +1,231 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Write-ahead log of the Tendermint consensus state.
+//!
+//! Keeps the current round state and all messages signed by this validator at the current height
+//! in the client database, so that a restarted validator does not sign anything conflicting.
+
+use util::*;
+use util::kvdb::KeyValueDB;
+use rlp::{UntrustedRlp, RlpStream, Encodable, Decodable, DecoderError};
+use db::COL_NODE_INFO;
+use super::{Height, View, BlockHash, Step};
+use super::message::{ConsensusMessage, VoteStep};
+
+const WAL_KEY: &'static [u8] = &*b"TENDERMINT_WAL";
+
+/// Round state of the consensus process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsensusState {
+	pub height: Height,
+	pub view: View,
+	pub step: Step,
+	pub last_lock: View,
+	pub lock_change: Option<ConsensusMessage>,
+	pub proposal: Option<BlockHash>,
+}
+
+/// Result of checking a message against the log before signing it.
+#[derive(Debug, PartialEq)]
+pub enum SigningCheck {
+	/// Nothing was signed at this step yet.
+	Fresh,
+	/// The same message was already signed.
+	AlreadySigned(ConsensusMessage),
+	/// A different message was already signed at this step.
+	Conflicting(ConsensusMessage),
+}
+
+fn encode_step(step: &Step) -> u8 {
+	match *step {
+		Step::Propose => 0,
+		Step::Prevote => 1,
+		Step::Precommit => 2,
+		Step::Commit => 3,
+	}
+}
+
+fn decode_step(rlp: &UntrustedRlp) -> Result<Step, DecoderError> {
+	match rlp.as_val()? {
+		0u8 => Ok(Step::Propose),
+		1 => Ok(Step::Prevote),
+		2 => Ok(Step::Precommit),
+		3 => Ok(Step::Commit),
+		_ => Err(DecoderError::Custom("Invalid step.")),
+	}
+}
+
+fn decode_option<T: Decodable>(rlp: &UntrustedRlp) -> Result<Option<T>, DecoderError> {
+	match rlp.item_count()? {
+		0 => Ok(None),
+		_ => rlp.val_at(0).map(Some),
+	}
+}
+
+struct Entry<'a> {
+	state: &'a ConsensusState,
+	signed: &'a [ConsensusMessage],
+}
+
+impl<'a> Encodable for Entry<'a> {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(7)
+			.append(&self.state.height)
+			.append(&self.state.view)
+			.append(&encode_step(&self.state.step))
+			.append(&self.state.last_lock);
+		match self.state.lock_change {
+			Some(ref m) => { s.begin_list(1).append(m); },
+			None => { s.begin_list(0); },
+		}
+		match self.state.proposal {
+			Some(ref h) => { s.begin_list(1).append(h); },
+			None => { s.begin_list(0); },
+		}
+		s.append_list(self.signed);
+	}
+}
+
+fn decode_entry(rlp: &UntrustedRlp) -> Result<(ConsensusState, Vec<ConsensusMessage>), DecoderError> {
+	let state = ConsensusState {
+		height: rlp.val_at(0)?,
+		view: rlp.val_at(1)?,
+		step: decode_step(&rlp.at(2)?)?,
+		last_lock: rlp.val_at(3)?,
+		lock_change: decode_option(&rlp.at(4)?)?,
+		proposal: decode_option(&rlp.at(5)?)?,
+	};
+	Ok((state, rlp.list_at(6)?))
+}
+
+/// Consensus state log backed by the client database.
+#[derive(Default)]
+pub struct Wal {
+	db: RwLock<Option<Arc<KeyValueDB>>>,
+	/// Messages signed by this validator at the current height.
+	signed: RwLock<Vec<ConsensusMessage>>,
+}
+
+impl Wal {
+	/// Start logging to the database and return the previously logged state, if any.
+	pub fn open(&self, db: Arc<KeyValueDB>) -> Option<ConsensusState> {
+		let stored = match db.get(COL_NODE_INFO, WAL_KEY) {
+			Ok(stored) => stored,
+			Err(e) => {
+				warn!(target: "engine", "Could not read consensus log: {}", e);
+				None
+			},
+		};
+		*self.db.write() = Some(db);
+		let (state, signed) = match stored.map(|entry| decode_entry(&UntrustedRlp::new(&entry))) {
+			Some(Ok(entry)) => entry,
+			Some(Err(e)) => {
+				warn!(target: "engine", "Corrupted consensus log: {}", e);
+				return None;
+			},
+			None => return None,
+		};
+		debug!(target: "engine", "Replaying consensus log at height {} view {}, {} signed messages.", state.height, state.view, signed.len());
+		*self.signed.write() = signed;
+		Some(state)
+	}
+
+	/// Check whether a message for the given step can be signed without equivocating.
+	pub fn check_signing(&self, vote_step: &VoteStep, block_hash: &Option<BlockHash>) -> SigningCheck {
+		match self.signed.read().iter().find(|m| m.vote_step == *vote_step) {
+			Some(m) if m.block_hash == *block_hash => SigningCheck::AlreadySigned(m.clone()),
+			Some(m) => SigningCheck::Conflicting(m.clone()),
+			None => SigningCheck::Fresh,
+		}
+	}
+
+	/// Remember a message signed by this validator.
+	pub fn record_signed(&self, message: ConsensusMessage) {
+		let mut signed = self.signed.write();
+		if !signed.contains(&message) {
+			signed.push(message);
+		}
+	}
+
+	/// Forget messages signed for heights lower than the given one.
+	pub fn new_height(&self, height: Height) {
+		self.signed.write().retain(|m| m.vote_step.height >= height);
+	}
+
+	/// Write the state together with the signed messages to the database.
+	pub fn persist(&self, state: &ConsensusState) {
+		if let Some(ref db) = *self.db.read() {
+			let signed = self.signed.read();
+			let entry = Entry { state: state, signed: &signed };
+			let mut batch = db.transaction();
+			batch.put_vec(COL_NODE_INFO, WAL_KEY, ::rlp::encode(&entry).to_vec());
+			if let Err(e) = db.write(batch) {
+				warn!(target: "engine", "Could not write consensus log: {}", e);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use util::kvdb::{in_memory, KeyValueDB};
+	use db::NUM_COLUMNS;
+	use engines::tendermint::Step;
+	use engines::tendermint::message::{ConsensusMessage, VoteStep};
+	use super::{Wal, ConsensusState, SigningCheck};
+
+	fn state(height: usize) -> ConsensusState {
+		ConsensusState {
+			height: height,
+			view: 2,
+			step: Step::Precommit,
+			last_lock: 1,
+			lock_change: Some(ConsensusMessage::new(H520::default(), height, 1, Step::Prevote, Some("1".sha3()))),
+			proposal: Some("1".sha3()),
+		}
+	}
+
+	#[test]
+	fn replays_state_and_signed_messages() {
+		let db: Arc<KeyValueDB> = Arc::new(in_memory(NUM_COLUMNS.unwrap_or(0)));
+		let wal = Wal::default();
+		assert_eq!(wal.open(db.clone()), None);
+
+		let prevote = ConsensusMessage::new(H520::random(), 5, 2, Step::Prevote, Some("1".sha3()));
+		wal.record_signed(prevote.clone());
+		wal.persist(&state(5));
+
+		// Simulate a restart.
+		let restarted = Wal::default();
+		assert_eq!(restarted.open(db), Some(state(5)));
+		let step = VoteStep::new(5, 2, Step::Prevote);
+		assert_eq!(restarted.check_signing(&step, &Some("1".sha3())), SigningCheck::AlreadySigned(prevote.clone()));
+		assert_eq!(restarted.check_signing(&step, &Some("2".sha3())), SigningCheck::Conflicting(prevote));
+		assert_eq!(restarted.check_signing(&VoteStep::new(5, 3, Step::Prevote), &None), SigningCheck::Fresh);
+	}
+
+	#[test]
+	fn forgets_old_heights() {
+		let wal = Wal::default();
+		let step = VoteStep::new(5, 0, Step::Precommit);
+		wal.record_signed(ConsensusMessage::new(H520::random(), 5, 0, Step::Precommit, None));
+		assert!(wal.check_signing(&step, &Some("1".sha3())) != SigningCheck::Fresh);
+		wal.new_height(6);
+		assert_eq!(wal.check_signing(&step, &Some("1".sha3())), SigningCheck::Fresh);
+	}
+}
@@ -20,11 +20,13 @@
 use std::sync::Weak;
 use util::*;
 use client::{Client, BlockChainClient};
+use header::BlockNumber;
 use super::ValidatorSet;
 use super::safe_contract::ValidatorSafeContract;
 
 /// The validator contract should have the following interface:
 /// [{"constant":true,"inputs":[],"name":"getValidators","outputs":[{"name":"","type":"address[]"}],"payable":false,"type":"function"}]
+/// Malicious validators are reported with `reportMalicious(address)`; the proof is kept locally as evidence.
 pub struct ValidatorContract {
 	validators: ValidatorSafeContract,
 	provider: RwLock<Option<provider::Contract>>,
@@ -52,7 +54,7 @@ impl ValidatorSet for ValidatorContract {
 		self.validators.count(bh)
 	}
 
-	fn report_malicious(&self, address: &Address) {
+	fn report_malicious(&self, address: &Address, _block: BlockNumber, _proof: Bytes) {
 		if let Some(ref provider) = *self.provider.read() {
 			match provider.report_malicious(address) {
 				Ok(_) => warn!(target: "engine", "Reported malicious validator {}", address),
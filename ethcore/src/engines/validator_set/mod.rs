@@ -22,9 +22,10 @@ mod contract;
 mod multi;
 
 use std::sync::Weak;
-use util::{Address, H256};
+use util::{Address, H256, Bytes};
 use ethjson::spec::ValidatorSet as ValidatorSpec;
 use client::Client;
+use header::BlockNumber;
 use self::simple_list::SimpleList;
 use self::contract::ValidatorContract;
 use self::safe_contract::ValidatorSafeContract;
@@ -49,10 +50,12 @@ pub trait ValidatorSet: Send + Sync {
 	fn get(&self, parent_block_hash: &H256, nonce: usize) -> Address;
 	/// Returns the current number of validators.
 	fn count(&self, parent_block_hash: &H256) -> usize;
-	/// Notifies about malicious behaviour.
-	fn report_malicious(&self, _validator: &Address) {}
+	/// Notifies about malicious behaviour, together with a proof of it.
+	fn report_malicious(&self, _validator: &Address, _block: BlockNumber, _proof: Bytes) {}
 	/// Notifies about benign misbehaviour.
 	fn report_benign(&self, _validator: &Address) {}
 	/// Allows blockchain state access.
 	fn register_contract(&self, _client: Weak<Client>) {}
+	/// Blocks where the validator set is switched.
+	fn transitions(&self) -> Vec<BlockNumber> { Vec::new() }
 }
@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::Weak;
-use util::{H256, Address, RwLock};
+use util::{H256, Address, RwLock, Bytes};
 use ids::BlockId;
 use header::BlockNumber;
 use client::{Client, BlockChainClient};
@@ -78,9 +78,9 @@ impl ValidatorSet for Multi {
 		self.correct_set(bh).map_or_else(usize::max_value, |set| set.count(bh))
 	}
 
-	fn report_malicious(&self, validator: &Address) {
+	fn report_malicious(&self, validator: &Address, block: BlockNumber, proof: Bytes) {
 		for set in self.sets.values() {
-			set.report_malicious(validator);
+			set.report_malicious(validator, block, proof.clone());
 		}
 	}
 
@@ -99,6 +99,12 @@ impl ValidatorSet for Multi {
 			.ok_or("No client!".into())
 			.and_then(|c| c.block_number(BlockId::Hash(*hash)).ok_or("Unknown block".into())));
 	}
+
+	fn transitions(&self) -> Vec<BlockNumber> {
+		self.sets.keys().cloned()
+			.chain(self.sets.values().flat_map(|set| set.transitions()))
+			.collect()
+	}
 }
 
 #[cfg(test)]
@@ -155,4 +161,10 @@ mod tests {
 		sync_client.flush_queue();
 		assert_eq!(sync_client.chain_info().best_block_number, 3);
 	}
+
+	#[test]
+	fn reports_set_transitions() {
+		let spec = Spec::new_validator_multi();
+		assert_eq!(spec.engine.fork_transitions(), vec![2]);
+	}
 }
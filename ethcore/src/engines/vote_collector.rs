@@ -18,7 +18,7 @@
 
 use std::fmt::Debug;
 use util::*;
-use rlp::Encodable;
+use rlp::{Encodable, RlpStream};
 
 pub trait Message: Clone + PartialEq + Eq + Hash + Encodable + Debug {
 	type Round: Clone + PartialEq + Eq + Hash + Default + Debug + Ord;
@@ -40,26 +40,46 @@ pub struct VoteCollector<M: Message> {
 
 #[derive(Debug, Default)]
 struct StepCollector<M: Message> {
-	voted: HashSet<Address>,
+	voted: HashMap<Address, M>,
 	pub block_votes: HashMap<Option<H256>, HashMap<H520, Address>>,
 	messages: HashSet<M>,
 }
 
+/// Two different messages signed by the same validator for a single round.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DoubleVote<'a, M: Message> {
+	pub author: &'a Address,
+	pub vote_one: M,
+	pub vote_two: M,
+}
+
+impl<'a, M: Message> Encodable for DoubleVote<'a, M> {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2)
+			.append(&self.vote_one)
+			.append(&self.vote_two);
+	}
+}
+
 impl <M: Message> StepCollector<M> {
-	/// Returns Some(&Address) when validator is double voting.
-	fn insert<'a>(&mut self, message: M, address: &'a Address) -> Option<&'a Address> {
+	/// Returns Some(DoubleVote) when validator is double voting.
+	fn insert<'a>(&mut self, message: M, address: &'a Address) -> Option<DoubleVote<'a, M>> {
 		// Do nothing when message was seen.
 		if self.messages.insert(message.clone()) {
-			if self.voted.insert(address.clone()) {
-				self
-					.block_votes
-					.entry(message.block_hash())
-					.or_insert_with(HashMap::new)
-					.insert(message.signature(), address.clone());
-			} else {
+			if let Some(previous) = self.voted.get(address) {
 				// Bad validator sent a different message.
-				return Some(address);
+				return Some(DoubleVote {
+					author: address,
+					vote_one: previous.clone(),
+					vote_two: message,
+				});
 			}
+			self.voted.insert(address.clone(), message.clone());
+			self
+				.block_votes
+				.entry(message.block_hash())
+				.or_insert_with(HashMap::new)
+				.insert(message.signature(), address.clone());
 		}
 		None
 	}
@@ -101,7 +121,7 @@ impl <M: Message + Default> Default for VoteCollector<M> {
 
 impl <M: Message + Default + Encodable + Debug> VoteCollector<M> {
 	/// Insert vote if it is newer than the oldest one.
-	pub fn vote<'a>(&self, message: M, voter: &'a Address) -> Option<&'a Address> {
+	pub fn vote<'a>(&self, message: M, voter: &'a Address) -> Option<DoubleVote<'a, M>> {
 		self
 			.votes
 			.write()
@@ -223,7 +243,7 @@ mod tests {
 		full_vote(collector, signature, step, block_hash, &H160::random()).is_none()
 	}
 
-	fn full_vote<'a>(collector: &VoteCollector<TestMessage>, signature: H520, step: TestStep, block_hash: Option<H256>, address: &'a Address) -> Option<&'a Address> {
+	fn full_vote<'a>(collector: &VoteCollector<TestMessage>, signature: H520, step: TestStep, block_hash: Option<H256>, address: &'a Address) -> Option<DoubleVote<'a, TestMessage>> {
 		collector.vote(TestMessage { signature: signature, step: step, block_hash: block_hash }, address)
 	}
 
@@ -318,10 +338,15 @@ mod tests {
 	fn malicious_authority() {
 		let collector = VoteCollector::default();
 		let round = 3;
+		let first = TestMessage { signature: H520::random(), step: round, block_hash: Some("0".sha3()) };
+		let second = TestMessage { signature: H520::random(), step: round, block_hash: Some("1".sha3()) };
 		// Vote is inserted fine.
-		assert!(full_vote(&collector, H520::random(), round, Some("0".sha3()), &Address::default()).is_none());
-		// Returns the double voting address.
-		full_vote(&collector, H520::random(), round, Some("1".sha3()), &Address::default()).unwrap();
+		assert!(collector.vote(first.clone(), &Address::default()).is_none());
+		// Returns both conflicting votes of the double voting address.
+		let double = collector.vote(second.clone(), &Address::default()).unwrap();
+		assert_eq!(double.author, &Address::default());
+		assert_eq!(double.vote_one, first);
+		assert_eq!(double.vote_two, second);
 		assert_eq!(collector.count_round_votes(&round), 1);
 	}
 }
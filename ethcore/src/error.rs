@@ -42,6 +42,19 @@ pub enum TransactionError {
 	TooCheapToReplace,
 	/// Transaction was not imported to the queue because limit has been reached.
 	LimitReached,
+	/// Transaction's gas price does not exceed the gas price of the queued transaction
+	/// with the same sender-nonce by the required percentage.
+	InsufficientPriceBump {
+		/// Minimal gas price to replace the queued transaction
+		minimal: U256,
+		/// Transaction gas price
+		got: U256,
+	},
+	/// Sender already holds the maximal number of future transactions in the queue.
+	SenderLimitReached {
+		/// Per-sender limit
+		limit: usize,
+	},
 	/// Transaction's gas price is below threshold.
 	InsufficientGasPrice {
 		/// Minimal expected gas price
@@ -80,6 +93,8 @@ pub enum TransactionError {
 	CodeBanned,
 	/// Invalid network ID given.
 	InvalidNetworkId,
+	/// Transaction is not permitted by the transaction permission contract.
+	NotAllowed,
 }
 
 impl fmt::Display for TransactionError {
@@ -90,6 +105,10 @@ impl fmt::Display for TransactionError {
 			Old => "No longer valid".into(),
 			TooCheapToReplace => "Gas price too low to replace".into(),
 			LimitReached => "Transaction limit reached".into(),
+			InsufficientPriceBump { minimal, got } =>
+				format!("Insufficient gas price to replace. Min={}, Given={}", minimal, got),
+			SenderLimitReached { limit } =>
+				format!("Sender transaction limit reached. Limit={}", limit),
 			InsufficientGasPrice { minimal, got } =>
 				format!("Insufficient gas price. Min={}, Given={}", minimal, got),
 			InsufficientGas { minimal, got } =>
@@ -104,6 +123,7 @@ impl fmt::Display for TransactionError {
 			RecipientBanned => "Recipient is temporarily banned.".into(),
 			CodeBanned => "Contract code is temporarily banned.".into(),
 			InvalidNetworkId => "Transaction of this network ID is not allowed on this chain.".into(),
+			NotAllowed => "Sender does not have permissions to execute this type of transaction".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
@@ -14,15 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethash::{quick_get_difficulty, slow_get_seedhash, EthashManager};
+use ethash::{quick_get_difficulty, slow_get_seedhash, EthashManager, CacheOptions};
 use util::*;
 use block::*;
 use builtin::Builtin;
 use env_info::EnvInfo;
 use error::{BlockError, TransactionError, Error};
-use header::Header;
+use header::{Header, BlockNumber};
 use state::CleanupMode;
 use spec::CommonParams;
+use tx_filter::TransactionFilter;
 use transaction::UnverifiedTransaction;
 use engines::Engine;
 use evm::Schedule;
@@ -132,19 +133,21 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 /// mainnet chains in the Olympic, Frontier and Homestead eras.
 pub struct Ethash {
 	params: CommonParams,
+	tx_filter: Option<TransactionFilter>,
 	ethash_params: EthashParams,
 	builtins: BTreeMap<Address, Builtin>,
-	pow: EthashManager,
+	pow: Arc<EthashManager>,
 }
 
 impl Ethash {
 	/// Create a new instance of Ethash engine
 	pub fn new(params: CommonParams, ethash_params: EthashParams, builtins: BTreeMap<Address, Builtin>) -> Self {
 		Ethash {
+			tx_filter: TransactionFilter::from_params(&params),
 			params: params,
 			ethash_params: ethash_params,
 			builtins: builtins,
-			pow: EthashManager::new(),
+			pow: Arc::new(EthashManager::new()),
 		}
 	}
 }
@@ -156,6 +159,28 @@ impl Engine for Ethash {
 	fn seal_fields(&self) -> usize { 2 }
 
 	fn params(&self) -> &CommonParams { &self.params }
+	fn transaction_filter(&self) -> Option<&TransactionFilter> { self.tx_filter.as_ref() }
+
+	fn transitions(&self) -> Vec<BlockNumber> {
+		let p = &self.ethash_params;
+		vec![
+			p.homestead_transition,
+			p.dao_hardfork_transition,
+			p.difficulty_hardfork_transition,
+			p.bomb_defuse_transition,
+			p.eip100b_transition,
+			p.eip150_transition,
+			p.eip155_transition,
+			p.eip160_transition,
+			p.eip161abc_transition,
+			p.eip161d_transition,
+			p.ecip1010_pause_transition,
+			p.ecip1010_continue_transition,
+			p.max_gas_limit_transition,
+			p.min_gas_price_transition,
+		]
+	}
+
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.ethash_params.registrar.hex()] }
 
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
@@ -385,6 +410,14 @@ impl Engine for Ethash {
 
 		Ok(())
 	}
+
+	fn set_pow_cache_options(&self, options: CacheOptions) {
+		self.pow.set_options(options);
+	}
+
+	fn pow_cache(&self) -> Option<Arc<EthashManager>> {
+		Some(self.pow.clone())
+	}
 }
 
 // Try to round gas_limit a bit so that:
@@ -25,6 +25,7 @@ pub mod ethash;
 pub mod denominations;
 
 pub use self::ethash::{Ethash};
+pub use ethash::CacheOptions as EthashCacheOptions;
 pub use self::denominations::*;
 
 use super::spec::*;
@@ -125,4 +126,11 @@ mod tests {
 
 		let _ = frontier.engine;
 	}
+
+	#[test]
+	fn foundation_fork_transitions() {
+		let frontier = new_foundation();
+
+		assert_eq!(frontier.fork_transitions(), vec![1150000, 1920000, 2463000, 2675000]);
+	}
 }
@@ -102,6 +102,7 @@ extern crate hyper;
 extern crate itertools;
 extern crate linked_hash_map;
 extern crate lru_cache;
+extern crate metrics;
 extern crate native_contracts;
 extern crate num_cpus;
 extern crate num;
@@ -147,6 +148,7 @@ pub mod db;
 pub mod verification;
 pub mod state;
 pub mod env_info;
+pub mod tx_filter;
 #[macro_use] pub mod evm;
 
 mod cache_manager;
@@ -0,0 +1,273 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pluggable transaction selection policies used while assembling a pending block.
+//!
+//! The miner hands the queue's candidates to an `AssemblyPolicy`, which may reorder them
+//! up front and then decides, transaction by transaction, whether each one may still go
+//! into the block given the gas already used by its sender and recipient.
+
+use std::collections::{HashMap, HashSet};
+
+use util::{Address, H256, U256};
+use transaction::{SignedTransaction, Action};
+
+/// Gas accounting of the block being assembled.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Assembly {
+	gas_limit: U256,
+	gas_used: U256,
+	by_sender: HashMap<Address, U256>,
+	by_recipient: HashMap<Address, U256>,
+}
+
+impl Assembly {
+	/// Create accounting for an empty block with the given gas limit.
+	pub fn new(gas_limit: U256) -> Self {
+		Assembly {
+			gas_limit: gas_limit,
+			..Default::default()
+		}
+	}
+
+	/// Gas limit of the block.
+	pub fn gas_limit(&self) -> U256 {
+		self.gas_limit
+	}
+
+	/// Gas used by all transactions included so far.
+	pub fn gas_used(&self) -> U256 {
+		self.gas_used
+	}
+
+	/// Gas used by transactions of the given sender.
+	pub fn gas_used_by_sender(&self, sender: &Address) -> U256 {
+		self.by_sender.get(sender).cloned().unwrap_or_else(U256::zero)
+	}
+
+	/// Gas used by transactions calling the given address.
+	pub fn gas_used_by_recipient(&self, recipient: &Address) -> U256 {
+		self.by_recipient.get(recipient).cloned().unwrap_or_else(U256::zero)
+	}
+
+	/// Account for a transaction which has been included in the block.
+	pub fn note_included(&mut self, transaction: &SignedTransaction, gas_used: U256) {
+		self.gas_used = self.gas_used + gas_used;
+		let by_sender = self.by_sender.entry(transaction.sender()).or_insert_with(U256::zero);
+		*by_sender = *by_sender + gas_used;
+		if let Action::Call(ref recipient) = transaction.action {
+			let by_recipient = self.by_recipient.entry(*recipient).or_insert_with(U256::zero);
+			*by_recipient = *by_recipient + gas_used;
+		}
+	}
+
+	/// Share of the gas limit given in percent.
+	fn share(&self, percent: usize) -> U256 {
+		self.gas_limit * U256::from(percent.min(100)) / U256::from(100)
+	}
+}
+
+/// Selects and orders transactions while a block is assembled.
+pub trait AssemblyPolicy: Send + Sync {
+	/// Reorder candidate transactions before they are pushed into the block.
+	/// Implementations must keep the relative order of transactions from the same sender.
+	fn order(&self, candidates: Vec<SignedTransaction>, _is_local: &Fn(&H256) -> bool) -> Vec<SignedTransaction> {
+		candidates
+	}
+
+	/// Whether the transaction may be pushed into the block given what has been included so far.
+	fn accept(&self, _transaction: &SignedTransaction, _assembly: &Assembly) -> bool {
+		true
+	}
+}
+
+/// Rules the miner's assembly policy is built from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssemblyRule {
+	/// Local transactions go in front of external ones.
+	LocalPriority,
+	/// Given percentage of the block gas is kept for whitelisted senders.
+	ReservedSenders {
+		/// Whitelisted senders.
+		senders: Vec<Address>,
+		/// Percentage of the block gas limit reserved for them.
+		percent: usize,
+	},
+	/// Any single recipient may use at most the given percentage of the block gas limit.
+	RecipientCap(usize),
+}
+
+/// Build a policy applying all the given rules in turn.
+pub fn policy_from_rules(rules: &[AssemblyRule]) -> Box<AssemblyPolicy> {
+	let policies = rules.iter().map(|rule| match *rule {
+		AssemblyRule::LocalPriority => Box::new(LocalPriority) as Box<AssemblyPolicy>,
+		AssemblyRule::ReservedSenders { ref senders, percent } =>
+			Box::new(ReservedSenders::new(senders.iter().cloned().collect(), percent)) as Box<AssemblyPolicy>,
+		AssemblyRule::RecipientCap(percent) => Box::new(RecipientCap::new(percent)) as Box<AssemblyPolicy>,
+	}).collect();
+
+	Box::new(Composite(policies))
+}
+
+/// Stable partition putting the transactions matching `first` in front.
+fn partition<F: Fn(&SignedTransaction) -> bool>(candidates: Vec<SignedTransaction>, first: F) -> Vec<SignedTransaction> {
+	let (mut front, back): (Vec<_>, Vec<_>) = candidates.into_iter().partition(|tx| first(tx));
+	front.extend(back);
+	front
+}
+
+/// Puts local transactions in front of external ones.
+#[derive(Debug, Default)]
+pub struct LocalPriority;
+
+impl AssemblyPolicy for LocalPriority {
+	fn order(&self, candidates: Vec<SignedTransaction>, is_local: &Fn(&H256) -> bool) -> Vec<SignedTransaction> {
+		partition(candidates, |tx| is_local(&tx.hash()))
+	}
+}
+
+/// Keeps a share of the block gas for whitelisted senders, which are also put in front.
+#[derive(Debug)]
+pub struct ReservedSenders {
+	senders: HashSet<Address>,
+	percent: usize,
+}
+
+impl ReservedSenders {
+	/// Reserve `percent` of the block gas limit for `senders`.
+	pub fn new(senders: HashSet<Address>, percent: usize) -> Self {
+		ReservedSenders {
+			senders: senders,
+			percent: percent,
+		}
+	}
+}
+
+impl AssemblyPolicy for ReservedSenders {
+	fn order(&self, candidates: Vec<SignedTransaction>, _is_local: &Fn(&H256) -> bool) -> Vec<SignedTransaction> {
+		partition(candidates, |tx| self.senders.contains(&tx.sender()))
+	}
+
+	fn accept(&self, transaction: &SignedTransaction, assembly: &Assembly) -> bool {
+		if self.senders.contains(&transaction.sender()) {
+			return true;
+		}
+
+		let reserved_used = self.senders.iter().fold(U256::zero(), |acc, s| acc + assembly.gas_used_by_sender(s));
+		let others_used = assembly.gas_used() - reserved_used;
+		let available = assembly.gas_limit() - assembly.share(self.percent);
+		others_used + transaction.gas <= available
+	}
+}
+
+/// Caps the share of block gas used by transactions calling any single address.
+#[derive(Debug)]
+pub struct RecipientCap {
+	percent: usize,
+}
+
+impl RecipientCap {
+	/// Allow at most `percent` of the block gas limit per recipient.
+	pub fn new(percent: usize) -> Self {
+		RecipientCap {
+			percent: percent,
+		}
+	}
+}
+
+impl AssemblyPolicy for RecipientCap {
+	fn accept(&self, transaction: &SignedTransaction, assembly: &Assembly) -> bool {
+		match transaction.action {
+			Action::Call(ref recipient) => assembly.gas_used_by_recipient(recipient) + transaction.gas <= assembly.share(self.percent),
+			Action::Create => true,
+		}
+	}
+}
+
+/// Applies several policies in turn: orderings are chained and all of them have to accept.
+/// Without any policies the queue order is kept and every transaction is accepted.
+pub struct Composite(pub Vec<Box<AssemblyPolicy>>);
+
+impl AssemblyPolicy for Composite {
+	fn order(&self, candidates: Vec<SignedTransaction>, is_local: &Fn(&H256) -> bool) -> Vec<SignedTransaction> {
+		self.0.iter().fold(candidates, |candidates, policy| policy.order(candidates, is_local))
+	}
+
+	fn accept(&self, transaction: &SignedTransaction, assembly: &Assembly) -> bool {
+		self.0.iter().all(|policy| policy.accept(transaction, assembly))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Generator, Random, KeyPair};
+	use util::{Address, U256};
+	use transaction::{SignedTransaction, Transaction, Action};
+	use super::*;
+
+	fn transaction(keypair: &KeyPair, to: Address, gas: u64) -> SignedTransaction {
+		Transaction {
+			action: Action::Call(to),
+			value: U256::zero(),
+			data: Vec::new(),
+			gas: gas.into(),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None)
+	}
+
+	#[test]
+	fn local_priority_keeps_relative_order() {
+		let (a, b, c) = (Random.generate().unwrap(), Random.generate().unwrap(), Random.generate().unwrap());
+		let txs = vec![transaction(&a, 1.into(), 21_000), transaction(&b, 1.into(), 21_000), transaction(&c, 1.into(), 21_000)];
+		let local = vec![txs[1].hash(), txs[2].hash()];
+
+		let ordered = LocalPriority.order(txs.clone(), &|hash| local.contains(hash));
+		assert_eq!(ordered, vec![txs[1].clone(), txs[2].clone(), txs[0].clone()]);
+	}
+
+	#[test]
+	fn reserved_senders_limit_others() {
+		let (reserved, other) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let policy = ReservedSenders::new(vec![reserved.address()].into_iter().collect(), 50);
+		let mut assembly = Assembly::new(100_000.into());
+
+		let tx = transaction(&other, 1.into(), 40_000);
+		assert!(policy.accept(&tx, &assembly));
+		assembly.note_included(&tx, 40_000.into());
+		assert!(!policy.accept(&transaction(&other, 1.into(), 20_000), &assembly));
+
+		// gas used by reserved senders doesn't count against others
+		let tx = transaction(&reserved, 1.into(), 50_000);
+		assert!(policy.accept(&tx, &assembly));
+		assembly.note_included(&tx, 50_000.into());
+		assert!(policy.accept(&transaction(&other, 2.into(), 10_000), &assembly));
+	}
+
+	#[test]
+	fn recipient_cap_limits_single_recipient() {
+		let sender = Random.generate().unwrap();
+		let policy = policy_from_rules(&[AssemblyRule::RecipientCap(30)]);
+		let mut assembly = Assembly::new(100_000.into());
+
+		let tx = transaction(&sender, 1.into(), 25_000);
+		assert!(policy.accept(&tx, &assembly));
+		assembly.note_included(&tx, 25_000.into());
+
+		assert!(!policy.accept(&transaction(&sender, 1.into(), 21_000), &assembly));
+		assert!(policy.accept(&transaction(&sender, 2.into(), 21_000), &assembly));
+	}
+}
@@ -0,0 +1,261 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Built-in multi-threaded CPU ethash miner, intended for development and test chains.
+//!
+//! Work packages are received through `NotifyWork` and solutions are submitted
+//! directly to the `Miner`. Low difficulty work is hashed against the light cache;
+//! otherwise the light cache is used until the full dataset for the current epoch has
+//! been generated in the background.
+
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::thread;
+
+use ethash::{EthashManager, Full, ETHASH_EPOCH_LENGTH};
+use rlp::encode;
+use util::{H256, H64, U256, Mutex, Condvar};
+use ethereum::ethash::Ethash;
+use block::IsBlock;
+use client::Client;
+use miner::{Miner, MinerService};
+use miner::work_notify::NotifyWork;
+
+/// Number of nonces tried before checking whether the work package has changed.
+const NONCE_BATCH: u64 = 64;
+/// Work below this difficulty is not worth generating the full dataset for.
+const FULL_DATASET_MIN_DIFFICULTY: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Job {
+	pow_hash: H256,
+	boundary: H256,
+	number: u64,
+	light_only: bool,
+}
+
+struct Work {
+	/// Current work package, `None` if there is nothing to mine or it has been solved.
+	job: Option<Job>,
+	/// Bumped on every change of `job`, so that workers can abandon stale searches.
+	version: u64,
+}
+
+struct Inner {
+	work: Mutex<Work>,
+	work_changed: Condvar,
+	light: Arc<EthashManager>,
+	full: Mutex<Option<Arc<Full>>>,
+	generating: AtomicBool,
+	stop: AtomicBool,
+	threads: usize,
+	miner: Weak<Miner>,
+	client: Weak<Client>,
+}
+
+impl Inner {
+	/// Wait for a work package. Returns `None` if the miner is stopping.
+	fn next_job(&self) -> Option<(Job, u64)> {
+		let mut work = self.work.lock();
+		while work.job.is_none() && !self.stop.load(AtomicOrdering::SeqCst) {
+			self.work_changed.wait(&mut work);
+		}
+		if self.stop.load(AtomicOrdering::SeqCst) {
+			return None;
+		}
+		work.job.clone().map(|job| (job, work.version))
+	}
+
+	fn is_current(&self, version: u64) -> bool {
+		!self.stop.load(AtomicOrdering::SeqCst) && self.work.lock().version == version
+	}
+
+	/// Mark the work package as solved and hand the seal to the miner.
+	fn submit(&self, job: &Job, version: u64, nonce: u64, mix_hash: H256) {
+		{
+			let mut work = self.work.lock();
+			if work.version != version {
+				return;
+			}
+			work.job = None;
+			work.version += 1;
+		}
+
+		let seal = vec![encode(&mix_hash).to_vec(), encode(&H64::from(nonce)).to_vec()];
+		if let (Some(miner), Some(client)) = (self.miner.upgrade(), self.client.upgrade()) {
+			match miner.submit_seal(&*client, job.pow_hash, seal) {
+				Ok(()) => info!(target: "miner", "Mined block #{} (pow hash {})", job.number, job.pow_hash),
+				Err(e) => warn!(target: "miner", "Internal miner solution rejected: {:?}", e),
+			}
+		}
+	}
+}
+
+/// Full dataset for the epoch of `number` if it is ready. Starts generating it otherwise.
+fn dataset(inner: &Arc<Inner>, number: u64) -> Option<Arc<Full>> {
+	let epoch = number / ETHASH_EPOCH_LENGTH;
+	if let Some(ref full) = *inner.full.lock() {
+		if full.block_number() / ETHASH_EPOCH_LENGTH == epoch {
+			return Some(full.clone());
+		}
+	}
+
+	if !inner.generating.swap(true, AtomicOrdering::SeqCst) {
+		let inner = inner.clone();
+		thread::spawn(move || {
+			info!(target: "miner", "Generating DAG for epoch {}", epoch);
+			let full = Arc::new(inner.light.full(number, inner.threads));
+			info!(target: "miner", "DAG for epoch {} is ready", epoch);
+			*inner.full.lock() = Some(full);
+			inner.generating.store(false, AtomicOrdering::SeqCst);
+		});
+	}
+	None
+}
+
+fn mine(inner: Arc<Inner>) {
+	let mut nonce: u64 = ::rand::random();
+	while let Some((job, version)) = inner.next_job() {
+		let full = if job.light_only { None } else { dataset(&inner, job.number) };
+		let mut solved = false;
+		while !solved && inner.is_current(version) {
+			for _ in 0..NONCE_BATCH {
+				let pow = match full {
+					Some(ref full) => full.compute(&job.pow_hash.0, nonce),
+					None => inner.light.compute_light(job.number, &job.pow_hash.0, nonce),
+				};
+				if pow.value[..] <= job.boundary[..] {
+					inner.submit(&job, version, nonce, H256(pow.mix_hash));
+					solved = true;
+					break;
+				}
+				nonce = nonce.wrapping_add(1);
+			}
+		}
+	}
+}
+
+/// Built-in CPU miner. Stops its worker threads when dropped.
+pub struct CpuMiner {
+	inner: Arc<Inner>,
+}
+
+impl CpuMiner {
+	/// Start `threads` mining threads submitting solutions to the given miner.
+	/// `light` should be the engine's cache manager, so that the caches are shared.
+	pub fn start(threads: usize, light: Arc<EthashManager>, miner: Weak<Miner>, client: Weak<Client>) -> CpuMiner {
+		let inner = Arc::new(Inner {
+			work: Mutex::new(Work { job: None, version: 0 }),
+			work_changed: Condvar::new(),
+			light: light,
+			full: Mutex::new(None),
+			generating: AtomicBool::new(false),
+			stop: AtomicBool::new(false),
+			threads: threads,
+			miner: miner,
+			client: client,
+		});
+
+		for i in 0..threads {
+			let inner = inner.clone();
+			thread::Builder::new()
+				.name(format!("CPU miner #{}", i))
+				.spawn(move || mine(inner))
+				.expect("Error creating CPU miner thread");
+		}
+
+		CpuMiner {
+			inner: inner,
+		}
+	}
+
+	/// Start the CPU miner and register it in the miner.
+	pub fn register(threads: usize, miner: Arc<Miner>, client: Weak<Client>) {
+		let light = match client.upgrade().and_then(|client| client.engine().pow_cache()) {
+			Some(light) => light,
+			None => {
+				warn!(target: "miner", "Internal CPU miner requires a proof-of-work engine");
+				return;
+			},
+		};
+		let cpu_miner = CpuMiner::start(threads, light, Arc::downgrade(&miner), client.clone());
+		info!(target: "miner", "Started internal CPU miner with {} threads", threads);
+
+		// kick off with the current work package, if any.
+		if let Some(client) = client.upgrade() {
+			let work = miner.map_sealing_work(&*client, |b| {
+				let header = b.block().header();
+				(b.hash(), *header.difficulty(), header.number())
+			});
+			if let Some((pow_hash, difficulty, number)) = work {
+				cpu_miner.notify(pow_hash, difficulty, number);
+			}
+		}
+
+		miner.push_notifier(Box::new(cpu_miner) as Box<NotifyWork>);
+	}
+}
+
+impl NotifyWork for CpuMiner {
+	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64) {
+		let job = Job {
+			pow_hash: pow_hash,
+			boundary: Ethash::difficulty_to_boundary(&difficulty),
+			number: number,
+			light_only: difficulty < U256::from(FULL_DATASET_MIN_DIFFICULTY),
+		};
+
+		let mut work = self.inner.work.lock();
+		if work.job.as_ref() != Some(&job) {
+			trace!(target: "miner", "New work for internal miner: {:?}", job);
+			work.job = Some(job);
+			work.version += 1;
+			self.inner.work_changed.notify_all();
+		}
+	}
+}
+
+impl Drop for CpuMiner {
+	fn drop(&mut self) {
+		// hold the lock so that no worker misses the wake-up.
+		let _work = self.inner.work.lock();
+		self.inner.stop.store(true, AtomicOrdering::SeqCst);
+		self.inner.work_changed.notify_all();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Weak};
+	use std::thread;
+	use std::time::Duration;
+	use util::{H256, U256};
+	use ethash::EthashManager;
+	use miner::work_notify::NotifyWork;
+	use super::CpuMiner;
+
+	#[test]
+	fn solves_trivial_work() {
+		let cpu_miner = CpuMiner::start(2, Arc::new(EthashManager::new()), Weak::new(), Weak::new());
+		cpu_miner.notify(H256::from(1), U256::one(), 0);
+		// any hash satisfies difficulty 1, so the job is solved by the first attempt.
+		while cpu_miner.inner.work.lock().job.is_some() {
+			thread::sleep(Duration::from_millis(50));
+		}
+		assert_eq!(cpu_miner.inner.work.lock().version, 2);
+	}
+}
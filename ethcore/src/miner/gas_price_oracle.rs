@@ -0,0 +1,301 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price oracle.
+//!
+//! Keeps the gas prices paid by transactions in the most recent canonical blocks
+//! and derives price recommendations from their percentiles, with every transaction
+//! weighted by the amount of gas it actually used.
+
+use std::collections::VecDeque;
+
+use rlp::UntrustedRlp;
+use util::{Address, H256, U256};
+use client::{BlockChainClient, BlockId};
+use header::BlockNumber;
+use receipt::Receipt;
+
+/// Percentile of recently used gas paid at or below the "fast" recommendation.
+const FAST_PERCENTILE: usize = 90;
+/// Percentile of recently used gas paid at or below the "standard" recommendation.
+const STANDARD_PERCENTILE: usize = 60;
+/// Percentile of recently used gas paid at or below the "slow" recommendation.
+const SLOW_PERCENTILE: usize = 30;
+
+/// Gas prices paid in a single block.
+#[derive(Debug, Clone, PartialEq)]
+struct BlockSample {
+	hash: H256,
+	author: Address,
+	timestamp: u64,
+	/// Gas price and gas used of every transaction, sorted by gas price.
+	prices: Vec<(U256, U256)>,
+}
+
+impl BlockSample {
+	/// Whether a transaction paying `gas_price` would have made it into this block.
+	/// Blocks without transactions accept any price.
+	fn accepts(&self, gas_price: &U256) -> bool {
+		self.prices.first().map_or(true, |&(ref min, _)| min <= gas_price)
+	}
+}
+
+/// A single gas price recommendation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPriceRecommendation {
+	/// Recommended gas price.
+	pub gas_price: U256,
+	/// Expected time until inclusion in seconds.
+	/// `None` if none of the sampled blocks would have included such a transaction.
+	pub expected_wait: Option<u64>,
+}
+
+/// Gas price recommendations for different inclusion speeds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasPriceRecommendations {
+	/// Price likely to be included in the next few blocks.
+	pub fast: GasPriceRecommendation,
+	/// Price paid for the bulk of recently used gas.
+	pub standard: GasPriceRecommendation,
+	/// Cheap price that may take a while to be included.
+	pub slow: GasPriceRecommendation,
+	/// Number of blocks the recommendations are based on.
+	pub blocks: usize,
+}
+
+/// Rolling gas price statistics over the last `max_blocks` canonical blocks.
+#[derive(Debug)]
+pub struct GasPriceOracle {
+	max_blocks: usize,
+	/// Samples of the most recent blocks, newest first.
+	samples: VecDeque<BlockSample>,
+}
+
+impl GasPriceOracle {
+	/// Create a new oracle sampling at most `max_blocks` blocks.
+	pub fn new(max_blocks: usize) -> Self {
+		GasPriceOracle {
+			max_blocks: max_blocks,
+			samples: VecDeque::with_capacity(max_blocks),
+		}
+	}
+
+	/// Bring the samples up to date with the best block of the chain.
+	/// Only blocks which haven't been sampled yet are read; retracted blocks are dropped.
+	pub fn update<C: BlockChainClient + ?Sized>(&mut self, chain: &C) {
+		let mut hash = chain.chain_info().best_block_hash;
+		let mut fresh = Vec::new();
+		let mut known = None;
+
+		while fresh.len() < self.max_blocks {
+			if let Some(index) = self.samples.iter().position(|s| s.hash == hash) {
+				known = Some(index);
+				break;
+			}
+
+			let (sample, parent, number) = match sample_block(chain, hash) {
+				Some(sampled) => sampled,
+				None => break,
+			};
+			fresh.push(sample);
+			if number <= 1 {
+				break;
+			}
+			hash = parent;
+		}
+
+		match known {
+			Some(index) => { self.samples.drain(..index); },
+			None => self.samples.clear(),
+		}
+		for sample in fresh.into_iter().rev() {
+			self.samples.push_front(sample);
+		}
+		self.samples.truncate(self.max_blocks);
+	}
+
+	/// Gas price at or below which the given percentage of recently used gas was paid.
+	/// Returns `None` if no transactions were sampled.
+	pub fn percentile(&self, percentile: usize) -> Option<U256> {
+		percentile_of(self.samples.iter(), percentile)
+	}
+
+	/// Like `percentile`, but ignores blocks authored by `author`, so that a node
+	/// can't drive its own minimal gas price with the blocks it seals.
+	pub fn percentile_excluding(&self, percentile: usize, author: &Address) -> Option<U256> {
+		percentile_of(self.samples.iter().filter(|s| s.author != *author), percentile)
+	}
+
+	/// Expected time in seconds until a transaction paying `gas_price` is included.
+	pub fn expected_wait(&self, gas_price: &U256) -> Option<u64> {
+		let accepting = self.samples.iter().filter(|s| s.accepts(gas_price)).count() as u64;
+		if accepting == 0 {
+			return None;
+		}
+		Some(self.average_block_time() * self.samples.len() as u64 / accepting)
+	}
+
+	/// Recommendations for fast, standard and slow inclusion.
+	/// Returns `None` if no transactions were sampled.
+	pub fn recommendations(&self) -> Option<GasPriceRecommendations> {
+		let recommend = |percentile| self.percentile(percentile).map(|gas_price| GasPriceRecommendation {
+			expected_wait: self.expected_wait(&gas_price),
+			gas_price: gas_price,
+		});
+
+		match (recommend(FAST_PERCENTILE), recommend(STANDARD_PERCENTILE), recommend(SLOW_PERCENTILE)) {
+			(Some(fast), Some(standard), Some(slow)) => Some(GasPriceRecommendations {
+				fast: fast,
+				standard: standard,
+				slow: slow,
+				blocks: self.samples.len(),
+			}),
+			_ => None,
+		}
+	}
+
+	fn average_block_time(&self) -> u64 {
+		match (self.samples.front(), self.samples.back()) {
+			(Some(newest), Some(oldest)) if self.samples.len() > 1 =>
+				newest.timestamp.saturating_sub(oldest.timestamp) / (self.samples.len() as u64 - 1),
+			_ => 0,
+		}
+	}
+}
+
+fn percentile_of<'a, I: Iterator<Item = &'a BlockSample>>(samples: I, percentile: usize) -> Option<U256> {
+	let mut prices: Vec<_> = samples
+		.flat_map(|s| s.prices.iter().cloned())
+		.collect();
+	if prices.is_empty() {
+		return None;
+	}
+	prices.sort();
+
+	let total = prices.iter().fold(U256::zero(), |acc, &(_, gas)| acc + gas);
+	let threshold = total * U256::from(percentile.min(100)) / U256::from(100);
+	let mut used = U256::zero();
+	for &(price, gas) in &prices {
+		used = used + gas;
+		if used >= threshold {
+			return Some(price);
+		}
+	}
+	prices.last().map(|&(price, _)| price)
+}
+
+/// Read the gas prices paid in the given block, together with its parent hash and number.
+fn sample_block<C: BlockChainClient + ?Sized>(chain: &C, hash: H256) -> Option<(BlockSample, H256, BlockNumber)> {
+	let block = match chain.block(BlockId::Hash(hash)) {
+		Some(block) => block,
+		None => return None,
+	};
+	let receipts: Vec<Receipt> = match chain.block_receipts(&hash).and_then(|r| UntrustedRlp::new(&r).as_list().ok()) {
+		Some(receipts) => receipts,
+		None => return None,
+	};
+
+	let mut cumulative = U256::zero();
+	let mut prices: Vec<_> = block.transaction_views().iter()
+		.zip(receipts.iter())
+		.map(|(tx, receipt)| {
+			let gas_used = receipt.gas_used - cumulative;
+			cumulative = receipt.gas_used;
+			(tx.gas_price(), gas_used)
+		})
+		.collect();
+	prices.sort();
+
+	let sample = BlockSample {
+		hash: hash,
+		author: block.author(),
+		timestamp: block.timestamp(),
+		prices: prices,
+	};
+	Some((sample, block.parent_hash(), block.number()))
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, U256};
+	use super::{GasPriceOracle, BlockSample};
+
+	fn oracle(blocks: Vec<(u64, Vec<(u64, u64)>)>) -> GasPriceOracle {
+		let mut oracle = GasPriceOracle::new(10);
+		for (i, (timestamp, prices)) in blocks.into_iter().enumerate() {
+			oracle.samples.push_back(BlockSample {
+				hash: (i as u64 + 1).into(),
+				author: (i as u64 + 1).into(),
+				timestamp: timestamp,
+				prices: prices.into_iter().map(|(p, g)| (p.into(), g.into())).collect(),
+			});
+		}
+		oracle
+	}
+
+	#[test]
+	fn percentiles_are_weighted_by_gas_used() {
+		// newest first
+		let oracle = oracle(vec![
+			(30, vec![(10, 21_000), (50, 900_000)]),
+			(15, vec![(20, 79_000)]),
+		]);
+
+		assert_eq!(oracle.percentile(2), Some(10.into()));
+		assert_eq!(oracle.percentile(10), Some(20.into()));
+		assert_eq!(oracle.percentile(50), Some(50.into()));
+		assert_eq!(oracle.percentile(100), Some(50.into()));
+	}
+
+	#[test]
+	fn percentiles_exclude_own_blocks() {
+		let oracle = oracle(vec![
+			(30, vec![(10, 21_000), (50, 900_000)]),
+			(15, vec![(20, 79_000)]),
+		]);
+
+		assert_eq!(oracle.percentile_excluding(50, &Address::from(1)), Some(20.into()));
+		assert_eq!(oracle.percentile_excluding(50, &Address::from(3)), Some(50.into()));
+	}
+
+	#[test]
+	fn expected_wait_follows_accepting_blocks() {
+		let oracle = oracle(vec![
+			(45, vec![(40, 21_000)]),
+			(30, vec![(10, 21_000)]),
+			(15, vec![(40, 21_000)]),
+			(0, vec![]),
+		]);
+
+		// every block would have included it
+		assert_eq!(oracle.expected_wait(&40.into()), Some(15));
+		// only the empty block and the cheap one
+		assert_eq!(oracle.expected_wait(&10.into()), Some(30));
+		assert_eq!(oracle.expected_wait(&U256::zero()), Some(60));
+	}
+
+	#[test]
+	fn no_recommendations_without_transactions() {
+		let empty = oracle(vec![(15, vec![]), (0, vec![])]);
+		assert_eq!(empty.recommendations(), None);
+
+		let single = oracle(vec![(15, vec![(30, 21_000)]), (0, vec![])]);
+		let recommendations = single.recommendations().unwrap();
+		assert_eq!(recommendations.fast.gas_price, 30.into());
+		assert_eq!(recommendations.slow.expected_wait, Some(15));
+		assert_eq!(recommendations.blocks, 2);
+	}
+}
@@ -34,9 +34,12 @@ use miner::{MinerService, MinerStatus, TransactionQueue, RemovalReason, Transact
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
 use miner::work_notify::{WorkPoster, NotifyWork};
 use miner::price_info::PriceInfo;
+use miner::gas_price_oracle::{GasPriceOracle, GasPriceRecommendations};
+use miner::block_assembly::{Assembly, AssemblyPolicy, AssemblyRule, policy_from_rules};
 use miner::local_transactions::{Status as LocalTransactionStatus};
 use miner::service_transaction_checker::ServiceTransactionChecker;
 use header::BlockNumber;
+use metrics::{Collector, Metric, MetricType};
 
 /// Different possible definitions for pending transaction set.
 #[derive(Debug, PartialEq)]
@@ -98,6 +101,10 @@ pub struct MinerOptions {
 	pub tx_queue_size: usize,
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Percentage by which a transaction's gas price has to exceed the one it replaces.
+	pub tx_queue_price_bump: usize,
+	/// Maximal number of future transactions a single sender may hold in the queue.
+	pub tx_queue_per_sender: usize,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
 	pub pending_set: PendingSet,
 	/// How many historical work packages can we store before running out?
@@ -110,6 +117,10 @@ pub struct MinerOptions {
 	pub tx_queue_banning: Banning,
 	/// Do we refuse to accept service transactions even if sender is certified.
 	pub refuse_service_transactions: bool,
+	/// Number of recent blocks the gas price oracle keeps statistics for.
+	pub gas_price_oracle_blocks: usize,
+	/// Rules for selecting and ordering transactions when assembling a block.
+	pub block_assembly: Vec<AssemblyRule>,
 }
 
 impl Default for MinerOptions {
@@ -123,6 +134,8 @@ impl Default for MinerOptions {
 			tx_queue_size: 1024,
 			tx_queue_gas_limit: GasLimit::Auto,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_price_bump: 0,
+			tx_queue_per_sender: usize::max_value(),
 			pending_set: PendingSet::AlwaysQueue,
 			reseal_min_period: Duration::from_secs(2),
 			reseal_max_period: Duration::from_secs(120),
@@ -130,6 +143,8 @@ impl Default for MinerOptions {
 			enable_resubmission: true,
 			tx_queue_banning: Banning::Disabled,
 			refuse_service_transactions: false,
+			gas_price_oracle_blocks: 100,
+			block_assembly: Vec::new(),
 		}
 	}
 }
@@ -143,6 +158,17 @@ pub struct GasPriceCalibratorOptions {
 	pub recalibration_period: Duration,
 }
 
+/// Options for the gas pricer following the gas price oracle.
+#[derive(Debug, PartialEq)]
+pub struct GasPriceOracleOptions {
+	/// Percentile of gas prices paid in recent blocks to follow.
+	pub percentile: usize,
+	/// Minimal gas price to set, whatever the oracle says.
+	pub min_gas_price: U256,
+	/// Maximal gas price to set, whatever the oracle says.
+	pub max_gas_price: U256,
+}
+
 /// The gas price validator variant for a `GasPricer`.
 #[derive(Debug, PartialEq)]
 pub struct GasPriceCalibrator {
@@ -179,6 +205,8 @@ pub enum GasPricer {
 	Fixed(U256),
 	/// Gas price is calibrated according to a fixed amount of USD.
 	Calibrated(GasPriceCalibrator),
+	/// Gas price follows a percentile of gas prices paid in recent blocks by other authors.
+	Oracle(GasPriceOracleOptions),
 }
 
 impl GasPricer {
@@ -195,10 +223,18 @@ impl GasPricer {
 		GasPricer::Fixed(gas_price)
 	}
 
-	fn recalibrate<F: Fn(U256) + Sync + Send + 'static>(&mut self, set_price: F) {
+	/// Create a new `GasPricer` following the gas price oracle.
+	pub fn new_oracle(options: GasPriceOracleOptions) -> GasPricer {
+		GasPricer::Oracle(options)
+	}
+
+	fn recalibrate<F: Fn(U256) + Sync + Send + 'static>(&mut self, oracle: &GasPriceOracle, author: &Address, set_price: F) {
 		match *self {
 			GasPricer::Fixed(ref max) => set_price(max.clone()),
 			GasPricer::Calibrated(ref mut cal) => cal.recalibrate(set_price),
+			GasPricer::Oracle(ref options) => if let Some(price) = oracle.percentile_excluding(options.percentile, author) {
+				set_price(min(max(price, options.min_gas_price), options.max_gas_price))
+			},
 		}
 	}
 }
@@ -228,6 +264,8 @@ pub struct Miner {
 	accounts: Option<Arc<AccountProvider>>,
 	notifiers: RwLock<Vec<Box<NotifyWork>>>,
 	gas_pricer: Mutex<GasPricer>,
+	gas_price_oracle: Mutex<GasPriceOracle>,
+	assembly_policy: RwLock<Box<AssemblyPolicy>>,
 	service_transaction_action: ServiceTransactionAction,
 }
 
@@ -250,7 +288,14 @@ impl Miner {
 			_ => !U256::zero(),
 		};
 
-		let txq = TransactionQueue::with_limits(options.tx_queue_strategy, options.tx_queue_size, gas_limit, options.tx_gas_limit);
+		let txq = TransactionQueue::with_limits(
+			options.tx_queue_strategy,
+			options.tx_queue_size,
+			gas_limit,
+			options.tx_gas_limit,
+			options.tx_queue_price_bump,
+			options.tx_queue_per_sender,
+		);
 		let txq = match options.tx_queue_banning {
 			Banning::Disabled => BanningTransactionQueue::new(txq, Threshold::NeverBan, Duration::from_secs(180)),
 			Banning::Enabled { ban_duration, min_offends, .. } => BanningTransactionQueue::new(
@@ -270,6 +315,9 @@ impl Miner {
 			false => ServiceTransactionAction::Check(ServiceTransactionChecker::default()),
 		};
 
+		let gas_price_oracle = GasPriceOracle::new(options.gas_price_oracle_blocks);
+		let assembly_policy = policy_from_rules(&options.block_assembly);
+
 		Miner {
 			transaction_queue: Arc::new(RwLock::new(txq)),
 			next_allowed_reseal: Mutex::new(Instant::now()),
@@ -289,6 +337,8 @@ impl Miner {
 			engine: spec.engine.clone(),
 			notifiers: RwLock::new(notifiers),
 			gas_pricer: Mutex::new(gas_pricer),
+			gas_price_oracle: Mutex::new(gas_price_oracle),
+			assembly_policy: RwLock::new(assembly_policy),
 			service_transaction_action: service_transaction_action,
 		}
 	}
@@ -303,6 +353,11 @@ impl Miner {
 		Miner::new_raw(Default::default(), GasPricer::new_fixed(20_000_000_000u64.into()), spec, None)
 	}
 
+	/// Replace the policy used to select and order transactions when assembling a block.
+	pub fn set_assembly_policy(&self, policy: Box<AssemblyPolicy>) {
+		*self.assembly_policy.write() = policy;
+	}
+
 	fn forced_sealing(&self) -> bool {
 		self.options.force_sealing || !self.notifiers.read().is_empty()
 	}
@@ -359,15 +414,43 @@ impl Miner {
 			(transactions, open_block, last_work_hash)
 		};
 
+		let policy = self.assembly_policy.read();
+		let transactions = {
+			let queue = self.transaction_queue.read();
+			let local_transactions = queue.local_transactions();
+			policy.order(transactions, &|hash: &H256| local_transactions.contains_key(hash))
+		};
+
+		// account for transactions already in a re-opened block
+		let mut assembly = Assembly::new(*open_block.block().fields().header.gas_limit());
+		{
+			let mut gas_before = U256::zero();
+			for (tx, receipt) in open_block.transactions().iter().zip(open_block.receipts()) {
+				assembly.note_included(tx, receipt.gas_used - gas_before);
+				gas_before = receipt.gas_used;
+			}
+		}
+
 		let mut invalid_transactions = HashSet::new();
 		let mut transactions_to_penalize = HashSet::new();
 		let block_number = open_block.block().fields().header.number();
+		let parent_hash = open_block.block().fields().header.parent_hash().clone();
 
 		// TODO Push new uncles too.
 		let mut tx_count: usize = 0;
 		let tx_total = transactions.len();
 		for tx in transactions {
 			let hash = tx.hash();
+			// Permissions may change with the next block, so the transaction stays queued.
+			if self.engine.verify_transaction_family(&tx, &parent_hash, chain).is_err() {
+				debug!(target: "miner", "Skipping transaction {:?} not permitted by the transaction permission contract in this block", hash);
+				continue;
+			}
+			if !policy.accept(&tx, &assembly) {
+				trace!(target: "miner", "Skipping transaction {:?} rejected by the block assembly policy", hash);
+				continue;
+			}
+			let gas_before = open_block.receipts().last().map_or_else(U256::zero, |r| r.gas_used);
 			let start = Instant::now();
 			let result = open_block.push_transaction(tx, None);
 			let took = start.elapsed();
@@ -418,6 +501,9 @@ impl Miner {
 				},
 				_ => {
 					tx_count += 1;
+					if let (Some(tx), Some(receipt)) = (open_block.transactions().last(), open_block.receipts().last()) {
+						assembly.note_included(tx, receipt.gas_used - gas_before);
+					}
 				}	// imported ok
 			}
 		}
@@ -443,7 +529,9 @@ impl Miner {
 	pub fn recalibrate_minimal_gas_price(&self) {
 		debug!(target: "miner", "minimal_gas_price: recalibrating...");
 		let txq = self.transaction_queue.clone();
-		self.gas_pricer.lock().recalibrate(move |price| {
+		let oracle = self.gas_price_oracle.lock();
+		let author = *self.author.read();
+		self.gas_pricer.lock().recalibrate(&*oracle, &author, move |price| {
 			debug!(target: "miner", "minimal_gas_price: Got gas price! {}", price);
 			txq.write().set_minimal_gas_price(price);
 		});
@@ -627,6 +715,10 @@ impl Miner {
 						debug!(target: "miner", "Rejected tx {:?} with invalid signature: {:?}", hash, e);
 						Err(e)
 					},
+					Ok(ref transaction) if self.engine.verify_transaction_family(transaction, &best_block_header.hash(), client).is_err() => {
+						debug!(target: "miner", "Rejected tx {:?}: not permitted by the transaction permission contract", hash);
+						Err(Error::Transaction(TransactionError::NotAllowed))
+					},
 					Ok(transaction) => {
 						let origin = accounts.as_ref().and_then(|accounts| {
 							match accounts.contains(&transaction.sender()) {
@@ -834,6 +926,10 @@ impl MinerService for Miner {
 		self.gas_range_target.read().0 / 5.into()
 	}
 
+	fn gas_price_recommendations(&self) -> Option<GasPriceRecommendations> {
+		self.gas_price_oracle.lock().recommendations()
+	}
+
 	fn transactions_limit(&self) -> usize {
 		self.transaction_queue.read().limit()
 	}
@@ -941,6 +1037,10 @@ impl MinerService for Miner {
 		queue.pending_transactions(BlockNumber::max_value(), u64::max_value())
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.transaction_queue.read().find(hash)
+	}
+
 	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus> {
 		let queue = self.transaction_queue.read();
 		queue.local_transactions()
@@ -1161,7 +1261,8 @@ impl MinerService for Miner {
 		// First update gas limit in transaction queue
 		self.update_gas_limit(chain);
 
-		// Update minimal gas price
+		// Refresh gas price statistics and update minimal gas price
+		self.gas_price_oracle.lock().update(chain);
 		self.recalibrate_minimal_gas_price();
 
 		// Then import all transactions...
@@ -1199,6 +1300,20 @@ impl MinerService for Miner {
 	}
 }
 
+impl Collector for Miner {
+	fn collect(&self) -> Vec<Metric> {
+		let status = self.status();
+
+		vec![
+			Metric::new("parity_transaction_queue_size", "Transactions in the queue by state.", MetricType::Gauge)
+				.with_sample(&[("state", "pending")], status.transactions_in_pending_queue as f64)
+				.with_sample(&[("state", "future")], status.transactions_in_future_queue as f64),
+			Metric::gauge("parity_pending_block_transactions", "Transactions in the block currently being sealed.",
+				status.transactions_in_pending_block as f64),
+		]
+	}
+}
+
 /// Action when service transaction is received
 enum ServiceTransactionAction {
 	/// Refuse service transaction immediately
@@ -1258,7 +1373,7 @@ mod tests {
 
 	use std::sync::Arc;
 	use std::time::Duration;
-	use super::super::{MinerService, PrioritizationStrategy};
+	use super::super::{MinerService, PrioritizationStrategy, AssemblyRule, policy_from_rules};
 	use super::*;
 	use block::IsBlock;
 	use util::{U256, Uint, FromHex};
@@ -1314,6 +1429,10 @@ mod tests {
 				tx_queue_size: 1024,
 				tx_queue_gas_limit: GasLimit::None,
 				tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+				tx_queue_price_bump: 0,
+				tx_queue_per_sender: usize::max_value(),
+				gas_price_oracle_blocks: 100,
+				block_assembly: Vec::new(),
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
@@ -1396,6 +1515,61 @@ mod tests {
 		assert!(miner.prepare_work_sealing(&client));
 	}
 
+	fn call_transaction(to: Address) -> SignedTransaction {
+		let keypair = Random.generate().unwrap();
+		Transaction {
+			action: Action::Call(to),
+			value: U256::zero(),
+			data: Vec::new(),
+			gas: U256::from(21_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None)
+	}
+
+	#[test]
+	fn should_cap_gas_used_by_single_recipient_when_assembling() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		miner.set_assembly_policy(policy_from_rules(&[AssemblyRule::RecipientCap(1)]));
+		let (contract, other) = (Address::from(0x10), Address::from(0x20));
+		let transactions = vec![
+			call_transaction(contract),
+			call_transaction(contract),
+			call_transaction(contract),
+			call_transaction(other),
+		];
+
+		// when
+		miner.import_external_transactions(&client, transactions.into_iter().map(Into::into).collect());
+
+		// then
+		// 1% of the ~3.14M block gas limit leaves room for a single 21k call per recipient
+		assert_eq!(miner.pending_transactions().len(), 4);
+		assert!(miner.prepare_work_sealing(&client));
+		assert_eq!(miner.map_sealing_work(&client, |b| b.transactions().len()), Some(2));
+	}
+
+	#[test]
+	fn should_put_local_transactions_first_when_assembling() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		miner.set_assembly_policy(policy_from_rules(&[AssemblyRule::LocalPriority, AssemblyRule::RecipientCap(1)]));
+		let contract = Address::from(0x10);
+		let external = call_transaction(contract);
+		let local = call_transaction(contract);
+
+		// when
+		miner.import_external_transactions(&client, vec![external.into()]).pop().unwrap().unwrap();
+		miner.import_own_transaction(&client, PendingTransaction::new(local.clone(), None)).unwrap();
+
+		// then
+		let included = miner.map_sealing_work(&client, |b| b.transactions().iter().map(|tx| tx.hash()).collect::<Vec<_>>());
+		assert_eq!(included, Some(vec![local.hash()]));
+	}
+
 	#[test]
 	fn should_not_seal_unless_enabled() {
 		let miner = miner();
@@ -42,7 +42,9 @@
 //! ```
 
 mod banning_queue;
+mod block_assembly;
 mod external;
+mod gas_price_oracle;
 mod local_transactions;
 mod miner;
 mod price_info;
@@ -50,16 +52,25 @@ mod service_transaction_checker;
 mod transaction_queue;
 mod work_notify;
 mod stratum;
+mod cpu_miner;
 
+pub use self::block_assembly::{
+	Assembly, AssemblyPolicy, AssemblyRule, LocalPriority, ReservedSenders, RecipientCap, Composite, policy_from_rules,
+};
 pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::gas_price_oracle::{GasPriceOracle, GasPriceRecommendations, GasPriceRecommendation};
 
-pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
+pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasPriceOracleOptions, GasLimit};
 pub use self::transaction_queue::{TransactionQueue, RemovalReason, TransactionDetailsProvider as TransactionQueueDetailsProvider,
 	PrioritizationStrategy, AccountDetails, TransactionOrigin};
 pub use self::local_transactions::{Status as LocalTransactionStatus};
 pub use client::TransactionImportResult;
 pub use self::work_notify::NotifyWork;
-pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
+pub use self::stratum::{
+	Stratum, Error as StratumError, Options as StratumOptions,
+	WorkerStatsProvider, WorkerStats as StratumWorkerStats,
+};
+pub use self::cpu_miner::CpuMiner;
 
 use std::collections::BTreeMap;
 use util::{H256, U256, Address, Bytes};
@@ -157,6 +168,9 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of all pending transactions in the queue.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// Query the queue (current and future transactions) for hash.
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction>;
+
 	/// Get a list of all transactions that can go into the given block.
 	fn ready_transactions(&self, best_block: BlockNumber, best_block_timestamp: u64) -> Vec<PendingTransaction>;
 
@@ -184,6 +198,9 @@ pub trait MinerService : Send + Sync {
 	/// Suggested gas limit.
 	fn sensible_gas_limit(&self) -> U256 { 21000.into() }
 
+	/// Gas price recommendations based on recent blocks.
+	fn gas_price_recommendations(&self) -> Option<GasPriceRecommendations>;
+
 	/// Latest account balance in pending state.
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256>;
 
@@ -20,18 +20,21 @@ use ethcore_stratum::{
 	JobDispatcher, PushWorkHandler,
 	Stratum as StratumService, Error as StratumServiceError,
 };
+pub use ethcore_stratum::WorkerStats;
 
 use std::sync::{Arc, Weak};
 use std::net::{SocketAddr, AddrParseError};
+use std::collections::HashMap;
 use std::fmt;
 
 use util::{H256, U256, H64, clean_0x};
 use ethereum::ethash::Ethash;
-use ethash::SeedHashCompute;
+use ethash::{SeedHashCompute, EthashManager};
 use util::Mutex;
 use miner::{self, Miner, MinerService};
 use client::Client;
 use block::IsBlock;
+use error::Error as EthcoreError;
 use std::str::FromStr;
 use rlp::encode;
 
@@ -48,15 +51,22 @@ pub struct Options {
 	pub secret: Option<H256>,
 }
 
+/// Provides share and hashrate accounting of stratum workers.
+pub trait WorkerStatsProvider: Send + Sync {
+	/// Statistics of all authorized workers, keyed by worker id.
+	fn worker_stats(&self) -> HashMap<String, WorkerStats>;
+}
+
 struct SubmitPayload {
 	nonce: H64,
 	pow_hash: H256,
-	mix_hash: H256,
+	/// Not submitted by EthereumStratum/1.0.0 workers.
+	mix_hash: Option<H256>,
 }
 
 impl SubmitPayload {
 	fn from_args(payload: Vec<String>) -> Result<Self, PayloadError> {
-		if payload.len() != 3 {
+		if payload.len() != 2 && payload.len() != 3 {
 			return Err(PayloadError::ArgumentsAmountUnexpected(payload.len()));
 		}
 
@@ -76,12 +86,13 @@ impl SubmitPayload {
 			}
 		};
 
-		let mix_hash = match H256::from_str(clean_0x(&payload[2])) {
-			Ok(mix_hash) => mix_hash,
-			Err(e) => {
+		let mix_hash = match payload.get(2).map(|mix_hash| H256::from_str(clean_0x(mix_hash))) {
+			Some(Ok(mix_hash)) => Some(mix_hash),
+			Some(Err(e)) => {
 				warn!(target: "stratum", "submit_work ({}): invalid mix-hash ({:?})",  &payload[2], e);
 				return Err(PayloadError::InvalidMixHash(payload[2].clone()));
-			}
+			},
+			None => None,
 		};
 
 		Ok(SubmitPayload {
@@ -109,6 +120,7 @@ impl fmt::Display for PayloadError {
 /// Job dispatcher for stratum service
 pub struct StratumJobDispatcher {
 	seed_compute: Mutex<SeedHashCompute>,
+	ethash: Arc<EthashManager>,
 	client: Weak<Client>,
 	miner: Weak<Miner>,
 }
@@ -131,6 +143,26 @@ impl JobDispatcher for StratumJobDispatcher {
 		)
 	}
 
+	fn difficulty(&self) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+				format!("[{}]", Self::share_difficulty(b.block().header().difficulty()))
+			})
+		)
+	}
+
+	fn ethereum_stratum_job(&self) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+				let pow_hash = b.hash();
+				let seed_hash = self.seed_compute.lock().get_seedhash(b.block().header().number());
+				// the header hash doubles as job id
+				format!(
+					r#"["{}","{}","{}",true]"#,
+					pow_hash.hex(), H256::from_slice(&seed_hash[..]).hex(), pow_hash.hex()
+				)
+			})
+		)
+	}
+
 	fn submit(&self, payload: Vec<String>) -> Result<(), StratumServiceError> {
 		let payload = SubmitPayload::from_args(payload).map_err(|e|
 			StratumServiceError::Dispatch(format!("{}", e))
@@ -138,28 +170,42 @@ impl JobDispatcher for StratumJobDispatcher {
 
 		trace!(
 			target: "stratum",
-			"submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={}",
+			"submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={:?}",
 			payload.nonce,
 			payload.pow_hash,
 			payload.mix_hash,
 		);
 
-		self.with_core_void(|client, miner| {
-			let seal = vec![encode(&payload.mix_hash).to_vec(), encode(&payload.nonce).to_vec()];
-			if let Err(e) = miner.submit_seal(&*client, payload.pow_hash, seal) {
-				warn!(target: "stratum", "submit_seal error: {:?}", e);
+		let result = self.with_core(|client, miner| {
+			let mix_hash = match payload.mix_hash {
+				Some(mix_hash) => mix_hash,
+				None => match miner.map_sealing_work(&*client, |b| b.block().header().number()) {
+					Some(number) => H256(self.ethash.compute_light(number, &payload.pow_hash.0, payload.nonce.low_u64()).mix_hash),
+					None => return None,
+				},
 			};
+			let seal = vec![encode(&mix_hash).to_vec(), encode(&payload.nonce).to_vec()];
+			Some(miner.submit_seal(&*client, payload.pow_hash, seal))
 		});
 
-		Ok(())
+		match result {
+			Some(Ok(())) => Ok(()),
+			Some(Err(EthcoreError::PowHashInvalid)) => Err(StratumServiceError::Stale),
+			Some(Err(e)) => {
+				warn!(target: "stratum", "submit_seal error: {:?}", e);
+				Err(StratumServiceError::Dispatch(format!("{}", e)))
+			},
+			None => Err(StratumServiceError::NoWork),
+		}
 	}
 }
 
 impl StratumJobDispatcher {
 	/// New stratum job dispatcher given the miner and client
-	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
+	fn new(miner: Weak<Miner>, client: Weak<Client>, ethash: Arc<EthashManager>) -> StratumJobDispatcher {
 		StratumJobDispatcher {
 			seed_compute: Mutex::new(SeedHashCompute::new()),
+			ethash: ethash,
 			client: client,
 			miner: miner,
 		}
@@ -177,16 +223,21 @@ impl StratumJobDispatcher {
 		)
 	}
 
+	/// Share difficulty in stratum units, where difficulty 1 corresponds to the boundary 2^224.
+	fn share_difficulty(difficulty: &U256) -> f64 {
+		let mut bytes = [0u8; 32];
+		difficulty.to_big_endian(&mut bytes);
+		bytes.iter().fold(0f64, |acc, b| acc * 256f64 + *b as f64) / 4294967296f64
+	}
+
 	fn with_core<F, R>(&self, f: F) -> Option<R> where F: Fn(Arc<Client>, Arc<Miner>) -> Option<R> {
 		self.client.upgrade().and_then(|client| self.miner.upgrade().and_then(|miner| (f)(client, miner)))
 	}
 
-	fn with_core_void<F>(&self, f: F) where F: Fn(Arc<Client>, Arc<Miner>) {
-		self.client.upgrade().map(|client| self.miner.upgrade().map(|miner| (f)(client, miner)));
-	}
 }
 
 /// Wrapper for dedicated stratum service
+#[derive(Clone)]
 pub struct Stratum {
 	dispatcher: Arc<StratumJobDispatcher>,
 	service: Arc<StratumService>,
@@ -199,6 +250,8 @@ pub enum Error {
 	Service(StratumServiceError),
 	/// Invalid network address
 	Address(AddrParseError),
+	/// The engine does not use proof-of-work
+	NoPowEngine,
 }
 
 impl From<StratumServiceError> for Error {
@@ -221,13 +274,21 @@ impl super::work_notify::NotifyWork for Stratum {
 	}
 }
 
+impl WorkerStatsProvider for Stratum {
+	fn worker_stats(&self) -> HashMap<String, WorkerStats> {
+		self.service.worker_stats()
+	}
+}
+
 impl Stratum {
 
 	/// New stratum job dispatcher, given the miner, client and dedicated stratum service
 	pub fn start(options: &Options, miner: Weak<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
 		use std::net::IpAddr;
 
-		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client));
+		// share the engine's light caches rather than building a second set
+		let ethash = client.upgrade().and_then(|client| client.engine().pow_cache()).ok_or(Error::NoPowEngine)?;
+		let dispatcher = Arc::new(StratumJobDispatcher::new(miner, client, ethash));
 
 		let stratum_svc = StratumService::start(
 			&SocketAddr::new(IpAddr::from_str(&options.listen_addr)?, options.port),
@@ -242,9 +303,9 @@ impl Stratum {
 	}
 
 	/// Start STRATUM job dispatcher and register it in the miner
-	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
+	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<Stratum, Error> {
 		let stratum = miner::Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
-		miner.push_notifier(Box::new(stratum) as Box<miner::NotifyWork>);
-		Ok(())
+		miner.push_notifier(Box::new(stratum.clone()) as Box<miner::NotifyWork>);
+		Ok(stratum)
 	}
 }
@@ -517,6 +517,8 @@ pub enum RemovalReason {
 	Invalid,
 	/// Transaction was canceled
 	Canceled,
+	/// Transaction was evicted to make room for a lower-nonce transaction of the same sender
+	Evicted,
 }
 
 /// Point in time when transaction was inserted.
@@ -541,6 +543,12 @@ pub struct TransactionQueue {
 	minimal_gas_price: U256,
 	/// The maximum amount of gas any individual transaction may use.
 	tx_gas_limit: U256,
+	/// Percentage by which the gas price of a transaction has to exceed the gas price
+	/// of a queued transaction with the same sender and nonce in order to replace it.
+	min_price_bump: usize,
+	/// Maximal number of transactions a single sender may hold in `future`.
+	/// Local transactions are not limited.
+	per_sender_limit: usize,
 	/// Current gas limit (block gas limit * factor). Transactions above the limit will not be accepted (default to !0)
 	gas_limit: U256,
 	/// Maximal time transaction may occupy the queue.
@@ -568,11 +576,18 @@ impl Default for TransactionQueue {
 impl TransactionQueue {
 	/// Creates new instance of this Queue
 	pub fn new(strategy: PrioritizationStrategy) -> Self {
-		Self::with_limits(strategy, 1024, !U256::zero(), !U256::zero())
+		Self::with_limits(strategy, 1024, !U256::zero(), !U256::zero(), 0, usize::max_value())
 	}
 
 	/// Create new instance of this Queue with specified limits
-	pub fn with_limits(strategy: PrioritizationStrategy, limit: usize, gas_limit: U256, tx_gas_limit: U256) -> Self {
+	pub fn with_limits(
+		strategy: PrioritizationStrategy,
+		limit: usize,
+		gas_limit: U256,
+		tx_gas_limit: U256,
+		min_price_bump: usize,
+		per_sender_limit: usize,
+	) -> Self {
 		let current = TransactionSet {
 			by_priority: BTreeSet::new(),
 			by_address: Table::new(),
@@ -593,6 +608,8 @@ impl TransactionQueue {
 			strategy: strategy,
 			minimal_gas_price: U256::zero(),
 			tx_gas_limit: tx_gas_limit,
+			min_price_bump: min_price_bump,
+			per_sender_limit: per_sender_limit,
 			gas_limit: !U256::zero(),
 			max_time_in_queue: DEFAULT_QUEUING_PERIOD,
 			current: current,
@@ -980,6 +997,9 @@ impl TransactionQueue {
 				RemovalReason::Canceled => self.local_transactions.mark_canceled(
 					PendingTransaction::new(transaction.transaction, transaction.condition)
 				),
+				RemovalReason::Evicted => self.local_transactions.mark_dropped(
+					transaction.transaction
+				),
 			}
 		}
 
@@ -1069,7 +1089,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_future(order.hash);
 				}
 				if let Some(old) = self.future.insert(*sender, k, order.clone()) {
-					Self::replace_orders(*sender, k, old, order, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(*sender, k, old, order, 0, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
 				}
 			} else {
 				trace!(target: "txqueue", "Removing old transaction: {:?} (nonce: {} < {})", order.hash, k, current_nonce);
@@ -1190,7 +1210,7 @@ impl TransactionQueue {
 					self.local_transactions.mark_pending(order.hash);
 				}
 				if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
-					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
+					let _ = Self::replace_orders(address, current_nonce, old, order, 0, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
 				}
 				update_last_nonce_to = Some(current_nonce);
 				current_nonce = current_nonce + U256::one();
@@ -1210,7 +1230,7 @@ impl TransactionQueue {
 	/// this.
 	///
 	/// It ignores transactions that has already been imported (same `hash`) and replaces the transaction
-	/// iff `(address, nonce)` is the same but `gas_price` is higher.
+	/// iff `(address, nonce)` is the same but `gas_price` is higher by at least `min_price_bump` percent.
 	///
 	/// Returns `true` when transaction was imported successfuly
 	fn import_tx(&mut self, tx: VerifiedTransaction, state_nonce: U256) -> Result<TransactionImportResult, TransactionError> {
@@ -1252,14 +1272,19 @@ impl TransactionQueue {
 		// Future transaction
 		if nonce > next_nonce {
 			// We have a gap - put to future.
+			// Make sure the sender doesn't hold too many transactions there
+			let evicted = self.check_sender_limit(&tx)?;
 			// Insert transaction (or replace old one with lower gas price)
-			check_too_cheap(
-				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
-			)?;
+			Self::replace_transaction(tx, state_nonce, min_gas_price, self.min_price_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions)?;
 			// Enforce limit in Future
 			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
 			// Return an error if this transaction was not imported because of limit.
 			check_if_removed(&address, &nonce, removed)?;
+			// The transaction is in, make room for it
+			if let Some(evicted) = evicted {
+				trace!(target: "txqueue", "Evicting future transaction {:?} of {:?} to make room for {:?}", evicted, address, hash);
+				self.remove(&evicted, &|_: &Address| state_nonce, RemovalReason::Evicted);
+			}
 
 			debug!(target: "txqueue", "Importing transaction to future: {:?}", hash);
 			debug!(target: "txqueue", "status: {:?}", self.status());
@@ -1271,9 +1296,7 @@ impl TransactionQueue {
 		self.move_matching_future_to_current(address, nonce + U256::one(), state_nonce);
 
 		// Replace transaction if any
-		check_too_cheap(
-			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
-		)?;
+		Self::replace_transaction(tx, state_nonce, min_gas_price, self.min_price_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions)?;
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
 		self.last_nonces.insert(address, new_max);
@@ -1290,6 +1313,37 @@ impl TransactionQueue {
 		Ok(TransactionImportResult::Current)
 	}
 
+	/// Checks that the sender of `tx` has a free slot in `future`.
+	///
+	/// If the sender is at the limit and `tx` has a lower nonce than the sender's highest
+	/// future transaction, returns the hash of that transaction, which has to be evicted
+	/// once `tx` is imported.
+	fn check_sender_limit(&self, tx: &VerifiedTransaction) -> Result<Option<H256>, TransactionError> {
+		if tx.origin.is_local() {
+			return Ok(None);
+		}
+
+		let address = tx.sender();
+		let nonce = tx.nonce();
+		let (count, highest) = match self.future.by_address.row(&address) {
+			// Replacing an existing transaction doesn't take a new slot
+			Some(by_nonce) if by_nonce.contains_key(&nonce) => return Ok(None),
+			Some(by_nonce) => (by_nonce.len(), by_nonce.iter().max_by_key(|&(n, _)| *n).map(|(n, order)| (*n, order.hash))),
+			None => (0, None),
+		};
+		if count < self.per_sender_limit {
+			return Ok(None);
+		}
+
+		match highest {
+			Some((highest_nonce, highest_hash)) if highest_nonce > nonce => Ok(Some(highest_hash)),
+			_ => {
+				trace!(target: "txqueue", "Dropping transaction above per-sender limit: {:?} ({} >= {})", tx.hash(), count, self.per_sender_limit);
+				Err(TransactionError::SenderLimitReached { limit: self.per_sender_limit })
+			},
+		}
+	}
+
 	/// Updates
 	fn update_last_nonces(&mut self, removed_min_nonces: &Option<HashMap<Address, U256>>) {
 		if let Some(ref min_nonces) = *removed_min_nonces {
@@ -1305,19 +1359,21 @@ impl TransactionQueue {
 
 	/// Replaces transaction in given set (could be `future` or `current`).
 	///
-	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
+	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher
+	/// by at least `price_bump` percent.
 	/// One of the transactions is dropped from set and also removed from queue entirely (from `by_hash`).
 	///
-	/// Returns `true` if transaction actually got to the queue (`false` if there was already a transaction with higher
-	/// gas_price)
+	/// Returns an error if the transaction didn't get to the queue because the queued one is not
+	/// sufficiently cheaper.
 	fn replace_transaction(
 		tx: VerifiedTransaction,
 		base_nonce: U256,
 		min_gas_price: (U256, PrioritizationStrategy),
+		price_bump: usize,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), TransactionError> {
 		let order = TransactionOrder::for_transaction(&tx, base_nonce, min_gas_price.0, min_gas_price.1);
 		let hash = tx.hash();
 		let address = tx.sender();
@@ -1329,9 +1385,9 @@ impl TransactionQueue {
 		trace!(target: "txqueue", "Inserting: {:?}", order);
 
 		if let Some(old) = set.insert(address, nonce, order.clone()) {
-			Self::replace_orders(address, nonce, old, order, set, by_hash, local)
+			Self::replace_orders(address, nonce, old, order, price_bump, set, by_hash, local)
 		} else {
-			true
+			Ok(())
 		}
 	}
 
@@ -1340,16 +1396,18 @@ impl TransactionQueue {
 		nonce: U256,
 		old: TransactionOrder,
 		order: TransactionOrder,
+		price_bump: usize,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), TransactionError> {
 		// There was already transaction in queue. Let's check which one should stay
 		let old_hash = old.hash;
 		let new_hash = order.hash;
 		let old_fee = old.gas_price;
 		let new_fee = order.gas_price;
-		if old_fee.cmp(&new_fee) == Ordering::Greater {
+		let min_fee = minimal_replacement_gas_price(old_fee, price_bump);
+		if new_fee < min_fee {
 			trace!(target: "txqueue", "Didn't insert transaction because gas price was too low: {:?} ({:?} stays in the queue)", order.hash, old.hash);
 			// Put back old transaction since it has greater priority (higher gas_price)
 			set.insert(address, nonce, old);
@@ -1358,7 +1416,14 @@ impl TransactionQueue {
 			if order.origin.is_local() {
 				local.mark_replaced(order.transaction, old_fee, old_hash);
 			}
-			false
+			if new_fee < old_fee {
+				Err(TransactionError::TooCheapToReplace)
+			} else {
+				Err(TransactionError::InsufficientPriceBump {
+					minimal: min_fee,
+					got: new_fee,
+				})
+			}
 		} else {
 			trace!(target: "txqueue", "Replaced transaction: {:?} with transaction with higher gas price: {:?}", old.hash, order.hash);
 			// Make sure we remove old transaction entirely
@@ -1366,16 +1431,20 @@ impl TransactionQueue {
 			if old.origin.is_local() {
 				local.mark_replaced(old.transaction, new_fee, new_hash);
 			}
-			true
+			Ok(())
 		}
 	}
 }
 
-fn check_too_cheap(is_in: bool) -> Result<(), TransactionError> {
-	if is_in {
-		Ok(())
-	} else {
-		Err(TransactionError::TooCheapToReplace)
+/// Lowest gas price that replaces a queued transaction with given `gas_price`.
+fn minimal_replacement_gas_price(gas_price: U256, price_bump: usize) -> U256 {
+	let bump = match gas_price.overflowing_mul(price_bump.into()) {
+		(_, true) => return !U256::zero(),
+		(val, false) => val / U256::from(100),
+	};
+	match gas_price.overflowing_add(bump) {
+		(_, true) => !U256::zero(),
+		(val, false) => val,
 	}
 }
 
@@ -1563,7 +1632,7 @@ pub mod test {
 	#[test]
 	fn should_return_correct_nonces_when_dropped_because_of_limit() {
 		// given
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 2, !U256::zero(), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 2, !U256::zero(), !U256::zero(), 0, usize::max_value());
 		let (tx1, tx2) = new_tx_pair(123.into(), 1.into(), 1.into(), 0.into());
 		let sender = tx1.sender();
 		let nonce = tx1.nonce;
@@ -2324,7 +2393,7 @@ pub mod test {
 	#[test]
 	fn should_drop_old_transactions_when_hitting_the_limit() {
 		// given
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero(), 0, usize::max_value());
 		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let sender = tx.sender();
 		let nonce = tx.nonce;
@@ -2345,7 +2414,7 @@ pub mod test {
 
 	#[test]
 	fn should_limit_future_transactions() {
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero(), 0, usize::max_value());
 		txq.current.set_limit(10);
 		let (tx1, tx2) = new_tx_pair_default(4.into(), 1.into());
 		let (tx3, tx4) = new_tx_pair_default(4.into(), 2.into());
@@ -2364,7 +2433,7 @@ pub mod test {
 
 	#[test]
 	fn should_limit_by_gas() {
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 100, default_gas_val() * U256::from(2), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 100, default_gas_val() * U256::from(2), !U256::zero(), 0, usize::max_value());
 		let (tx1, tx2) = new_tx_pair_default(U256::from(1), U256::from(1));
 		let (tx3, tx4) = new_tx_pair_default(U256::from(1), U256::from(2));
 		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
@@ -2377,7 +2446,7 @@ pub mod test {
 
 	#[test]
 	fn should_keep_own_transactions_above_gas_limit() {
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 100, default_gas_val() * U256::from(2), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 100, default_gas_val() * U256::from(2), !U256::zero(), 0, usize::max_value());
 		let (tx1, tx2) = new_tx_pair_default(U256::from(1), U256::from(1));
 		let (tx3, tx4) = new_tx_pair_default(U256::from(1), U256::from(2));
 		let (tx5, _) = new_tx_pair_default(U256::from(1), U256::from(2));
@@ -2523,6 +2592,68 @@ pub mod test {
 		assert_eq!(txq.top_transactions()[1].gas_price, U256::from(200));
 	}
 
+	#[test]
+	fn should_require_minimal_price_bump_to_replace() {
+		// given
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1024, !U256::zero(), !U256::zero(), 10, usize::max_value());
+		let keypair = Random.generate().unwrap();
+		let tx_with_price = |gas_price: u64| new_unsigned_tx(123.into(), default_gas_val(), gas_price.into()).sign(keypair.secret(), None);
+		txq.add(tx_with_price(100), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res1 = txq.add(tx_with_price(105), TransactionOrigin::External, 0, None, &default_tx_provider());
+		let res2 = txq.add(tx_with_price(90), TransactionOrigin::External, 0, None, &default_tx_provider());
+		let res3 = txq.add(tx_with_price(110), TransactionOrigin::External, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res1), TransactionError::InsufficientPriceBump { minimal: 110.into(), got: 105.into() });
+		assert_eq!(unwrap_tx_err(res2), TransactionError::TooCheapToReplace);
+		assert_eq!(res3.unwrap(), TransactionImportResult::Current);
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(110));
+	}
+
+	#[test]
+	fn should_limit_future_transactions_per_sender() {
+		// given
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1024, !U256::zero(), !U256::zero(), 0, 2);
+		let keypair = Random.generate().unwrap();
+		let tx_with_nonce = |nonce: u64| new_unsigned_tx(nonce.into(), default_gas_val(), 1.into()).sign(keypair.secret(), None);
+		let evicted = tx_with_nonce(126);
+		txq.add(tx_with_nonce(125), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(evicted.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res1 = txq.add(tx_with_nonce(127), TransactionOrigin::External, 0, None, &default_tx_provider());
+		let res2 = txq.add(tx_with_nonce(124), TransactionOrigin::External, 0, None, &default_tx_provider());
+		let res3 = txq.add(tx_with_nonce(128), TransactionOrigin::Local, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res1), TransactionError::SenderLimitReached { limit: 2 });
+		assert_eq!(res2.unwrap(), TransactionImportResult::Future);
+		assert_eq!(res3.unwrap(), TransactionImportResult::Future);
+		assert!(txq.find(&evicted.hash()).is_none());
+		assert_eq!(txq.status().future, 3);
+	}
+
+	#[test]
+	fn should_not_evict_for_transaction_dropped_by_future_limit() {
+		// given
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero(), 0, 1);
+		let keypair = Random.generate().unwrap();
+		let tx_with_nonce = |nonce: u64| new_unsigned_tx(nonce.into(), default_gas_val(), 1.into()).sign(keypair.secret(), None);
+		let retracted = tx_with_nonce(126);
+		txq.add(retracted.clone(), TransactionOrigin::RetractedBlock, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res = txq.add(tx_with_nonce(125), TransactionOrigin::External, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::LimitReached);
+		assert!(txq.find(&retracted.hash()).is_some());
+		assert_eq!(txq.status().future, 1);
+	}
+
 	#[test]
 	fn should_recalculate_height_when_removing_from_future() {
 		// given
@@ -2625,7 +2756,7 @@ pub mod test {
 	#[test]
 	fn should_keep_right_order_in_future() {
 		// given
-		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero());
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 1, !U256::zero(), !U256::zero(), 0, usize::max_value());
 		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let prev_nonce = default_account_details().nonce - U256::one();
 
@@ -48,7 +48,9 @@ pub enum ClientIoMessage {
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
 	/// New consensus message received.
-	NewMessage(Bytes)
+	NewMessage(Bytes),
+	/// New private transaction messages are queued for import.
+	NewPrivateTransaction,
 }
 
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
@@ -96,6 +98,7 @@ impl ClientService {
 
 
 		let pruning = config.pruning;
+		let snapshot_config = config.snapshot.clone();
 		let client = Client::new(config, &spec, db.clone(), miner, io_service.channel())?;
 
 		let snapshot_params = SnapServiceParams {
@@ -106,6 +109,7 @@ impl ClientService {
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
 			db_restore: client.clone(),
+			config: snapshot_config,
 		};
 		let snapshot = Arc::new(SnapshotService::new(snapshot_params)?);
 
@@ -34,7 +34,7 @@ use util::{Bytes, Hashable, HashDB, DBValue, snappy, U256, Uint};
 use util::Mutex;
 use util::hash::{H256};
 use util::journaldb::{self, Algorithm, JournalDB};
-use util::kvdb::Database;
+use util::kvdb::{Database, KeyValueDB};
 use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
 use util::sha3::SHA3_NULL_RLP;
 use rlp::{RlpStream, UntrustedRlp};
@@ -51,7 +51,7 @@ use rand::{Rng, OsRng};
 
 pub use self::error::Error;
 
-pub use self::service::{Service, DatabaseRestore};
+pub use self::service::{Service, DatabaseRestore, SnapshotConfiguration};
 pub use self::traits::SnapshotService;
 pub use self::watcher::Watcher;
 pub use types::snapshot_manifest::ManifestData;
@@ -396,7 +396,7 @@ pub struct StateRebuilder {
 
 impl StateRebuilder {
 	/// Create a new state rebuilder to write into the given backing DB.
-	pub fn new(db: Arc<Database>, pruning: Algorithm) -> Self {
+	pub fn new(db: Arc<KeyValueDB>, pruning: Algorithm) -> Self {
 		StateRebuilder {
 			db: journaldb::new(db.clone(), pruning, ::db::COL_STATE),
 			state_root: SHA3_NULL_RLP,
@@ -407,6 +407,50 @@ impl StateRebuilder {
 		}
 	}
 
+	/// Create a state rebuilder which picks up from progress previously
+	/// produced by `progress_rlp`, writing into the same backing DB.
+	pub fn resume(db: Arc<KeyValueDB>, pruning: Algorithm, progress: &[u8]) -> Result<Self, ::error::Error> {
+		let rlp = UntrustedRlp::new(progress);
+		let mut rebuilder = StateRebuilder::new(db, pruning);
+
+		rebuilder.state_root = rlp.val_at(0)?;
+		for pair in rlp.at(1)?.iter() {
+			rebuilder.known_code.insert(pair.val_at(0)?, pair.val_at(1)?);
+		}
+		for pair in rlp.at(2)?.iter() {
+			rebuilder.missing_code.insert(pair.val_at(0)?, pair.list_at(1)?);
+		}
+		for pair in rlp.at(3)?.iter() {
+			rebuilder.known_storage_roots.insert(pair.val_at(0)?, pair.val_at(1)?);
+		}
+
+		Ok(rebuilder)
+	}
+
+	/// Encode the in-memory progress of the rebuilder.
+	/// Only meaningful once all fed chunks have been flushed to the backing DB.
+	pub fn progress_rlp(&self) -> Bytes {
+		let mut stream = RlpStream::new_list(4);
+		stream.append(&self.state_root);
+
+		stream.begin_list(self.known_code.len());
+		for (code_hash, first_with) in &self.known_code {
+			stream.begin_list(2).append(code_hash).append(first_with);
+		}
+
+		stream.begin_list(self.missing_code.len());
+		for (code_hash, accounts) in &self.missing_code {
+			stream.begin_list(2).append(code_hash).append_list(accounts);
+		}
+
+		stream.begin_list(self.known_storage_roots.len());
+		for (addr_hash, root) in &self.known_storage_roots {
+			stream.begin_list(2).append(addr_hash).append(root);
+		}
+
+		stream.out()
+	}
+
 	/// Feed an uncompressed state chunk into the rebuilder.
 	pub fn feed(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
@@ -609,6 +653,32 @@ impl BlockRebuilder {
 		})
 	}
 
+	/// Create a BlockRebuilder which picks up from progress previously
+	/// produced by `progress_rlp`.
+	pub fn resume(chain: BlockChain, db: Arc<Database>, manifest: &ManifestData, progress: &[u8]) -> Result<Self, ::error::Error> {
+		let rlp = UntrustedRlp::new(progress);
+		let mut rebuilder = BlockRebuilder::new(chain, db, manifest)?;
+
+		rebuilder.fed_blocks = rlp.val_at(0)?;
+		for pair in rlp.at(1)?.iter() {
+			rebuilder.disconnected.push((pair.val_at(0)?, pair.val_at(1)?));
+		}
+
+		Ok(rebuilder)
+	}
+
+	/// Encode the in-memory progress of the rebuilder.
+	pub fn progress_rlp(&self) -> Bytes {
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&self.fed_blocks);
+		stream.begin_list(self.disconnected.len());
+		for &(num, ref hash) in &self.disconnected {
+			stream.begin_list(2).append(&num).append(hash);
+		}
+
+		stream.out()
+	}
+
 	/// Feed the rebuilder an uncompressed block chunk.
 	/// Returns the number of blocks fed or any errors.
 	pub fn feed(&mut self, chunk: &[u8], engine: &Engine, abort_flag: &AtomicBool) -> Result<u64, ::error::Error> {
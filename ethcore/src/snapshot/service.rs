@@ -18,13 +18,14 @@
 
 use std::collections::{HashMap, HashSet};
 use std::io::ErrorKind;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
-use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
+use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter, PackedWriter};
 
 use blockchain::BlockChain;
 use client::{BlockChainClient, Client};
@@ -34,11 +35,16 @@ use ids::BlockId;
 use service::ClientIoMessage;
 
 use io::IoChannel;
+use metrics::{Collector, Metric, MetricType};
 
 use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
 use util::journaldb::Algorithm;
 use util::kvdb::{Database, DatabaseConfig};
 use util::snappy;
+use rlp::{RlpStream, UntrustedRlp};
+
+/// Number of chunks fed between two checkpoints of the restoration progress.
+const CHECKPOINT_INTERVAL: usize = 64;
 
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
@@ -76,6 +82,8 @@ struct Restoration {
 	guard: Guard,
 	canonical_hashes: HashMap<u64, H256>,
 	db: Arc<Database>,
+	progress_path: PathBuf,
+	unsaved_chunks: usize,
 }
 
 struct RestorationParams<'a> {
@@ -86,6 +94,34 @@ struct RestorationParams<'a> {
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
+	progress_path: PathBuf, // where to write restoration progress.
+	progress: Option<Bytes>, // progress of an interrupted restoration to resume.
+}
+
+// read the progress of an interrupted restoration, provided it was of the same
+// manifest and recovery mode.
+fn read_progress(path: &Path, manifest: &ManifestData, recover: bool) -> Option<Bytes> {
+	let mut progress = Vec::new();
+	match File::open(path).and_then(|mut file| file.read_to_end(&mut progress)) {
+		Ok(_) => {},
+		Err(_) => return None,
+	}
+
+	let matches = {
+		let rlp = UntrustedRlp::new(&progress);
+		let stored_manifest = rlp.at(0).and_then(|m| ManifestData::from_rlp(m.as_raw()));
+		let stored_recover = rlp.val_at::<bool>(1);
+
+		match (stored_manifest, stored_recover) {
+			(Ok(m), Ok(r)) => &m == manifest && r == recover,
+			_ => false,
+		}
+	};
+
+	match matches {
+		true => Some(progress),
+		false => None,
+	}
 }
 
 impl Restoration {
@@ -93,31 +129,114 @@ impl Restoration {
 	fn new(params: RestorationParams) -> Result<Self, Error> {
 		let manifest = params.manifest;
 
-		let state_chunks = manifest.state_hashes.iter().cloned().collect();
-		let block_chunks = manifest.block_hashes.iter().cloned().collect();
+		let mut state_chunks: HashSet<H256> = manifest.state_hashes.iter().cloned().collect();
+		let mut block_chunks: HashSet<H256> = manifest.block_hashes.iter().cloned().collect();
 
 		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
 			.map_err(UtilError::SimpleString)?);
 
 		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
-		let blocks = BlockRebuilder::new(chain, raw_db.clone(), &manifest)?;
+
+		let mut canonical_hashes = HashMap::new();
+
+		let (state, blocks) = match params.progress {
+			Some(ref progress) => {
+				let rlp = UntrustedRlp::new(progress);
+
+				for hash in rlp.list_at::<H256>(2)? {
+					state_chunks.remove(&hash);
+					block_chunks.remove(&hash);
+				}
+
+				for pair in rlp.at(5)?.iter() {
+					canonical_hashes.insert(pair.val_at(0)?, pair.val_at(1)?);
+				}
+
+				let state = StateRebuilder::resume(raw_db.clone(), params.pruning, rlp.at(3)?.as_raw())?;
+				let blocks = BlockRebuilder::resume(chain, raw_db.clone(), &manifest, rlp.at(4)?.as_raw())?;
+				(state, blocks)
+			}
+			None => {
+				let state = StateRebuilder::new(raw_db.clone(), params.pruning);
+				let blocks = BlockRebuilder::new(chain, raw_db.clone(), &manifest)?;
+				(state, blocks)
+			}
+		};
 
 		let root = manifest.state_root.clone();
 		Ok(Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
-			state: StateRebuilder::new(raw_db.clone(), params.pruning),
+			state: state,
 			blocks: blocks,
 			writer: params.writer,
 			snappy_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
-			canonical_hashes: HashMap::new(),
+			canonical_hashes: canonical_hashes,
 			db: raw_db,
+			progress_path: params.progress_path,
+			unsaved_chunks: 0,
 		})
 	}
 
+	// hashes of all chunks which have been fed so far.
+	fn completed_chunks(&self) -> Vec<H256> {
+		let state_done = self.manifest.state_hashes.iter().filter(|h| !self.state_chunks_left.contains(h));
+		let blocks_done = self.manifest.block_hashes.iter().filter(|h| !self.block_chunks_left.contains(h));
+
+		state_done.chain(blocks_done).cloned().collect()
+	}
+
+	// write the progress of the restoration to disk so it can be resumed later.
+	// the database must have been flushed beforehand.
+	fn checkpoint(&self) -> Result<(), Error> {
+		let mut stream = RlpStream::new_list(6);
+		stream.append_raw(&self.manifest.clone().into_rlp(), 1);
+		stream.append(&self.writer.is_some());
+		stream.append_list::<H256, H256>(&self.completed_chunks());
+		stream.append_raw(&self.state.progress_rlp(), 1);
+		stream.append_raw(&self.blocks.progress_rlp(), 1);
+		stream.begin_list(self.canonical_hashes.len());
+		for (num, hash) in &self.canonical_hashes {
+			stream.begin_list(2).append(num).append(hash);
+		}
+
+		// write to a temporary file first so a crash can't leave a torn checkpoint.
+		let mut temp_path = self.progress_path.clone();
+		temp_path.set_extension("tmp");
+		File::create(&temp_path)?.write_all(&stream.out())?;
+		fs::rename(&temp_path, &self.progress_path)?;
+
+		Ok(())
+	}
+
+	// flush the database and write the progress once every `CHECKPOINT_INTERVAL` chunks.
+	fn note_chunk_fed(&mut self) -> Result<(), Error> {
+		self.unsaved_chunks += 1;
+		match self.unsaved_chunks >= CHECKPOINT_INTERVAL {
+			true => self.save(),
+			false => Ok(()),
+		}
+	}
+
+	// flush the database and write the progress.
+	fn save(&mut self) -> Result<(), Error> {
+		self.db.flush().map_err(UtilError::SimpleString)?;
+		self.checkpoint()?;
+		self.unsaved_chunks = 0;
+		Ok(())
+	}
+
+	// stop the restoration, keeping everything on disk to be resumed later.
+	fn suspend(mut self) {
+		if let Err(e) = self.save() {
+			warn!("Failed to save snapshot restoration progress: {}", e);
+		}
+		self.guard.disarm();
+	}
+
 	// feeds a state chunk, aborts early if `flag` becomes false.
 	fn feed_state(&mut self, hash: H256, chunk: &[u8], flag: &AtomicBool) -> Result<(), Error> {
 		if self.state_chunks_left.remove(&hash) {
@@ -188,6 +307,24 @@ impl Restoration {
 /// Type alias for client io channel.
 pub type Channel = IoChannel<ClientIoMessage>;
 
+/// Configuration for the snapshots this node produces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotConfiguration {
+	/// Number of snapshots to keep around, including the most recent one.
+	pub retention: usize,
+	/// Directory to additionally export every snapshot taken to as a packed file.
+	pub export_dir: Option<PathBuf>,
+}
+
+impl Default for SnapshotConfiguration {
+	fn default() -> Self {
+		SnapshotConfiguration {
+			retention: 1,
+			export_dir: None,
+		}
+	}
+}
+
 /// Snapshot service parameters.
 pub struct ServiceParams {
 	/// The consensus engine this is built on.
@@ -205,6 +342,8 @@ pub struct ServiceParams {
 	pub snapshot_root: PathBuf,
 	/// A handle for database restoration.
 	pub db_restore: Arc<DatabaseRestore>,
+	/// Retention and export of snapshots taken.
+	pub config: SnapshotConfiguration,
 }
 
 /// `SnapshotService` implementation.
@@ -217,6 +356,8 @@ pub struct Service {
 	pruning: Algorithm,
 	status: Mutex<RestorationStatus>,
 	reader: RwLock<Option<LooseReader>>,
+	retained: RwLock<Vec<LooseReader>>,
+	config: SnapshotConfiguration,
 	engine: Arc<Engine>,
 	genesis_block: Bytes,
 	state_chunks: AtomicUsize,
@@ -238,6 +379,8 @@ impl Service {
 			pruning: params.pruning,
 			status: Mutex::new(RestorationStatus::Inactive),
 			reader: RwLock::new(None),
+			retained: RwLock::new(Vec::new()),
+			config: params.config,
 			engine: params.engine,
 			genesis_block: params.genesis_block,
 			state_chunks: AtomicUsize::new(0),
@@ -255,12 +398,7 @@ impl Service {
 			}
 		}
 
-		// delete the temporary restoration dir if it does exist.
-		if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
-			if e.kind() != ErrorKind::NotFound {
-				return Err(e.into())
-			}
-		}
+		// the restoration dir is kept: an interrupted restoration may be resumed.
 
 		// delete the temporary snapshot dir if it does exist.
 		if let Err(e) = fs::remove_dir_all(service.temp_snapshot_dir()) {
@@ -272,9 +410,66 @@ impl Service {
 		let reader = LooseReader::new(service.snapshot_dir()).ok();
 		*service.reader.get_mut() = reader;
 
+		let retained = service.load_retained();
+		*service.retained.get_mut() = retained;
+		service.prune_retained()?;
+
 		Ok(service)
 	}
 
+	// load older snapshots kept around, most recent first.
+	fn load_retained(&self) -> Vec<LooseReader> {
+		let entries = match fs::read_dir(self.retained_dir()) {
+			Ok(entries) => entries,
+			Err(_) => return Vec::new(),
+		};
+
+		let mut retained: Vec<_> = entries
+			.filter_map(|entry| entry.ok())
+			.filter_map(|entry| LooseReader::new(entry.path()).ok())
+			.collect();
+
+		retained.sort_by(|a, b| b.manifest().block_number.cmp(&a.manifest().block_number));
+		retained
+	}
+
+	// delete older snapshots beyond the retention limit.
+	fn prune_retained(&self) -> Result<(), Error> {
+		let keep = self.config.retention.saturating_sub(1);
+		let mut retained = self.retained.write();
+
+		while retained.len() > keep {
+			if let Some(reader) = retained.pop() {
+				trace!(target: "snapshot", "removing snapshot at #{}", reader.manifest().block_number);
+				fs::remove_dir_all(self.retained_snapshot_dir(reader.manifest().block_number))?;
+			}
+		}
+
+		Ok(())
+	}
+
+	// write a snapshot out to the export directory as a packed file.
+	fn export_snapshot(&self, reader: &LooseReader, export_dir: &Path) -> Result<(), Error> {
+		let manifest = reader.manifest().clone();
+
+		fs::create_dir_all(export_dir)?;
+		let mut path = export_dir.to_owned();
+		path.push(format!("snapshot-{}.pack", manifest.block_number));
+
+		info!("Exporting snapshot at #{} to {}", manifest.block_number, path.display());
+
+		let mut writer = PackedWriter::new(&path)?;
+		for &hash in &manifest.state_hashes {
+			writer.write_state_chunk(hash, &reader.chunk(hash)?)?;
+		}
+		for &hash in &manifest.block_hashes {
+			writer.write_block_chunk(hash, &reader.chunk(hash)?)?;
+		}
+		writer.finish(manifest)?;
+
+		Ok(())
+	}
+
 	// get the current snapshot dir.
 	fn snapshot_dir(&self) -> PathBuf {
 		let mut dir = self.snapshot_root.clone();
@@ -289,6 +484,20 @@ impl Service {
 		dir
 	}
 
+	// get the directory holding older snapshots.
+	fn retained_dir(&self) -> PathBuf {
+		let mut dir = self.snapshot_root.clone();
+		dir.push("retained");
+		dir
+	}
+
+	// get the directory of an older snapshot taken at the given block.
+	fn retained_snapshot_dir(&self, num: u64) -> PathBuf {
+		let mut dir = self.retained_dir();
+		dir.push(format!("{}", num));
+		dir
+	}
+
 	// get the restoration directory.
 	fn restoration_dir(&self) -> PathBuf {
 		let mut dir = self.snapshot_root.clone();
@@ -303,6 +512,13 @@ impl Service {
 		dir
 	}
 
+	// restoration progress path.
+	fn restoration_progress(&self) -> PathBuf {
+		let mut dir = self.restoration_dir();
+		dir.push("progress");
+		dir
+	}
+
 	// temporary snapshot recovery path.
 	fn temp_recovery_dir(&self) -> PathBuf {
 		let mut dir = self.restoration_dir();
@@ -373,23 +589,45 @@ impl Service {
 
 		let mut reader = self.reader.write();
 
-		// destroy the old snapshot reader.
-		*reader = None;
+		// destroy the old snapshot reader, keeping the snapshot around if configured to.
+		let old_num = reader.take().map(|r| r.manifest().block_number);
 
 		if snapshot_dir.exists() {
-			fs::remove_dir_all(&snapshot_dir)?;
+			match old_num {
+				Some(old_num) if self.config.retention > 1 && old_num != num => {
+					let retained_dir = self.retained_snapshot_dir(old_num);
+					let _ = fs::remove_dir_all(&retained_dir);
+					fs::create_dir_all(self.retained_dir())?;
+					fs::rename(&snapshot_dir, &retained_dir)?;
+
+					self.retained.write().insert(0, LooseReader::new(retained_dir)?);
+				}
+				_ => fs::remove_dir_all(&snapshot_dir)?,
+			}
 		}
 
 		fs::rename(temp_dir, &snapshot_dir)?;
 
 		*reader = Some(LooseReader::new(snapshot_dir)?);
+		drop(reader);
 
 		guard.disarm();
+		self.prune_retained()?;
+
+		if let Some(ref export_dir) = self.config.export_dir {
+			if let Some(ref reader) = *self.reader.read() {
+				if let Err(e) = self.export_snapshot(reader, export_dir) {
+					warn!("Failed to export snapshot at #{}: {}", num, e);
+				}
+			}
+		}
+
 		Ok(())
 	}
 
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
+	/// If a previous restoration of the same manifest was interrupted, it is resumed.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
 		let rest_dir = self.restoration_dir();
 
@@ -398,14 +636,20 @@ impl Service {
 		self.state_chunks.store(0, Ordering::SeqCst);
 		self.block_chunks.store(0, Ordering::SeqCst);
 
-		// tear down existing restoration.
-		*res = None;
+		// tear down existing restoration, keeping its progress around.
+		if let Some(rest) = res.take() {
+			rest.suspend();
+		}
+
+		let progress = read_progress(&self.restoration_progress(), &manifest, recover);
 
-		// delete and restore the restoration dir.
-		if let Err(e) = fs::remove_dir_all(&rest_dir) {
-			match e.kind() {
-				ErrorKind::NotFound => {},
-				_ => return Err(e.into()),
+		// delete and restore the restoration dir unless we can pick up where we left off.
+		if progress.is_none() {
+			if let Err(e) = fs::remove_dir_all(&rest_dir) {
+				match e.kind() {
+					ErrorKind::NotFound => {},
+					_ => return Err(e.into()),
+				}
 			}
 		}
 
@@ -425,12 +669,25 @@ impl Service {
 			writer: writer,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
+			progress_path: self.restoration_progress(),
+			progress: progress,
 		};
 
 		let state_chunks = params.manifest.state_hashes.len();
 		let block_chunks = params.manifest.block_hashes.len();
 
-		*res = Some(Restoration::new(params)?);
+		let restoration = Restoration::new(params)?;
+		let state_chunks_done = state_chunks - restoration.state_chunks_left.len();
+		let block_chunks_done = block_chunks - restoration.block_chunks_left.len();
+
+		if state_chunks_done + block_chunks_done != 0 {
+			info!("Resuming snapshot restoration with {} of {} chunks already restored",
+				state_chunks_done + block_chunks_done, state_chunks + block_chunks);
+		}
+
+		self.state_chunks.store(state_chunks_done, Ordering::SeqCst);
+		self.block_chunks.store(block_chunks_done, Ordering::SeqCst);
+		*res = Some(restoration);
 
 		*self.status.lock() = RestorationStatus::Ongoing {
 			state_chunks: state_chunks as u32,
@@ -512,7 +769,10 @@ impl Service {
 									drop(db);
 									return self.finalize_restoration(&mut *restoration);
 								},
-								false => Ok(())
+								false => match *restoration {
+									Some(ref mut rest) => rest.note_chunk_fed(),
+									None => Ok(()),
+								}
 							}
 						}
 						other => other.map(drop),
@@ -556,8 +816,22 @@ impl SnapshotService for Service {
 		self.reader.read().as_ref().map(|r| r.manifest().clone())
 	}
 
+	fn manifest_at(&self, block_number: u64) -> Option<ManifestData> {
+		if let Some(manifest) = self.manifest() {
+			if manifest.block_number == block_number {
+				return Some(manifest);
+			}
+		}
+
+		self.retained.read().iter()
+			.map(|r| r.manifest())
+			.find(|m| m.block_number == block_number)
+			.cloned()
+	}
+
 	fn chunk(&self, hash: H256) -> Option<Bytes> {
 		self.reader.read().as_ref().and_then(|r| r.chunk(hash).ok())
+			.or_else(|| self.retained.read().iter().filter_map(|r| r.chunk(hash).ok()).next())
 	}
 
 	fn status(&self) -> RestorationStatus {
@@ -594,6 +868,22 @@ impl SnapshotService for Service {
 		}
 	}
 
+	fn completed_chunks(&self, manifest: ManifestData) -> Vec<H256> {
+		if let Some(ref rest) = *self.restoration.lock() {
+			if rest.manifest == manifest {
+				return rest.completed_chunks();
+			}
+		}
+
+		// restorations begun over the network always recover the snapshot.
+		let progress = match read_progress(&self.restoration_progress(), &manifest, true) {
+			Some(progress) => progress,
+			None => return Vec::new(),
+		};
+
+		UntrustedRlp::new(&progress).list_at(2).unwrap_or_else(|_| Vec::new())
+	}
+
 	fn provide_canon_hashes(&self, canonical: &[(u64, H256)]) {
 		let mut rest = self.restoration.lock();
 
@@ -603,9 +893,39 @@ impl SnapshotService for Service {
 	}
 }
 
+impl Collector for Service {
+	fn collect(&self) -> Vec<Metric> {
+		let (restoring, chunks, chunks_done) = match self.status() {
+			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
+				(true, (state_chunks, block_chunks), (state_chunks_done, block_chunks_done)),
+			_ => (false, (0, 0), (0, 0)),
+		};
+		let taking = self.taking_snapshot.load(Ordering::SeqCst);
+
+		vec![
+			Metric::gauge("parity_snapshot_restoring", "Whether a snapshot restoration is ongoing.", restoring as u8 as f64),
+			Metric::new("parity_snapshot_restoration_chunks", "Chunks of the snapshot being restored by kind.", MetricType::Gauge)
+				.with_sample(&[("kind", "state")], chunks.0 as f64)
+				.with_sample(&[("kind", "block")], chunks.1 as f64),
+			Metric::new("parity_snapshot_restoration_chunks_done", "Chunks of the snapshot restored so far by kind.", MetricType::Gauge)
+				.with_sample(&[("kind", "state")], chunks_done.0 as f64)
+				.with_sample(&[("kind", "block")], chunks_done.1 as f64),
+			Metric::gauge("parity_snapshot_taking", "Whether a snapshot is being taken.", taking as u8 as f64),
+			Metric::new("parity_snapshot_progress", "Progress of the snapshot being taken.", MetricType::Gauge)
+				.with_sample(&[("item", "accounts")], self.progress.accounts() as f64)
+				.with_sample(&[("item", "blocks")], self.progress.blocks() as f64)
+				.with_sample(&[("item", "bytes")], self.progress.size() as f64),
+		]
+	}
+}
+
 impl Drop for Service {
 	fn drop(&mut self) {
-		self.abort_restore();
+		// keep any restoration in progress on disk so it can be resumed after a restart.
+		self.restoring_snapshot.store(false, Ordering::SeqCst);
+		if let Some(rest) = self.restoration.lock().take() {
+			rest.suspend();
+		}
 	}
 }
 
@@ -647,6 +967,7 @@ mod tests {
 			channel: service.channel(),
 			snapshot_root: dir,
 			db_restore: Arc::new(NoopDBRestore),
+			config: Default::default(),
 		};
 
 		let service = Service::new(snapshot_params).unwrap();
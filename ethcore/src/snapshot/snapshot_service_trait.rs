@@ -27,6 +27,9 @@ pub trait SnapshotService : Sync + Send {
 	/// Query the most recent manifest data.
 	fn manifest(&self) -> Option<ManifestData>;
 
+	/// Query a retained manifest by the number of the block it was taken at.
+	fn manifest_at(&self, block_number: u64) -> Option<ManifestData>;
+
 	/// Get raw chunk for a given hash.
 	fn chunk(&self, hash: H256) -> Option<Bytes>;
 
@@ -49,6 +52,11 @@ pub trait SnapshotService : Sync + Send {
 	/// no-op if currently restoring.
 	fn restore_block_chunk(&self, hash: H256, chunk: Bytes);
 
+	/// Get the hashes of chunks of the given manifest which have already been restored,
+	/// either by the restoration in progress or by one interrupted before a restart.
+	/// `begin_restore` with the same manifest continues from these.
+	fn completed_chunks(&self, manifest: ManifestData) -> Vec<H256>;
+
 	/// Give the restoration in-progress some canonical block hashes for
 	/// extra verification (performed at the end)
 	fn provide_canon_hashes(&self, canonical: &[(u64, H256)]);
@@ -21,7 +21,8 @@ use std::sync::Arc;
 use client::{BlockChainClient, Client};
 use ids::BlockId;
 use snapshot::service::{Service, ServiceParams};
-use snapshot::{self, ManifestData, SnapshotService};
+use snapshot::io::{PackedReader, SnapshotReader};
+use snapshot::{self, ManifestData, SnapshotService, SnapshotConfiguration};
 use spec::Spec;
 use tests::helpers::generate_dummy_client_with_spec_and_data;
 
@@ -73,6 +74,7 @@ fn restored_is_equivalent() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path,
 		db_restore: client2.clone(),
+		config: Default::default(),
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -116,6 +118,7 @@ fn guards_delete_folders() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path.clone(),
 		db_restore: Arc::new(NoopDBRestore),
+		config: Default::default(),
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -139,6 +142,173 @@ fn guards_delete_folders() {
 	service.init_restore(manifest.clone(), true).unwrap();
 	assert!(path.exists());
 
+	// dropping the service keeps the restoration around to be resumed.
 	drop(service);
-	assert!(!path.exists());
+	assert!(path.exists());
+}
+
+#[test]
+fn resumes_interrupted_restoration() {
+	const NUM_BLOCKS: u32 = 400;
+	const TX_PER: usize = 5;
+
+	let gas_prices = vec![1.into(), 2.into(), 3.into(), 999.into()];
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, TX_PER, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, client_db.to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let service_params = |path: ::std::path::PathBuf| ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config.clone(),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path,
+		db_restore: client2.clone(),
+		config: Default::default(),
+	};
+
+	let service = Service::new(service_params(path.clone())).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	let manifest = service.manifest().unwrap();
+	let (first_state, rest_state) = manifest.state_hashes.split_at(manifest.state_hashes.len() / 2);
+
+	service.init_restore(manifest.clone(), true).unwrap();
+	for hash in first_state {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_state_chunk(*hash, &chunk);
+	}
+
+	// restart the service midway.
+	drop(service);
+	let service = Service::new(service_params(path)).unwrap();
+
+	let mut completed = service.completed_chunks(manifest.clone());
+	completed.sort();
+	let mut expected = first_state.to_vec();
+	expected.sort();
+	assert_eq!(completed, expected);
+
+	service.init_restore(manifest.clone(), true).unwrap();
+	for hash in rest_state {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_state_chunk(*hash, &chunk);
+	}
+
+	for hash in &manifest.block_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_block_chunk(*hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+
+	for x in 0..NUM_BLOCKS {
+		let block1 = client.block(BlockId::Number(x as u64)).unwrap();
+		let block2 = client2.block(BlockId::Number(x as u64)).unwrap();
+
+		assert_eq!(block1, block2);
+	}
+}
+
+#[test]
+fn retains_configured_number_of_snapshots() {
+	const NUM_BLOCKS: u32 = 20;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 1, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let path = path.as_path().clone();
+	let spec = Spec::new_null();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		config: SnapshotConfiguration {
+			retention: 2,
+			export_dir: None,
+		},
+	};
+
+	let service = Service::new(service_params).unwrap();
+	let oldest = NUM_BLOCKS as u64 - 2;
+	let previous = NUM_BLOCKS as u64 - 1;
+	for num in oldest..(NUM_BLOCKS as u64 + 1) {
+		service.take_snapshot(&client, num).unwrap();
+	}
+
+	assert_eq!(service.manifest().unwrap().block_number, NUM_BLOCKS as u64);
+
+	// the previous snapshot is kept and its chunks are still served.
+	let retained = service.manifest_at(previous).unwrap();
+	assert!(path.join("retained").join(format!("{}", previous)).exists());
+	for hash in retained.state_hashes.iter().chain(&retained.block_hashes) {
+		assert!(service.chunk(*hash).is_some());
+	}
+
+	// the oldest one is pruned.
+	assert!(service.manifest_at(oldest).is_none());
+	assert!(!path.join("retained").join(format!("{}", oldest)).exists());
+}
+
+#[test]
+fn exports_snapshot() {
+	const NUM_BLOCKS: u32 = 20;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 1, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let path = path.as_path().clone();
+	let mut snapshot_root = path.clone();
+	let mut export_dir = path.clone();
+	snapshot_root.push("snapshot");
+	export_dir.push("export");
+
+	let spec = Spec::new_null();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: snapshot_root,
+		db_restore: Arc::new(NoopDBRestore),
+		config: SnapshotConfiguration {
+			retention: 1,
+			export_dir: Some(export_dir.clone()),
+		},
+	};
+
+	let service = Service::new(service_params).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	let manifest = service.manifest().unwrap();
+	let reader = PackedReader::new(&export_dir.join(format!("snapshot-{}.pack", NUM_BLOCKS))).unwrap().unwrap();
+
+	assert_eq!(reader.manifest(), &manifest);
+	for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
+		assert_eq!(reader.chunk(*hash).unwrap(), service.chunk(*hash).unwrap());
+	}
 }
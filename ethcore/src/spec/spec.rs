@@ -57,6 +57,17 @@ pub struct CommonParams {
 	pub eip98_transition: BlockNumber,
 	/// Validate block receipts root.
 	pub validate_receipts_transition: u64,
+	/// Contract deciding which senders are allowed to call or create contracts.
+	pub transaction_permission_contract: Option<Address>,
+	/// Contract deciding which pairs of nodes are allowed to connect to each other.
+	pub node_permission_contract: Option<Address>,
+}
+
+impl CommonParams {
+	/// Block numbers where common consensus rules change.
+	pub fn transitions(&self) -> Vec<BlockNumber> {
+		vec![self.eip98_transition, self.validate_receipts_transition]
+	}
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
@@ -71,6 +82,8 @@ impl From<ethjson::spec::Params> for CommonParams {
 			fork_block: if let (Some(n), Some(h)) = (p.fork_block, p.fork_hash) { Some((n.into(), h.into())) } else { None },
 			eip98_transition: p.eip98_transition.map_or(0, Into::into),
 			validate_receipts_transition: p.validate_receipts_transition.map_or(0, Into::into),
+			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
+			node_permission_contract: p.node_permission_contract.map(Into::into),
 		}
 	}
 }
@@ -192,6 +205,9 @@ impl Spec {
 	/// Get the configured network fork block.
 	pub fn fork_block(&self) -> Option<(BlockNumber, H256)> { self.params.fork_block }
 
+	/// Get all blocks where consensus rules change.
+	pub fn fork_transitions(&self) -> Vec<BlockNumber> { self.engine.fork_transitions() }
+
 	/// Get the header of the genesis block.
 	pub fn genesis_header(&self) -> Header {
 		let mut header: Header = Default::default();
@@ -346,6 +362,14 @@ impl Spec {
 	/// Accounts with secrets "0".sha3() and "1".sha3() are the validators.
 	pub fn new_test_round() -> Self { load_bundled!("authority_round") }
 
+	/// Create a new Spec with AuthorityRound consensus whose rewards are decided by a block reward contract
+	/// at address 0x42. The contract always rewards 0x99 with 1000 wei, regardless of the benefactors.
+	pub fn new_test_round_block_reward_contract() -> Self { load_bundled!("authority_round_block_reward_contract") }
+
+	/// Create a new Spec with InstantSeal consensus and a transaction permission contract at address 0x06.
+	/// Only 0x00a329c0648769a73afac7f9381e08fb43dbea72 is allowed to create contracts, calls are always allowed.
+	pub fn new_test_tx_permission() -> Self { load_bundled!("tx_permission") }
+
 	/// Create a new Spec with Tendermint consensus which does internal sealing (not requiring work).
 	/// Account "0".sha3() and "1".sha3() are a authorities.
 	pub fn new_test_tendermint() -> Self { load_bundled!("tendermint") }
@@ -23,16 +23,17 @@ use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::Entry;
 
 use receipt::Receipt;
-use engines::Engine;
+use action_params::{ActionParams, ActionValue};
+use engines::{Engine, EngineError, SYSTEM_ADDRESS};
 use env_info::EnvInfo;
 use error::Error;
 use executive::{Executive, TransactOptions};
 use factory::Factories;
-use trace::FlatTrace;
+use trace::{FlatTrace, NoopTracer, NoopVMTracer};
 use pod_account::*;
 use pod_state::{self, PodState};
 use types::basic_account::BasicAccount;
-use types::executed::{Executed, ExecutionError};
+use types::executed::{Executed, ExecutionError, CallType};
 use types::state_diff::StateDiff;
 use transaction::SignedTransaction;
 use state_db::StateDB;
@@ -612,6 +613,34 @@ impl<B: Backend> State<B> {
 		Ok(ApplyOutcome{receipt: receipt, trace: e.trace})
 	}
 
+	/// Call `contract_address` with `data` on behalf of the system address.
+	/// No gas is paid and the nonce of the system address is left untouched;
+	/// this is meant for engine-level calls made while closing a block.
+	pub fn system_call(&mut self, env_info: &EnvInfo, engine: &Engine, contract_address: Address, gas: U256, data: Bytes) -> Result<Bytes, Error> {
+		let params = ActionParams {
+			code_address: contract_address.clone(),
+			address: contract_address.clone(),
+			sender: SYSTEM_ADDRESS,
+			origin: SYSTEM_ADDRESS,
+			gas: gas,
+			gas_price: U256::zero(),
+			value: ActionValue::Transfer(U256::zero()),
+			code: self.code(&contract_address)?,
+			code_hash: self.code_hash(&contract_address)?,
+			data: Some(data),
+			call_type: CallType::Call,
+		};
+		let mut substate = Substate::new();
+		let mut output = Vec::new();
+		let vm_factory = self.factories.vm.clone();
+
+		Executive::new(self, env_info, engine, &vm_factory)
+			.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut NoopTracer, &mut NoopVMTracer)
+			.map_err(|e| EngineError::FailedSystemCall(format!("{}", e)))?;
+
+		Ok(output)
+	}
+
 	// Execute a given transaction.
 	fn execute(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> Result<Executed, ExecutionError> {
 		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
@@ -27,6 +27,7 @@ use builtin::Builtin;
 use state::*;
 use evm::Schedule;
 use engines::Engine;
+use tx_filter::TransactionFilter;
 use env_info::EnvInfo;
 use ethereum;
 use ethereum::ethash::EthashParams;
@@ -68,6 +69,10 @@ impl Engine for TestEngine {
 		self.engine.params()
 	}
 
+	fn transaction_filter(&self) -> Option<&TransactionFilter> {
+		self.engine.transaction_filter()
+	}
+
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
 		self.engine.builtins()
 	}
@@ -215,8 +215,8 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		block_number: BlockNumber,
 		tx_number: usize
 	) -> Vec<LocalizedTrace> {
-		let tx_hash = self.extras.transaction_hash(block_number, tx_number)
-			.expect("Expected to find transaction hash. Database is probably corrupted");
+		// traces past the last transaction (e.g. block rewards) have no transaction hash.
+		let tx_hash = self.extras.transaction_hash(block_number, tx_number);
 
 		let flat_traces: Vec<FlatTrace> = traces.into();
 		flat_traces.into_iter()
@@ -227,7 +227,7 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_number,
+						transaction_number: tx_hash.map(|_| tx_number),
 						transaction_hash: tx_hash.clone(),
 						block_number: block_number,
 						block_hash: block_hash
@@ -313,15 +313,14 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				// this may and should be optimized
 				.and_then(|traces| traces.into_iter().find(|trace| trace.trace_address == trace_position_deq))
 				.map(|trace| {
-					let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-						.expect("Expected to find transaction hash. Database is probably corrupted");
+					let tx_hash = self.extras.transaction_hash(block_number, tx_position);
 
 					LocalizedTrace {
 						action: trace.action,
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_position,
+						transaction_number: tx_hash.map(|_| tx_position),
 						transaction_hash: tx_hash,
 						block_number: block_number,
 						block_hash: block_hash,
@@ -336,8 +335,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				.and_then(|traces| traces.into_iter().nth(tx_position))
 				.map(Into::<Vec<FlatTrace>>::into)
 				.map(|traces| {
-					let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-						.expect("Expected to find transaction hash. Database is probably corrupted");
+					let tx_hash = self.extras.transaction_hash(block_number, tx_position);
 
 					traces.into_iter()
 					.map(|trace| LocalizedTrace {
@@ -345,7 +343,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 						result: trace.result,
 						subtraces: trace.subtraces,
 						trace_address: trace.trace_address.into_iter().collect(),
-						transaction_number: tx_position,
+						transaction_number: tx_hash.map(|_| tx_position),
 						transaction_hash: tx_hash.clone(),
 						block_number: block_number,
 						block_hash: block_hash
@@ -363,8 +361,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 						.map(Into::<Vec<FlatTrace>>::into)
 						.enumerate()
 						.flat_map(|(tx_position, traces)| {
-							let tx_hash = self.extras.transaction_hash(block_number, tx_position)
-								.expect("Expected to find transaction hash. Database is probably corrupted");
+							let tx_hash = self.extras.transaction_hash(block_number, tx_position);
 
 							traces.into_iter()
 								.map(|trace| LocalizedTrace {
@@ -372,7 +369,7 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 									result: trace.result,
 									subtraces: trace.subtraces,
 									trace_address: trace.trace_address.into_iter().collect(),
-									transaction_number: tx_position,
+									transaction_number: tx_hash.map(|_| tx_position),
 									transaction_hash: tx_hash.clone(),
 									block_number: block_number,
 									block_hash: block_hash,
@@ -543,8 +540,8 @@ mod tests {
 			result: Res::FailedCall(TraceError::OutOfGas),
 			trace_address: vec![],
 			subtraces: 0,
-			transaction_number: 0,
-			transaction_hash: tx_hash,
+			transaction_number: Some(0),
+			transaction_hash: Some(tx_hash),
 			block_number: block_number,
 			block_hash: block_hash,
 		}
@@ -0,0 +1,140 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction permissioning: only senders whitelisted by a contract named in the chain spec
+//! may call or create contracts.
+
+use futures::{future, Future};
+use lru_cache::LruCache;
+use native_contracts::TransactPermission;
+use client::MiningBlockChainClient;
+use spec::CommonParams;
+use transaction::{Action, SignedTransaction};
+use types::ids::BlockId;
+use util::{Address, H256, Mutex};
+
+const MAX_CACHE_SIZE: usize = 4096;
+
+/// Kind of a transaction as understood by the permission contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransactionKind {
+	/// Message call, including plain value transfers.
+	Call = 0,
+	/// Contract creation.
+	Create = 1,
+}
+
+/// Checks transactions against the permission contract, caching the results per block.
+pub struct TransactionFilter {
+	contract: TransactPermission,
+	permission_cache: Mutex<LruCache<(H256, Address, Address, TransactionKind), bool>>,
+}
+
+impl TransactionFilter {
+	/// Create a filter if the chain spec names a permission contract.
+	pub fn from_params(params: &CommonParams) -> Option<TransactionFilter> {
+		params.transaction_permission_contract.map(|address| TransactionFilter {
+			contract: TransactPermission::new(address),
+			permission_cache: Mutex::new(LruCache::new(MAX_CACHE_SIZE)),
+		})
+	}
+
+	/// Check if the transaction is allowed in a block on top of `parent_hash`.
+	pub fn transaction_allowed(&self, parent_hash: &H256, transaction: &SignedTransaction, client: &MiningBlockChainClient) -> bool {
+		let sender = transaction.sender();
+		let (to, kind) = match transaction.action {
+			Action::Create => (Address::default(), TransactionKind::Create),
+			Action::Call(ref address) => (*address, TransactionKind::Call),
+		};
+		let key = (*parent_hash, sender, to, kind);
+
+		if let Some(allowed) = self.permission_cache.lock().get_mut(&key) {
+			return *allowed;
+		}
+
+		let allowed = self.contract.is_allowed(
+			|addr, data| future::done(client.call_contract(BlockId::Hash(*parent_hash), addr, data)),
+			sender,
+			to,
+			kind as u8,
+		).wait().unwrap_or_else(|e| {
+			debug!(target: "tx_filter", "Error calling transaction permission contract: {}", e);
+			false
+		});
+		trace!(target: "tx_filter", "Transaction {:?} from {} allowed: {}", kind, sender, allowed);
+
+		self.permission_cache.lock().insert(key, allowed);
+		allowed
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use util::{Address, U256};
+	use spec::Spec;
+	use transaction::{Action, Transaction};
+	use tests::helpers::generate_dummy_client_with_spec_and_accounts;
+	use client::BlockChainClient;
+	use super::TransactionFilter;
+
+	#[test]
+	fn transaction_filter() {
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_tx_permission, None);
+		let filter = TransactionFilter::from_params(client.engine().params()).unwrap();
+		let genesis = client.chain_info().best_block_hash;
+
+		let creator = Address::from_str("00a329c0648769a73afac7f9381e08fb43dbea72").unwrap();
+		let other = Address::from(0x1234);
+		let transaction = |action, sender| Transaction {
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas: 21_000.into(),
+			action: action,
+			value: U256::zero(),
+			data: Vec::new(),
+		}.fake_sign(sender);
+
+		assert!(filter.transaction_allowed(&genesis, &transaction(Action::Create, creator), &*client));
+		assert!(!filter.transaction_allowed(&genesis, &transaction(Action::Create, other), &*client));
+		assert!(filter.transaction_allowed(&genesis, &transaction(Action::Call(creator), other), &*client));
+		// Cached result is used for the second check.
+		assert!(!filter.transaction_allowed(&genesis, &transaction(Action::Create, other), &*client));
+	}
+
+	#[test]
+	fn engine_verifies_transaction_permission() {
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_test_tx_permission, None);
+		let genesis = client.chain_info().best_block_hash;
+		let transaction = |sender| Transaction {
+			nonce: U256::zero(),
+			gas_price: U256::zero(),
+			gas: 21_000.into(),
+			action: Action::Create,
+			value: U256::zero(),
+			data: Vec::new(),
+		}.fake_sign(sender);
+
+		let creator = Address::from_str("00a329c0648769a73afac7f9381e08fb43dbea72").unwrap();
+		assert!(client.engine().verify_transaction_family(&transaction(creator), &genesis, &*client).is_ok());
+		assert!(client.engine().verify_transaction_family(&transaction(Address::from(0x1234)), &genesis, &*client).is_err());
+	}
+
+	#[test]
+	fn no_filter_without_contract() {
+		assert!(TransactionFilter::from_params(&Spec::new_instant().params).is_none());
+	}
+}
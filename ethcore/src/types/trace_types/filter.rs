@@ -130,6 +130,10 @@ impl Filter {
 				let from_matches = self.from_address.matches(&suicide.address);
 				let to_matches = self.to_address.matches(&suicide.refund_address);
 				from_matches && to_matches
+			},
+			Action::Reward(ref reward) => {
+				// rewards have no sender, so they only match an unrestricted `from` filter.
+				self.from_address.matches_all() && self.to_address.matches(&reward.author)
 			}
 		}
 	}
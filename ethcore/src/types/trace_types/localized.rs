@@ -35,9 +35,11 @@ pub struct LocalizedTrace {
 	/// [index in root, index in first CALL, index in second CALL, ...]
 	pub trace_address: Vec<usize>,
 	/// Transaction number within the block.
-	pub transaction_number: usize,
+	/// `None` for traces which do not belong to a transaction, e.g. block rewards.
+	pub transaction_number: Option<usize>,
 	/// Signed transaction hash.
-	pub transaction_hash: H256,
+	/// `None` for traces which do not belong to a transaction, e.g. block rewards.
+	pub transaction_hash: Option<H256>,
 	/// Block number.
 	pub block_number: BlockNumber,
 	/// Block hash.
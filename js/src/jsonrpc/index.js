@@ -19,6 +19,7 @@ import eth from './interfaces/eth';
 import net from './interfaces/net';
 import parity from './interfaces/parity';
 import personal from './interfaces/personal';
+import privateTx from './interfaces/private';
 import shh from './interfaces/shh';
 import signer from './interfaces/signer';
 import trace from './interfaces/trace';
@@ -30,6 +31,7 @@ export default {
   parity,
   net,
   personal,
+  private: privateTx,
   shh,
   signer,
   trace,
@@ -285,6 +285,40 @@ export default {
     }
   },
 
+  gasPriceRecommendations: {
+    section: SECTION_NET,
+    desc: 'Returns fast, standard and slow gas price recommendations based on the gas used in recent blocks.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'Recommendations, each with a `gasPrice` and the `expectedWait` until inclusion in seconds (`null` if unlikely to be included).',
+      details: {
+        fast: {
+          type: Object,
+          desc: 'Price likely to be included in the next few blocks.'
+        },
+        standard: {
+          type: Object,
+          desc: 'Price paid for the bulk of recently used gas.'
+        },
+        slow: {
+          type: Object,
+          desc: 'Cheap price that may take a while to be included.'
+        },
+        blocks: {
+          type: Quantity,
+          desc: 'Number of blocks the recommendations are based on.'
+        }
+      },
+      example: {
+        fast: { gasPrice: '0x4a817c800', expectedWait: 15 },
+        standard: { gasPrice: '0x3b9aca000', expectedWait: 30 },
+        slow: { gasPrice: '0x2540be400', expectedWait: 120 },
+        blocks: 100
+      }
+    }
+  },
+
   generateSecretPhrase: {
     section: SECTION_ACCOUNTS,
     desc: 'Creates a secret phrase that can be associated with an account.',
@@ -398,6 +432,37 @@ export default {
     }
   },
 
+  maliciousEvidence: {
+    section: SECTION_NODE,
+    desc: 'Returns evidence of validator misbehaviour (double signing) detected by the consensus engine.',
+    params: [],
+    returns: {
+      type: Array,
+      desc: 'Array of evidence objects.',
+      details: {
+        validator: {
+          type: Address,
+          desc: 'Misbehaving validator.'
+        },
+        blockNumber: {
+          type: Quantity,
+          desc: 'Block number at which the misbehaviour happened.'
+        },
+        proof: {
+          type: Data,
+          desc: 'RLP-encoded conflicting signed headers or consensus messages.'
+        }
+      },
+      example: [
+        {
+          validator: '0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e',
+          blockNumber: fromDecimal(5),
+          proof: '0xf9...'
+        }
+      ]
+    }
+  },
+
   minGasPrice: {
     section: SECTION_MINING,
     desc: 'Returns currently set minimal gas price',
@@ -489,7 +554,7 @@ export default {
         },
         peers: {
           type: Array,
-          desc: 'List of all peers with details.'
+          desc: 'List of all peers with details, including each peer\'s `reputation` score.'
         }
       },
       example: {
@@ -633,8 +698,11 @@ export default {
       example: {
         '0xdff37270050bcfba242116c745885ce2656094b2d3a0f855649b4a0ee9b5d15a': {
           firstSeen: 3032066,
+          receivedFrom: null,
           propagatedTo: {
-            '0x605e04a43b1156966b3a3b66b980c87b7f18522f7f712035f84576016be909a2798a438b2b17b1a8c58db314d88539a77419ca4be36148c086900fba487c9d39': 1,
+            '0x605e04a43b1156966b3a3b66b980c87b7f18522f7f712035f84576016be909a2798a438b2b17b1a8c58db314d88539a77419ca4be36148c086900fba487c9d39': 1
+          },
+          announcedTo: {
             '0xbab827781c852ecf52e7c8bf89b806756329f8cbf8d3d011e744a0bc5e3a0b0e1095257af854f3a8415ebe71af11b0c537f8ba797b25972f519e75339d6d1864': 1
           }
         }
@@ -799,6 +867,92 @@ export default {
     }
   },
 
+  stratumWorkers: {
+    section: SECTION_MINING,
+    desc: 'Returns share and hashrate statistics of workers connected to the stratum server, error if not enabled.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'Mapping of worker name to its statistics.',
+      details: {
+        hashrate: {
+          type: Quantity,
+          desc: 'Hashrate last reported by the worker.'
+        },
+        acceptedShares: {
+          type: Quantity,
+          desc: 'Number of accepted shares.'
+        },
+        staleShares: {
+          type: Quantity,
+          desc: 'Number of shares submitted for out-of-date work.'
+        },
+        rejectedShares: {
+          type: Quantity,
+          desc: 'Number of invalid shares.'
+        },
+        lastShare: {
+          type: Quantity,
+          desc: 'Unix timestamp of the last submitted share or `null`.'
+        }
+      },
+      example: {
+        'miner1.rig1': {
+          hashrate: '0x500000',
+          acceptedShares: 3,
+          staleShares: 1,
+          rejectedShares: 0,
+          lastShare: 1500000000
+        }
+      }
+    }
+  },
+
+  syncDiagnostics: {
+    section: SECTION_NET,
+    desc: 'Returns detailed block sync diagnostics: downloader state, per-peer requests and recent sync errors',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'The diagnostics object',
+      details: {
+        state: {
+          type: String,
+          desc: 'Sync state'
+        },
+        lastProgressBlockNumber: {
+          type: Quantity,
+          desc: 'Best block number when sync last made progress'
+        },
+        lastProgressAge: {
+          type: Quantity,
+          desc: 'Seconds since the best block last advanced'
+        },
+        stallRecoveries: {
+          type: Quantity,
+          desc: 'Number of times sync was restarted after stalling'
+        },
+        newBlocks: {
+          type: Object,
+          desc: 'New blocks downloader state'
+        },
+        oldBlocks: {
+          type: Object,
+          desc: 'Ancient blocks downloader state, if downloading',
+          optional: true
+        },
+        peers: {
+          type: Array,
+          desc: 'Connected peers with their pending requests'
+        },
+        recentErrors: {
+          type: Array,
+          desc: 'Recent sync errors, oldest first'
+        }
+      }
+    }
+  },
+
   transactionsLimit: {
     section: SECTION_MINING,
     desc: 'Changes limit for transactions in queue.',
@@ -1716,6 +1870,23 @@ export default {
     }
   },
 
+  setLogLevels: {
+    subdoc: SUBDOC_SET,
+    desc: 'Changes logging levels without a restart. Levels given on the command line are replaced, `RUST_LOG` directives are kept.',
+    params: [
+      {
+        type: String,
+        desc: 'Logging levels, in the same format as `--logging`.',
+        example: 'sync=trace,rpc=debug'
+      }
+    ],
+    returns: {
+      type: Boolean,
+      desc: '`true` if the call succeeded.',
+      example: true
+    }
+  },
+
   setMode: {
     subdoc: SUBDOC_SET,
     desc: 'Changes the operating mode of Parity.',
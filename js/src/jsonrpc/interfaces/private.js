@@ -0,0 +1,79 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+import { Address, BlockNumber, Data, Hash, CallRequest } from '../types';
+
+export default {
+  sendTransaction: {
+    desc: 'Sends a signed private transaction to the validators of its contract.',
+    params: [
+      {
+        type: Data,
+        desc: 'The signed transaction data.',
+        example: '0xd46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675'
+      }
+    ],
+    returns: {
+      type: Object,
+      desc: 'Receipt of the private transaction.',
+      details: {
+        transactionHash: {
+          type: Hash,
+          desc: 'Hash of the private transaction.'
+        },
+        contractAddress: {
+          type: Address,
+          desc: 'Address of the private contract.'
+        },
+        output: {
+          type: Data,
+          desc: 'Output of the local execution.'
+        }
+      },
+      example: {
+        transactionHash: '0x4bbe6f3bf0ae5d7b5b10aa0c3e8b3f11af87c3c4e2b4fd6c4c8b5f08a3e4ab1b',
+        contractAddress: '0x407d73d8a49eeb85d32cf465507dd71d507100c1',
+        output: '0x'
+      }
+    }
+  },
+
+  call: {
+    desc: 'Executes a call against the decrypted state of a private contract without creating a transaction.',
+    params: [
+      {
+        type: CallRequest,
+        desc: 'The transaction call object.',
+        format: 'inputCallFormatter',
+        example: {
+          from: '0x407d73d8a49eeb85d32cf465507dd71d507100c1',
+          to: '0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b'
+        }
+      },
+      {
+        type: BlockNumber,
+        desc: 'Integer block number, or the string `\'latest\'` or `\'earliest\'`, see the [default block parameter](#the-default-block-parameter).',
+        format: 'inputDefaultBlockNumberFormatter',
+        optional: true
+      }
+    ],
+    returns: {
+      type: Data,
+      desc: 'The return value of the executed contract.',
+      example: '0x'
+    }
+  }
+};
@@ -34,6 +34,10 @@ pub struct AuthorityRoundParams {
 	/// Block reward.
 	#[serde(rename="blockReward")]
 	pub block_reward: Option<Uint>,
+	/// Block reward contract address.
+	/// If present, rewards are decided by calling this contract instead of paying `blockReward`.
+	#[serde(rename="blockRewardContractAddress")]
+	pub block_reward_contract_address: Option<Address>,
 	/// Address of the registrar contract.
 	pub registrar: Option<Address>,
 	/// Starting step. Determined automatically if not specified.
@@ -58,6 +62,9 @@ pub struct AuthorityRound {
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use util::H160;
+	use hash::Address;
+	use std::str::FromStr;
 	use spec::authority_round::AuthorityRound;
 
 	#[test]
@@ -70,11 +77,13 @@ mod tests {
 					"list" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
 				},
 				"blockReward": "0x50",
+				"blockRewardContractAddress": "0x2000000000000000000000000000000000000002",
 				"startStep" : 24,
 				"eip155Transition": "0x42"
 			}
 		}"#;
 
-		let _deserialized: AuthorityRound = serde_json::from_str(s).unwrap();
+		let deserialized: AuthorityRound = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.block_reward_contract_address, Some(Address(H160::from_str("2000000000000000000000000000000000000002").unwrap())));
 	}
 }
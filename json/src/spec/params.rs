@@ -17,7 +17,7 @@
 //! Spec params deserialization.
 
 use uint::Uint;
-use hash::H256;
+use hash::{H256, Address};
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -56,11 +56,19 @@ pub struct Params {
 	/// See `CommonParams` docs.
 	#[serde(rename="validateReceiptsTransition")]
 	pub validate_receipts_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="transactionPermissionContract")]
+	pub transaction_permission_contract: Option<Address>,
+	/// See `CommonParams` docs.
+	#[serde(rename="nodePermissionContract")]
+	pub node_permission_contract: Option<Address>,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use util::H160;
+	use hash::Address;
 	use spec::params::Params;
 
 	#[test]
@@ -72,10 +80,14 @@ mod tests {
 			"chainID" : "0x15",
 			"subprotocolName" : "exp",
 			"minGasLimit": "0x1388",
-			"accountStartNonce": "0x00"
+			"accountStartNonce": "0x00",
+			"transactionPermissionContract": "0x0000000000000000000000000000000000000005",
+			"nodePermissionContract": "0x0000000000000000000000000000000000000006"
 		}"#;
 
-		let _deserialized: Params = serde_json::from_str(s).unwrap();
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.transaction_permission_contract, Some(Address(H160::from(5))));
+		assert_eq!(deserialized.node_permission_contract, Some(Address(H160::from(6))));
 		// TODO: validate all fields
 	}
 }
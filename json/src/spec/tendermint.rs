@@ -43,6 +43,10 @@ pub struct TendermintParams {
 	/// Block reward.
 	#[serde(rename="blockReward")]
 	pub block_reward: Option<Uint>,
+	/// Block reward contract address.
+	/// If present, rewards are decided by calling this contract instead of paying `blockReward`.
+	#[serde(rename="blockRewardContractAddress")]
+	pub block_reward_contract_address: Option<Address>,
 	/// Address of the registrar contract.
 	pub registrar: Option<Address>,
 }
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
+//! Manages local node data: pending local transactions, transaction queue journal,
+//! sync security level
 
 use std::sync::Arc;
 use std::fmt;
@@ -45,6 +46,7 @@ extern crate log;
 extern crate ethkey;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const QUEUE_JOURNAL_KEY: &'static [u8] = &*b"TXQ_JOURNAL";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
 const UPDATE_TIMEOUT_MS: u64 = 15 * 60 * 1000; // once every 15 minutes.
@@ -98,6 +100,16 @@ struct TransactionEntry {
 }
 
 impl TransactionEntry {
+	fn into_unverified(self) -> Option<UnverifiedTransaction> {
+		match UntrustedRlp::new(&self.rlp_bytes).as_val() {
+			Err(e) => {
+				warn!(target: "local_store", "Invalid journaled transaction stored: {}", e);
+				None
+			}
+			Ok(tx) => Some(tx),
+		}
+	}
+
 	fn into_pending(self) -> Option<PendingTransaction> {
 		let tx: UnverifiedTransaction = match UntrustedRlp::new(&self.rlp_bytes).as_val() {
 			Err(e) => {
@@ -131,6 +143,12 @@ impl From<PendingTransaction> for TransactionEntry {
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get all queued transactions (current and future) which are not of local origin.
+	/// These are journaled only when the node opts in; the default is not to journal anything.
+	fn queued_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
 }
 
 /// Create a new local data store, given a database, a column to write to, and a node.
@@ -169,6 +187,31 @@ impl<T: NodeInfo> LocalDataStore<T> {
 		}
 	}
 
+	/// Attempt to read the transaction queue journal out of the store.
+	///
+	/// Transactions are returned unverified: they should be re-imported into the queue
+	/// which checks them against the current state and drops the stale ones.
+	pub fn queued_transactions(&self) -> Result<Vec<UnverifiedTransaction>, Error> {
+		if let Some(val) = self.db.get(self.col, QUEUE_JOURNAL_KEY).map_err(Error::Database)? {
+			let queued_txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
+				.map_err(Error::Json)?
+				.into_iter()
+				.filter_map(TransactionEntry::into_unverified)
+				.collect();
+
+			Ok(queued_txs)
+		} else {
+			Ok(Vec::new())
+		}
+	}
+
+	/// Remove the transaction queue journal from the store.
+	pub fn clear_queued_transactions(&self) -> Result<(), Error> {
+		let mut batch = self.db.transaction();
+		batch.delete(self.col, QUEUE_JOURNAL_KEY);
+		self.db.write(batch).map_err(Error::Database)
+	}
+
 	/// Update the entries in the database.
 	pub fn update(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Updating local store entries.");
@@ -184,6 +227,20 @@ impl<T: NodeInfo> LocalDataStore<T> {
 		let json_str = format!("{}", local_json);
 
 		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
+
+		let queued_entries: Vec<TransactionEntry> = self.node.queued_transactions()
+			.into_iter()
+			.map(Into::into)
+			.collect();
+
+		if queued_entries.is_empty() {
+			batch.delete(self.col, QUEUE_JOURNAL_KEY);
+		} else {
+			trace!(target: "local_store", "Journaling {} queued transactions.", queued_entries.len());
+			let queued_json = ::serde_json::to_value(&queued_entries).map_err(Error::Json)?;
+			batch.put_vec(self.col, QUEUE_JOURNAL_KEY, format!("{}", queued_json).into_bytes());
+		}
+
 		self.db.write(batch).map_err(Error::Database)
 	}
 }
@@ -228,6 +285,12 @@ mod tests {
 		fn pending_transactions(&self) -> Vec<PendingTransaction> { self.0.clone() }
 	}
 
+	struct Journaling(Vec<PendingTransaction>);
+	impl NodeInfo for Journaling {
+		fn pending_transactions(&self) -> Vec<PendingTransaction> { Vec::new() }
+		fn queued_transactions(&self) -> Vec<PendingTransaction> { self.0.clone() }
+	}
+
 	#[test]
 	fn twice_empty() {
 		let db = Arc::new(::util::kvdb::in_memory(0));
@@ -312,4 +375,50 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn journals_queued_transactions() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..10u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let db = Arc::new(::util::kvdb::in_memory(0));
+		{
+			// nothing journaled yet, will journal the queue.
+			let store = super::create(db.clone(), None, Journaling(transactions.clone()));
+			assert!(store.queued_transactions().unwrap().is_empty());
+			assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		}
+		{
+			// queue journaled, journaling disabled so the journal is cleared.
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let loaded = store.queued_transactions().unwrap();
+			let expected: Vec<_> = transactions.into_iter().map(|tx| tx.transaction.into()).collect();
+			assert_eq!(loaded, expected);
+		}
+		{
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			assert!(store.queued_transactions().unwrap().is_empty());
+		}
+	}
+
+	#[test]
+	fn clears_journal() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let tx = PendingTransaction::new(Transaction::default().sign(keypair.secret(), None), None);
+
+		let db = Arc::new(::util::kvdb::in_memory(0));
+		{
+			let _store = super::create(db.clone(), None, Journaling(vec![tx]));
+		}
+
+		let store = super::create(db.clone(), None, Journaling(vec![]));
+		assert_eq!(store.queued_transactions().unwrap().len(), 1);
+		store.clear_queued_transactions().unwrap();
+		assert!(store.queued_transactions().unwrap().is_empty());
+	}
 }
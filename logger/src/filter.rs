@@ -0,0 +1,162 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Log level filter which can be changed at runtime.
+
+use std::str::FromStr;
+use rlog::{LogLevelFilter, LogMetadata, LogRecord, MaxLogLevelFilter};
+use env_logger::{LogBuilder, Logger};
+use parking_lot::{Mutex, RwLock};
+
+/// Module-level log filter. Uses the same directive format as `RUST_LOG`.
+pub struct Filter {
+	/// Directives from `RUST_LOG`, always applied before the configured ones.
+	base: Option<String>,
+	/// Human-readable description of active directives.
+	levels: RwLock<String>,
+	logger: RwLock<Logger>,
+	max_level: Mutex<Option<MaxLogLevelFilter>>,
+}
+
+impl Filter {
+	/// Create a filter from `RUST_LOG` directives and configured levels.
+	pub fn new(base: Option<String>, levels: Option<&str>) -> Self {
+		let logger = Self::build(base.as_ref().map(|s| s.as_str()), levels);
+		Filter {
+			levels: RwLock::new(Self::describe(base.as_ref().map(|s| s.as_str()), levels)),
+			base: base,
+			logger: RwLock::new(logger),
+			max_level: Mutex::new(None),
+		}
+	}
+
+	fn build(base: Option<&str>, levels: Option<&str>) -> Logger {
+		let mut builder = LogBuilder::new();
+		// Disable ws info logging by default.
+		builder.filter(Some("ws"), LogLevelFilter::Warn);
+		// Disable rustls info logging by default.
+		builder.filter(Some("rustls"), LogLevelFilter::Warn);
+		builder.filter(None, LogLevelFilter::Info);
+
+		if let Some(base) = base {
+			builder.parse(base);
+		}
+
+		if let Some(levels) = levels {
+			builder.parse(levels);
+		}
+
+		builder.build()
+	}
+
+	/// Attach the global max level handle, so it follows the filter.
+	pub fn set_max_level(&self, max_level: MaxLogLevelFilter) {
+		max_level.set(self.logger.read().filter());
+		*self.max_level.lock() = Some(max_level);
+	}
+
+	/// Replace configured levels. `RUST_LOG` directives are kept.
+	pub fn set_levels(&self, levels: &str) -> Result<(), String> {
+		validate(levels)?;
+
+		let base = self.base.as_ref().map(|s| s.as_str());
+		let logger = Self::build(base, Some(levels));
+		let filter = logger.filter();
+		*self.logger.write() = logger;
+		*self.levels.write() = Self::describe(base, Some(levels));
+		if let Some(ref max_level) = *self.max_level.lock() {
+			max_level.set(filter);
+		}
+		Ok(())
+	}
+
+	fn describe(base: Option<&str>, levels: Option<&str>) -> String {
+		let mut result = String::new();
+		if let Some(base) = base {
+			result.push_str(base);
+			result.push_str(",");
+		}
+		if let Some(levels) = levels {
+			result.push_str(levels);
+		}
+		result
+	}
+
+	/// Active levels, as reported by `parity_devLogsLevels`.
+	pub fn levels(&self) -> String {
+		self.levels.read().clone()
+	}
+
+	/// Whether a log message with given metadata would be logged.
+	pub fn enabled(&self, metadata: &LogMetadata) -> bool {
+		use rlog::Log;
+		self.logger.read().enabled(metadata)
+	}
+
+	/// Whether the record should be logged.
+	pub fn matches(&self, record: &LogRecord) -> bool {
+		self.logger.read().matches(record)
+	}
+}
+
+/// Check that levels are well-formed `RUST_LOG` directives.
+pub fn validate(levels: &str) -> Result<(), String> {
+	let directives = levels.splitn(2, '/').next().unwrap_or("");
+	for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+		let mut parts = directive.split('=');
+		let (_module, level) = (parts.next(), parts.next());
+		if parts.next().is_some() {
+			return Err(format!("Invalid logging directive: {}", directive));
+		}
+		if let Some(level) = level {
+			if LogLevelFilter::from_str(level.trim()).is_err() {
+				return Err(format!("Invalid log level in directive: {}", directive));
+			}
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Filter, validate};
+
+	#[test]
+	fn should_validate_levels() {
+		assert!(validate("").is_ok());
+		assert!(validate("info").is_ok());
+		assert!(validate("sync=trace,rpc=debug").is_ok());
+		assert!(validate("sync=trace/block").is_ok());
+		assert!(validate("sync=verbose").is_err());
+		assert!(validate("sync=trace=debug").is_err());
+	}
+
+	#[test]
+	fn should_replace_levels() {
+		// given
+		let filter = Filter::new(Some("own_tx=trace".into()), Some("sync=debug"));
+		assert_eq!(filter.levels(), "own_tx=trace,sync=debug");
+
+		// when
+		let invalid = filter.set_levels("sync=loud");
+		let valid = filter.set_levels("rpc=trace");
+
+		// then
+		assert!(invalid.is_err());
+		assert!(valid.is_ok());
+		assert_eq!(filter.levels(), "own_tx=trace,rpc=trace");
+	}
+}
@@ -27,24 +27,55 @@ extern crate lazy_static;
 extern crate parking_lot;
 extern crate ansi_term;
 
+mod filter;
+mod log_file;
 mod rotating;
 
-use std::{env, thread, fs};
+use std::{env, thread, io};
+use std::str::FromStr;
 use std::sync::{Weak, Arc};
 use std::io::Write;
 use isatty::{stderr_isatty, stdout_isatty};
-use env_logger::LogBuilder;
 use regex::Regex;
 use ansi_term::Colour;
 use parking_lot::Mutex;
+use rlog::{Log, LogLevel, LogLevelFilter, LogMetadata, LogRecord};
+use filter::Filter;
+use log_file::LogFile;
 
 pub use rotating::{RotatingLogger, init_log};
 
+/// Output format of log lines.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+	/// Human-readable text, optionally colored.
+	Text,
+	/// One JSON object per line.
+	Json,
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"text" => Ok(Format::Text),
+			"json" => Ok(Format::Json),
+			other => Err(format!("Invalid log format: {}. Expected 'text' or 'json'.", other)),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	pub format: Format,
+	/// Rotate the log file once it exceeds this many bytes.
+	pub file_max_size: Option<u64>,
+	/// Number of rotated log files to keep.
+	pub file_max_count: usize,
 }
 
 impl Default for Config {
@@ -53,6 +84,9 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			format: Format::Text,
+			file_max_size: None,
+			file_max_count: 5,
 		}
 	}
 }
@@ -61,63 +95,60 @@ lazy_static! {
 	static ref ROTATING_LOGGER : Mutex<Weak<RotatingLogger>> = Mutex::new(Default::default());
 }
 
-/// Sets up the logger
-pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
-	use rlog::*;
-
-	let mut levels = String::new();
-	let mut builder = LogBuilder::new();
-	// Disable ws info logging by default.
-	builder.filter(Some("ws"), LogLevelFilter::Warn);
-	// Disable rustls info logging by default.
-	builder.filter(Some("rustls"), LogLevelFilter::Warn);
-	builder.filter(None, LogLevelFilter::Info);
-
-	if let Ok(lvl) = env::var("RUST_LOG") {
-		levels.push_str(&lvl);
-		levels.push_str(",");
-		builder.parse(&lvl);
+/// Logger writing formatted records to stderr, with levels changeable at runtime.
+struct Logger<F> {
+	filter: Arc<Filter>,
+	format: F,
+}
+
+impl<F> Log for Logger<F> where F: Fn(&LogRecord) -> String + Send + Sync {
+	fn enabled(&self, metadata: &LogMetadata) -> bool {
+		self.filter.enabled(metadata)
 	}
 
-	if let Some(ref s) = config.mode {
-		levels.push_str(s);
-		builder.parse(s);
+	fn log(&self, record: &LogRecord) {
+		if self.filter.matches(record) {
+			// ignore errors - there's nothing we can do
+			let _ = writeln!(io::stderr(), "{}", (self.format)(record));
+		}
 	}
+}
+
+/// Sets up the logger
+pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
+	let filter = Arc::new(Filter::new(env::var("RUST_LOG").ok(), config.mode.as_ref().map(|s| s.as_str())));
 
 	let isatty = stderr_isatty();
-	let enable_color = config.color && isatty;
-	let logs = Arc::new(RotatingLogger::new(levels));
+	let json = config.format == Format::Json;
+	let enable_color = config.color && isatty && !json;
+	let logs = Arc::new(RotatingLogger::with_filter(filter.clone()));
 	let logger = logs.clone();
-	let mut open_options = fs::OpenOptions::new();
 
 	let maybe_file = match config.file.as_ref() {
-		Some(f) => Some(open_options
-			.append(true).create(true).open(f)
-			.map_err(|_| format!("Cannot write to log file given: {}", f))?),
+		Some(f) => Some(Mutex::new(LogFile::open(f, config.file_max_size, config.file_max_count)
+			.map_err(|_| format!("Cannot write to log file given: {}", f))?)),
 		None => None,
 	};
 
 	let format = move |record: &LogRecord| {
-		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
-
-		let with_color = if max_log_level() <= LogLevelFilter::Info {
-			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
-		} else {
-			let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
-			format!("{} {} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
-		};
-
-		let removed_color = kill_color(with_color.as_ref());
-
-		let ret = match enable_color {
-			true => with_color,
-			false => removed_color.clone(),
+		let (ret, removed_color) = match json {
+			true => {
+				let line = json_line(record);
+				(line.clone(), line)
+			},
+			false => {
+				let with_color = text_line(record);
+				let removed_color = kill_color(with_color.as_ref());
+				match enable_color {
+					true => (with_color, removed_color),
+					false => (removed_color.clone(), removed_color),
+				}
+			},
 		};
 
-		if let Some(mut file) = maybe_file.as_ref() {
+		if let Some(ref file) = maybe_file {
 			// ignore errors - there's nothing we can do
-			let _ = file.write_all(removed_color.as_bytes());
-			let _ = file.write_all(b"\n");
+			let _ = file.lock().write_line(&removed_color);
 		}
 		logger.append(removed_color);
 		if !isatty && record.level() <= LogLevel::Info && stdout_isatty() {
@@ -126,10 +157,17 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		}
 
 		ret
-    };
+	};
+
+	let log = Logger {
+		filter: filter.clone(),
+		format: format,
+	};
 
-	builder.format(format);
-	builder.init()
+	rlog::set_logger(|max_level| {
+			filter.set_max_level(max_level);
+			Box::new(log)
+		})
 		.and_then(|_| {
 			*ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
 			Ok(logs)
@@ -142,6 +180,47 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		})
 }
 
+fn text_line(record: &LogRecord) -> String {
+	let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
+
+	if rlog::max_log_level() <= LogLevelFilter::Info {
+		format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
+	} else {
+		let name = thread::current().name().map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
+		format!("{} {} {} {}  {}", Colour::Black.bold().paint(timestamp), name, record.level(), record.target(), record.args())
+	}
+}
+
+fn json_line(record: &LogRecord) -> String {
+	let timestamp = format!("{}", time::now_utc().rfc3339());
+	let thread = thread::current();
+
+	format!(
+		"{{\"timestamp\":\"{}\",\"level\":\"{}\",\"target\":\"{}\",\"thread\":\"{}\",\"message\":\"{}\"}}",
+		timestamp,
+		record.level(),
+		escape_json(record.target()),
+		escape_json(thread.name().unwrap_or("")),
+		escape_json(&kill_color(&format!("{}", record.args())))
+	)
+}
+
+fn escape_json(s: &str) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
 fn kill_color(s: &str) -> String {
 	lazy_static! {
 		static ref RE: Regex = Regex::new("\x1b\\[[^m]+m").unwrap();
@@ -162,3 +241,17 @@ fn should_remove_multiple_colour() {
 	let after = kill_color(&t);
 	assert_eq!(after, "test again");
 }
+
+#[test]
+fn should_escape_json() {
+	assert_eq!(escape_json("plain"), "plain");
+	assert_eq!(escape_json("a \"quoted\"\\path\n"), "a \\\"quoted\\\"\\\\path\\n");
+	assert_eq!(escape_json("\u{1b}[0m"), "\\u001b[0m");
+}
+
+#[test]
+fn should_parse_format() {
+	assert_eq!("text".parse::<Format>(), Ok(Format::Text));
+	assert_eq!("json".parse::<Format>(), Ok(Format::Json));
+	assert!("xml".parse::<Format>().is_err());
+}
@@ -0,0 +1,152 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Log file with optional size-based rotation.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Log file which is rotated once it grows over given size.
+///
+/// Rotated files get a numeric suffix, `.1` being the most recent one.
+/// Only `max_files` rotated files are kept.
+pub struct LogFile {
+	path: PathBuf,
+	file: File,
+	size: u64,
+	max_size: Option<u64>,
+	max_files: usize,
+}
+
+impl LogFile {
+	/// Open (or create) log file at given path for appending.
+	pub fn open<P: AsRef<Path>>(path: P, max_size: Option<u64>, max_files: usize) -> io::Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let file = Self::open_file(&path)?;
+		let size = file.metadata()?.len();
+
+		Ok(LogFile {
+			path: path,
+			file: file,
+			size: size,
+			max_size: max_size,
+			max_files: max_files,
+		})
+	}
+
+	fn open_file(path: &Path) -> io::Result<File> {
+		OpenOptions::new().append(true).create(true).open(path)
+	}
+
+	fn rotated_path(&self, index: usize) -> PathBuf {
+		let mut name = self.path.clone().into_os_string();
+		name.push(format!(".{}", index));
+		name.into()
+	}
+
+	fn rotate(&mut self) -> io::Result<()> {
+		if self.max_files == 0 {
+			fs::remove_file(&self.path)?;
+		} else {
+			let _ = fs::remove_file(self.rotated_path(self.max_files));
+			for index in (1..self.max_files).rev() {
+				let from = self.rotated_path(index);
+				if from.exists() {
+					fs::rename(&from, self.rotated_path(index + 1))?;
+				}
+			}
+			fs::rename(&self.path, self.rotated_path(1))?;
+		}
+
+		self.file = Self::open_file(&self.path)?;
+		self.size = 0;
+		Ok(())
+	}
+
+	/// Append a line to the file, rotating it first if the line would not fit.
+	pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+		let len = line.len() as u64 + 1;
+		if let Some(max_size) = self.max_size {
+			if self.size > 0 && self.size + len > max_size {
+				self.rotate()?;
+			}
+		}
+
+		self.file.write_all(line.as_bytes())?;
+		self.file.write_all(b"\n")?;
+		self.size += len;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{env, fs};
+	use std::io::Read;
+	use std::path::PathBuf;
+	use time;
+	use super::LogFile;
+
+	fn temp_dir(name: &str) -> PathBuf {
+		let dir = env::temp_dir().join(format!("parity-logger-{}-{}", name, time::precise_time_ns()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn read(path: PathBuf) -> String {
+		let mut s = String::new();
+		fs::File::open(path).unwrap().read_to_string(&mut s).unwrap();
+		s
+	}
+
+	#[test]
+	fn should_rotate_by_size() {
+		// given
+		let dir = temp_dir("rotate");
+		let path = dir.join("parity.log");
+		let mut file = LogFile::open(&path, Some(8), 2).unwrap();
+
+		// when
+		for line in &["aaa", "bbb", "ccc", "ddd", "eee"] {
+			file.write_line(line).unwrap();
+		}
+
+		// then
+		assert_eq!(read(path.clone()), "eee\n");
+		assert_eq!(read(dir.join("parity.log.1")), "ccc\nddd\n");
+		assert_eq!(read(dir.join("parity.log.2")), "aaa\nbbb\n");
+		assert!(!dir.join("parity.log.3").exists());
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn should_append_without_limit() {
+		// given
+		let dir = temp_dir("append");
+		let path = dir.join("parity.log");
+		LogFile::open(&path, None, 2).unwrap().write_line("first").unwrap();
+
+		// when
+		LogFile::open(&path, None, 2).unwrap().write_line("second").unwrap();
+
+		// then
+		assert_eq!(read(path), "first\nsecond\n");
+		assert!(!dir.join("parity.log.1").exists());
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -17,11 +17,13 @@
 //! Common log helper functions
 
 use std::env;
+use std::sync::Arc;
 use rlog::LogLevelFilter;
 use env_logger::LogBuilder;
 use arrayvec::ArrayVec;
 
 use parking_lot::{RwLock, RwLockReadGuard};
+use filter::Filter;
 
 lazy_static! {
 	static ref LOG_DUMMY: () = {
@@ -48,7 +50,9 @@ const LOG_SIZE : usize = 128;
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
 	/// Defined logger levels
-	levels: String,
+	levels: RwLock<String>,
+	/// Filter of the active logger, if levels are enforced.
+	filter: Option<Arc<Filter>>,
 	/// Logs array. Latest log is always at index 0
 	logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
@@ -59,7 +63,17 @@ impl RotatingLogger {
 	/// It does not enforce levels - it's just read only.
 	pub fn new(levels: String) -> Self {
 		RotatingLogger {
-			levels: levels,
+			levels: RwLock::new(levels),
+			filter: None,
+			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
+		}
+	}
+
+	/// Creates new `RotatingLogger` reporting and changing levels of given filter.
+	pub fn with_filter(filter: Arc<Filter>) -> Self {
+		RotatingLogger {
+			levels: RwLock::new(filter.levels()),
+			filter: Some(filter),
 			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
 		}
 	}
@@ -70,8 +84,23 @@ impl RotatingLogger {
 	}
 
 	/// Return levels
-	pub fn levels(&self) -> &str {
-		&self.levels
+	pub fn levels(&self) -> String {
+		self.levels.read().clone()
+	}
+
+	/// Change levels of the active logger. Accepts the same format as `RUST_LOG`.
+	pub fn set_levels(&self, levels: &str) -> Result<(), String> {
+		match self.filter {
+			Some(ref filter) => {
+				filter.set_levels(levels)?;
+				*self.levels.write() = filter.levels();
+			},
+			None => {
+				::filter::validate(levels)?;
+				*self.levels.write() = levels.to_owned();
+			},
+		}
+		Ok(())
 	}
 
 	/// Return logs
@@ -101,6 +130,21 @@ mod test {
 		assert_eq!(levels, "test");
 	}
 
+	#[test]
+	fn should_change_log_levels() {
+		// given
+		let logger = logger();
+
+		// when
+		let invalid = logger.set_levels("sync=nope");
+		let valid = logger.set_levels("sync=trace");
+
+		// then
+		assert!(invalid.is_err());
+		assert!(valid.is_ok());
+		assert_eq!(logger.levels(), "sync=trace");
+	}
+
 	#[test]
 	fn should_return_latest_logs() {
 		// given
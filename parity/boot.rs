@@ -108,6 +108,7 @@ Usage:
 				color: self.flag_no_color || cfg!(windows),
 				mode: self.flag_logging.clone(),
 				file: self.flag_log_file.clone(),
+				..Default::default()
 			}
 		}
 	}
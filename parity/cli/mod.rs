@@ -35,6 +35,8 @@ usage! {
 		cmd_sign: bool,
 		cmd_reject: bool,
 		cmd_snapshot: bool,
+		cmd_verify: bool,
+		cmd_info: bool,
 		cmd_restore: bool,
 		cmd_ui: bool,
 		cmd_dapp: bool,
@@ -152,6 +154,16 @@ usage! {
 		flag_no_ancient_blocks: bool = false, or |_| None,
 		flag_no_serve_light: bool = false,
 			or |c: &Config| otry!(c.network).no_serve_light.clone(),
+		flag_max_upload_rate: Option<u64> = None,
+			or |c: &Config| otry!(c.network).max_upload_rate.clone().map(Some),
+		flag_max_download_rate: Option<u64> = None,
+			or |c: &Config| otry!(c.network).max_download_rate.clone().map(Some),
+		flag_max_peer_upload_rate: Option<u64> = None,
+			or |c: &Config| otry!(c.network).max_peer_upload_rate.clone().map(Some),
+		flag_max_peer_download_rate: Option<u64> = None,
+			or |c: &Config| otry!(c.network).max_peer_download_rate.clone().map(Some),
+		flag_protocol_rate_limits: Option<String> = None,
+			or |c: &Config| otry!(c.network).protocol_rate_limits.as_ref().map(|vec| Some(vec.join(","))),
 
 		// -- API and Console Options
 		// RPC
@@ -228,6 +240,28 @@ usage! {
 		flag_ipfs_api_hosts: String = "none",
 			or |c: &Config| otry!(c.ipfs).hosts.as_ref().map(|vec| vec.join(",")),
 
+		// Metrics
+		flag_metrics: bool = false,
+			or |c: &Config| otry!(c.metrics).enable.clone(),
+		flag_metrics_port: u16 = 9615u16,
+			or |c: &Config| otry!(c.metrics).port.clone(),
+		flag_metrics_interface: String = "local",
+			or |c: &Config| otry!(c.metrics).interface.clone(),
+
+		// Private Transactions
+		flag_private_tx_enabled: bool = false,
+			or |c: &Config| otry!(c.private_tx).enabled.clone(),
+		flag_private_signer: Option<String> = None,
+			or |c: &Config| otry!(c.private_tx).signer.clone().map(Some),
+		flag_private_validators: Option<String> = None,
+			or |c: &Config| otry!(c.private_tx).validators.as_ref().map(|vec| Some(vec.join(","))),
+		flag_private_passwords: Option<String> = None,
+			or |c: &Config| otry!(c.private_tx).passwords.clone().map(Some),
+		flag_private_sstore_url: Option<String> = None,
+			or |c: &Config| otry!(c.private_tx).sstore_url.clone().map(Some),
+		flag_private_sstore_secret: Option<String> = None,
+			or |c: &Config| otry!(c.private_tx).sstore_secret.clone().map(Some),
+
 		// -- Sealing/Mining Options
 		flag_author: Option<String> = None,
 			or |c: &Config| otry!(c.mining).author.clone().map(Some),
@@ -255,6 +289,14 @@ usage! {
 			or |c: &Config| otry!(c.mining).usd_per_eth.clone(),
 		flag_price_update_period: String = "hourly",
 			or |c: &Config| otry!(c.mining).price_update_period.clone(),
+		flag_gas_price_percentile: Option<usize> = None,
+			or |c: &Config| otry!(c.mining).gas_price_percentile.clone().map(Some),
+		flag_gas_price_oracle_blocks: usize = 100usize,
+			or |c: &Config| otry!(c.mining).gas_price_oracle_blocks.clone(),
+		flag_gas_price_floor: String = "0",
+			or |c: &Config| otry!(c.mining).gas_price_floor.clone(),
+		flag_gas_price_ceiling: Option<String> = None,
+			or |c: &Config| otry!(c.mining).gas_price_ceiling.clone().map(Some),
 		flag_gas_floor_target: String = "4700000",
 			or |c: &Config| otry!(c.mining).gas_floor_target.clone(),
 		flag_gas_cap: String = "6283184",
@@ -271,12 +313,26 @@ usage! {
 			or |c: &Config| otry!(c.mining).tx_queue_ban_count.clone(),
 		flag_tx_queue_ban_time: u16 = 180u16,
 			or |c: &Config| otry!(c.mining).tx_queue_ban_time.clone(),
+		flag_tx_queue_price_bump: usize = 0usize,
+			or |c: &Config| otry!(c.mining).tx_queue_price_bump.clone(),
+		flag_tx_queue_per_sender: Option<usize> = None,
+			or |c: &Config| otry!(c.mining).tx_queue_per_sender.clone().map(Some),
+		flag_tx_queue_journal: bool = false,
+			or |c: &Config| otry!(c.mining).tx_queue_journal.clone(),
 		flag_remove_solved: bool = false,
 			or |c: &Config| otry!(c.mining).remove_solved.clone(),
 		flag_notify_work: Option<String> = None,
 			or |c: &Config| otry!(c.mining).notify_work.as_ref().map(|vec| Some(vec.join(","))),
 		flag_refuse_service_transactions: bool = false,
 			or |c: &Config| otry!(c.mining).refuse_service_transactions.clone(),
+		flag_block_assembly: Option<String> = None,
+			or |c: &Config| otry!(c.mining).block_assembly.as_ref().map(|vec| Some(vec.join(","))),
+		flag_assembly_reserved_senders: Option<String> = None,
+			or |c: &Config| otry!(c.mining).assembly_reserved_senders.as_ref().map(|vec| Some(vec.join(","))),
+		flag_mine: bool = false,
+			or |c: &Config| otry!(c.mining).mine.clone(),
+		flag_miner_threads: Option<usize> = None,
+			or |c: &Config| otry!(c.mining).miner_threads.clone().map(Some),
 
 		flag_stratum: bool = false,
 			or |c: &Config| Some(c.stratum.is_some()),
@@ -316,6 +372,10 @@ usage! {
 			or |c: &Config| otry!(c.footprint).scale_verifiers.clone(),
 		flag_num_verifiers: Option<usize> = None,
 			or |c: &Config| otry!(c.footprint).num_verifiers.clone().map(Some),
+		flag_ethash_cache_dir: String = "$HOME/.ethash/light",
+			or |c: &Config| otry!(c.footprint).ethash_cache_dir.clone(),
+		flag_ethash_cache_epochs: usize = 3usize,
+			or |c: &Config| otry!(c.footprint).ethash_cache_epochs.clone(),
 
 		// -- Import/Export Options
 		flag_from: String = "1", or |_| None,
@@ -331,6 +391,12 @@ usage! {
 		flag_at: String = "latest", or |_| None,
 		flag_no_periodic_snapshot: bool = false,
 			or |c: &Config| otry!(c.snapshots).disable_periodic.clone(),
+		flag_snapshot_interval: u64 = 10000u64,
+			or |c: &Config| otry!(c.snapshots).interval.clone(),
+		flag_snapshot_retention: usize = 1usize,
+			or |c: &Config| otry!(c.snapshots).retention.clone(),
+		flag_snapshot_export_dir: Option<String> = None,
+			or |c: &Config| otry!(c.snapshots).export_dir.clone().map(Some),
 
 		// -- Virtual Machine Options
 		flag_jitvm: bool = false,
@@ -342,6 +408,12 @@ usage! {
 			or |c: &Config| otry!(c.misc).logging.clone().map(Some),
 		flag_log_file: Option<String> = None,
 			or |c: &Config| otry!(c.misc).log_file.clone().map(Some),
+		flag_log_format: String = "text",
+			or |c: &Config| otry!(c.misc).log_format.clone(),
+		flag_log_file_max_size: Option<u64> = None,
+			or |c: &Config| otry!(c.misc).log_file_max_size.clone().map(Some),
+		flag_log_file_max_count: usize = 5usize,
+			or |c: &Config| otry!(c.misc).log_file_max_count.clone(),
 		flag_no_color: bool = false,
 			or |c: &Config| otry!(c.misc).color.map(|c| !c).clone(),
 
@@ -382,6 +454,8 @@ struct Config {
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
 	ipfs: Option<Ipfs>,
+	metrics: Option<Metrics>,
+	private_tx: Option<PrivateTransactions>,
 	mining: Option<Mining>,
 	footprint: Option<Footprint>,
 	snapshots: Option<Snapshots>,
@@ -442,6 +516,11 @@ struct Network {
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
+	max_upload_rate: Option<u64>,
+	max_download_rate: Option<u64>,
+	max_peer_upload_rate: Option<u64>,
+	max_peer_download_rate: Option<u64>,
+	protocol_rate_limits: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -505,6 +584,23 @@ struct Ipfs {
 	hosts: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, RustcDecodable)]
+struct Metrics {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+}
+
+#[derive(Default, Debug, PartialEq, RustcDecodable)]
+struct PrivateTransactions {
+	enabled: Option<bool>,
+	signer: Option<String>,
+	validators: Option<Vec<String>>,
+	passwords: Option<String>,
+	sstore_url: Option<String>,
+	sstore_secret: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Mining {
 	author: Option<String>,
@@ -520,6 +616,10 @@ struct Mining {
 	usd_per_tx: Option<String>,
 	usd_per_eth: Option<String>,
 	price_update_period: Option<String>,
+	gas_price_percentile: Option<usize>,
+	gas_price_oracle_blocks: Option<usize>,
+	gas_price_floor: Option<String>,
+	gas_price_ceiling: Option<String>,
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
 	extra_data: Option<String>,
@@ -528,9 +628,16 @@ struct Mining {
 	tx_queue_strategy: Option<String>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
+	tx_queue_price_bump: Option<usize>,
+	tx_queue_per_sender: Option<usize>,
+	tx_queue_journal: Option<bool>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
 	refuse_service_transactions: Option<bool>,
+	block_assembly: Option<Vec<String>>,
+	assembly_reserved_senders: Option<Vec<String>>,
+	mine: Option<bool>,
+	miner_threads: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -556,11 +663,16 @@ struct Footprint {
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	ethash_cache_dir: Option<String>,
+	ethash_cache_epochs: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	interval: Option<u64>,
+	retention: Option<usize>,
+	export_dir: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -572,6 +684,9 @@ struct VM {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	log_format: Option<String>,
+	log_file_max_size: Option<u64>,
+	log_file_max_count: Option<usize>,
 	color: Option<bool>,
 }
 
@@ -653,6 +768,8 @@ mod tests {
 			cmd_reject: false,
 			cmd_new_token: false,
 			cmd_snapshot: false,
+			cmd_verify: false,
+			cmd_info: false,
 			cmd_restore: false,
 			cmd_ui: false,
 			cmd_dapp: false,
@@ -713,6 +830,11 @@ mod tests {
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
+			flag_max_upload_rate: Some(1024),
+			flag_max_download_rate: None,
+			flag_max_peer_upload_rate: Some(256),
+			flag_max_peer_download_rate: None,
+			flag_protocol_rate_limits: Some("par:512:0,pip:128:128".into()),
 
 			// -- API and Console Options
 			// RPC
@@ -757,6 +879,19 @@ mod tests {
 			flag_ipfs_api_cors: Some("null".into()),
 			flag_ipfs_api_hosts: "none".into(),
 
+			// METRICS
+			flag_metrics: false,
+			flag_metrics_port: 9615u16,
+			flag_metrics_interface: "local".into(),
+
+			// PRIVATE TRANSACTIONS
+			flag_private_tx_enabled: false,
+			flag_private_signer: None,
+			flag_private_validators: Some("".into()),
+			flag_private_passwords: None,
+			flag_private_sstore_url: None,
+			flag_private_sstore_secret: None,
+
 			// -- Sealing/Mining Options
 			flag_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			flag_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -771,6 +906,10 @@ mod tests {
 			flag_usd_per_tx: "0.0025".into(),
 			flag_usd_per_eth: "auto".into(),
 			flag_price_update_period: "hourly".into(),
+			flag_gas_price_percentile: None,
+			flag_gas_price_oracle_blocks: 100usize,
+			flag_gas_price_floor: "0".into(),
+			flag_gas_price_ceiling: None,
 			flag_gas_floor_target: "4700000".into(),
 			flag_gas_cap: "6283184".into(),
 			flag_extra_data: Some("Parity".into()),
@@ -779,9 +918,16 @@ mod tests {
 			flag_tx_queue_strategy: "gas_factor".into(),
 			flag_tx_queue_ban_count: 1u16,
 			flag_tx_queue_ban_time: 180u16,
+			flag_tx_queue_price_bump: 0usize,
+			flag_tx_queue_per_sender: Some(16),
+			flag_tx_queue_journal: false,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
 			flag_refuse_service_transactions: false,
+			flag_block_assembly: Some("local".into()),
+			flag_assembly_reserved_senders: None,
+			flag_mine: false,
+			flag_miner_threads: None,
 
 			flag_stratum: false,
 			flag_stratum_interface: "local".to_owned(),
@@ -803,6 +949,8 @@ mod tests {
 			flag_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			flag_num_verifiers: Some(6),
+			flag_ethash_cache_dir: "$HOME/.ethash/light".into(),
+			flag_ethash_cache_epochs: 3usize,
 
 			// -- Import/Export Options
 			flag_from: "1".into(),
@@ -817,6 +965,9 @@ mod tests {
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			flag_snapshot_interval: 10000u64,
+			flag_snapshot_retention: 1usize,
+			flag_snapshot_export_dir: Some("$HOME/.parity/snapshots".into()),
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
@@ -862,6 +1013,9 @@ mod tests {
 			flag_config: "$BASE/config.toml".into(),
 			flag_logging: Some("own_tx=trace".into()),
 			flag_log_file: Some("/var/log/parity.log".into()),
+			flag_log_format: "text".into(),
+			flag_log_file_max_size: Some(100),
+			flag_log_file_max_count: 5usize,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -931,6 +1085,11 @@ mod tests {
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
+				max_upload_rate: None,
+				max_download_rate: None,
+				max_peer_upload_rate: None,
+				max_peer_download_rate: None,
+				protocol_rate_limits: None,
 			}),
 			websockets: Some(Ws {
 				disable: Some(true),
@@ -981,6 +1140,8 @@ mod tests {
 				cors: None,
 				hosts: None,
 			}),
+			metrics: None,
+			private_tx: None,
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
@@ -993,6 +1154,10 @@ mod tests {
 				usd_per_tx: None,
 				usd_per_eth: None,
 				price_update_period: Some("hourly".into()),
+				gas_price_percentile: None,
+				gas_price_oracle_blocks: None,
+				gas_price_floor: None,
+				gas_price_ceiling: None,
 				gas_floor_target: None,
 				gas_cap: None,
 				tx_queue_size: Some(1024),
@@ -1000,12 +1165,19 @@ mod tests {
 				tx_queue_strategy: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
+				tx_queue_price_bump: None,
+				tx_queue_per_sender: None,
+				tx_queue_journal: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
 				refuse_service_transactions: None,
+				block_assembly: None,
+				assembly_reserved_senders: None,
+				mine: None,
+				miner_threads: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
@@ -1022,9 +1194,14 @@ mod tests {
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				ethash_cache_dir: None,
+				ethash_cache_epochs: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				interval: None,
+				retention: None,
+				export_dir: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
@@ -1032,6 +1209,9 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				log_format: None,
+				log_file_max_size: None,
+				log_file_max_count: None,
 				color: Some(true),
 			}),
 			stratum: None,
@@ -19,7 +19,7 @@ use std::io::{Read, Write, stderr};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::collections::BTreeMap;
-use std::cmp::max;
+use std::cmp::{max, min};
 use cli::{Args, ArgsError};
 use util::{Hashable, H256, U256, Uint, Bytes, version_data, Address};
 use util::journaldb::Algorithm;
@@ -27,20 +27,26 @@ use util::Colour;
 use ethsync::{NetworkConfiguration, is_valid_node_url, AllowIP};
 use ethcore::ethstore::ethkey::{Secret, Public};
 use ethcore::client::{VMType};
+use ethcore::snapshot::SnapshotConfiguration;
 use ethcore::miner::{MinerOptions, Banning, StratumOptions};
 use ethcore::verification::queue::VerifierSettings;
+use ethcore::ethereum::EthashCacheOptions;
+use ethkey::KeyPair;
+use private_tx::{ProviderConfig, EncryptorConfig};
 
 use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
 use rpc_apis::ApiSet;
 use parity_rpc::NetworkSettings;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home, replace_home_for_db,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy, passwords_from_files, password_from_file, to_assembly_rules,
+kbps_to_bytes, to_protocol_rate_limits};
 use params::{SpecType, ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, Pruning, Switch};
 use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use dapps::Configuration as DappsConfiguration;
 use ipfs::Configuration as IpfsConfiguration;
+use metrics::Configuration as MetricsConfiguration;
 use signer::{Configuration as SignerConfiguration};
 use secretstore::Configuration as SecretStoreConfiguration;
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
@@ -113,7 +119,7 @@ impl Configuration {
 			mode => Some(to_mode(&mode, self.args.flag_mode_timeout, self.args.flag_mode_alarm)?),
 		};
 		let update_policy = self.update_policy()?;
-		let logger_config = self.logger_config();
+		let logger_config = self.logger_config()?;
 		let ws_conf = self.ws_config()?;
 		let http_conf = self.http_config()?;
 		let ipc_conf = self.ipc_config()?;
@@ -137,6 +143,7 @@ impl Configuration {
 		let ui_address = self.ui_port().map(|port| (self.ui_interface(), port));
 		let mut dapps_conf = self.dapps_config();
 		let ipfs_conf = self.ipfs_config();
+		let metrics_conf = self.metrics_config();
 		let signer_conf = self.signer_config();
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
@@ -307,7 +314,13 @@ impl Configuration {
 				compaction: compaction,
 				file_path: self.args.arg_file.clone(),
 				wal: wal,
-				kind: snapshot::Kind::Take,
+				kind: if self.args.cmd_verify {
+					snapshot::Kind::Verify
+				} else if self.args.cmd_info {
+					snapshot::Kind::Info
+				} else {
+					snapshot::Kind::Take
+				},
 				block_at: to_block_id(&self.args.flag_at)?,
 			};
 			Cmd::Snapshot(snapshot_cmd)
@@ -362,6 +375,12 @@ impl Configuration {
 				gas_pricer: gas_pricer,
 				miner_extras: self.miner_extras()?,
 				stratum: self.stratum_options()?,
+				miner_threads: self.miner_threads(),
+				tx_queue_journal: self.args.flag_tx_queue_journal,
+				private_tx_enabled: self.args.flag_private_tx_enabled,
+				private_provider_conf: self.private_provider_config()?,
+				private_encryptor_conf: self.private_encryptor_config()?,
+				ethash_cache: self.ethash_cache_options(),
 				update_policy: update_policy,
 				mode: mode,
 				tracing: tracing,
@@ -376,6 +395,7 @@ impl Configuration {
 				net_settings: self.network_settings(),
 				dapps_conf: dapps_conf,
 				ipfs_conf: ipfs_conf,
+				metrics_conf: metrics_conf,
 				signer_conf: signer_conf,
 				secretstore_conf: secretstore_conf,
 				dapp: self.dapp_to_open()?,
@@ -383,6 +403,8 @@ impl Configuration {
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				snapshot_period: self.snapshot_period()?,
+				snapshot_conf: self.snapshot_config()?,
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
@@ -398,6 +420,24 @@ impl Configuration {
 		})
 	}
 
+	fn snapshot_period(&self) -> Result<u64, String> {
+		match self.args.flag_snapshot_interval {
+			0 => Err("--snapshot-interval must be greater than zero.".into()),
+			period => Ok(period),
+		}
+	}
+
+	fn snapshot_config(&self) -> Result<SnapshotConfiguration, String> {
+		if self.args.flag_snapshot_retention == 0 {
+			return Err("--snapshot-retention must be at least 1 to keep the most recent snapshot.".into());
+		}
+
+		Ok(SnapshotConfiguration {
+			retention: self.args.flag_snapshot_retention,
+			export_dir: self.args.flag_snapshot_export_dir.as_ref().map(|d| replace_home(&self.directories().base, d).into()),
+		})
+	}
+
 	fn vm_type(&self) -> Result<VMType, String> {
 		if self.args.flag_jitvm {
 			VMType::jit().ok_or("Parity is built without the JIT EVM.".into())
@@ -446,12 +486,15 @@ impl Configuration {
 		}
 	}
 
-	fn logger_config(&self) -> LogConfig {
-		LogConfig {
+	fn logger_config(&self) -> Result<LogConfig, String> {
+		Ok(LogConfig {
 			mode: self.args.flag_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.flag_log_file.clone(),
-		}
+			format: self.args.flag_log_format.parse()?,
+			file_max_size: self.args.flag_log_file_max_size.map(|mb| mb * 1024 * 1024),
+			file_max_count: self.args.flag_log_file_max_count,
+		})
 	}
 
 	fn chain(&self) -> String {
@@ -518,6 +561,19 @@ impl Configuration {
 		} else { Ok(None) }
 	}
 
+	fn ethash_cache_options(&self) -> EthashCacheOptions {
+		EthashCacheOptions {
+			path: replace_home(&self.directories().base, &self.args.flag_ethash_cache_dir).into(),
+			epochs: self.args.flag_ethash_cache_epochs,
+		}
+	}
+
+	fn miner_threads(&self) -> Option<usize> {
+		if self.args.flag_mine {
+			Some(self.args.flag_miner_threads.unwrap_or_else(::num_cpus::get))
+		} else { None }
+	}
+
 	fn miner_options(&self, reseal_min_period: u64) -> Result<MinerOptions, String> {
 		let reseal = self.args.flag_reseal_on_txs.parse::<ResealPolicy>()?;
 
@@ -533,6 +589,13 @@ impl Configuration {
 			tx_queue_size: self.args.flag_tx_queue_size,
 			tx_queue_gas_limit: to_gas_limit(&self.args.flag_tx_queue_gas)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.flag_tx_queue_strategy)?,
+			tx_queue_price_bump: self.args.flag_tx_queue_price_bump,
+			tx_queue_per_sender: self.args.flag_tx_queue_per_sender.unwrap_or(usize::max_value()),
+			gas_price_oracle_blocks: self.args.flag_gas_price_oracle_blocks,
+			block_assembly: to_assembly_rules(
+				&self.args.flag_block_assembly,
+				&to_addresses(&self.args.flag_assembly_reserved_senders)?,
+			)?,
 			pending_set: to_pending_set(&self.args.flag_relay_set)?,
 			reseal_min_period: Duration::from_millis(reseal_min_period),
 			reseal_max_period: Duration::from_millis(self.args.flag_reseal_max_period),
@@ -587,6 +650,43 @@ impl Configuration {
 		})
 	}
 
+	fn private_provider_config(&self) -> Result<ProviderConfig, String> {
+		let passwords = match self.args.flag_private_passwords {
+			Some(ref file) => passwords_from_files(&[file.clone()])?,
+			None => Vec::new(),
+		};
+
+		Ok(ProviderConfig {
+			validator_accounts: to_addresses(&self.args.flag_private_validators)?,
+			signer_account: match self.args.flag_private_signer {
+				Some(ref signer) => Some(to_address(Some(signer.clone()))?),
+				None => None,
+			},
+			passwords: passwords,
+		})
+	}
+
+	fn private_encryptor_config(&self) -> Result<Option<EncryptorConfig>, String> {
+		if !self.args.flag_private_tx_enabled {
+			return Ok(None);
+		}
+
+		let base_url = self.args.flag_private_sstore_url.clone()
+			.ok_or_else(|| "--private-sstore-url is required to enable private transactions".to_owned())?;
+		// the secret is read from a file, so that it doesn't show up in the process list.
+		let secret: Secret = match self.args.flag_private_sstore_secret {
+			Some(ref path) => password_from_file(path.clone())?.parse()
+				.map_err(|e| format!("Invalid private transactions secret in {}: {:?}", path, e))?,
+			None => return Err("--private-sstore-secret is required to enable private transactions".into()),
+		};
+		let key_pair = KeyPair::from_secret(secret).map_err(|e| format!("Invalid private transactions secret: {:?}", e))?;
+
+		Ok(Some(EncryptorConfig {
+			base_url: base_url,
+			key_pair: key_pair,
+		}))
+	}
+
 	fn ipfs_config(&self) -> IpfsConfiguration {
 		IpfsConfiguration {
 			enabled: self.args.flag_ipfs_api,
@@ -597,6 +697,14 @@ impl Configuration {
 		}
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			port: self.args.flag_metrics_port,
+			interface: self.metrics_interface(),
+		}
+	}
+
 	fn dapp_to_open(&self) -> Result<Option<String>, String> {
 		if !self.args.cmd_dapp {
 			return Ok(None);
@@ -623,11 +731,32 @@ impl Configuration {
 		}
 
 		let usd_per_tx = to_price(&self.args.flag_usd_per_tx)?;
+		// Just a very rough estimate to avoid accepting
+		// ZGP transactions before the price is fetched
+		// if user does not want it.
+		let last_known_usd_per_eth = 10.0;
+
+		if let Some(percentile) = self.args.flag_gas_price_percentile {
+			if percentile > 100 {
+				return Err(format!("Invalid gas price percentile: {}. It must be between 0 and 100.", percentile));
+			}
+			let min_gas_price = to_u256(&self.args.flag_gas_price_floor)?;
+			let max_gas_price = match self.args.flag_gas_price_ceiling {
+				Some(ref ceiling) => to_u256(ceiling)?,
+				None => U256::max_value(),
+			};
+			if min_gas_price > max_gas_price {
+				return Err(format!("Invalid gas price bounds: floor {} is above ceiling {}.", min_gas_price, max_gas_price));
+			}
+			return Ok(GasPricerConfig::Oracle {
+				initial_minimum: min(max(wei_per_gas(usd_per_tx, last_known_usd_per_eth), min_gas_price), max_gas_price),
+				percentile: percentile,
+				min_gas_price: min_gas_price,
+				max_gas_price: max_gas_price,
+			});
+		}
+
 		if "auto" == self.args.flag_usd_per_eth.as_str() {
-			// Just a very rough estimate to avoid accepting
-			// ZGP transactions before the price is fetched
-			// if user does not want it.
-			let last_known_usd_per_eth = 10.0;
 			return Ok(GasPricerConfig::Calibrated {
 				initial_minimum: wei_per_gas(usd_per_tx, last_known_usd_per_eth),
 				usd_per_tx: usd_per_tx,
@@ -711,6 +840,14 @@ impl Configuration {
 		ret.config_path = Some(net_path.to_str().unwrap().to_owned());
 		ret.reserved_nodes = self.init_reserved_nodes()?;
 		ret.allow_non_reserved = !self.args.flag_reserved_only;
+		ret.max_upload_rate = self.args.flag_max_upload_rate.map(kbps_to_bytes);
+		ret.max_download_rate = self.args.flag_max_download_rate.map(kbps_to_bytes);
+		ret.max_peer_upload_rate = self.args.flag_max_peer_upload_rate.map(kbps_to_bytes);
+		ret.max_peer_download_rate = self.args.flag_max_peer_download_rate.map(kbps_to_bytes);
+		ret.protocol_rate_limits = match self.args.flag_protocol_rate_limits {
+			Some(ref limits) => to_protocol_rate_limits(limits)?,
+			None => Vec::new(),
+		};
 		Ok(ret)
 	}
 
@@ -944,6 +1081,10 @@ impl Configuration {
 		Self::interface(&self.args.flag_ipfs_api_interface)
 	}
 
+	fn metrics_interface(&self) -> String {
+		Self::interface(&self.args.flag_metrics_interface)
+	}
+
 	fn secretstore_interface(&self) -> String {
 		Self::interface(&self.args.flag_secretstore_interface)
 	}
@@ -1046,6 +1187,7 @@ mod tests {
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 	use std::io::Write;
 	use std::fs::{File, create_dir};
+	use ethcore_logger::Format as LogFormat;
 
 	#[derive(Debug, PartialEq)]
 	struct TestPasswordReader(&'static str);
@@ -1159,6 +1301,27 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_snapshot_verify() {
+		let args = vec!["parity", "snapshot", "verify", "snapshot_dir"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Snapshot(SnapshotCommand {
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			spec: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			compaction: Default::default(),
+			file_path: Some("snapshot_dir".into()),
+			wal: true,
+			kind: snapshot::Kind::Verify,
+			block_at: BlockId::Latest,
+		}));
+	}
+
 	#[test]
 	fn test_command_state_export() {
 		let args = vec!["parity", "export", "state", "state.json"];
@@ -1256,6 +1419,7 @@ mod tests {
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
 			ipfs_conf: Default::default(),
+			metrics_conf: Default::default(),
 			signer_conf: Default::default(),
 			secretstore_conf: Default::default(),
 			ui: false,
@@ -1264,7 +1428,15 @@ mod tests {
 			custom_bootnodes: false,
 			fat_db: Default::default(),
 			no_periodic_snapshot: false,
+			snapshot_period: 10000,
+			snapshot_conf: Default::default(),
 			stratum: None,
+			miner_threads: None,
+			tx_queue_journal: false,
+			private_tx_enabled: false,
+			private_provider_conf: Default::default(),
+			private_encryptor_conf: None,
+			ethash_cache: Default::default(),
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
@@ -1395,6 +1567,26 @@ mod tests {
 		assert_eq!(conf3.ipfs_hosts(), Some(vec!["ethcore.io".into(), "something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_logger_config() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--log-format", "json", "--log-file-max-size", "10", "--log-file-max-count", "2"]);
+		let conf2 = parse(&["parity", "--log-format", "xml"]);
+
+		// then
+		let logger0 = conf0.logger_config().unwrap();
+		assert_eq!(logger0.format, LogFormat::Text);
+		assert_eq!(logger0.file_max_size, None);
+		let logger1 = conf1.logger_config().unwrap();
+		assert_eq!(logger1.format, LogFormat::Json);
+		assert_eq!(logger1.file_max_size, Some(10 * 1024 * 1024));
+		assert_eq!(logger1.file_max_count, 2);
+		assert!(conf2.logger_config().is_err());
+	}
+
 	#[test]
 	fn should_parse_ipfs_cors() {
 		// given
@@ -1514,4 +1706,42 @@ mod tests {
 			_ => panic!("Should be Cmd::Run"),
 		}
 	}
+
+	#[test]
+	fn should_use_gas_price_oracle_with_percentile() {
+		let args = vec!["parity", "--gas-price-percentile", "30", "--gas-price-oracle-blocks", "50"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => {
+				match c.gas_pricer {
+					GasPricerConfig::Oracle { percentile, min_gas_price, max_gas_price, .. } => {
+						assert_eq!(percentile, 30);
+						assert_eq!(min_gas_price, 0.into());
+						assert_eq!(max_gas_price, U256::max_value());
+					},
+					ref other => panic!("Unexpected gas pricer: {:?}", other),
+				}
+				assert_eq!(c.miner_options.gas_price_oracle_blocks, 50);
+			},
+			_ => panic!("Should be Cmd::Run"),
+		}
+
+		let args = vec!["parity", "--gas-price-percentile", "101"];
+		assert!(parse(&args).into_command().is_err());
+
+		let args = vec!["parity", "--gas-price-percentile", "30", "--gas-price-floor", "1000", "--gas-price-ceiling", "100"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
+	#[test]
+	fn should_reject_zero_snapshot_retention() {
+		let args = vec!["parity", "--snapshot-retention", "0"];
+		assert!(parse(&args).into_command().is_err());
+
+		let args = vec!["parity", "--snapshot-retention", "3"];
+		match parse(&args).into_command().unwrap().cmd {
+			Cmd::Run(c) => assert_eq!(c.snapshot_conf.retention, 3),
+			_ => panic!("Should be Cmd::Run"),
+		}
+	}
 }
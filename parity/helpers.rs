@@ -21,7 +21,7 @@ use std::fs::File;
 use util::{clean_0x, U256, Uint, Address, CompactionProfile};
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
-use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy};
+use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy, AssemblyRule};
 use cache::CacheConfig;
 use dir::DatabaseDirectories;
 use upgrade::{upgrade, upgrade_data_paths};
@@ -127,6 +127,35 @@ pub fn to_addresses(s: &Option<String>) -> Result<Vec<Address>, String> {
 	}
 }
 
+fn to_percent(s: &str) -> Result<usize, String> {
+	match s.parse::<usize>() {
+		Ok(percent) if percent <= 100 => Ok(percent),
+		_ => Err(format!("Invalid percentage: {}. It must be between 0 and 100.", s)),
+	}
+}
+
+pub fn to_assembly_rules(s: &Option<String>, reserved_senders: &[Address]) -> Result<Vec<AssemblyRule>, String> {
+	let rules = match *s {
+		Some(ref rules) => rules,
+		None => return Ok(Vec::new()),
+	};
+
+	rules.split(',').filter(|rule| !rule.is_empty()).map(|rule| {
+		let mut parts = rule.splitn(2, ':');
+		match (parts.next(), parts.next()) {
+			(Some("local"), None) => Ok(AssemblyRule::LocalPriority),
+			(Some("contract-cap"), Some(percent)) => Ok(AssemblyRule::RecipientCap(to_percent(percent)?)),
+			(Some("reserve"), Some(_)) if reserved_senders.is_empty() =>
+				Err("Reserving block gas requires --assembly-reserved-senders.".to_owned()),
+			(Some("reserve"), Some(percent)) => Ok(AssemblyRule::ReservedSenders {
+				senders: reserved_senders.to_vec(),
+				percent: to_percent(percent)?,
+			}),
+			_ => Err(format!("Invalid block assembly rule: {}", rule)),
+		}
+	}).collect()
+}
+
 /// Tries to parse string as a price.
 pub fn to_price(s: &str) -> Result<f32, String> {
 	s.parse::<f32>().map_err(|_| format!("Invalid transaciton price 's' given. Must be a decimal number."))
@@ -190,6 +219,31 @@ pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	}
 }
 
+/// Converts a rate in kilobytes per second to bytes per second.
+pub fn kbps_to_bytes(kbps: u64) -> u64 {
+	kbps * 1024
+}
+
+/// Parses subprotocol rate limits given as `PROTOCOL:UPLOAD:DOWNLOAD` entries, in kilobytes per second.
+/// Zero means unlimited.
+pub fn to_protocol_rate_limits(s: &str) -> Result<Vec<(String, Option<u64>, Option<u64>)>, String> {
+	fn to_limit(s: &str, entry: &str) -> Result<Option<u64>, String> {
+		match s.parse::<u64>() {
+			Ok(0) => Ok(None),
+			Ok(kbps) => Ok(Some(kbps_to_bytes(kbps))),
+			Err(_) => Err(format!("Invalid rate given in protocol rate limit: {}", entry)),
+		}
+	}
+
+	s.split(',').filter(|entry| !entry.is_empty()).map(|entry| {
+		let parts: Vec<_> = entry.split(':').collect();
+		if parts.len() != 3 || parts[0].len() != 3 {
+			return Err(format!("Invalid protocol rate limit: {}. Expected PROTOCOL:UPLOAD:DOWNLOAD.", entry));
+		}
+		Ok((parts[0].to_owned(), to_limit(parts[1], entry)?, to_limit(parts[2], entry)?))
+	}).collect()
+}
+
 #[cfg(test)]
 pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 	use ethsync::{NetworkConfiguration, AllowIP};
@@ -210,6 +264,11 @@ pub fn default_network_config() -> ::ethsync::NetworkConfiguration {
 		allow_ips: AllowIP::All,
 		reserved_nodes: Vec::new(),
 		allow_non_reserved: true,
+		max_upload_rate: None,
+		max_download_rate: None,
+		max_peer_upload_rate: None,
+		max_peer_download_rate: None,
+		protocol_rate_limits: Vec::new(),
 	}
 }
 
@@ -343,8 +402,8 @@ mod tests {
 	use devtools::RandomTempPath;
 	use util::{U256};
 	use ethcore::client::{Mode, BlockId};
-	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
+	use ethcore::miner::{PendingSet, AssemblyRule};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file, to_assembly_rules};
 
 	#[test]
 	fn test_to_duration() {
@@ -427,6 +486,23 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_to_assembly_rules() {
+		let senders = vec!["D9A111feda3f362f55Ef1744347CDC8Dd9964a41".parse().unwrap()];
+		assert_eq!(to_assembly_rules(&None, &[]).unwrap(), vec![]);
+		assert_eq!(
+			to_assembly_rules(&Some("local,contract-cap:25,reserve:10".into()), &senders).unwrap(),
+			vec![
+				AssemblyRule::LocalPriority,
+				AssemblyRule::RecipientCap(25),
+				AssemblyRule::ReservedSenders { senders: senders.clone(), percent: 10 },
+			]
+		);
+		assert!(to_assembly_rules(&Some("reserve:10".into()), &[]).is_err());
+		assert!(to_assembly_rules(&Some("contract-cap:101".into()), &[]).is_err());
+		assert!(to_assembly_rules(&Some("fifo".into()), &[]).is_err());
+	}
+
 	#[test]
 	fn test_password() {
 		let path = RandomTempPath::new();
@@ -481,4 +557,16 @@ but the first password is trimmed
 		assert_eq!(to_bootnodes(&Some(one_bootnode.into())), Ok(vec![one_bootnode.into()]));
 		assert_eq!(to_bootnodes(&Some(two_bootnodes.into())), Ok(vec![one_bootnode.into(), one_bootnode.into()]));
 	}
+
+	#[test]
+	fn test_to_protocol_rate_limits() {
+		assert_eq!(to_protocol_rate_limits(""), Ok(vec![]));
+		assert_eq!(to_protocol_rate_limits("par:512:0,pip:1:2"), Ok(vec![
+			("par".into(), Some(512 * 1024), None),
+			("pip".into(), Some(1024), Some(2048)),
+		]));
+		assert!(to_protocol_rate_limits("par:512").is_err());
+		assert!(to_protocol_rate_limits("parity:1:1").is_err());
+		assert!(to_protocol_rate_limits("par:fast:1").is_err());
+	}
 }
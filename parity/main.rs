@@ -51,10 +51,13 @@ extern crate ethcore_ipc_hypervisor as hypervisor;
 extern crate ethcore_ipc_nano as nanoipc;
 extern crate ethcore_light as light;
 extern crate ethcore_logger;
+extern crate ethcore_private_tx as private_tx;
 extern crate ethcore_signer;
 extern crate ethcore_util as util;
 extern crate ethkey;
 extern crate ethsync;
+extern crate metrics as metrics_api;
+extern crate node_filter;
 extern crate parity_hash_fetch as hash_fetch;
 extern crate parity_ipfs_api;
 extern crate parity_local_store as local_store;
@@ -107,6 +110,7 @@ mod dir;
 mod helpers;
 mod informant;
 mod light_helpers;
+mod metrics;
 mod migration;
 mod modules;
 mod params;
@@ -0,0 +1,49 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use metrics_api::{self, Registry, Server};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Configuration {
+	pub enabled: bool,
+	pub port: u16,
+	pub interface: String,
+}
+
+impl Default for Configuration {
+	fn default() -> Self {
+		Configuration {
+			enabled: false,
+			port: 9615,
+			interface: "127.0.0.1".into(),
+		}
+	}
+}
+
+pub fn start_server(conf: Configuration, registry: Arc<Registry>) -> Result<Option<Server>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse::<SocketAddr>().map_err(|_| format!("Invalid metrics listen host/port given: {}", url))?;
+
+	metrics_api::start_server(&addr, registry)
+		.map(Some)
+		.map_err(|e| format!("Metrics error: {:?}", e))
+}
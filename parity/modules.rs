@@ -19,9 +19,10 @@ use std::path::Path;
 
 use ethcore::client::BlockChainClient;
 use hypervisor::Hypervisor;
-use ethsync::{SyncConfig, NetworkConfiguration, NetworkError, Params};
+use ethsync::{SyncConfig, NetworkConfiguration, NetworkError, Params, ConnectionFilter, PrivateTxHandler};
 use ethcore::snapshot::SnapshotService;
 use light::Provider;
+use metrics_api::Registry;
 
 #[cfg(not(feature="ipc"))]
 use self::no_ipc_deps::*;
@@ -150,6 +151,9 @@ pub fn sync(
 	_client: Arc<BlockChainClient>,
 	_snapshot_service: Arc<SnapshotService>,
 	_provider: Arc<Provider>,
+	_connection_filter: Option<Arc<ConnectionFilter>>,
+	_private_tx_handler: Option<Arc<PrivateTxHandler>>,
+	_metrics: &Registry,
 	log_settings: &LogConfig,
 ) -> Result<SyncModules, NetworkError> {
 	let mut hypervisor = hypervisor_ref.take().expect("There should be hypervisor for ipc configuration");
@@ -180,6 +184,9 @@ pub fn sync(
 	client: Arc<BlockChainClient>,
 	snapshot_service: Arc<SnapshotService>,
 	provider: Arc<Provider>,
+	connection_filter: Option<Arc<ConnectionFilter>>,
+	private_tx_handler: Option<Arc<PrivateTxHandler>>,
+	metrics: &Registry,
 	_log_settings: &LogConfig,
 ) -> Result<SyncModules, NetworkError> {
 	let eth_sync = EthSync::new(Params {
@@ -188,7 +195,8 @@ pub fn sync(
 		provider: provider,
 		snapshot_service: snapshot_service,
 		network_config: net_cfg,
-	})?;
+	}, connection_filter, private_tx_handler)?;
+	metrics.register(eth_sync.clone());
 
 	Ok((eth_sync.clone() as Arc<SyncProvider>, eth_sync.clone() as Arc<ManageNetwork>, eth_sync.clone() as Arc<ChainNotify>))
 }
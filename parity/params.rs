@@ -21,7 +21,7 @@ use util::journaldb::Algorithm;
 use ethcore::spec::Spec;
 use ethcore::ethereum;
 use ethcore::client::Mode;
-use ethcore::miner::{GasPricer, GasPriceCalibratorOptions};
+use ethcore::miner::{GasPricer, GasPriceCalibratorOptions, GasPriceOracleOptions};
 use user_defaults::UserDefaults;
 
 #[derive(Debug, PartialEq)]
@@ -201,7 +201,13 @@ pub enum GasPricerConfig {
 		initial_minimum: U256,
 		usd_per_tx: f32,
 		recalibration_period: Duration,
-	}
+	},
+	Oracle {
+		initial_minimum: U256,
+		percentile: usize,
+		min_gas_price: U256,
+		max_gas_price: U256,
+	},
 }
 
 impl GasPricerConfig {
@@ -209,6 +215,7 @@ impl GasPricerConfig {
 		match *self {
 			GasPricerConfig::Fixed(ref min) => min.clone(),
 			GasPricerConfig::Calibrated { ref initial_minimum, .. } => initial_minimum.clone(),
+			GasPricerConfig::Oracle { ref initial_minimum, .. } => initial_minimum.clone(),
 		}
 	}
 }
@@ -232,7 +239,14 @@ impl Into<GasPricer> for GasPricerConfig {
 					usd_per_tx: usd_per_tx,
 					recalibration_period: recalibration_period,
 				})
-			}
+			},
+			GasPricerConfig::Oracle { percentile, min_gas_price, max_gas_price, .. } => {
+				GasPricer::new_oracle(GasPriceOracleOptions {
+					percentile: percentile,
+					min_gas_price: min_gas_price,
+					max_gas_price: max_gas_price,
+				})
+			},
 		}
 	}
 }
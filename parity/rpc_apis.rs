@@ -24,7 +24,7 @@ pub use parity_rpc::SignerService;
 
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::Client;
-use ethcore::miner::{Miner, ExternalMiner};
+use ethcore::miner::{Miner, ExternalMiner, WorkerStatsProvider};
 use ethcore::snapshot::SnapshotService;
 use parity_rpc::{Metadata, NetworkSettings};
 use parity_rpc::informant::{ActivityNotifier, Middleware, RpcStats, ClientNotifier};
@@ -59,6 +59,8 @@ pub enum Api {
 	Traces,
 	/// Rpc (Safe)
 	Rpc,
+	/// Private transactions (UNSAFE: Side Effects, broadcasts transactions to contract validators)
+	Private,
 }
 
 impl FromStr for Api {
@@ -78,6 +80,7 @@ impl FromStr for Api {
 			"parity_set" => Ok(ParitySet),
 			"traces" => Ok(Traces),
 			"rpc" => Ok(Rpc),
+			"private" => Ok(Private),
 			api => Err(format!("Unknown api: {}", api))
 		}
 	}
@@ -156,6 +159,7 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 			Api::ParitySet => ("parity_set", "1.0"),
 			Api::Traces => ("traces", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
+			Api::Private => ("private", "1.0"),
 		};
 		modules.insert(name.into(), version.into());
 	}
@@ -190,7 +194,9 @@ pub struct FullDependencies {
 	pub geth_compatibility: bool,
 	pub dapps_interface: Option<String>,
 	pub dapps_port: Option<u16>,
+	pub stratum: Option<Arc<WorkerStatsProvider>>,
 	pub fetch: FetchClient,
+	pub private_tx_provider: Option<Arc<::private_tx::Provider>>,
 }
 
 impl Dependencies for FullDependencies {
@@ -272,6 +278,7 @@ impl Dependencies for FullDependencies {
 						signer,
 						self.dapps_interface.clone(),
 						self.dapps_port,
+						self.stratum.clone(),
 					).to_delegate());
 
 					add_signing_methods!(EthSigning, handler, self);
@@ -286,6 +293,7 @@ impl Dependencies for FullDependencies {
 						&self.miner,
 						&self.updater,
 						&self.net_service,
+						self.logger.clone(),
 						self.fetch.clone(),
 					).to_delegate())
 				},
@@ -295,7 +303,14 @@ impl Dependencies for FullDependencies {
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
-				}
+				},
+				Api::Private => {
+					handler.extend_with(PrivateClient::new(
+						self.private_tx_provider.clone(),
+						&self.client,
+						&self.miner,
+					).to_delegate())
+				},
 			}
 		}
 	}
@@ -414,6 +429,7 @@ impl Dependencies for LightDependencies {
 				Api::ParitySet => {
 					handler.extend_with(light::ParitySetClient::new(
 						self.sync.clone(),
+						self.logger.clone(),
 						self.fetch.clone(),
 					).to_delegate())
 				},
@@ -423,7 +439,10 @@ impl Dependencies for LightDependencies {
 				Api::Rpc => {
 					let modules = to_modules(&apis);
 					handler.extend_with(RpcClient::new(modules).to_delegate());
-				}
+				},
+				Api::Private => {
+					warn!(target: "rpc", "Private transactions are not supported by the light client.");
+				},
 			}
 		}
 	}
@@ -495,6 +514,7 @@ mod test {
 		assert_eq!(Api::ParitySet, "parity_set".parse().unwrap());
 		assert_eq!(Api::Traces, "traces".parse().unwrap());
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
+		assert_eq!(Api::Private, "private".parse().unwrap());
 		assert!("rp".parse::<Api>().is_err());
 	}
 
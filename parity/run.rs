@@ -19,21 +19,23 @@ use std::net::{TcpListener};
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use parity_rpc::{NetworkSettings, informant, is_major_importing};
-use ethsync::NetworkConfiguration;
+use ethsync::{NetworkConfiguration, ConnectionFilter, PrivateTxHandler};
 use util::{Colour, version, Mutex, Condvar};
 use io::{MayPanic, ForwardPanic, PanicHandler};
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
-use ethcore::miner::{StratumOptions, Stratum};
+use ethcore::miner::{StratumOptions, Stratum, WorkerStatsProvider, CpuMiner};
 use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
 use ethcore::service::ClientService;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
-use ethcore::snapshot;
+use ethcore::snapshot::{self, SnapshotConfiguration};
+use ethcore::ethereum::EthashCacheOptions;
 use ethcore::verification::queue::VerifierSettings;
 use light::Cache as LightDataCache;
 use ethsync::SyncConfig;
 use informant::Informant;
 use updater::{UpdatePolicy, Updater};
+use node_filter::NodeFilter;
 use parity_reactor::EventLoop;
 use hash_fetch::fetch::{Fetch, Client as FetchClient};
 
@@ -48,15 +50,15 @@ use cache::CacheConfig;
 use user_defaults::UserDefaults;
 use dapps;
 use ipfs;
+use metrics;
+use metrics_api;
 use signer;
 use secretstore;
 use modules;
 use rpc_apis;
 use rpc;
 use url;
-
-// how often to take periodic snapshots.
-const SNAPSHOT_PERIOD: u64 = 10000;
+use private_tx::{self, ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
 
 // how many blocks to wait before starting a periodic snapshot.
 const SNAPSHOT_HISTORY: u64 = 100;
@@ -102,6 +104,7 @@ pub struct RunCmd {
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
 	pub ipfs_conf: ipfs::Configuration,
+	pub metrics_conf: metrics::Configuration,
 	pub signer_conf: signer::Configuration,
 	pub secretstore_conf: secretstore::Configuration,
 	pub dapp: Option<String>,
@@ -109,12 +112,20 @@ pub struct RunCmd {
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub stratum: Option<StratumOptions>,
+	pub miner_threads: Option<usize>,
+	pub tx_queue_journal: bool,
+	pub ethash_cache: EthashCacheOptions,
 	pub no_periodic_snapshot: bool,
+	pub snapshot_period: u64,
+	pub snapshot_conf: SnapshotConfiguration,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
 	pub light: bool,
+	pub private_tx_enabled: bool,
+	pub private_provider_conf: ProviderConfig,
+	pub private_encryptor_conf: Option<EncryptorConfig>,
 }
 
 pub fn open_ui(signer_conf: &signer::Configuration) -> Result<(), String> {
@@ -143,6 +154,7 @@ pub fn open_dapp(dapps_conf: &dapps::Configuration, rpc_conf: &rpc::HttpConfigur
 // node info fetcher for the local store.
 struct FullNodeInfo {
 	miner: Arc<Miner>, // TODO: only TXQ needed, just use that after decoupling.
+	journal_queue: bool,
 }
 
 impl ::local_store::NodeInfo for FullNodeInfo {
@@ -154,6 +166,32 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 			.filter(|tx| local_txs.contains_key(&tx.hash()))
 			.collect()
 	}
+
+	fn queued_transactions(&self) -> Vec<::ethcore::transaction::PendingTransaction> {
+		if !self.journal_queue {
+			return Vec::new();
+		}
+
+		let local_txs = self.miner.local_transactions();
+		self.miner.pending_transactions()
+			.into_iter()
+			.chain(self.miner.future_transactions())
+			.filter(|tx| !local_txs.contains_key(&tx.hash()))
+			.collect()
+	}
+}
+
+// private transactions handler for the sync protocol.
+struct PrivateTxSyncHandler(Arc<private_tx::Provider>);
+
+impl PrivateTxHandler for PrivateTxSyncHandler {
+	fn queue_private_transaction(&self, rlp: &[u8]) -> Result<::util::H256, String> {
+		self.0.queue_private_transaction(rlp).map_err(|e| e.to_string())
+	}
+
+	fn queue_signed_private_transaction(&self, rlp: &[u8]) -> Result<::util::H256, String> {
+		self.0.queue_signed_private_transaction(rlp).map_err(|e| e.to_string())
+	}
 }
 
 // helper for light execution.
@@ -263,6 +301,10 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	let rpc_stats = Arc::new(informant::RpcStats::default());
 	let signer_path = cmd.signer_conf.signer_path.clone();
 
+	// collect metrics; only RPC stats are available in light mode.
+	let metrics_registry = Arc::new(metrics_api::Registry::new());
+	metrics_registry.register(rpc_stats.clone());
+
 	// start RPCs
 	let deps_for_rpc_apis = Arc::new(rpc_apis::LightDependencies {
 		signer_service: Arc::new(rpc_apis::SignerService::new(move || {
@@ -309,6 +351,9 @@ fn execute_light(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) ->
 	let signing_queue = deps_for_rpc_apis.signer_service.queue();
 	let _signer_server = signer::start(cmd.signer_conf.clone(), signing_queue, signer_deps)?;
 
+	// the metrics server
+	let _metrics_server = metrics::start_server(cmd.metrics_conf.clone(), metrics_registry)?;
+
 	// TODO: Dapps
 
 	// minimal informant thread. Just prints block number every 5 seconds.
@@ -345,6 +390,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 
 	// load spec
 	let spec = cmd.spec.spec()?;
+	spec.engine.set_pow_cache_options(cmd.ethash_cache.clone());
 
 	// load genesis hash
 	let genesis_hash = spec.genesis_header().hash();
@@ -478,6 +524,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.snapshot = cmd.snapshot_conf;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -501,6 +548,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		miner.clone(),
 	).map_err(|e| format!("Client service error: {:?}", e))?;
 
+	let connection_filter_address = spec.params.node_permission_contract;
 	// drop the spec to free up genesis state.
 	drop(spec);
 
@@ -516,6 +564,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		let db = service.db();
 		let node_info = FullNodeInfo {
 			miner: miner.clone(),
+			journal_queue: cmd.tx_queue_journal,
 		};
 
 		let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
@@ -532,6 +581,25 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
+		// re-import the journaled queue, dropping transactions which are no longer valid.
+		// a journal left over from a run with journaling enabled is discarded otherwise.
+		if cmd.tx_queue_journal {
+			match store.queued_transactions() {
+				Ok(ref queued) if queued.is_empty() => {},
+				Ok(queued) => {
+					let total = queued.len();
+					let imported = miner.import_external_transactions(&*client, queued)
+						.into_iter()
+						.filter(|res| res.is_ok())
+						.count();
+					info!("Restored {} of {} journaled queue transactions", imported, total);
+				}
+				Err(e) => warn!("Error loading transaction queue journal from disk: {}", e),
+			}
+		} else if let Err(e) = store.clear_queued_transactions() {
+			warn!("Error clearing transaction queue journal: {}", e);
+		}
+
 		Arc::new(store)
 	};
 
@@ -542,11 +610,49 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let external_miner = Arc::new(ExternalMiner::default());
 
 	// start stratum
-	if let Some(ref stratum_config) = cmd.stratum {
-		Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
-			.map_err(|e| format!("Stratum start error: {:?}", e))?;
+	let stratum = match cmd.stratum {
+		Some(ref stratum_config) => Some(Stratum::register(stratum_config, miner.clone(), Arc::downgrade(&client))
+			.map_err(|e| format!("Stratum start error: {:?}", e))?),
+		None => None,
+	};
+
+	// start the built-in CPU miner
+	if let Some(threads) = cmd.miner_threads {
+		CpuMiner::register(threads, miner.clone(), Arc::downgrade(&client));
 	}
 
+	let connection_filter = connection_filter_address.map(|a| {
+		let client = client.clone() as Arc<BlockChainClient>;
+		Arc::new(NodeFilter::new(Arc::downgrade(&client), a)) as Arc<ConnectionFilter>
+	});
+
+	// private transactions provider
+	let private_tx_provider = match (cmd.private_tx_enabled, cmd.private_encryptor_conf) {
+		(true, Some(encryptor_conf)) => {
+			let encryptor = SecretStoreEncryptor::new(encryptor_conf)
+				.map_err(|e| format!("Error starting private transactions encryptor: {}", e))?;
+			Some(Arc::new(private_tx::Provider::new(
+				client.clone(),
+				account_provider.clone(),
+				Box::new(encryptor),
+				cmd.private_provider_conf.clone(),
+				service.io().channel(),
+			)))
+		},
+		_ => None,
+	};
+	if let Some(ref provider) = private_tx_provider {
+		service.register_io_handler(provider.clone()).map_err(|_| "Unable to register private transactions handler".to_owned())?;
+	}
+	let private_tx_handler = private_tx_provider.as_ref()
+		.map(|provider| Arc::new(PrivateTxSyncHandler(provider.clone())) as Arc<PrivateTxHandler>);
+
+	// collect metrics from each subsystem
+	let metrics_registry = Arc::new(metrics_api::Registry::new());
+	metrics_registry.register(client.clone());
+	metrics_registry.register(miner.clone());
+	metrics_registry.register(snapshot_service.clone());
+
 	// create sync object
 	let (sync_provider, manage_network, chain_notify) = modules::sync(
 		&mut hypervisor,
@@ -555,10 +661,16 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		client.clone(),
 		snapshot_service.clone(),
 		client.clone(),
+		connection_filter,
+		private_tx_handler,
+		&metrics_registry,
 		&cmd.logger_config,
 	).map_err(|e| format!("Sync error: {}", e))?;
 
 	service.add_notify(chain_notify.clone());
+	if let Some(ref provider) = private_tx_provider {
+		provider.add_notify(chain_notify.clone());
+	}
 
 	// start network
 	if network_enabled {
@@ -583,6 +695,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 
 	// set up dependencies for rpc servers
 	let rpc_stats = Arc::new(informant::RpcStats::default());
+	metrics_registry.register(rpc_stats.clone());
 	let signer_path = cmd.signer_conf.signer_path.clone();
 	let secret_store = match cmd.public_node {
 		true => None,
@@ -613,7 +726,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			true => Some(cmd.http_conf.port),
 			false => None,
 		},
+		stratum: stratum.map(|stratum| Arc::new(stratum) as Arc<WorkerStatsProvider>),
 		fetch: fetch.clone(),
+		private_tx_provider: private_tx_provider.clone(),
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -660,6 +775,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// the ipfs server
 	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
 
+	// the metrics server
+	let metrics_server = metrics::start_server(cmd.metrics_conf.clone(), metrics_registry.clone())?;
+
 	// the informant
 	let informant = Arc::new(Informant::new(
 		service.client(),
@@ -697,7 +815,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 				service.client(),
 				move || is_major_importing(Some(sync.status().state), client.queue_info()),
 				service.io().channel(),
-				SNAPSHOT_PERIOD,
+				cmd.snapshot_period,
 				SNAPSHOT_HISTORY,
 			));
 
@@ -719,7 +837,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let restart = wait_for_exit(panic_handler, Some(updater), Some(client), can_restart);
 
 	// drop this stuff as soon as exit detected.
-	drop((ws_server, http_server, ipc_server, signer_server, secretstore_key_server, ipfs_server, event_loop));
+	drop((ws_server, http_server, ipc_server, signer_server, secretstore_key_server, ipfs_server, metrics_server, event_loop));
 
 	info!("Finishing work, please wait...");
 
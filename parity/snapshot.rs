@@ -21,7 +21,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
-use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
+use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter, LooseReader};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType};
@@ -36,6 +36,7 @@ use user_defaults::UserDefaults;
 use fdlimit;
 
 use io::PanicHandler;
+use util::{Bytes, H256};
 
 /// Kinds of snapshot commands.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -43,7 +44,11 @@ pub enum Kind {
 	/// Take a snapshot.
 	Take,
 	/// Restore a snapshot.
-	Restore
+	Restore,
+	/// Verify a snapshot without importing it.
+	Verify,
+	/// Print information about a snapshot.
+	Info,
 }
 
 /// Command for snapshot creation or restoration.
@@ -64,6 +69,123 @@ pub struct SnapshotCommand {
 	pub block_at: BlockId,
 }
 
+// open the snapshot at the given path, which is either a directory
+// of loose chunks or a packed snapshot file.
+fn open_reader(path: &Path) -> Result<Box<SnapshotReader>, String> {
+	if path.is_dir() {
+		LooseReader::new(path.to_owned())
+			.map(|reader| Box::new(reader) as Box<SnapshotReader>)
+			.map_err(|e| format!("Couldn't open snapshot directory: {}", e))
+	} else {
+		PackedReader::new(path)
+			.map_err(|e| format!("Couldn't open snapshot file: {}", e))
+			.and_then(|x| x.ok_or("Snapshot file has invalid format.".into()))
+			.map(|reader| Box::new(reader) as Box<SnapshotReader>)
+	}
+}
+
+// read a chunk and check it against its hash in the manifest.
+fn read_chunk(reader: &SnapshotReader, hash: H256) -> Result<Bytes, String> {
+	use util::sha3::Hashable;
+
+	let chunk = reader.chunk(hash)
+		.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", hash, e))?;
+
+	let actual = chunk.sha3();
+	if actual != hash {
+		return Err(format!("Mismatched chunk hash. Expected {:?}, got {:?}", hash, actual));
+	}
+
+	Ok(chunk)
+}
+
+// check every chunk of the snapshot and rebuild the state in memory
+// to compare against the manifest's state root.
+fn verify_using(reader: &SnapshotReader) -> Result<String, String> {
+	use std::sync::atomic::AtomicBool;
+	use ethcore::snapshot::StateRebuilder;
+	use rlp::UntrustedRlp;
+	use util::journaldb::Algorithm;
+	use util::kvdb::in_memory;
+	use util::snappy;
+
+	let manifest = reader.manifest().clone();
+	let num_state = manifest.state_hashes.len();
+
+	let db = Arc::new(in_memory(::ethcore::db::NUM_COLUMNS.unwrap_or(0)));
+	let mut rebuilder = StateRebuilder::new(db, Algorithm::Archive);
+	let flag = AtomicBool::new(true);
+
+	info!("Verifying state of snapshot at block #{} (0x{:?})", manifest.block_number, manifest.block_hash);
+	for (i, &state_hash) in manifest.state_hashes.iter().enumerate() {
+		let chunk = read_chunk(reader, state_hash)?;
+		let raw = snappy::decompress(&chunk)
+			.map_err(|e| format!("Failed to decompress state chunk {:?}: {}", state_hash, e))?;
+
+		rebuilder.feed(&raw, &flag)
+			.map_err(|e| format!("Invalid state chunk {:?}: {}", state_hash, e))?;
+
+		if (i + 1) % 100 == 0 {
+			info!("Verified {}/{} state chunks.", i + 1, num_state);
+		}
+	}
+
+	let state_root = rebuilder.state_root();
+	if state_root != manifest.state_root {
+		return Err(format!("Mismatched state root. Expected {:?}, got {:?}", manifest.state_root, state_root));
+	}
+
+	rebuilder.finalize(manifest.block_number, manifest.block_hash)
+		.map_err(|e| format!("Restored state is incomplete: {}", e))?;
+
+	info!("Verifying blocks");
+	for &block_hash in &manifest.block_hashes {
+		let chunk = read_chunk(reader, block_hash)?;
+		let raw = snappy::decompress(&chunk)
+			.map_err(|e| format!("Failed to decompress block chunk {:?}: {}", block_hash, e))?;
+
+		// block chunks begin with the parent's number, hash, and total difficulty.
+		match UntrustedRlp::new(&raw).item_count() {
+			Ok(count) if count >= 3 => {},
+			_ => return Err(format!("Malformed block chunk {:?}", block_hash)),
+		}
+	}
+
+	Ok(format!("Snapshot is valid: {} state chunks and {} block chunks with state root 0x{:?}.",
+		num_state, manifest.block_hashes.len(), state_root))
+}
+
+// summarize the snapshot's manifest and chunk sizes.
+fn info_using(reader: &SnapshotReader) -> Result<String, String> {
+	use informant::format_bytes;
+
+	let manifest = reader.manifest();
+
+	let total_size = |hashes: &[H256]| -> Result<usize, String> {
+		let mut size = 0;
+		for &hash in hashes {
+			size += reader.chunk(hash)
+				.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", hash, e))?
+				.len();
+		}
+		Ok(size)
+	};
+
+	let state_size = total_size(&manifest.state_hashes)?;
+	let block_size = total_size(&manifest.block_hashes)?;
+
+	Ok(format!("Version: {}\nBlock number: {}\nBlock hash: 0x{:?}\nState root: 0x{:?}\nState chunks: {} ({})\nBlock chunks: {} ({})",
+		manifest.version,
+		manifest.block_number,
+		manifest.block_hash,
+		manifest.state_root,
+		manifest.state_hashes.len(),
+		format_bytes(state_size),
+		manifest.block_hashes.len(),
+		format_bytes(block_size)
+	))
+}
+
 // helper for reading chunks from arbitrary reader and feeding them into the
 // service.
 fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R, recover: bool) -> Result<(), String> {
@@ -231,6 +353,22 @@ impl SnapshotCommand {
 		Ok(())
 	}
 
+	/// Verify a snapshot without touching the local database.
+	pub fn verify(self) -> Result<String, String> {
+		let file_path = self.file_path.ok_or("No snapshot path provided.".to_owned())?;
+		let reader = open_reader(Path::new(&file_path))?;
+
+		verify_using(&*reader)
+	}
+
+	/// Print information about a snapshot.
+	pub fn info(self) -> Result<String, String> {
+		let file_path = self.file_path.ok_or("No snapshot path provided.".to_owned())?;
+		let reader = open_reader(Path::new(&file_path))?;
+
+		info_using(&*reader)
+	}
+
 	/// Take a snapshot from the head of the chain.
 	pub fn take_snapshot(self) -> Result<(), String> {
 		let file_path = self.file_path.clone().ok_or("No file path provided.".to_owned())?;
@@ -280,6 +418,8 @@ pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
 		Kind::Take => cmd.take_snapshot()?,
 		Kind::Restore => cmd.restore()?,
+		Kind::Verify => return cmd.verify(),
+		Kind::Info => return cmd.info(),
 	}
 
 	Ok(String::new())
@@ -57,7 +57,7 @@ pub fn main() {
 		snapshot_service: remote_snapshot.service().clone(), 
 		provider: remote_provider.service().clone(),
 		network_config: service_config.net
-	}).unwrap();
+	}, None, None).unwrap();
 
 	let _ = boot::main_thread();
 	let service_stop = Arc::new(AtomicBool::new(false));
@@ -46,11 +46,13 @@ extern crate ethkey;
 extern crate ethstore;
 extern crate ethsync;
 extern crate ethcore_logger;
+extern crate ethcore_private_tx;
 extern crate fetch;
 extern crate parity_reactor;
 extern crate parity_updater as updater;
 extern crate rlp;
 extern crate stats;
+extern crate metrics;
 
 #[macro_use]
 extern crate log;
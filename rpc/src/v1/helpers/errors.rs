@@ -48,6 +48,7 @@ mod codes {
 	pub const FETCH_ERROR: i64 = -32060;
 	pub const NO_LIGHT_PEERS: i64 = -32065;
 	pub const DEPRECATED: i64 = -32070;
+	pub const PRIVATE_ERROR: i64 = -32080;
 }
 
 pub fn unimplemented(details: Option<String>) -> Error {
@@ -209,6 +210,14 @@ pub fn dapps_disabled() -> Error {
 	}
 }
 
+pub fn stratum_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Stratum server is disabled. This API is not available.".into(),
+		data: None,
+	}
+}
+
 pub fn network_disabled() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -217,6 +226,22 @@ pub fn network_disabled() -> Error {
 	}
 }
 
+pub fn private_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Private transactions are disabled. This API is not available.".into(),
+		data: None,
+	}
+}
+
+pub fn private_message<T: fmt::Debug>(error: T) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::PRIVATE_ERROR),
+		message: "Private transactions call failed.".into(),
+		data: Some(Value::String(format!("{:?}", error))),
+	}
+}
+
 pub fn encryption_error<T: fmt::Debug>(error: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ENCRYPTION_ERROR),
@@ -277,6 +302,12 @@ pub fn transaction_message(error: TransactionError) -> String {
 		LimitReached => {
 			"There are too many transactions in the queue. Your transaction was dropped due to limit. Try increasing the fee.".into()
 		},
+		InsufficientPriceBump { minimal, got } => {
+			format!("Transaction gas price is too low to replace the transaction with same nonce in the queue (minimal: {}, got: {}). Try increasing the gas price or incrementing the nonce.", minimal, got)
+		},
+		SenderLimitReached { limit } => {
+			format!("There are too many future transactions from this sender in the queue (limit: {}). Wait for the pending ones to be mined or send transactions with lower nonces first.", limit)
+		},
 		InsufficientGas { minimal, got } => {
 			format!("Transaction gas is too low. There is not enough gas to cover minimal cost of the transaction (minimal: {}, got: {}). Try increasing supplied gas.", minimal, got)
 		},
@@ -294,6 +325,7 @@ pub fn transaction_message(error: TransactionError) -> String {
 		SenderBanned => "Sender is banned in local queue.".into(),
 		RecipientBanned => "Recipient is banned in local queue.".into(),
 		CodeBanned => "Code is banned in local queue.".into(),
+		NotAllowed => "Transaction is not permitted.".into(),
 	}
 }
 
@@ -17,16 +17,22 @@
 //! RPC Requests Statistics
 
 use std::fmt;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
 use std::time;
 use futures::Future;
 use jsonrpc_core as rpc;
+use metrics::{Collector, Histogram, Metric, MetricType, LATENCY_BUCKETS};
 use order_stat;
 use util::RwLock;
 
 const RATE_SECONDS: usize = 10;
 const STATS_SAMPLES: usize = 60;
+/// Maximal number of distinct method names tracked. Method names come from the client,
+/// so calls to further methods are recorded under `UNKNOWN_METHOD`.
+const MAX_TRACKED_METHODS: usize = 512;
+const UNKNOWN_METHOD: &'static str = "unknown";
 
 struct RateCalculator {
 	era: time::Instant,
@@ -133,6 +139,7 @@ impl<T: Default + Copy + Ord> StatsCalculator<T> {
 pub struct RpcStats {
 	requests: RwLock<RateCalculator>,
 	roundtrips: RwLock<StatsCalculator<u32>>,
+	methods: RwLock<BTreeMap<String, Histogram>>,
 	active_sessions: AtomicUsize,
 }
 
@@ -158,6 +165,25 @@ impl RpcStats {
 		self.roundtrips.write().add(microseconds)
 	}
 
+	/// Record a call to given method together with its latency.
+	pub fn add_method_call(&self, method: &str, latency: time::Duration) {
+		let seconds = latency.as_secs() as f64 + latency.subsec_nanos() as f64 / 1_000_000_000.0;
+		let mut methods = self.methods.write();
+		let method = match methods.contains_key(method) || methods.len() < MAX_TRACKED_METHODS {
+			true => method,
+			false => UNKNOWN_METHOD,
+		};
+		if !methods.contains_key(method) {
+			methods.insert(method.to_owned(), Histogram::new(LATENCY_BUCKETS));
+		}
+		methods.get_mut(method).expect("inserted above; qed").observe(seconds);
+	}
+
+	/// Returns number of calls to given method.
+	pub fn method_calls(&self, method: &str) -> u64 {
+		self.methods.read().get(method).map_or(0, |h| h.count())
+	}
+
 	/// Returns number of open sessions
 	pub fn sessions(&self) -> usize {
 		self.active_sessions.load(atomic::Ordering::Relaxed)
@@ -174,6 +200,24 @@ impl RpcStats {
 	}
 }
 
+impl Collector for RpcStats {
+	fn collect(&self) -> Vec<Metric> {
+		let methods = self.methods.read();
+		let mut requests = Metric::new("parity_rpc_requests_total", "RPC requests by method.", MetricType::Counter);
+		let mut durations = Metric::new("parity_rpc_request_duration_seconds", "RPC request latency by method.", MetricType::Histogram);
+		for (method, histogram) in methods.iter() {
+			requests = requests.with_sample(&[("method", method.as_str())], histogram.count() as f64);
+			durations = durations.with_histogram(&[("method", method.as_str())], histogram);
+		}
+
+		vec![
+			requests,
+			durations,
+			Metric::gauge("parity_rpc_sessions", "Open RPC sessions.", self.sessions() as f64),
+		]
+	}
+}
+
 /// Notifies about RPC activity.
 pub trait ActivityNotifier: Send + Sync + 'static {
 	/// Activity on RPC interface
@@ -198,6 +242,38 @@ impl<T: ActivityNotifier> Middleware<T> {
 	fn as_micro(dur: time::Duration) -> u32 {
 		(dur.as_secs() * 1_000_000) as u32 + dur.subsec_nanos() / 1_000
 	}
+
+	/// Method calls in the request, paired with their ids.
+	/// Notifications are left out, since they get no response telling whether the method is served.
+	fn method_calls(request: &rpc::Request) -> Vec<(rpc::Id, String)> {
+		fn method_call(call: &rpc::Call) -> Option<(rpc::Id, String)> {
+			match *call {
+				rpc::Call::MethodCall(ref call) => Some((call.id.clone(), call.method.clone())),
+				rpc::Call::Notification(_) | rpc::Call::Invalid(_) => None,
+			}
+		}
+
+		match *request {
+			rpc::Request::Single(ref call) => method_call(call).into_iter().collect(),
+			rpc::Request::Batch(ref calls) => calls.iter().filter_map(method_call).collect(),
+		}
+	}
+
+	/// Ids of calls that were answered with "method not found".
+	fn unknown_method_ids(response: &Option<rpc::Response>) -> Vec<rpc::Id> {
+		fn unknown_id(output: &rpc::Output) -> Option<rpc::Id> {
+			match *output {
+				rpc::Output::Failure(ref failure) if failure.error.code == rpc::ErrorCode::MethodNotFound => Some(failure.id.clone()),
+				_ => None,
+			}
+		}
+
+		match *response {
+			Some(rpc::Response::Single(ref output)) => unknown_id(output).into_iter().collect(),
+			Some(rpc::Response::Batch(ref outputs)) => outputs.iter().filter_map(unknown_id).collect(),
+			None => Vec::new(),
+		}
+	}
 }
 
 impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T> {
@@ -205,13 +281,22 @@ impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T>
 		F: FnOnce(rpc::Request, M) -> rpc::FutureResponse,
 	{
 		let start = time::Instant::now();
+		let calls = Self::method_calls(&request);
 		let response = process(request, meta);
 
 		self.notifier.active();
 		let stats = self.stats.clone();
 		stats.count_request();
 		response.map(move |res| {
-			stats.add_roundtrip(Self::as_micro(start.elapsed()));
+			let elapsed = start.elapsed();
+			stats.add_roundtrip(Self::as_micro(elapsed));
+			// Only methods the handler actually serves get their own label.
+			let unknown = Self::unknown_method_ids(&res);
+			for &(ref id, ref method) in &calls {
+				if !unknown.contains(id) {
+					stats.add_method_call(method, elapsed);
+				}
+			}
 			res
 		}).boxed()
 	}
@@ -232,7 +317,9 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
-	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use std::time::Duration;
+	use metrics::Collector;
+	use super::{RateCalculator, StatsCalculator, RpcStats, MAX_TRACKED_METHODS, UNKNOWN_METHOD};
 
 	#[test]
 	fn should_calculate_rate() {
@@ -289,6 +376,43 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_collect_method_metrics() {
+		// given
+		let stats = RpcStats::default();
+
+		// when
+		stats.add_method_call("eth_blockNumber", Duration::from_millis(2));
+		stats.add_method_call("eth_blockNumber", Duration::from_millis(40));
+		stats.add_method_call("eth_call", Duration::from_millis(300));
+		let metrics = stats.collect();
+
+		// then
+		assert_eq!(stats.method_calls("eth_blockNumber"), 2);
+		assert_eq!(stats.method_calls("eth_call"), 1);
+		assert_eq!(stats.method_calls("eth_getBalance"), 0);
+		let requests = metrics.iter().find(|m| m.name == "parity_rpc_requests_total").unwrap();
+		assert_eq!(requests.samples.len(), 2);
+		assert_eq!(requests.samples[0].value, 2.0);
+	}
+
+	#[test]
+	fn should_bound_number_of_tracked_methods() {
+		// given
+		let stats = RpcStats::default();
+
+		// when
+		for i in 0..(MAX_TRACKED_METHODS + 10) {
+			stats.add_method_call(&format!("random_{}", i), Duration::from_millis(1));
+		}
+		stats.add_method_call("random_0", Duration::from_millis(1));
+
+		// then
+		assert_eq!(stats.methods.read().len(), MAX_TRACKED_METHODS + 1);
+		assert_eq!(stats.method_calls("random_0"), 2);
+		assert_eq!(stats.method_calls(UNKNOWN_METHOD), 10);
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
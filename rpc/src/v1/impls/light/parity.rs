@@ -37,11 +37,11 @@ use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, H160, H256, H512,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, GasPriceRecommendations,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader,
+	OperationsInfo, DappId, ChainStatus, MaliciousEvidence, StratumWorker,
+	AccountInfo, HwAccountInfo, Header, RichHeader, SyncDiagnostics,
 };
 
 /// Parity implementation for light client.
@@ -160,7 +160,7 @@ impl Parity for ParityClient {
 	}
 
 	fn dev_logs_levels(&self) -> Result<String, Error> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String, Error> {
@@ -210,6 +210,10 @@ impl Parity for ParityClient {
 			.boxed()
 	}
 
+	fn gas_price_recommendations(&self) -> Result<GasPriceRecommendations, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn unsigned_transactions_count(&self) -> Result<usize, Error> {
 		match self.signer {
 			None => Err(errors::signer_disabled()),
@@ -345,6 +349,10 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn sync_diagnostics(&self) -> Result<SyncDiagnostics, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -354,6 +362,14 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn malicious_evidence(&self) -> Result<Vec<MaliciousEvidence>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn stratum_workers(&self) -> Result<BTreeMap<String, StratumWorker>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn block_header(&self, number: Trailing<BlockNumber>) -> BoxFuture<Option<RichHeader>, Error> {
 		use ethcore::encoded;
 
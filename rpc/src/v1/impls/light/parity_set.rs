@@ -21,6 +21,7 @@ use std::io;
 use std::sync::Arc;
 
 use ethsync::ManageNetwork;
+use ethcore_logger::RotatingLogger;
 use fetch::Fetch;
 use futures::{BoxFuture, Future};
 use util::sha3;
@@ -33,14 +34,16 @@ use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction};
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
 	net: Arc<ManageNetwork>,
+	logger: Arc<RotatingLogger>,
 	fetch: F,
 }
 
 impl<F: Fetch> ParitySetClient<F> {
 	/// Creates new `ParitySetClient` with given `Fetch`.
-	pub fn new(net: Arc<ManageNetwork>, fetch: F) -> Self {
+	pub fn new(net: Arc<ManageNetwork>, logger: Arc<RotatingLogger>, fetch: F) -> Self {
 		ParitySetClient {
 			net: net,
+			logger: logger,
 			fetch: fetch,
 		}
 	}
@@ -117,6 +120,11 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_log_levels(&self, levels: String) -> Result<bool, Error> {
+		self.logger.set_levels(&levels).map_err(|e| errors::invalid_params("levels", e))?;
+		Ok(true)
+	}
+
 	fn set_spec_name(&self, _spec_name: String) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -23,6 +23,7 @@ mod parity;
 mod parity_accounts;
 mod parity_set;
 mod personal;
+mod private;
 mod signer;
 mod signing;
 mod signing_unsafe;
@@ -40,6 +41,7 @@ pub use self::parity::ParityClient;
 pub use self::parity_accounts::ParityAccountsClient;
 pub use self::parity_set::ParitySetClient;
 pub use self::personal::PersonalClient;
+pub use self::private::PrivateClient;
 pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
 pub use self::signing_unsafe::SigningUnsafeClient;
@@ -29,7 +29,7 @@ use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
 use ethcore::ids::BlockId;
-use ethcore::miner::MinerService;
+use ethcore::miner::{MinerService, WorkerStatsProvider};
 use ethcore::client::{MiningBlockChainClient};
 use ethcore::mode::Mode;
 use ethcore::account_provider::AccountProvider;
@@ -44,11 +44,11 @@ use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, H160, H256, H512,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, GasPriceRecommendations,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader
+	OperationsInfo, DappId, ChainStatus, MaliciousEvidence, StratumWorker,
+	AccountInfo, HwAccountInfo, Header, RichHeader, SyncDiagnostics,
 };
 
 /// Parity implementation.
@@ -69,6 +69,7 @@ pub struct ParityClient<C, M, S: ?Sized, U> where
 	signer: Option<Arc<SignerService>>,
 	dapps_interface: Option<String>,
 	dapps_port: Option<u16>,
+	stratum: Option<Arc<WorkerStatsProvider>>,
 }
 
 impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
@@ -90,6 +91,7 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 		signer: Option<Arc<SignerService>>,
 		dapps_interface: Option<String>,
 		dapps_port: Option<u16>,
+		stratum: Option<Arc<WorkerStatsProvider>>,
 	) -> Self {
 		ParityClient {
 			client: Arc::downgrade(client),
@@ -103,6 +105,7 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 			signer: signer,
 			dapps_interface: dapps_interface,
 			dapps_port: dapps_port,
+			stratum: stratum,
 		}
 	}
 
@@ -190,7 +193,7 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String, Error> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String, Error> {
@@ -254,6 +257,13 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		).boxed()
 	}
 
+	fn gas_price_recommendations(&self) -> Result<GasPriceRecommendations, Error> {
+		take_weak!(self.miner)
+			.gas_price_recommendations()
+			.map(Into::into)
+			.ok_or_else(errors::not_enough_data)
+	}
+
 	fn unsigned_transactions_count(&self) -> Result<usize, Error> {
 		match self.signer {
 			None => Err(errors::signer_disabled()),
@@ -381,6 +391,10 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn sync_diagnostics(&self) -> Result<SyncDiagnostics, Error> {
+		Ok(take_weak!(self.sync).diagnostics().into())
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind, Error> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -395,6 +409,18 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		})
 	}
 
+	fn malicious_evidence(&self) -> Result<Vec<MaliciousEvidence>, Error> {
+		let client = take_weak!(self.client);
+		Ok(client.malicious_evidence().into_iter().map(Into::into).collect())
+	}
+
+	fn stratum_workers(&self) -> Result<BTreeMap<String, StratumWorker>, Error> {
+		self.stratum
+			.as_ref()
+			.map(|stratum| stratum.worker_stats().into_iter().map(|(id, stats)| (id, stats.into())).collect())
+			.ok_or_else(|| errors::stratum_disabled())
+	}
+
 	fn block_header(&self, number: Trailing<BlockNumber>) -> BoxFuture<Option<RichHeader>, Error> {
 		const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
@@ -22,6 +22,7 @@ use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
 use ethcore::mode::Mode;
 use ethsync::ManageNetwork;
+use ethcore_logger::RotatingLogger;
 use fetch::{self, Fetch};
 use futures::{BoxFuture, Future};
 use util::sha3;
@@ -38,17 +39,19 @@ pub struct ParitySetClient<C, M, U, F = fetch::Client> {
 	miner: Weak<M>,
 	updater: Weak<U>,
 	net: Weak<ManageNetwork>,
+	logger: Arc<RotatingLogger>,
 	fetch: F,
 }
 
 impl<C, M, U, F> ParitySetClient<C, M, U, F> {
 	/// Creates new `ParitySetClient` with given `Fetch`.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>, updater: &Arc<U>, net: &Arc<ManageNetwork>, fetch: F) -> Self {
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, updater: &Arc<U>, net: &Arc<ManageNetwork>, logger: Arc<RotatingLogger>, fetch: F) -> Self {
 		ParitySetClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			updater: Arc::downgrade(updater),
 			net: Arc::downgrade(net),
+			logger: logger,
 			fetch: fetch,
 		}
 	}
@@ -146,6 +149,11 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_log_levels(&self, levels: String) -> Result<bool, Error> {
+		self.logger.set_levels(&levels).map_err(|e| errors::invalid_params("levels", e))?;
+		Ok(true)
+	}
+
 	fn set_spec_name(&self, spec_name: String) -> Result<bool, Error> {
 		take_weak!(self.client).set_spec_name(spec_name);
 		Ok(true)
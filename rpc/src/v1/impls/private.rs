@@ -0,0 +1,79 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions api implementation.
+
+use std::sync::{Arc, Weak};
+
+use rlp::UntrustedRlp;
+use ethcore::client::Client;
+use ethcore::miner::Miner;
+use ethcore::transaction::SignedTransaction;
+use ethcore_private_tx::Provider as PrivateTransactionManager;
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::traits::Private;
+use v1::helpers::{errors, fake_sign};
+use v1::types::{Bytes, CallRequest, BlockNumber, PrivateTransactionReceipt};
+
+/// Private transactions api implementation.
+pub struct PrivateClient {
+	private: Option<Arc<PrivateTransactionManager>>,
+	client: Weak<Client>,
+	miner: Weak<Miner>,
+}
+
+impl PrivateClient {
+	/// Creates a new instance.
+	pub fn new(private: Option<Arc<PrivateTransactionManager>>, client: &Arc<Client>, miner: &Arc<Miner>) -> Self {
+		PrivateClient {
+			private: private,
+			client: Arc::downgrade(client),
+			miner: Arc::downgrade(miner),
+		}
+	}
+
+	fn unwrap_manager(&self) -> Result<&PrivateTransactionManager, Error> {
+		match self.private {
+			Some(ref manager) => Ok(&**manager),
+			None => Err(errors::private_disabled()),
+		}
+	}
+}
+
+impl Private for PrivateClient {
+	fn send_transaction(&self, request: Bytes) -> Result<PrivateTransactionReceipt, Error> {
+		let signed_transaction = UntrustedRlp::new(&request.into_vec()).as_val()
+			.map_err(errors::from_rlp_error)
+			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::from_transaction_error))?;
+
+		self.unwrap_manager()?
+			.create_private_transaction(signed_transaction)
+			.map(Into::into)
+			.map_err(errors::private_message)
+	}
+
+	fn private_call(&self, request: CallRequest, block: Trailing<BlockNumber>) -> Result<Bytes, Error> {
+		let manager = self.unwrap_manager()?;
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+
+		manager.private_call(block.0.into(), &signed)
+			.map(|executed| executed.output.into())
+			.map_err(errors::private_message)
+	}
+}
@@ -60,6 +60,10 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			tx_queue_size: 1024,
 			tx_gas_limit: !U256::zero(),
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_price_bump: 0,
+			tx_queue_per_sender: usize::max_value(),
+			gas_price_oracle_blocks: 100,
+			block_assembly: Vec::new(),
 			tx_queue_gas_limit: GasLimit::None,
 			tx_queue_banning: Banning::Disabled,
 			pending_set: PendingSet::SealingOrElseQueue,
@@ -25,7 +25,7 @@ use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::header::BlockNumber;
 use ethcore::transaction::{UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use ethcore::receipt::{Receipt, RichReceipt};
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus};
+use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus, GasPriceRecommendations};
 use ethcore::account_provider::SignError as AccountError;
 
 /// Test miner service.
@@ -44,6 +44,8 @@ pub struct TestMinerService {
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
 	/// Password held by Engine.
 	pub password: RwLock<String>,
+	/// Gas price recommendations.
+	pub gas_price_recommendations: RwLock<Option<GasPriceRecommendations>>,
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
@@ -66,6 +68,7 @@ impl Default for TestMinerService {
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
 			author: RwLock::new(Address::zero()),
 			password: RwLock::new(String::new()),
+			gas_price_recommendations: RwLock::new(None),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
 			tx_gas_limit: RwLock::new(!U256::zero()),
@@ -229,6 +232,10 @@ impl MinerService for TestMinerService {
 		self.pending_transactions.lock().values().cloned().map(Into::into).collect()
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<PendingTransaction> {
+		self.pending_transactions.lock().get(hash).cloned().map(Into::into)
+	}
+
 	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus> {
 		self.local_transactions.lock().iter().map(|(hash, stats)| (*hash, stats.clone())).collect()
 	}
@@ -313,4 +320,8 @@ impl MinerService for TestMinerService {
 	fn sensible_gas_price(&self) -> U256 {
 		20000000000u64.into()
 	}
+
+	fn gas_price_recommendations(&self) -> Option<GasPriceRecommendations> {
+		self.gas_price_recommendations.read().clone()
+	}
 }
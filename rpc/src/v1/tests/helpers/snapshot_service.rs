@@ -41,11 +41,13 @@ impl TestSnapshotService {
 
 impl SnapshotService for TestSnapshotService {
 	fn manifest(&self) -> Option<ManifestData> { None }
+	fn manifest_at(&self, _block_number: u64) -> Option<ManifestData> { None }
 	fn chunk(&self, _hash: H256) -> Option<Bytes> { None }
 	fn status(&self) -> RestorationStatus { self.status.lock().clone() }
 	fn begin_restore(&self, _manifest: ManifestData) { }
 	fn abort_restore(&self) { }
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) { }
+	fn completed_chunks(&self, _manifest: ManifestData) -> Vec<H256> { Vec::new() }
 	fn provide_canon_hashes(&self, _hashes: &[(u64, H256)]) { }
 }
\ No newline at end of file
@@ -18,7 +18,10 @@
 
 use std::collections::BTreeMap;
 use util::{H256, RwLock};
-use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use ethsync::{
+	SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats,
+	SyncDiagnostics, DownloaderDiagnostics, PeerSyncInfo, SyncError,
+};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -78,6 +81,7 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/62".to_owned(), "eth/63".to_owned()],
     			remote_address: "127.0.0.1:7777".to_owned(),
 				local_address: "127.0.0.1:8888".to_owned(),
+				reputation: 10,
 				eth_info: Some(EthProtocolInfo {
 					version: 62,
 					difficulty: Some(40.into()),
@@ -91,6 +95,7 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/63".to_owned(), "eth/64".to_owned()],
     			remote_address: "Handshake".to_owned(),
 				local_address: "127.0.0.1:3333".to_owned(),
+				reputation: 0,
 				eth_info: Some(EthProtocolInfo {
 					version: 64,
 					difficulty: None,
@@ -109,17 +114,70 @@ impl SyncProvider for TestSyncProvider {
 		map![
 			1.into() => TransactionStats {
 				first_seen: 10,
+				received_from: None,
 				propagated_to: map![
 					128.into() => 16
 				],
+				announced_to: Default::default(),
 			},
 			5.into() => TransactionStats {
 				first_seen: 16,
+				received_from: Some(32.into()),
 				propagated_to: map![
 					16.into() => 1
 				],
+				announced_to: map![
+					64.into() => 2
+				],
 			}
 		]
 	}
+
+	fn diagnostics(&self) -> SyncDiagnostics {
+		SyncDiagnostics {
+			state: SyncState::Blocks,
+			last_progress_block_number: 100,
+			last_progress_age: 200,
+			stall_recoveries: 1,
+			new_blocks: DownloaderDiagnostics {
+				state: "Blocks".to_owned(),
+				last_imported_block_number: 100,
+				last_imported_block_hash: 1.into(),
+				highest_block_number: Some(150),
+				target_hash: None,
+				round_start: 90,
+				imported_this_round: Some(10),
+				retract_step: 1,
+				subchain_heads: 2,
+				downloaded_blocks: 64,
+				downloading_headers: 0,
+				downloading_bodies: 1,
+				downloading_receipts: 0,
+			},
+			old_blocks: None,
+			peers: vec![
+				PeerSyncInfo {
+					id: 1,
+					protocol_version: 63,
+					difficulty: Some(40.into()),
+					latest_hash: 2.into(),
+					asking: "BlockBodies".to_owned(),
+					asking_blocks: 32,
+					asking_for: Some(3),
+					block_set: Some("NewBlocks".to_owned()),
+					expired: false,
+					active: true,
+					confirmed: true,
+				}
+			],
+			recent_errors: vec![
+				SyncError {
+					timestamp: 1500000000,
+					peer: Some(2),
+					message: "Bad block".to_owned(),
+				}
+			],
+		}
+	}
 }
 
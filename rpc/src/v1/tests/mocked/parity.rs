@@ -15,12 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::collections::HashMap;
 use ethcore_logger::RotatingLogger;
 use util::Address;
 use ethsync::ManageNetwork;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{TestBlockChainClient};
-use ethcore::miner::LocalTransactionStatus;
+use ethcore::miner::{LocalTransactionStatus, WorkerStatsProvider, StratumWorkerStats, GasPriceRecommendations, GasPriceRecommendation};
 use ethstore::ethkey::{Generator, Random};
 
 use jsonrpc_core::IoHandler;
@@ -43,6 +44,7 @@ pub struct Dependencies {
 	pub accounts: Arc<AccountProvider>,
 	pub dapps_interface: Option<String>,
 	pub dapps_port: Option<u16>,
+	pub stratum: Option<Arc<WorkerStatsProvider>>,
 }
 
 impl Dependencies {
@@ -68,6 +70,7 @@ impl Dependencies {
 			accounts: Arc::new(AccountProvider::transient_provider()),
 			dapps_interface: Some("127.0.0.1".into()),
 			dapps_port: Some(18080),
+			stratum: None,
 		}
 	}
 
@@ -86,6 +89,7 @@ impl Dependencies {
 			signer,
 			self.dapps_interface.clone(),
 			self.dapps_port,
+			self.stratum.clone(),
 		)
 	}
 
@@ -304,7 +308,7 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null},"reputation":10},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null},"reputation":0}]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -364,6 +368,27 @@ fn rpc_parity_unsigned_transactions_count_when_signer_disabled() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_gas_price_recommendations() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_gasPriceRecommendations", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The node does not have enough data to compute the given statistic."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let recommendation = |price: u64, wait| GasPriceRecommendation { gas_price: price.into(), expected_wait: wait };
+	*deps.miner.gas_price_recommendations.write() = Some(GasPriceRecommendations {
+		fast: recommendation(0x30, Some(15)),
+		standard: recommendation(0x20, Some(30)),
+		slow: recommendation(0x10, None),
+		blocks: 100,
+	});
+
+	let response = r#"{"jsonrpc":"2.0","result":{"blocks":100,"fast":{"expectedWait":15,"gasPrice":"0x30"},"slow":{"expectedWait":null,"gasPrice":"0x10"},"standard":{"expectedWait":30,"gasPrice":"0x20"}},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_pending_transactions() {
 	let deps = Dependencies::new();
@@ -466,7 +491,7 @@ fn rpc_parity_transactions_stats() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsStats", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"firstSeen":10,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080":16}},"0x0000000000000000000000000000000000000000000000000000000000000005":{"firstSeen":16,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":1}}},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"0x0000000000000000000000000000000000000000000000000000000000000001":{"announcedTo":{},"firstSeen":10,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000080":16},"receivedFrom":null},"0x0000000000000000000000000000000000000000000000000000000000000005":{"announcedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000040":2},"firstSeen":16,"propagatedTo":{"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000010":1},"receivedFrom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000020"}},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -500,6 +525,17 @@ fn rpc_parity_chain_status() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_sync_diagnostics() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_syncDiagnostics", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"lastProgressAge":200,"lastProgressBlockNumber":"0x64","newBlocks":{"downloadedBlocks":64,"downloadingBodies":1,"downloadingHeaders":0,"downloadingReceipts":0,"highestBlockNumber":"0x96","importedThisRound":10,"lastImportedBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","lastImportedBlockNumber":"0x64","retractStep":1,"roundStart":"0x5a","state":"Blocks","subchainHeads":2,"targetHash":null},"oldBlocks":null,"peers":[{"active":true,"asking":"BlockBodies","askingBlocks":32,"askingFor":3,"blockSet":"NewBlocks","confirmed":true,"difficulty":"0x28","expired":false,"id":1,"latestHash":"0x0000000000000000000000000000000000000000000000000000000000000002","protocolVersion":63}],"recentErrors":[{"message":"Bad block","peer":2,"timestamp":1500000000}],"stallRecoveries":1,"state":"Blocks"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_kind() {
 	let deps = Dependencies::new();
@@ -511,6 +547,56 @@ fn rpc_parity_node_kind() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_malicious_evidence() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_maliciousEvidence", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+struct TestStratum;
+
+impl WorkerStatsProvider for TestStratum {
+	fn worker_stats(&self) -> HashMap<String, StratumWorkerStats> {
+		let mut workers = HashMap::new();
+		workers.insert("miner1.rig1".to_owned(), StratumWorkerStats {
+			hashrate: 0x500000.into(),
+			accepted: 3,
+			stale: 1,
+			rejected: 0,
+			last_share: Some(1500000000),
+		});
+		workers
+	}
+}
+
+#[test]
+fn rpc_parity_stratum_workers() {
+	let mut deps = Dependencies::new();
+	deps.stratum = Some(Arc::new(TestStratum));
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stratumWorkers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"miner1.rig1":{"acceptedShares":3,"hashrate":"0x500000","lastShare":1500000000,"rejectedShares":0,"staleShares":1}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_stratum_workers_disabled() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stratumWorkers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Stratum server is disabled. This API is not available."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_cid() {
 	let deps = Dependencies::new();
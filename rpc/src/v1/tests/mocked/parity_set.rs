@@ -22,6 +22,7 @@ use util::{U256, Address};
 use ethcore::miner::MinerService;
 use ethcore::client::TestBlockChainClient;
 use ethsync::ManageNetwork;
+use ethcore_logger::RotatingLogger;
 
 use jsonrpc_core::IoHandler;
 use v1::{ParitySet, ParitySetClient};
@@ -44,10 +45,14 @@ fn updater_service() -> Arc<TestUpdater> {
 	Arc::new(TestUpdater::default())
 }
 
+fn logger_service() -> Arc<RotatingLogger> {
+	Arc::new(RotatingLogger::new("rpc=trace".to_owned()))
+}
+
 pub type TestParitySetClient = ParitySetClient<TestBlockChainClient, TestMinerService, TestUpdater, TestFetch>;
 
 fn parity_set_client(client: &Arc<TestBlockChainClient>, miner: &Arc<TestMinerService>, updater: &Arc<TestUpdater>, net: &Arc<TestManageNetwork>) -> TestParitySetClient {
-	ParitySetClient::new(client, miner, updater, &(net.clone() as Arc<ManageNetwork>), TestFetch::default())
+	ParitySetClient::new(client, miner, updater, &(net.clone() as Arc<ManageNetwork>), logger_service(), TestFetch::default())
 }
 
 #[test]
@@ -139,6 +144,28 @@ fn rpc_parity_set_extra_data() {
 	assert_eq!(miner.extra_data(), "cd1722f3947def4cf144679da39c4c32bdc35681".from_hex().unwrap());
 }
 
+#[test]
+fn rpc_parity_set_log_levels() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let logger = logger_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(ParitySetClient::new(&client, &miner, &updater, &(network as Arc<ManageNetwork>), logger.clone(), TestFetch::default()).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setLogLevels", "params":["sync=debug,rpc=info"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(logger.levels(), "sync=debug,rpc=info");
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setLogLevels", "params":["sync=loud"], "id": 2}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains("\"error\""));
+	assert_eq!(logger.levels(), "sync=debug,rpc=info");
+}
+
 #[test]
 fn rpc_parity_set_author() {
 	let miner = miner_service();
@@ -24,11 +24,11 @@ use futures::BoxFuture;
 
 use v1::types::{
 	H160, H256, H512, U256, Bytes,
-	Peers, Transaction, RpcSettings, Histogram,
+	Peers, Transaction, RpcSettings, Histogram, GasPriceRecommendations,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, RichHeader,
+	OperationsInfo, DappId, ChainStatus, MaliciousEvidence, StratumWorker,
+	AccountInfo, HwAccountInfo, RichHeader, SyncDiagnostics,
 };
 
 build_rpc_trait! {
@@ -104,6 +104,10 @@ build_rpc_trait! {
 		#[rpc(async, name = "parity_gasPriceHistogram")]
 		fn gas_price_histogram(&self) -> BoxFuture<Histogram, Error>;
 
+		/// Returns fast, standard and slow gas price recommendations based on recent blocks.
+		#[rpc(name = "parity_gasPriceRecommendations")]
+		fn gas_price_recommendations(&self) -> Result<GasPriceRecommendations, Error>;
+
 		/// Returns number of unsigned transactions waiting in the signer queue (if signer enabled)
 		/// Returns error when signer is disabled
 		#[rpc(name = "parity_unsignedTransactionsCount")]
@@ -195,10 +199,22 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
 
+		/// Get detailed block sync diagnostics: downloader state, per-peer requests and recent errors.
+		#[rpc(name = "parity_syncDiagnostics")]
+		fn sync_diagnostics(&self) -> Result<SyncDiagnostics, Error>;
+
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind, Error>;
 
+		/// Get evidence of validator misbehaviour detected by the consensus engine.
+		#[rpc(name = "parity_maliciousEvidence")]
+		fn malicious_evidence(&self) -> Result<Vec<MaliciousEvidence>, Error>;
+
+		/// Returns share and hashrate statistics of workers connected to the stratum server.
+		#[rpc(name = "parity_stratumWorkers")]
+		fn stratum_workers(&self) -> Result<BTreeMap<String, StratumWorker>, Error>;
+
 		/// Get block header.
 		/// Same as `eth_getBlockByNumber` but without uncles and transactions.
 		#[rpc(async, name = "parity_getBlockHeaderByNumber")]
@@ -88,6 +88,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setMode")]
 		fn set_mode(&self, String) -> Result<bool, Error>;
 
+		/// Set logging levels at runtime. Argument has the same format as `--logging` (e.g. "sync=trace,rpc=debug").
+		#[rpc(name = "parity_setLogLevels")]
+		fn set_log_levels(&self, String) -> Result<bool, Error>;
+
 		/// Set the network spec. Argument must be one of: "foundation", "ropsten", "morden", "kovan", "olympic", "classic", "dev", "expanse" or a filename.
 		#[rpc(name = "parity_setChain")]
 		fn set_spec_name(&self, String) -> Result<bool, Error>;
@@ -0,0 +1,34 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Private transactions rpc interface.
+
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use v1::types::{Bytes, CallRequest, BlockNumber, PrivateTransactionReceipt};
+
+build_rpc_trait! {
+	/// Private transaction management RPC interface.
+	pub trait Private {
+		/// Sends a signed private transaction to the validators of its contract.
+		#[rpc(name = "private_sendTransaction")]
+		fn send_transaction(&self, Bytes) -> Result<PrivateTransactionReceipt, Error>;
+
+		/// Executes a call against the decrypted state of a private contract, returning the output data.
+		#[rpc(name = "private_call")]
+		fn private_call(&self, CallRequest, Trailing<BlockNumber>) -> Result<Bytes, Error>;
+	}
+}
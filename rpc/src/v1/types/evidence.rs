@@ -0,0 +1,59 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validator misbehaviour evidence.
+
+use ethcore::engines::MaliciousEvidence as EthMaliciousEvidence;
+use v1::types::{Bytes, H160, U256};
+
+/// Proof of a validator signing conflicting blocks or consensus messages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaliciousEvidence {
+	/// Misbehaving validator.
+	pub validator: H160,
+	/// Block number at which the misbehaviour happened.
+	#[serde(rename="blockNumber")]
+	pub block_number: U256,
+	/// RLP-encoded proof of misbehaviour.
+	pub proof: Bytes,
+}
+
+impl From<EthMaliciousEvidence> for MaliciousEvidence {
+	fn from(e: EthMaliciousEvidence) -> Self {
+		MaliciousEvidence {
+			validator: e.validator.into(),
+			block_number: e.block_number.into(),
+			proof: e.proof.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::MaliciousEvidence;
+
+	#[test]
+	fn evidence_serialization() {
+		let evidence = MaliciousEvidence {
+			validator: 5.into(),
+			block_number: 10.into(),
+			proof: vec![0xc0].into(),
+		};
+		let serialized = serde_json::to_string(&evidence).unwrap();
+		assert_eq!(serialized, r#"{"validator":"0x0000000000000000000000000000000000000005","blockNumber":"0xa","proof":"0xc0"}"#);
+	}
+}
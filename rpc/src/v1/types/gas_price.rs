@@ -0,0 +1,64 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price recommendations.
+
+use ethcore::miner;
+use v1::types::U256;
+
+/// A single gas price recommendation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GasPriceRecommendation {
+	/// Recommended gas price.
+	#[serde(rename="gasPrice")]
+	pub gas_price: U256,
+	/// Expected time until inclusion in seconds.
+	#[serde(rename="expectedWait")]
+	pub expected_wait: Option<u64>,
+}
+
+impl From<miner::GasPriceRecommendation> for GasPriceRecommendation {
+	fn from(r: miner::GasPriceRecommendation) -> Self {
+		GasPriceRecommendation {
+			gas_price: r.gas_price.into(),
+			expected_wait: r.expected_wait,
+		}
+	}
+}
+
+/// Gas price recommendations for different inclusion speeds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GasPriceRecommendations {
+	/// Price likely to be included in the next few blocks.
+	pub fast: GasPriceRecommendation,
+	/// Price paid for the bulk of recently used gas.
+	pub standard: GasPriceRecommendation,
+	/// Cheap price that may take a while to be included.
+	pub slow: GasPriceRecommendation,
+	/// Number of blocks the recommendations are based on.
+	pub blocks: u64,
+}
+
+impl From<miner::GasPriceRecommendations> for GasPriceRecommendations {
+	fn from(r: miner::GasPriceRecommendations) -> Self {
+		GasPriceRecommendations {
+			fast: r.fast.into(),
+			standard: r.standard.into(),
+			slow: r.slow.into(),
+			blocks: r.blocks as u64,
+		}
+	}
+}
@@ -25,15 +25,19 @@ mod call_request;
 mod confirmations;
 mod consensus_status;
 mod derivation;
+mod evidence;
 mod filter;
+mod gas_price;
 mod hash;
 mod histogram;
 mod index;
 mod log;
 mod node_kind;
+mod private_receipt;
 mod provenance;
 mod receipt;
 mod rpc_settings;
+mod stratum;
 mod sync;
 mod trace;
 mod trace_filter;
@@ -54,18 +58,23 @@ pub use self::confirmations::{
 };
 pub use self::consensus_status::*;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::evidence::MaliciousEvidence;
 pub use self::filter::{Filter, FilterChanges};
+pub use self::gas_price::{GasPriceRecommendation, GasPriceRecommendations};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::private_receipt::PrivateTransactionReceipt;
 pub use self::provenance::{Origin, DappId};
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
+pub use self::stratum::StratumWorker;
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	SyncDiagnostics, DownloaderDiagnostics, PeerSyncInfo, SyncError,
 };
 pub use self::trace::{LocalizedTrace, TraceResults};
 pub use self::trace_filter::TraceFilter;
@@ -0,0 +1,70 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stratum worker statistics.
+
+use ethcore::miner::StratumWorkerStats;
+use v1::types::U256;
+
+/// Share and hashrate accounting of a single stratum worker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StratumWorker {
+	/// Hashrate last reported by the worker.
+	pub hashrate: U256,
+	/// Number of accepted shares.
+	#[serde(rename="acceptedShares")]
+	pub accepted_shares: u64,
+	/// Number of shares submitted for out-of-date work.
+	#[serde(rename="staleShares")]
+	pub stale_shares: u64,
+	/// Number of invalid shares.
+	#[serde(rename="rejectedShares")]
+	pub rejected_shares: u64,
+	/// Unix timestamp of the last submitted share.
+	#[serde(rename="lastShare")]
+	pub last_share: Option<u64>,
+}
+
+impl From<StratumWorkerStats> for StratumWorker {
+	fn from(s: StratumWorkerStats) -> Self {
+		StratumWorker {
+			hashrate: s.hashrate.into(),
+			accepted_shares: s.accepted,
+			stale_shares: s.stale,
+			rejected_shares: s.rejected,
+			last_share: s.last_share,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::StratumWorker;
+
+	#[test]
+	fn stratum_worker_serialization() {
+		let worker = StratumWorker {
+			hashrate: 0x500000.into(),
+			accepted_shares: 3,
+			stale_shares: 1,
+			rejected_shares: 0,
+			last_share: Some(1500000000),
+		};
+		let serialized = serde_json::to_string(&worker).unwrap();
+		assert_eq!(serialized, r#"{"hashrate":"0x500000","acceptedShares":3,"staleShares":1,"rejectedShares":0,"lastShare":1500000000}"#);
+	}
+}
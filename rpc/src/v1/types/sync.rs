@@ -17,7 +17,7 @@
 use std::collections::BTreeMap;
 use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
 use serde::{Serialize, Serializer};
-use v1::types::{U256, H512};
+use v1::types::{U256, H256, H512};
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -63,6 +63,8 @@ pub struct PeerInfo {
 	pub caps: Vec<String>,
 	/// Network information
 	pub network: PeerNetworkInfo,
+	/// Reputation score
+	pub reputation: i32,
 	/// Protocols information
 	pub protocols: PeerProtocolsInfo,
 }
@@ -154,9 +156,15 @@ pub struct TransactionStats {
 	/// Block no this transaction was first seen.
 	#[serde(rename="firstSeen")]
 	pub first_seen: u64,
+	/// Peer this transaction was first received from.
+	#[serde(rename="receivedFrom")]
+	pub received_from: Option<H512>,
 	/// Peers this transaction was propagated to with count.
 	#[serde(rename="propagatedTo")]
 	pub propagated_to: BTreeMap<H512, usize>,
+	/// Peers this transaction hash was announced to with count.
+	#[serde(rename="announcedTo")]
+	pub announced_to: BTreeMap<H512, usize>,
 }
 
 impl From<SyncPeerInfo> for PeerInfo {
@@ -169,6 +177,7 @@ impl From<SyncPeerInfo> for PeerInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
 			},
+			reputation: p.reputation,
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
 				pip: p.pip_info.map(Into::into),
@@ -181,10 +190,191 @@ impl From<SyncTransactionStats> for TransactionStats {
 	fn from(s: SyncTransactionStats) -> Self {
 		TransactionStats {
 			first_seen: s.first_seen,
+			received_from: s.received_from.map(Into::into),
 			propagated_to: s.propagated_to
 				.into_iter()
 				.map(|(id, count)| (id.into(), count))
 				.collect(),
+			announced_to: s.announced_to
+				.into_iter()
+				.map(|(id, count)| (id.into(), count))
+				.collect(),
+		}
+	}
+}
+
+/// Block downloader state.
+#[derive(Default, Debug, Serialize)]
+pub struct DownloaderDiagnostics {
+	/// Downloader state
+	pub state: String,
+	/// Last imported block number
+	#[serde(rename="lastImportedBlockNumber")]
+	pub last_imported_block_number: U256,
+	/// Last imported block hash
+	#[serde(rename="lastImportedBlockHash")]
+	pub last_imported_block_hash: H256,
+	/// Highest block number seen
+	#[serde(rename="highestBlockNumber")]
+	pub highest_block_number: Option<U256>,
+	/// Block the download stops at
+	#[serde(rename="targetHash")]
+	pub target_hash: Option<H256>,
+	/// First block number of the current round
+	#[serde(rename="roundStart")]
+	pub round_start: U256,
+	/// Blocks imported in the current round
+	#[serde(rename="importedThisRound")]
+	pub imported_this_round: Option<usize>,
+	/// Number of blocks to step back when searching for a common block
+	#[serde(rename="retractStep")]
+	pub retract_step: u64,
+	/// Subchain heads waiting to be downloaded
+	#[serde(rename="subchainHeads")]
+	pub subchain_heads: usize,
+	/// Downloaded blocks waiting to be imported
+	#[serde(rename="downloadedBlocks")]
+	pub downloaded_blocks: usize,
+	/// Headers being downloaded
+	#[serde(rename="downloadingHeaders")]
+	pub downloading_headers: usize,
+	/// Block bodies being downloaded
+	#[serde(rename="downloadingBodies")]
+	pub downloading_bodies: usize,
+	/// Block receipts being downloaded
+	#[serde(rename="downloadingReceipts")]
+	pub downloading_receipts: usize,
+}
+
+impl From<ethsync::DownloaderDiagnostics> for DownloaderDiagnostics {
+	fn from(d: ethsync::DownloaderDiagnostics) -> Self {
+		DownloaderDiagnostics {
+			state: d.state,
+			last_imported_block_number: d.last_imported_block_number.into(),
+			last_imported_block_hash: d.last_imported_block_hash.into(),
+			highest_block_number: d.highest_block_number.map(Into::into),
+			target_hash: d.target_hash.map(Into::into),
+			round_start: d.round_start.into(),
+			imported_this_round: d.imported_this_round,
+			retract_step: d.retract_step,
+			subchain_heads: d.subchain_heads,
+			downloaded_blocks: d.downloaded_blocks,
+			downloading_headers: d.downloading_headers,
+			downloading_bodies: d.downloading_bodies,
+			downloading_receipts: d.downloading_receipts,
+		}
+	}
+}
+
+/// Sync state of a connected peer.
+#[derive(Default, Debug, Serialize)]
+pub struct PeerSyncInfo {
+	/// Peer id
+	pub id: usize,
+	/// Protocol version
+	#[serde(rename="protocolVersion")]
+	pub protocol_version: u8,
+	/// Peer total difficulty if known
+	pub difficulty: Option<U256>,
+	/// Peer best block hash
+	#[serde(rename="latestHash")]
+	pub latest_hash: H256,
+	/// Type of data being requested from peer
+	pub asking: String,
+	/// Number of blocks being requested
+	#[serde(rename="askingBlocks")]
+	pub asking_blocks: usize,
+	/// Seconds since the pending request was sent
+	#[serde(rename="askingFor")]
+	pub asking_for: Option<u64>,
+	/// Block set being downloaded from peer
+	#[serde(rename="blockSet")]
+	pub block_set: Option<String>,
+	/// Pending request is expired
+	pub expired: bool,
+	/// Peer is active in the current sync round
+	pub active: bool,
+	/// Peer fork is confirmed
+	pub confirmed: bool,
+}
+
+impl From<ethsync::PeerSyncInfo> for PeerSyncInfo {
+	fn from(p: ethsync::PeerSyncInfo) -> Self {
+		PeerSyncInfo {
+			id: p.id,
+			protocol_version: p.protocol_version,
+			difficulty: p.difficulty.map(Into::into),
+			latest_hash: p.latest_hash.into(),
+			asking: p.asking,
+			asking_blocks: p.asking_blocks,
+			asking_for: p.asking_for,
+			block_set: p.block_set,
+			expired: p.expired,
+			active: p.active,
+			confirmed: p.confirmed,
+		}
+	}
+}
+
+/// Recorded sync error.
+#[derive(Default, Debug, Serialize)]
+pub struct SyncError {
+	/// Unix timestamp in seconds
+	pub timestamp: u64,
+	/// Peer that caused the error
+	pub peer: Option<usize>,
+	/// Error description
+	pub message: String,
+}
+
+impl From<ethsync::SyncError> for SyncError {
+	fn from(e: ethsync::SyncError) -> Self {
+		SyncError {
+			timestamp: e.timestamp,
+			peer: e.peer,
+			message: e.message,
+		}
+	}
+}
+
+/// Sync diagnostics.
+#[derive(Default, Debug, Serialize)]
+pub struct SyncDiagnostics {
+	/// Sync state
+	pub state: String,
+	/// Best block number when sync last made progress
+	#[serde(rename="lastProgressBlockNumber")]
+	pub last_progress_block_number: U256,
+	/// Seconds since the best block last advanced
+	#[serde(rename="lastProgressAge")]
+	pub last_progress_age: u64,
+	/// Number of sync restarts after stalling
+	#[serde(rename="stallRecoveries")]
+	pub stall_recoveries: usize,
+	/// New blocks downloader state
+	#[serde(rename="newBlocks")]
+	pub new_blocks: DownloaderDiagnostics,
+	/// Ancient blocks downloader state
+	#[serde(rename="oldBlocks")]
+	pub old_blocks: Option<DownloaderDiagnostics>,
+	/// Connected peers
+	pub peers: Vec<PeerSyncInfo>,
+	/// Recent errors, oldest first
+	#[serde(rename="recentErrors")]
+	pub recent_errors: Vec<SyncError>,
+}
+
+impl From<ethsync::SyncDiagnostics> for SyncDiagnostics {
+	fn from(d: ethsync::SyncDiagnostics) -> Self {
+		SyncDiagnostics {
+			state: format!("{:?}", d.state),
+			last_progress_block_number: d.last_progress_block_number.into(),
+			last_progress_age: d.last_progress_age,
+			stall_recoveries: d.stall_recoveries,
+			new_blocks: d.new_blocks.into(),
+			old_blocks: d.old_blocks.map(Into::into),
+			peers: d.peers.into_iter().map(Into::into).collect(),
+			recent_errors: d.recent_errors.into_iter().map(Into::into).collect(),
 		}
 	}
 }
@@ -201,7 +391,7 @@ pub struct ChainStatus {
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, SyncDiagnostics, SyncError};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -244,12 +434,26 @@ mod tests {
 	fn test_serialize_transaction_stats() {
 		let stats = TransactionStats {
 			first_seen: 100,
+			received_from: None,
 			propagated_to: map![
 				10.into() => 50
 			],
+			announced_to: map![
+				11.into() => 1
+			],
 		};
 
 		let serialized = serde_json::to_string(&stats).unwrap();
-		assert_eq!(serialized, r#"{"firstSeen":100,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
+		assert_eq!(serialized, r#"{"firstSeen":100,"receivedFrom":null,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50},"announcedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000b":1}}"#)
+	}
+
+	#[test]
+	fn test_serialize_sync_diagnostics() {
+		let mut t = SyncDiagnostics::default();
+		t.state = "Blocks".into();
+		t.recent_errors.push(SyncError { timestamp: 1500000000, peer: Some(3), message: "Bad block".into() });
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"state":"Blocks","lastProgressBlockNumber":"0x0","lastProgressAge":0,"stallRecoveries":0,"newBlocks":{"state":"","lastImportedBlockNumber":"0x0","lastImportedBlockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","highestBlockNumber":null,"targetHash":null,"roundStart":"0x0","importedThisRound":null,"retractStep":0,"subchainHeads":0,"downloadedBlocks":0,"downloadingHeaders":0,"downloadingBodies":0,"downloadingReceipts":0},"oldBlocks":null,"peers":[],"recentErrors":[{"timestamp":1500000000,"peer":3,"message":"Bad block"}]}"#);
 	}
 }
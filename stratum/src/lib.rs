@@ -54,11 +54,16 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
-use util::{H256, Hashable, RwLock, RwLockReadGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use util::{H256, U256, Hashable, RwLock, RwLockReadGuard, clean_0x};
 
 type RpcResult = BoxFuture<jsonrpc_core::Value, jsonrpc_core::Error>;
 
 const NOTIFY_COUNTER_INITIAL: u32 = 16;
+/// Protocol announced by NiceHash-compatible miners in `mining.subscribe`.
+const ETHEREUM_STRATUM_VERSION: &'static str = "EthereumStratum/1.0.0";
+/// Length of a full 8-byte nonce in hex characters.
+const NONCE_HEX_LENGTH: usize = 16;
 
 struct StratumRpc {
 	stratum: RwLock<Option<Arc<Stratum>>>,
@@ -79,6 +84,31 @@ impl StratumRpc {
 		self.stratum.read().as_ref().expect("RPC methods are called after stratum is set.")
 			.submit(params, meta)
 	}
+
+	fn extranonce_subscribe(&self, params: Params, meta: SocketMetadata) -> RpcResult {
+		self.stratum.read().as_ref().expect("RPC methods are called after stratum is set.")
+			.extranonce_subscribe(params, meta)
+	}
+
+	fn submit_hashrate(&self, params: Params, meta: SocketMetadata) -> RpcResult {
+		self.stratum.read().as_ref().expect("RPC methods are called after stratum is set.")
+			.submit_hashrate(params, meta)
+	}
+}
+
+/// Share and hashrate accounting of a single worker.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerStats {
+	/// Hashrate last reported by the worker (hashes per second)
+	pub hashrate: U256,
+	/// Number of accepted shares
+	pub accepted: u64,
+	/// Number of shares submitted for out-of-date work
+	pub stale: u64,
+	/// Number of invalid shares
+	pub rejected: u64,
+	/// Unix timestamp of the last submitted share
+	pub last_share: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -124,6 +154,12 @@ pub struct Stratum {
 	dispatcher: Arc<JobDispatcher>,
 	/// Authorized workers (socket - worker_id)
 	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Subscribers speaking EthereumStratum/1.0.0 (socket - extranonce)
+	extranonces: RwLock<HashMap<SocketAddr, String>>,
+	/// Next extranonce to assign
+	extranonce_counter: RwLock<u16>,
+	/// Per-worker statistics (worker_id - stats)
+	stats: RwLock<HashMap<String, WorkerStats>>,
 	/// Secret if any
 	secret: Option<H256>,
 	/// Dispatch notify couinter
@@ -152,6 +188,8 @@ impl Stratum {
 		delegate.add_method_with_meta("mining.subscribe", StratumRpc::subscribe);
 		delegate.add_method_with_meta("mining.authorize", StratumRpc::authorize);
 		delegate.add_method_with_meta("mining.submit", StratumRpc::submit);
+		delegate.add_method_with_meta("mining.extranonce.subscribe", StratumRpc::extranonce_subscribe);
+		delegate.add_method_with_meta("eth_submitHashrate", StratumRpc::submit_hashrate);
 		let mut handler = MetaIoHandler::<SocketMetadata>::with_compatibility(Compatibility::Both);
 		handler.extend_with(delegate);
 
@@ -167,6 +205,9 @@ impl Stratum {
 			job_que: RwLock::new(HashSet::new()),
 			dispatcher: dispatcher,
 			workers: Arc::new(RwLock::new(HashMap::new())),
+			extranonces: RwLock::new(HashMap::new()),
+			extranonce_counter: RwLock::new(0),
+			stats: RwLock::new(HashMap::new()),
 			secret: secret,
 			notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
 		});
@@ -182,22 +223,35 @@ impl Stratum {
 		}
 	}
 
-	fn submit(&self, params: Params, _meta: SocketMetadata) -> RpcResult {
+	fn submit(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		future::ok(match params {
 			Params::Array(vals) => {
-				// first two elements are service messages (worker_id & job_id)
-				match self.dispatcher.submit(vals.iter().skip(2)
-					.filter_map(|val| match val { &Value::String(ref str) => Some(str.to_owned()), _ => None })
-					.collect::<Vec<String>>()) {
-						Ok(()) => {
-							self.update_peers();
-							to_value(true)
-						},
-						Err(submit_err) => {
-							warn!("Error while submitting share: {:?}", submit_err);
-							to_value(false)
-						}
+				let worker_id = self.workers.read().get(meta.addr()).cloned();
+				let payload = match self.extranonces.read().get(meta.addr()) {
+					// worker_id, job_id & nonce without the extranonce prefix
+					Some(extranonce) => Self::ethereum_stratum_payload(extranonce, &vals),
+					// first two elements are service messages (worker_id & job_id)
+					None => Some(vals.iter().skip(2)
+						.filter_map(|val| match val { &Value::String(ref str) => Some(str.to_owned()), _ => None })
+						.collect::<Vec<String>>()),
+				};
+				let result = match payload {
+					Some(payload) => self.dispatcher.submit(payload),
+					None => Err(Error::Dispatch(format!("Malformed share: {:?}", vals))),
+				};
+				if let Some(worker_id) = worker_id {
+					self.account_share(worker_id, &result);
+				}
+				match result {
+					Ok(()) => {
+						self.update_peers();
+						to_value(true)
+					},
+					Err(submit_err) => {
+						warn!("Error while submitting share: {:?}", submit_err);
+						to_value(false)
 					}
+				}
 			},
 			_ => {
 				trace!(target: "stratum", "Invalid submit work format {:?}", params);
@@ -206,12 +260,73 @@ impl Stratum {
 		}.expect("Only true/false is returned and it's always serializable; qed")).boxed()
 	}
 
-	fn subscribe(&self, _params: Params, meta: SocketMetadata) -> RpcResult {
+	/// Converts EthereumStratum/1.0.0 submit params into the `[nonce, pow_hash]` dispatcher payload.
+	fn ethereum_stratum_payload(extranonce: &str, vals: &[Value]) -> Option<Vec<String>> {
+		match (vals.get(1), vals.get(2)) {
+			(Some(&Value::String(ref job_id)), Some(&Value::String(ref nonce))) => {
+				let nonce = format!("{}{}", extranonce, clean_0x(nonce));
+				if nonce.len() != NONCE_HEX_LENGTH {
+					return None;
+				}
+				Some(vec![format!("0x{}", nonce), format!("0x{}", clean_0x(job_id))])
+			},
+			_ => None,
+		}
+	}
+
+	fn account_share(&self, worker_id: String, result: &Result<(), Error>) {
+		let mut stats = self.stats.write();
+		let stats = stats.entry(worker_id).or_insert_with(WorkerStats::default);
+		match *result {
+			Ok(()) => stats.accepted += 1,
+			Err(Error::Stale) => stats.stale += 1,
+			Err(_) => stats.rejected += 1,
+		}
+		stats.last_share = SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+	}
+
+	fn subscribe(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		use std::str::FromStr;
 
+		trace!(target: "stratum", "Subscription request from {:?}", meta.addr());
+
+		if Self::is_ethereum_stratum(&params) {
+			let extranonce = {
+				let mut extranonces = self.extranonces.write();
+				// release extranonces of a previous session and of disconnected peers
+				extranonces.remove(meta.addr());
+				extranonces.retain(|addr, _| self.tcp_dispatcher.is_connected(addr));
+				match Self::next_extranonce(&mut *self.extranonce_counter.write(), &extranonces) {
+					Some(extranonce) => {
+						extranonces.insert(meta.addr().clone(), extranonce.clone());
+						extranonce
+					},
+					None => {
+						warn!(target: "stratum", "Refusing {} subscription from {}: all extranonces are in use", ETHEREUM_STRATUM_VERSION, meta.addr());
+						return future::err(jsonrpc_core::Error {
+							code: jsonrpc_core::ErrorCode::InternalError,
+							message: "All extranonces are in use".into(),
+							data: None,
+						}).boxed();
+					},
+				}
+			};
+			self.subscribers.write().push(meta.addr().clone());
+			// work is pushed once the worker is authorized
+			let session_id = meta.addr().to_string().sha3().hex()[..32].to_owned();
+			trace!(target: "stratum", "{} session {} with extranonce {}", ETHEREUM_STRATUM_VERSION, session_id, extranonce);
+			return future::ok(Value::Array(vec![
+				Value::Array(vec![
+					Value::String("mining.notify".into()),
+					Value::String(session_id),
+					Value::String(ETHEREUM_STRATUM_VERSION.into()),
+				]),
+				Value::String(extranonce),
+			])).boxed();
+		}
+
 		self.subscribers.write().push(meta.addr().clone());
 		self.job_que.write().insert(meta.addr().clone());
-		trace!(target: "stratum", "Subscription request from {:?}", meta.addr());
 
 		future::ok(match self.dispatcher.initial() {
 			Some(initial) => match jsonrpc_core::Value::from_str(&initial) {
@@ -234,15 +349,88 @@ impl Stratum {
 				}
 			}
 			trace!(target: "stratum", "New worker #{} registered", worker_id);
+			self.stats.write().entry(worker_id.clone()).or_insert_with(WorkerStats::default);
 			self.workers.write().insert(meta.addr().clone(), worker_id);
+			if self.extranonces.read().contains_key(meta.addr()) {
+				for message in self.ethereum_stratum_messages() {
+					if let Err(e) = self.tcp_dispatcher.push_message(meta.addr(), message) {
+						warn!(target: "stratum", "Failed to push work to {}: {:?}", meta.addr(), e);
+					}
+				}
+			}
 			to_value(true)
 		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))).boxed()
 	}
 
+	fn extranonce_subscribe(&self, _params: Params, meta: SocketMetadata) -> RpcResult {
+		// extranonce is fixed for the lifetime of the session, so no `mining.set_extranonce` will follow
+		trace!(target: "stratum", "Extranonce subscription from {:?}", meta.addr());
+		future::ok(to_value(true).expect("Only true/false is returned and it's always serializable; qed")).boxed()
+	}
+
+	fn submit_hashrate(&self, params: Params, meta: SocketMetadata) -> RpcResult {
+		use std::str::FromStr;
+
+		future::result(params.parse::<(String, String)>().map(|(rate, _id)| {
+			let hashrate = match U256::from_str(clean_0x(&rate)) {
+				Ok(hashrate) => hashrate,
+				Err(_) => return to_value(false),
+			};
+			match self.workers.read().get(meta.addr()) {
+				Some(worker_id) => {
+					trace!(target: "stratum", "Worker #{} reported hashrate {}", worker_id, hashrate);
+					self.stats.write().entry(worker_id.clone()).or_insert_with(WorkerStats::default).hashrate = hashrate;
+					to_value(true)
+				},
+				None => to_value(false),
+			}
+		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))).boxed()
+	}
+
+	fn is_ethereum_stratum(params: &Params) -> bool {
+		match *params {
+			Params::Array(ref vals) => vals.iter().any(|val| match *val {
+				Value::String(ref protocol) => protocol.starts_with("EthereumStratum/"),
+				_ => false,
+			}),
+			_ => false,
+		}
+	}
+
+	/// Next extranonce not held by any session, or `None` if all of them are taken.
+	fn next_extranonce(counter: &mut u16, extranonces: &HashMap<SocketAddr, String>) -> Option<String> {
+		let used: HashSet<&String> = extranonces.values().collect();
+		for _ in 0..(::std::u16::MAX as usize + 1) {
+			let extranonce = format!("{:04x}", *counter);
+			*counter = counter.wrapping_add(1);
+			if !used.contains(&extranonce) {
+				return Some(extranonce);
+			}
+		}
+		None
+	}
+
+	/// `mining.set_difficulty` and `mining.notify` messages for EthereumStratum/1.0.0 workers.
+	fn ethereum_stratum_messages(&self) -> Vec<String> {
+		let mut messages = Vec::with_capacity(2);
+		if let Some(difficulty) = self.dispatcher.difficulty() {
+			messages.push(format!("{{ \"id\": null, \"method\": \"mining.set_difficulty\", \"params\": {} }}", difficulty));
+		}
+		if let Some(job) = self.dispatcher.ethereum_stratum_job() {
+			messages.push(format!("{{ \"id\": null, \"method\": \"mining.notify\", \"params\": {} }}", job));
+		}
+		messages
+	}
+
 	pub fn subscribers(&self) -> RwLockReadGuard<Vec<SocketAddr>> {
 		self.subscribers.read()
 	}
 
+	/// Statistics of all workers that have been authorized, keyed by worker id.
+	pub fn worker_stats(&self) -> HashMap<String, WorkerStats> {
+		self.stats.read().clone()
+	}
+
 	pub fn maintain(&self) {
 		let mut job_que = self.job_que.write();
 		let job_payload = self.dispatcher.job();
@@ -266,19 +454,25 @@ impl PushWorkHandler for Stratum {
 			};
 
 			let mut hup_peers = HashSet::with_capacity(0); // most of the cases won't be needed, hence avoid allocation
-			let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
-			trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
+			let workers_msg = vec![format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload)];
+			let extranonces = self.extranonces.read();
+			let ethereum_stratum_msgs = if extranonces.is_empty() { Vec::new() } else { self.ethereum_stratum_messages() };
+			trace!(target: "stratum", "pushing work for {} workers (payload: '{:?}')", workers.len(), &workers_msg);
 			for (ref addr, _) in workers.iter() {
 				trace!(target: "stratum", "pusing work to {}", addr);
-				match self.tcp_dispatcher.push_message(addr, workers_msg.clone()) {
-					Err(PushMessageError::NoSuchPeer) => {
-						trace!(target: "stratum", "Worker no longer connected: {}", &addr);
-						hup_peers.insert(*addr.clone());
-					},
-					Err(e) => {
-						warn!(target: "stratum", "Unexpected transport error: {:?}", e);
-					},
-					Ok(_) => { },
+				let messages = if extranonces.contains_key(*addr) { &ethereum_stratum_msgs } else { &workers_msg };
+				for message in messages {
+					match self.tcp_dispatcher.push_message(addr, message.clone()) {
+						Err(PushMessageError::NoSuchPeer) => {
+							trace!(target: "stratum", "Worker no longer connected: {}", &addr);
+							hup_peers.insert(*addr.clone());
+							break;
+						},
+						Err(e) => {
+							warn!(target: "stratum", "Unexpected transport error: {:?}", e);
+						},
+						Ok(_) => { },
+					}
 				}
 			}
 			hup_peers
@@ -286,7 +480,11 @@ impl PushWorkHandler for Stratum {
 
 		if !hup_peers.is_empty() {
 			let mut workers = self.workers.write();
-			for hup_peer in hup_peers { workers.remove(&hup_peer); }
+			let mut extranonces = self.extranonces.write();
+			for hup_peer in hup_peers {
+				workers.remove(&hup_peer);
+				extranonces.remove(&hup_peer);
+			}
 		}
 
 		Ok(())
@@ -320,8 +518,10 @@ impl PushWorkHandler for Stratum {
 mod tests {
 	use super::*;
 	use std::str::FromStr;
-	use std::net::SocketAddr;
+	use std::net::{SocketAddr, TcpStream as StdTcpStream};
+	use std::io::{BufRead, BufReader, Write};
 	use std::sync::Arc;
+	use util::{Mutex, U256};
 
 	use tokio_core::reactor::{Core, Timeout};
 	use tokio_core::net::TcpStream;
@@ -497,4 +697,178 @@ mod tests {
 			"{ \"id\": 17, \"method\": \"mining.notify\", \"params\": { \"00040008\", \"100500\" } }\n",
 			response);
 	}
+
+	struct ShareManager {
+		result: Mutex<Result<(), Error>>,
+		submitted: Mutex<Vec<Vec<String>>>,
+		difficulty: Option<String>,
+		job: Option<String>,
+	}
+
+	impl ShareManager {
+		fn new() -> ShareManager {
+			ShareManager {
+				result: Mutex::new(Ok(())),
+				submitted: Mutex::new(Vec::new()),
+				difficulty: None,
+				job: None,
+			}
+		}
+	}
+
+	impl JobDispatcher for ShareManager {
+		fn difficulty(&self) -> Option<String> {
+			self.difficulty.clone()
+		}
+
+		fn ethereum_stratum_job(&self) -> Option<String> {
+			self.job.clone()
+		}
+
+		fn submit(&self, payload: Vec<String>) -> Result<(), Error> {
+			self.submitted.lock().push(payload);
+			self.result.lock().clone()
+		}
+	}
+
+	/// Sends requests over a single connection, reading one line after each.
+	fn session_requests(addr: &SocketAddr, requests: &[&str]) -> Vec<String> {
+		let mut stream = StdTcpStream::connect(addr).expect("Stratum should accept connections");
+		let mut reader = BufReader::new(stream.try_clone().expect("Socket should be cloneable"));
+		requests.iter().map(|request| {
+			stream.write_all(request.as_bytes()).expect("Request should be sent");
+			stream.write_all(b"\n").expect("Request should be sent");
+			let mut response = String::new();
+			reader.read_line(&mut response).expect("Response should be received");
+			response
+		}).collect()
+	}
+
+	const ETHEREUM_STRATUM_SUBSCRIBE: &'static str =
+		r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": ["ethminer/0.12.0", "EthereumStratum/1.0.0"], "id": 1}"#;
+
+	#[test]
+	fn assigns_extranonce_to_ethereum_stratum_subscribers() {
+		let addr = SocketAddr::from_str("127.0.0.1:19960").unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+
+		let first = session_requests(&addr, &[ETHEREUM_STRATUM_SUBSCRIBE]);
+		let second = session_requests(&addr, &[ETHEREUM_STRATUM_SUBSCRIBE]);
+
+		assert!(first[0].contains(r#""EthereumStratum/1.0.0"],"0000"]"#), "Unexpected response: {}", first[0]);
+		assert!(second[0].contains(r#""EthereumStratum/1.0.0"],"0001"]"#), "Unexpected response: {}", second[0]);
+		assert_eq!(2, stratum.extranonces.read().len());
+		assert!(stratum.job_que.read().is_empty());
+	}
+
+	#[test]
+	fn skips_taken_extranonces_and_refuses_when_exhausted() {
+		let mut extranonces = HashMap::new();
+		extranonces.insert(SocketAddr::from_str("127.0.0.1:1").unwrap(), "0000".to_owned());
+		let mut counter = ::std::u16::MAX;
+
+		assert_eq!(Some("ffff".to_owned()), Stratum::next_extranonce(&mut counter, &extranonces));
+		assert_eq!(Some("0001".to_owned()), Stratum::next_extranonce(&mut counter, &extranonces));
+
+		for port in 0..(::std::u16::MAX as usize + 1) {
+			let addr = SocketAddr::new("127.0.0.2".parse().unwrap(), port as u16);
+			extranonces.insert(addr, format!("{:04x}", port));
+		}
+		assert_eq!(None, Stratum::next_extranonce(&mut counter, &extranonces));
+	}
+
+	#[test]
+	fn pushes_difficulty_and_job_on_ethereum_stratum_authorization() {
+		let addr = SocketAddr::from_str("127.0.0.1:19965").unwrap();
+		let manager = ShareManager {
+			difficulty: Some("[0.5]".into()),
+			job: Some(r#"["aa", "bb", "cc", true]"#.into()),
+			..ShareManager::new()
+		};
+		let _stratum = Stratum::start(&addr, Arc::new(manager), None).unwrap();
+
+		let mut stream = StdTcpStream::connect(&addr).unwrap();
+		let mut reader = BufReader::new(stream.try_clone().unwrap());
+		stream.write_all(ETHEREUM_STRATUM_SUBSCRIBE.as_bytes()).unwrap();
+		stream.write_all(b"\n").unwrap();
+		let mut line = String::new();
+		reader.read_line(&mut line).unwrap();
+
+		stream.write_all(br#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1.rig1", "x"], "id": 2}"#).unwrap();
+		stream.write_all(b"\n").unwrap();
+		let mut lines = (0..3).map(|_| {
+			let mut line = String::new();
+			reader.read_line(&mut line).unwrap();
+			line
+		}).collect::<Vec<_>>();
+		lines.sort();
+
+		assert_eq!(lines, vec![
+			terminated_str(r#"{ "id": null, "method": "mining.notify", "params": ["aa", "bb", "cc", true] }"#),
+			terminated_str(r#"{ "id": null, "method": "mining.set_difficulty", "params": [0.5] }"#),
+			terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":2}"#),
+		]);
+	}
+
+	#[test]
+	fn accounts_worker_shares() {
+		let addr = SocketAddr::from_str("127.0.0.1:19950").unwrap();
+		let manager = Arc::new(ShareManager::new());
+		let stratum = Stratum::start(&addr, manager.clone(), None).unwrap();
+
+		let job_id = "0x1111111111111111111111111111111111111111111111111111111111111111";
+		let submit = format!(r#"{{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1.rig1", "{}", "000000000001"], "id": 3}}"#, job_id);
+		let responses = session_requests(&addr, &[
+			ETHEREUM_STRATUM_SUBSCRIBE,
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1.rig1", "x"], "id": 2}"#,
+			submit.as_str(),
+		]);
+		assert_eq!(responses[2], terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":3}"#));
+		assert_eq!(*manager.submitted.lock(), vec![vec!["0x0000000000000001".to_owned(), job_id.to_owned()]]);
+
+		*manager.result.lock() = Err(Error::Stale);
+		let responses = session_requests(&addr, &[
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1.rig1", "x"], "id": 2}"#,
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1.rig1", "00", "0x01", "0x02", "0x03"], "id": 4}"#,
+		]);
+		assert_eq!(responses[1], terminated_str(r#"{"jsonrpc":"2.0","result":false,"id":4}"#));
+
+		let stats = stratum.worker_stats();
+		let worker = &stats["miner1.rig1"];
+		assert_eq!(worker.accepted, 1);
+		assert_eq!(worker.stale, 1);
+		assert_eq!(worker.rejected, 0);
+		assert!(worker.last_share.is_some());
+	}
+
+	#[test]
+	fn rejects_ethereum_stratum_share_of_wrong_length() {
+		let addr = SocketAddr::from_str("127.0.0.1:19945").unwrap();
+		let manager = Arc::new(ShareManager::new());
+		let stratum = Stratum::start(&addr, manager.clone(), None).unwrap();
+
+		let responses = session_requests(&addr, &[
+			ETHEREUM_STRATUM_SUBSCRIBE,
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", "x"], "id": 2}"#,
+			r#"{"jsonrpc": "2.0", "method": "mining.submit", "params": ["miner1", "0x11", "0001"], "id": 3}"#,
+		]);
+
+		assert_eq!(responses[2], terminated_str(r#"{"jsonrpc":"2.0","result":false,"id":3}"#));
+		assert!(manager.submitted.lock().is_empty());
+		assert_eq!(stratum.worker_stats()["miner1"].rejected, 1);
+	}
+
+	#[test]
+	fn records_reported_hashrate() {
+		let addr = SocketAddr::from_str("127.0.0.1:19940").unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+
+		let responses = session_requests(&addr, &[
+			r#"{"jsonrpc": "2.0", "method": "mining.authorize", "params": ["miner1", ""], "id": 1}"#,
+			r#"{"jsonrpc": "2.0", "method": "eth_submitHashrate", "params": ["0x500000", "0x59daa26581d0acd1fce254fb7e85952f4c09d0915afd33d3886cd914bc7d283c"], "id": 2}"#,
+		]);
+
+		assert_eq!(responses[1], terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":2}"#));
+		assert_eq!(stratum.worker_stats()["miner1"].hashrate, U256::from(0x500000));
+	}
 }
@@ -25,6 +25,7 @@ use jsonrpc_tcp_server::PushMessageError;
 pub enum Error {
 	NoWork,
 	NoWorkers,
+	Stale,
 	Io(String),
 	Tcp(String),
 	Dispatch(String),
@@ -51,7 +52,9 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self) -> Option<String> { None }
-	// miner job result
+	// json for job update of EthereumStratum/1.0.0 workers (`mining.notify` params)
+	fn ethereum_stratum_job(&self) -> Option<String> { None }
+	// miner job result (`Error::Stale` if the job is out of date)
 	fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
 }
 
@@ -20,7 +20,7 @@ use std::io;
 use util::Bytes;
 use network::{NetworkProtocolHandler, NetworkService, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, NetworkError,
-	AllowIP as NetworkAllowIP};
+	AllowIP as NetworkAllowIP, ConnectionFilter, KEY_CHAIN, KEY_LES, BandwidthConfig, BandwidthLimit};
 use util::{U256, H256, H512};
 use io::{TimerToken};
 use ethcore::ethstore::ethkey::Secret;
@@ -28,15 +28,20 @@ use ethcore::client::{BlockChainClient, ChainNotify};
 use ethcore::snapshot::SnapshotService;
 use ethcore::header::BlockNumber;
 use sync_io::NetSyncIo;
-use chain::{ChainSync, SyncStatus as EthSyncStatus};
+use chain::{ChainSync, SyncStatus as EthSyncStatus, SyncDiagnostics};
 use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
 use parking_lot::RwLock;
+use rlp;
 use chain::{ETH_PACKET_COUNT, SNAPSHOT_SYNC_PACKET_COUNT};
 use light::client::AsLightClient;
 use light::Provider;
+use metrics::{Collector, Metric, MetricType};
+use light::net::request_credits::FlowParams;
 use light::net::{self as light_net, LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext};
+use private_tx::{PrivateTxHandler, PrivateTxProtocolHandler, PRIVATE_TX_PACKET_COUNT, PRIVATE_TX_PROTOCOL_VERSIONS,
+	PRIVATE_TRANSACTION_PACKET, SIGNED_PRIVATE_TRANSACTION_PACKET};
 
 /// Parity sync protocol
 pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
@@ -44,6 +49,13 @@ pub const WARP_SYNC_PROTOCOL_ID: ProtocolId = *b"par";
 pub const ETH_PROTOCOL: ProtocolId = *b"eth";
 /// Ethereum light protocol
 pub const LIGHT_PROTOCOL: ProtocolId = *b"pip";
+/// Private transactions protocol
+pub const PRIVATE_TX_PROTOCOL: ProtocolId = *b"ptx";
+
+/// Discovery topic advertised by nodes serving the light protocol for the chain with the given genesis.
+fn light_topic(genesis_hash: &H256) -> String {
+	format!("{}@{}", String::from_utf8_lossy(&LIGHT_PROTOCOL), genesis_hash.hex())
+}
 
 /// Sync configuration
 #[derive(Debug, Clone, Copy)]
@@ -97,6 +109,9 @@ pub trait SyncProvider: Send + Sync {
 
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
+
+	/// Get block downloader and peers state for troubleshooting.
+	fn diagnostics(&self) -> SyncDiagnostics;
 }
 
 /// Transaction stats
@@ -105,8 +120,12 @@ pub trait SyncProvider: Send + Sync {
 pub struct TransactionStats {
 	/// Block number where this TX was first seen.
 	pub first_seen: u64,
+	/// Peer it was first received from, if it was not submitted locally.
+	pub received_from: Option<H512>,
 	/// Peers it was propagated to.
 	pub propagated_to: BTreeMap<H512, usize>,
+	/// Peers it was announced to by hash.
+	pub announced_to: BTreeMap<H512, usize>,
 }
 
 /// Peer connection information
@@ -123,6 +142,8 @@ pub struct PeerInfo {
 	pub remote_address: String,
 	/// Local endpoint address
 	pub local_address: String,
+	/// Reputation score
+	pub reputation: i32,
 	/// Eth protocol info.
 	pub eth_info: Option<EthProtocolInfo>,
 	/// Light protocol info.
@@ -190,18 +211,32 @@ pub struct EthSync {
 	subprotocol_name: [u8; 3],
 	/// Light subprotocol name.
 	light_subprotocol_name: [u8; 3],
+	/// Private transactions protocol handler
+	private_tx_proto: Option<Arc<PrivateTxProtocolHandler>>,
 }
 
 impl EthSync {
 	/// Creates and register protocol with the network service
-	pub fn new(params: Params) -> Result<Arc<EthSync>, NetworkError> {
+	pub fn new(
+		params: Params,
+		connection_filter: Option<Arc<ConnectionFilter>>,
+		private_tx_handler: Option<Arc<PrivateTxHandler>>,
+	) -> Result<Arc<EthSync>, NetworkError> {
 		let pruning_info = params.chain.pruning_info();
+		let genesis_hash = params.chain.chain_info().genesis_hash;
+		let mut network_config = params.network_config.clone().into_basic()?;
+		network_config.node_record.push((KEY_CHAIN.into(), rlp::encode(&genesis_hash).to_vec()));
+
 		let light_proto = match params.config.serve_light {
 			false => None,
 			true => Some({
+				let flow_params = FlowParams::default();
+				network_config.node_record.push((KEY_LES.into(), rlp::encode(flow_params.recharge_rate()).to_vec()));
+				network_config.discovery_topics.push(light_topic(&genesis_hash));
+
 				let light_params = LightParams {
 					network_id: params.config.network_id,
-					flow_params: Default::default(),
+					flow_params: flow_params,
 					capabilities: Capabilities {
 						serve_headers: true,
 						serve_chain_since: Some(pruning_info.earliest_chain),
@@ -218,7 +253,7 @@ impl EthSync {
 		};
 
 		let chain_sync = ChainSync::new(params.config, &*params.chain);
-		let service = NetworkService::new(params.network_config.clone().into_basic()?)?;
+		let service = NetworkService::new(network_config, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
 			network: service,
@@ -231,6 +266,7 @@ impl EthSync {
 			light_proto: light_proto,
 			subprotocol_name: params.config.subprotocol_name,
 			light_subprotocol_name: params.config.light_subprotocol_name,
+			private_tx_proto: private_tx_handler.map(|handler| Arc::new(PrivateTxProtocolHandler::new(handler))),
 		});
 
 		Ok(sync)
@@ -244,6 +280,11 @@ impl SyncProvider for EthSync {
 		self.eth_handler.sync.write().status()
 	}
 
+	/// Get block downloader and peers state
+	fn diagnostics(&self) -> SyncDiagnostics {
+		self.eth_handler.sync.read().diagnostics()
+	}
+
 	/// Get sync peers
 	fn peers(&self) -> Vec<PeerInfo> {
 		self.network.with_context_eval(self.subprotocol_name, |ctx| {
@@ -263,6 +304,7 @@ impl SyncProvider for EthSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					reputation: session_info.reputation,
 					eth_info: eth_sync.peer_info(&peer_id),
 					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(&peer_id)).map(Into::into),
 				})
@@ -327,6 +369,37 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 	}
 }
 
+impl Collector for EthSync {
+	fn collect(&self) -> Vec<Metric> {
+		let peers = self.peers();
+		let eth_peers = peers.iter().filter(|p| p.eth_info.is_some()).count();
+		let pip_peers = peers.iter().filter(|p| p.pip_info.is_some()).count();
+		let status = self.status();
+		let rates = self.network.stats().rates();
+
+		let mut protocol_rates = Metric::new("parity_network_protocol_rate_bytes", "Current subprotocol traffic in bytes per second.", MetricType::Gauge);
+		for &(ref protocol, upload, download) in &rates.protocols {
+			let protocol = String::from_utf8_lossy(&protocol[..]).into_owned();
+			protocol_rates = protocol_rates
+				.with_sample(&[("protocol", protocol.as_str()), ("direction", "upload")], upload as f64)
+				.with_sample(&[("protocol", protocol.as_str()), ("direction", "download")], download as f64);
+		}
+
+		vec![
+			Metric::new("parity_peers", "Connected peers by protocol.", MetricType::Gauge)
+				.with_sample(&[("protocol", "eth")], eth_peers as f64)
+				.with_sample(&[("protocol", "pip")], pip_peers as f64),
+			Metric::gauge("parity_sync_active_peers", "Peers currently used for syncing.", status.num_active_peers as f64),
+			Metric::gauge("parity_sync_highest_block", "Highest block number seen on the network.", status.highest_block_number.unwrap_or(0) as f64),
+			Metric::counter("parity_sync_blocks_received_total", "Blocks received from the network since startup.", status.blocks_received as f64),
+			Metric::new("parity_network_rate_bytes", "Current network traffic in bytes per second.", MetricType::Gauge)
+				.with_sample(&[("direction", "upload")], rates.upload as f64)
+				.with_sample(&[("direction", "download")], rates.download as f64),
+			protocol_rates,
+		]
+	}
+}
+
 impl ChainNotify for EthSync {
 	fn new_blocks(&self,
 		imported: Vec<H256>,
@@ -351,6 +424,11 @@ impl ChainNotify for EthSync {
 				&proposed);
 		});
 
+		// connection permissions may have changed with the new best block.
+		if !enacted.is_empty() {
+			self.network.disconnect_disallowed_peers();
+		}
+
 		self.network.with_context(self.light_subprotocol_name, |context| {
 			let light_proto = match self.light_proto.as_ref() {
 				Some(lp) => lp,
@@ -380,7 +458,7 @@ impl ChainNotify for EthSync {
 		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, ETH_PACKET_COUNT, &[62u8, 63u8])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, SNAPSHOT_SYNC_PACKET_COUNT, &[1u8, 2u8, 3u8, 4u8, 5u8])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
 
 		// register the light protocol.
@@ -388,6 +466,12 @@ impl ChainNotify for EthSync {
 			self.network.register_protocol(light_proto, self.light_subprotocol_name, ::light::net::PACKET_COUNT, ::light::net::PROTOCOL_VERSIONS)
 				.unwrap_or_else(|e| warn!("Error registering light client protocol: {:?}", e));
 		}
+
+		// register the private transactions protocol.
+		if let Some(private_tx_proto) = self.private_tx_proto.as_ref().map(|x| x.clone()) {
+			self.network.register_protocol(private_tx_proto, PRIVATE_TX_PROTOCOL, PRIVATE_TX_PACKET_COUNT, PRIVATE_TX_PROTOCOL_VERSIONS)
+				.unwrap_or_else(|e| warn!("Error registering private transactions protocol: {:?}", e));
+		}
 	}
 
 	fn stop(&self) {
@@ -402,6 +486,22 @@ impl ChainNotify for EthSync {
 		});
 	}
 
+	fn broadcast_private_transaction(&self, message: Vec<u8>) {
+		if let Some(ref private_tx_proto) = self.private_tx_proto {
+			self.network.with_context(PRIVATE_TX_PROTOCOL, |context| {
+				private_tx_proto.propagate(context, PRIVATE_TRANSACTION_PACKET, message, None);
+			});
+		}
+	}
+
+	fn broadcast_signed_private_transaction(&self, message: Vec<u8>) {
+		if let Some(ref private_tx_proto) = self.private_tx_proto {
+			self.network.with_context(PRIVATE_TX_PROTOCOL, |context| {
+				private_tx_proto.propagate(context, SIGNED_PRIVATE_TRANSACTION_PACKET, message, None);
+			});
+		}
+	}
+
 	fn transactions_received(&self, hashes: Vec<H256>, peer_id: PeerId) {
 		let mut sync = self.eth_handler.sync.write();
 		sync.transactions_received(hashes, peer_id);
@@ -541,6 +641,16 @@ pub struct NetworkConfiguration {
 	pub allow_non_reserved: bool,
 	/// IP Filtering
 	pub allow_ips: AllowIP,
+	/// Total upload limit in bytes per second.
+	pub max_upload_rate: Option<u64>,
+	/// Total download limit in bytes per second.
+	pub max_download_rate: Option<u64>,
+	/// Upload limit of each peer in bytes per second.
+	pub max_peer_upload_rate: Option<u64>,
+	/// Download limit of each peer in bytes per second.
+	pub max_peer_download_rate: Option<u64>,
+	/// Subprotocol upload and download limits in bytes per second.
+	pub protocol_rate_limits: Vec<(String, Option<u64>, Option<u64>)>,
 }
 
 impl NetworkConfiguration {
@@ -577,6 +687,21 @@ impl NetworkConfiguration {
 				AllowIP::Public => NetworkAllowIP::Public,
 			},
 			non_reserved_mode: if self.allow_non_reserved { NonReservedPeerMode::Accept } else { NonReservedPeerMode::Deny },
+			discovery_topics: Vec::new(),
+			discovery_searches: Vec::new(),
+			node_record: Vec::new(),
+			bandwidth: BandwidthConfig {
+				total: BandwidthLimit { upload: self.max_upload_rate, download: self.max_download_rate },
+				peer: BandwidthLimit { upload: self.max_peer_upload_rate, download: self.max_peer_download_rate },
+				protocols: self.protocol_rate_limits.into_iter()
+					.filter(|&(ref name, _, _)| name.len() == 3)
+					.map(|(name, upload, download)| {
+						let mut protocol = [0u8; 3];
+						protocol.copy_from_slice(name.as_bytes());
+						(protocol, BandwidthLimit { upload: upload, download: download })
+					})
+					.collect(),
+			},
 		})
 	}
 }
@@ -604,6 +729,13 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 				NetworkAllowIP::Public => AllowIP::Public,
 			},
 			allow_non_reserved: match other.non_reserved_mode { NonReservedPeerMode::Accept => true, _ => false } ,
+			max_upload_rate: other.bandwidth.total.upload,
+			max_download_rate: other.bandwidth.total.download,
+			max_peer_upload_rate: other.bandwidth.peer.upload,
+			max_peer_download_rate: other.bandwidth.peer.download,
+			protocol_rate_limits: other.bandwidth.protocols.iter()
+				.map(|(protocol, limit)| (String::from_utf8_lossy(&protocol[..]).into_owned(), limit.upload, limit.download))
+				.collect(),
 		}
 	}
 }
@@ -706,7 +838,12 @@ impl LightSync {
 			(sync_handler, Arc::new(light_proto))
 		};
 
-		let service = try!(NetworkService::new(params.network_config));
+		let genesis_hash = params.client.chain_info().genesis_hash;
+		let mut network_config = params.network_config;
+		network_config.node_record.push((KEY_CHAIN.into(), rlp::encode(&genesis_hash).to_vec()));
+		network_config.discovery_searches.push(light_topic(&genesis_hash));
+
+		let service = try!(NetworkService::new(network_config, None));
 
 		Ok(LightSync {
 			proto: light_proto,
@@ -804,6 +941,7 @@ impl LightSyncProvider for LightSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					reputation: session_info.reputation,
 					eth_info: None,
 					pip_info: self.proto.peer_status(&peer_id).map(Into::into),
 				})
@@ -48,6 +48,37 @@ pub enum State {
 	Complete,
 }
 
+/// Block downloader state digest.
+#[derive(Debug, Clone)]
+pub struct DownloaderDiagnostics {
+	/// Downloader state.
+	pub state: String,
+	/// Last imported block number.
+	pub last_imported_block_number: BlockNumber,
+	/// Last imported block hash.
+	pub last_imported_block_hash: H256,
+	/// Highest block number seen.
+	pub highest_block_number: Option<BlockNumber>,
+	/// Block the download stops at, if any.
+	pub target_hash: Option<H256>,
+	/// First block number of the current round.
+	pub round_start: BlockNumber,
+	/// Blocks imported in the current round.
+	pub imported_this_round: Option<usize>,
+	/// Number of blocks to step back when searching for a common block.
+	pub retract_step: u64,
+	/// Subchain heads waiting to be downloaded.
+	pub subchain_heads: usize,
+	/// Downloaded blocks waiting to be imported.
+	pub downloaded_blocks: usize,
+	/// Headers being downloaded.
+	pub downloading_headers: usize,
+	/// Block bodies being downloaded.
+	pub downloading_bodies: usize,
+	/// Block receipts being downloaded.
+	pub downloading_receipts: usize,
+}
+
 /// Data that needs to be requested from a peer.
 pub enum BlockRequest {
 	Headers {
@@ -212,6 +243,26 @@ impl BlockDownloader {
 		self.last_imported_block
 	}
 
+	/// Returns a digest of the downloader state.
+	pub fn diagnostics(&self) -> DownloaderDiagnostics {
+		let (downloading_headers, downloading_bodies, downloading_receipts) = self.blocks.downloading_count();
+		DownloaderDiagnostics {
+			state: format!("{:?}", self.state),
+			last_imported_block_number: self.last_imported_block,
+			last_imported_block_hash: self.last_imported_hash.clone(),
+			highest_block_number: self.highest_block,
+			target_hash: self.target_hash.clone(),
+			round_start: self.last_round_start,
+			imported_this_round: self.imported_this_round,
+			retract_step: self.retract_step,
+			subchain_heads: self.blocks.heads_count(),
+			downloaded_blocks: self.blocks.blocks_count(),
+			downloading_headers: downloading_headers,
+			downloading_bodies: downloading_bodies,
+			downloading_receipts: downloading_receipts,
+		}
+	}
+
 	/// Add new block headers.
 	pub fn import_headers(&mut self, io: &mut SyncIo, r: &UntrustedRlp, expected_hash: Option<H256>) -> Result<DownloadAction, BlockDownloaderImportError> {
 		let item_count = r.item_count().unwrap_or(0);
@@ -111,6 +111,21 @@ impl BlockCollection {
 		self.downloading_receipts.clear();
 	}
 
+	/// Number of subchain heads waiting to be downloaded.
+	pub fn heads_count(&self) -> usize {
+		self.heads.len()
+	}
+
+	/// Number of downloaded blocks.
+	pub fn blocks_count(&self) -> usize {
+		self.blocks.len()
+	}
+
+	/// Number of headers, bodies and receipts being downloaded.
+	pub fn downloading_count(&self) -> (usize, usize, usize) {
+		(self.downloading_headers.len(), self.downloading_bodies.len(), self.downloading_receipts.len())
+	}
+
 	/// Reset collection for a new sync round with given subchain block hashes.
 	pub fn reset_to(&mut self, hashes: Vec<H256>) {
 		self.clear();
@@ -90,6 +90,7 @@
 ///
 
 use util::*;
+use util::hash::H64;
 use rlp::*;
 use network::*;
 use ethcore::header::{BlockNumber, Header as BlockHeader};
@@ -100,11 +101,13 @@ use ethcore::transaction::PendingTransaction;
 use sync_io::SyncIo;
 use time;
 use super::SyncConfig;
-use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction};
+use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction, DownloaderDiagnostics};
 use rand::Rng;
 use snapshot::{Snapshot, ChunkType};
 use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use transactions_fetch::TransactionsFetch;
+use fork_id::{ForkFilter, ForkId};
 
 known_heap_size!(0, PeerInfo);
 
@@ -114,6 +117,9 @@ const PROTOCOL_VERSION_63: u8 = 63;
 const PROTOCOL_VERSION_62: u8 = 62;
 const PROTOCOL_VERSION_1: u8 = 1;
 const PROTOCOL_VERSION_2: u8 = 2;
+const PROTOCOL_VERSION_3: u8 = 3;
+const PROTOCOL_VERSION_4: u8 = 4;
+const PROTOCOL_VERSION_5: u8 = 5;
 const MAX_BODIES_TO_SEND: usize = 256;
 const MAX_HEADERS_TO_SEND: usize = 512;
 const MAX_NODE_DATA_TO_SEND: usize = 1024;
@@ -128,6 +134,10 @@ const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
 const MAX_TRANSACTION_SIZE: usize = 300*1024;
 // Maximal number of transactions in sent in single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
+// Maximal number of transaction hashes announced in single packet.
+const MAX_TRANSACTION_HASHES_TO_ANNOUNCE: usize = 1024;
+// Maximal number of transactions requested from a single peer at a time.
+const MAX_TRANSACTIONS_TO_REQUEST: usize = 256;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 100000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
@@ -153,13 +163,22 @@ const SNAPSHOT_MANIFEST_PACKET: u8 = 0x12;
 const GET_SNAPSHOT_DATA_PACKET: u8 = 0x13;
 const SNAPSHOT_DATA_PACKET: u8 = 0x14;
 const CONSENSUS_DATA_PACKET: u8 = 0x15;
+const COMPACT_BLOCK_PACKET: u8 = 0x16;
+const GET_BLOCK_TRANSACTIONS_PACKET: u8 = 0x17;
+const BLOCK_TRANSACTIONS_PACKET: u8 = 0x18;
+const NEW_TRANSACTION_HASHES_PACKET: u8 = 0x19;
+const GET_TRANSACTIONS_PACKET: u8 = 0x1a;
 
-pub const SNAPSHOT_SYNC_PACKET_COUNT: u8 = 0x16;
+pub const SNAPSHOT_SYNC_PACKET_COUNT: u8 = 0x1b;
 
 const MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD: usize = 3;
 
 const MIN_SUPPORTED_SNAPSHOT_MANIFEST_VERSION: u64 = 1;
 
+const MAX_PENDING_COMPACT_BLOCKS: usize = 8;
+const SHORT_ID_INDEX_REFRESH_SEC: u64 = 1;
+const MAX_RECENT_ERRORS: usize = 32;
+
 const WAIT_PEERS_TIMEOUT_SEC: u64 = 5;
 const STATUS_TIMEOUT_SEC: u64 = 5;
 const HEADERS_TIMEOUT_SEC: u64 = 15;
@@ -168,6 +187,9 @@ const RECEIPTS_TIMEOUT_SEC: u64 = 10;
 const FORK_HEADER_TIMEOUT_SEC: u64 = 3;
 const SNAPSHOT_MANIFEST_TIMEOUT_SEC: u64 = 5;
 const SNAPSHOT_DATA_TIMEOUT_SEC: u64 = 120;
+const COMPACT_BLOCK_TIMEOUT_SEC: u64 = 5;
+const TRANSACTIONS_TIMEOUT_SEC: u64 = 10;
+const STALL_TIMEOUT_SEC: u64 = 180;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Sync state
@@ -248,6 +270,65 @@ impl SyncStatus {
 	}
 }
 
+/// Sync state of a connected peer.
+#[derive(Debug, Clone)]
+pub struct PeerSyncInfo {
+	/// Peer id.
+	pub id: PeerId,
+	/// Protocol version.
+	pub protocol_version: u8,
+	/// Peer total difficulty if known.
+	pub difficulty: Option<U256>,
+	/// Peer best block hash.
+	pub latest_hash: H256,
+	/// Type of data currently being requested from peer.
+	pub asking: String,
+	/// Number of blocks being requested.
+	pub asking_blocks: usize,
+	/// Seconds since the pending request was sent.
+	pub asking_for: Option<u64>,
+	/// Block set being downloaded from peer.
+	pub block_set: Option<String>,
+	/// Pending request is expired.
+	pub expired: bool,
+	/// Peer is active in the current sync round.
+	pub active: bool,
+	/// Peer fork is confirmed.
+	pub confirmed: bool,
+}
+
+/// Sync error recorded for diagnostics.
+#[derive(Debug, Clone)]
+pub struct SyncError {
+	/// Unix timestamp in seconds.
+	pub timestamp: u64,
+	/// Peer that caused the error, if any.
+	pub peer: Option<PeerId>,
+	/// Error description.
+	pub message: String,
+}
+
+/// Sync state dump for troubleshooting stalled sync.
+#[derive(Debug, Clone)]
+pub struct SyncDiagnostics {
+	/// Sync state.
+	pub state: SyncState,
+	/// Best block number when sync last made progress.
+	pub last_progress_block_number: BlockNumber,
+	/// Seconds since the best block last advanced.
+	pub last_progress_age: u64,
+	/// Number of times sync was restarted after stalling.
+	pub stall_recoveries: usize,
+	/// New blocks downloader state.
+	pub new_blocks: DownloaderDiagnostics,
+	/// Ancient blocks downloader state, if downloading.
+	pub old_blocks: Option<DownloaderDiagnostics>,
+	/// Connected peers.
+	pub peers: Vec<PeerSyncInfo>,
+	/// Recent errors, oldest first.
+	pub recent_errors: Vec<SyncError>,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 /// Peer data type requested
 enum PeerAsking {
@@ -313,6 +394,8 @@ struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Fork identifier announced in status
+	fork_id: Option<ForkId>,
 }
 
 impl PeerInfo {
@@ -324,6 +407,14 @@ impl PeerInfo {
 		self.confirmation != ForkConfirmation::Unconfirmed && !self.expired
 	}
 
+	fn supports_compact_blocks(&self) -> bool {
+		self.protocol_version == PROTOCOL_VERSION_3 || self.protocol_version == PROTOCOL_VERSION_4 || self.protocol_version == PROTOCOL_VERSION_5
+	}
+
+	fn supports_transaction_hashes(&self) -> bool {
+		self.protocol_version == PROTOCOL_VERSION_4 || self.protocol_version == PROTOCOL_VERSION_5
+	}
+
 	fn reset_asking(&mut self) {
 		self.asking_blocks.clear();
 		self.asking_hash = None;
@@ -334,6 +425,30 @@ impl PeerInfo {
 	}
 }
 
+/// Block announced in compact form, waiting for its missing transactions.
+struct CompactBlock {
+	/// Peer that announced the block.
+	peer: PeerId,
+	/// Block header RLP.
+	header: Bytes,
+	/// Block uncles list RLP.
+	uncles: Bytes,
+	/// Total difficulty announced with the block.
+	difficulty: U256,
+	/// Block transactions RLP in block order. `None` for transactions not found in the queue.
+	transactions: Vec<Option<Bytes>>,
+	/// All transactions were requested after the block could not be rebuilt from the queue.
+	full: bool,
+	/// Announcement timestamp.
+	received: u64,
+}
+
+impl CompactBlock {
+	fn missing(&self) -> Vec<usize> {
+		self.transactions.iter().enumerate().filter_map(|(i, tx)| if tx.is_none() { Some(i) } else { None }).collect()
+	}
+}
+
 #[cfg(not(test))]
 mod random {
 	use rand;
@@ -368,6 +483,8 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// Genesis and transition blocks used to compute and check fork identifiers
+	fork_filter: ForkFilter,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
 	/// Connected peers pending Status message.
@@ -377,10 +494,24 @@ pub struct ChainSync {
 	sync_start_time: Option<u64>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Transactions announced by hash and not received yet
+	transactions_fetch: TransactionsFetch,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
 	/// Enable warp sync.
 	enable_warp_sync: bool,
+	/// Compact blocks waiting for missing transactions.
+	compact_blocks: HashMap<H256, CompactBlock>,
+	/// Short ids of queued transactions mapped to their hashes.
+	short_id_index: HashMap<H64, H256>,
+	/// Time the short id index was last rebuilt.
+	short_id_index_updated: u64,
+	/// Best block number and the time it was first seen. Used to detect stalled sync.
+	last_progress: (BlockNumber, u64),
+	/// Number of times sync was restarted after stalling.
+	stall_recoveries: usize,
+	/// Recent sync errors.
+	recent_errors: VecDeque<SyncError>,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -401,11 +532,19 @@ impl ChainSync {
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			fork_filter: ForkFilter::new(&chain_info.genesis_hash, chain.fork_transitions()),
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			transactions_fetch: TransactionsFetch::default(),
 			enable_warp_sync: config.warp_sync,
+			compact_blocks: HashMap::new(),
+			short_id_index: HashMap::new(),
+			short_id_index_updated: 0,
+			last_progress: (chain_info.best_block_number, time::precise_time_ns()),
+			stall_recoveries: 0,
+			recent_errors: VecDeque::new(),
 		};
 		sync.update_targets(chain);
 		sync
@@ -446,6 +585,48 @@ impl ChainSync {
 		})
 	}
 
+	/// Returns downloader, peers and recent errors state
+	pub fn diagnostics(&self) -> SyncDiagnostics {
+		let now = time::precise_time_ns();
+		SyncDiagnostics {
+			state: self.state.clone(),
+			last_progress_block_number: self.last_progress.0,
+			last_progress_age: (now - self.last_progress.1) / 1_000_000_000,
+			stall_recoveries: self.stall_recoveries,
+			new_blocks: self.new_blocks.diagnostics(),
+			old_blocks: self.old_blocks.as_ref().map(|d| d.diagnostics()),
+			peers: self.peers.iter().map(|(id, p)| PeerSyncInfo {
+				id: *id,
+				protocol_version: p.protocol_version,
+				difficulty: p.difficulty,
+				latest_hash: p.latest_hash.clone(),
+				asking: format!("{:?}", p.asking),
+				asking_blocks: p.asking_blocks.len(),
+				asking_for: match p.asking {
+					PeerAsking::Nothing => None,
+					_ => Some((now - p.ask_time) / 1_000_000_000),
+				},
+				block_set: p.block_set.map(|s| format!("{:?}", s)),
+				expired: p.expired,
+				active: self.active_peers.contains(id),
+				confirmed: p.confirmation == ForkConfirmation::Confirmed,
+			}).collect(),
+			recent_errors: self.recent_errors.iter().cloned().collect(),
+		}
+	}
+
+	/// Records an error for diagnostics.
+	fn note_error(&mut self, peer: Option<PeerId>, message: String) {
+		if self.recent_errors.len() == MAX_RECENT_ERRORS {
+			self.recent_errors.pop_front();
+		}
+		self.recent_errors.push_back(SyncError {
+			timestamp: time::get_time().sec as u64,
+			peer: peer,
+			message: message,
+		});
+	}
+
 	/// Returns transactions propagation statistics
 	pub fn transactions_stats(&self) -> &H256FastMap<TransactionStats> {
 		self.transactions_stats.stats()
@@ -602,7 +783,7 @@ impl ChainSync {
 		self.handshaking_peers.remove(&peer_id);
 		let protocol_version: u8 = r.val_at(0)?;
 		let warp_protocol = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer_id) != 0;
-		let peer = PeerInfo {
+		let mut peer = PeerInfo {
 			protocol_version: protocol_version,
 			network_id: r.val_at(1)?,
 			difficulty: Some(r.val_at(2)?),
@@ -619,14 +800,20 @@ impl ChainSync {
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
+			fork_id: if warp_protocol && protocol_version == PROTOCOL_VERSION_5 {
+				let fork_id = r.at(7)?;
+				Some(ForkId { hash: fork_id.val_at(0)?, next: fork_id.val_at(1)? })
+			} else {
+				None
+			},
 		};
 
 		if self.sync_start_time.is_none() {
 			self.sync_start_time = Some(time::precise_time_ns());
 		}
 
-		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?})",
-			peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis, peer.snapshot_number);
+		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?}, fork id:{:?})",
+			peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis, peer.snapshot_number, peer.fork_id);
 		if io.is_expired() {
 			trace!(target: "sync", "Status packet from expired session {}:{}", peer_id, io.peer_info(peer_id));
 			return Ok(());
@@ -647,21 +834,34 @@ impl ChainSync {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, self.network_id, peer.network_id);
 			return Ok(());
 		}
-		if (warp_protocol && peer.protocol_version != PROTOCOL_VERSION_1 && peer.protocol_version != PROTOCOL_VERSION_2) || (!warp_protocol && peer.protocol_version != PROTOCOL_VERSION_63 && peer.protocol_version != PROTOCOL_VERSION_62) {
+		if (warp_protocol && peer.protocol_version != PROTOCOL_VERSION_1 && peer.protocol_version != PROTOCOL_VERSION_2 && peer.protocol_version != PROTOCOL_VERSION_3 && peer.protocol_version != PROTOCOL_VERSION_4 && peer.protocol_version != PROTOCOL_VERSION_5) || (!warp_protocol && peer.protocol_version != PROTOCOL_VERSION_63 && peer.protocol_version != PROTOCOL_VERSION_62) {
 			io.disable_peer(peer_id);
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Ok(());
 		}
+		if let Some(fork_id) = peer.fork_id {
+			if !self.fork_filter.is_compatible(chain_info.best_block_number, &fork_id) {
+				io.disable_peer(peer_id);
+				trace!(target: "sync", "Peer {} incompatible fork id (ours: {:?}, theirs: {:?})", peer_id, self.fork_filter.fork_id(chain_info.best_block_number), fork_id);
+				self.note_error(Some(peer_id), format!("Incompatible fork id {:08x}, next transition {}", fork_id.hash, fork_id.next));
+				return Ok(());
+			}
+			// Fork id covers the configured fork block only once both sides have passed it
+			let best_block = chain_info.best_block_number;
+			if self.fork_block.map_or(false, |(number, _)| number <= best_block && self.fork_filter.passed_transition(&fork_id, number)) {
+				peer.confirmation = ForkConfirmation::Confirmed;
+			}
+		}
 
+		let confirmed = peer.confirmation == ForkConfirmation::Confirmed;
 		self.peers.insert(peer_id.clone(), peer);
 		// Don't activate peer immediatelly when searching for common block.
 		// Let the current sync round complete first.
 		self.active_peers.insert(peer_id.clone());
 		debug!(target: "sync", "Connected {}:{}", peer_id, io.peer_info(peer_id));
-		if let Some((fork_block, _)) = self.fork_block {
-			self.request_fork_header_by_number(io, peer_id, fork_block);
-		} else {
-			self.sync_peer(io, peer_id, false);
+		match self.fork_block {
+			Some((fork_block, _)) if !confirmed => self.request_fork_header_by_number(io, peer_id, fork_block),
+			_ => self.sync_peer(io, peer_id, false),
 		}
 		Ok(())
 	}
@@ -704,7 +904,7 @@ impl ChainSync {
 		let expected_hash = self.peers.get(&peer_id).and_then(|p| p.asking_hash);
 		let allowed = self.peers.get(&peer_id).map(|p| p.is_allowed()).unwrap_or(false);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
-		if !self.reset_peer_asking(peer_id, PeerAsking::BlockHeaders) || expected_hash.is_none() || !allowed {
+		if !self.reset_peer_asking(io, peer_id, PeerAsking::BlockHeaders) || expected_hash.is_none() || !allowed {
 			trace!(target: "sync", "{}: Ignored unexpected headers, expected_hash = {:?}", peer_id, expected_hash);
 			self.continue_sync(io);
 			return Ok(());
@@ -744,6 +944,8 @@ impl ChainSync {
 				self.deactivate_peer(io, peer_id);
 			},
 			Err(DownloaderImportError::Invalid) => {
+				self.note_error(Some(peer_id), format!("Invalid block headers for {:?}", block_set));
+				io.note_reputation(peer_id, ReputationChange::BadBlock);
 				io.disable_peer(peer_id);
 				self.deactivate_peer(io, peer_id);
 				self.continue_sync(io);
@@ -755,9 +957,11 @@ impl ChainSync {
 				for (_, ref mut p) in self.peers.iter_mut().filter(|&(_, ref p)| p.block_set == Some(block_set)) {
 					p.reset_asking();
 				}
-
+				io.note_reputation(peer_id, ReputationChange::UsefulData);
 			}
-			Ok(DownloadAction::None) => {},
+			Ok(DownloadAction::None) => {
+				io.note_reputation(peer_id, ReputationChange::UsefulData);
+			},
 		}
 
 		self.collect_blocks(io, block_set);
@@ -772,7 +976,7 @@ impl ChainSync {
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
-		if !self.reset_peer_asking(peer_id, PeerAsking::BlockBodies) {
+		if !self.reset_peer_asking(io, peer_id, PeerAsking::BlockBodies) {
 			trace!(target: "sync", "{}: Ignored unexpected bodies", peer_id);
 			self.continue_sync(io);
 			return Ok(());
@@ -804,6 +1008,8 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					self.note_error(Some(peer_id), format!("Invalid block bodies for {:?}", block_set));
+					io.note_reputation(peer_id, ReputationChange::BadBlock);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -812,7 +1018,7 @@ impl ChainSync {
 				Err(DownloaderImportError::Useless) => {
 					self.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.note_reputation(peer_id, ReputationChange::UsefulData),
 			}
 
 			self.collect_blocks(io, block_set);
@@ -826,7 +1032,7 @@ impl ChainSync {
 	fn on_peer_block_receipts(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
-		if !self.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) {
+		if !self.reset_peer_asking(io, peer_id, PeerAsking::BlockReceipts) {
 			trace!(target: "sync", "{}: Ignored unexpected receipts", peer_id);
 			self.continue_sync(io);
 			return Ok(());
@@ -858,6 +1064,8 @@ impl ChainSync {
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					self.note_error(Some(peer_id), format!("Invalid block receipts for {:?}", block_set));
+					io.note_reputation(peer_id, ReputationChange::BadBlock);
 					io.disable_peer(peer_id);
 					self.deactivate_peer(io, peer_id);
 					self.continue_sync(io);
@@ -866,7 +1074,7 @@ impl ChainSync {
 				Err(DownloaderImportError::Useless) => {
 					self.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.note_reputation(peer_id, ReputationChange::UsefulData),
 			}
 
 			self.collect_blocks(io, block_set);
@@ -884,12 +1092,18 @@ impl ChainSync {
 			return Ok(());
 		}
 		let difficulty: U256 = r.val_at(1)?;
+		let block_rlp = r.at(0)?;
+		self.import_new_block(io, peer_id, difficulty, &block_rlp)
+	}
+
+	/// Imports a block announced by peer, either in full or rebuilt from a compact block.
+	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
+	fn import_new_block(&mut self, io: &mut SyncIo, peer_id: PeerId, difficulty: U256, block_rlp: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
 			if peer.difficulty.map_or(true, |pd| difficulty > pd) {
 				peer.difficulty = Some(difficulty);
 			}
 		}
-		let block_rlp = r.at(0)?;
 		let header_rlp = block_rlp.at(0)?;
 		let h = header_rlp.as_raw().sha3();
 		trace!(target: "sync", "{} -> NewBlock ({})", peer_id, h);
@@ -917,6 +1131,7 @@ impl ChainSync {
 				trace!(target: "sync", "New block already queued {:?}", h);
 			},
 			Ok(_) => {
+				io.note_reputation(peer_id, ReputationChange::UsefulData);
 				// abort current download of the same block
 				self.complete_sync(io);
 				self.new_blocks.mark_as_known(&header.hash(), header.number());
@@ -928,6 +1143,8 @@ impl ChainSync {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+				self.note_error(Some(peer_id), format!("Bad new block {:?}: {:?}", h, e));
+				io.note_reputation(peer_id, ReputationChange::BadBlock);
 				io.disable_peer(peer_id);
 			}
 		};
@@ -944,6 +1161,146 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Handles `CompactBlock` packet. Rebuilds the block from the transaction queue and requests any missing transactions.
+	fn on_peer_compact_block(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
+			trace!(target: "sync", "Ignoring compact block from unconfirmed peer {}", peer_id);
+			return Ok(());
+		}
+		let header_rlp = r.at(0)?;
+		let uncles_rlp = r.at(1)?;
+		let short_ids: Vec<H64> = r.list_at(2)?;
+		let difficulty: U256 = r.val_at(3)?;
+		let header: BlockHeader = header_rlp.as_val()?;
+		let hash = header.hash();
+		trace!(target: "sync", "{} -> CompactBlock ({}, {} transactions)", peer_id, hash, short_ids.len());
+		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
+			if peer.difficulty.map_or(true, |pd| difficulty > pd) {
+				peer.difficulty = Some(difficulty);
+			}
+			peer.latest_hash = hash.clone();
+		}
+		match io.chain().block_status(BlockId::Hash(hash.clone())) {
+			BlockStatus::InChain | BlockStatus::Queued => {
+				trace!(target: "sync", "Compact block already known {:?}", hash);
+				return Ok(());
+			},
+			_ => {},
+		}
+		if self.compact_blocks.contains_key(&hash) {
+			trace!(target: "sync", "Compact block already pending {:?}", hash);
+			return Ok(());
+		}
+
+		if short_ids.iter().any(|id| !self.short_id_index.contains_key(id)) {
+			self.refresh_short_id_index(io);
+		}
+		let transactions = short_ids.iter()
+			.map(|id| self.short_id_index.get(id)
+				.and_then(|hash| io.chain().queued_transaction(hash))
+				.map(|tx| ::rlp::encode(&tx.transaction).to_vec()))
+			.collect();
+		let block = CompactBlock {
+			peer: peer_id,
+			header: header_rlp.as_raw().to_vec(),
+			uncles: uncles_rlp.as_raw().to_vec(),
+			difficulty: difficulty,
+			transactions: transactions,
+			full: false,
+			received: time::precise_time_ns(),
+		};
+		self.complete_compact_block(io, hash, block)
+	}
+
+	/// Rebuilds the short id index from the transaction queue, at most once per `SHORT_ID_INDEX_REFRESH_SEC`.
+	fn refresh_short_id_index(&mut self, io: &SyncIo) {
+		let now = time::precise_time_ns();
+		if now.saturating_sub(self.short_id_index_updated) < SHORT_ID_INDEX_REFRESH_SEC * 1_000_000_000 {
+			return;
+		}
+		self.short_id_index = io.chain().queued_transactions().into_iter()
+			.map(|tx| {
+				let hash = tx.transaction.hash();
+				(H64::from(hash), hash)
+			})
+			.collect();
+		self.short_id_index_updated = now;
+	}
+
+	/// Handles `BlockTransactions` packet with transactions missing from a compact block.
+	fn on_peer_block_transactions(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		let hash: H256 = r.val_at(0)?;
+		if self.compact_blocks.get(&hash).map_or(true, |b| b.peer != peer_id) {
+			trace!(target: "sync", "{} -> Unexpected BlockTransactions ({})", peer_id, hash);
+			return Ok(());
+		}
+		let mut block = self.compact_blocks.remove(&hash).expect("presence checked above; qed");
+		let transactions = r.at(1)?;
+		let missing = block.missing();
+		trace!(target: "sync", "{} -> BlockTransactions ({}, {} entries)", peer_id, hash, transactions.item_count()?);
+		if transactions.item_count()? != missing.len() {
+			// Peer can't serve the transactions; fall back to downloading the full block.
+			trace!(target: "sync", "{} -> Incomplete BlockTransactions for {}, downloading the block", peer_id, hash);
+			if self.state == SyncState::Idle {
+				self.sync_peer(io, peer_id, true);
+			}
+			return Ok(());
+		}
+		for (i, tx) in missing.into_iter().zip(transactions.iter()) {
+			block.transactions[i] = Some(tx.as_raw().to_vec());
+		}
+		self.complete_compact_block(io, hash, block)
+	}
+
+	/// Imports a compact block once all its transactions are known, otherwise requests the missing ones.
+	fn complete_compact_block(&mut self, io: &mut SyncIo, hash: H256, mut block: CompactBlock) -> Result<(), PacketDecodeError> {
+		let missing = block.missing();
+		if !missing.is_empty() {
+			if self.compact_blocks.len() >= MAX_PENDING_COMPACT_BLOCKS {
+				// Download the block in full instead, as for compact blocks which time out
+				trace!(target: "sync", "Too many pending compact blocks, downloading {} from {}", hash, block.peer);
+				if self.state == SyncState::Idle {
+					self.sync_peer(io, block.peer, true);
+				}
+				return Ok(());
+			}
+			trace!(target: "sync", "{} <- GetBlockTransactions ({}, {} entries)", block.peer, hash, missing.len());
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&hash);
+			rlp.append_list::<usize, usize>(&missing);
+			let peer_id = block.peer;
+			self.compact_blocks.insert(hash, block);
+			self.send_packet(io, peer_id, GET_BLOCK_TRANSACTIONS_PACKET, rlp.out());
+			return Ok(());
+		}
+
+		let transactions: Vec<Bytes> = block.transactions.iter().filter_map(|tx| tx.clone()).collect();
+		let header: BlockHeader = UntrustedRlp::new(&block.header).as_val()?;
+		if ordered_trie_root(transactions.iter().cloned()) != *header.transactions_root() {
+			if block.full {
+				debug!(target: "sync", "Bad transactions for compact block {:?}", hash);
+				io.note_reputation(block.peer, ReputationChange::BadBlock);
+				io.disable_peer(block.peer);
+				return Ok(());
+			}
+			// Short id collision or a stale queue entry; request the whole transaction list.
+			trace!(target: "sync", "Compact block {} rebuilt with wrong transactions, requesting all of them", hash);
+			block.transactions = vec![None; transactions.len()];
+			block.full = true;
+			return self.complete_compact_block(io, hash, block);
+		}
+
+		let mut rlp = RlpStream::new_list(3);
+		rlp.append_raw(&block.header, 1);
+		rlp.begin_list(transactions.len());
+		for tx in &transactions {
+			rlp.append_raw(tx, 1);
+		}
+		rlp.append_raw(&block.uncles, 1);
+		let block_bytes = rlp.out();
+		self.import_new_block(io, block.peer, block.difficulty, &UntrustedRlp::new(&block_bytes))
+	}
+
 	/// Handles `NewHashes` packet. Initiates headers download for any unknown hashes.
 	fn on_peer_new_hashes(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
@@ -1004,6 +1361,7 @@ impl ChainSync {
 				},
 				BlockStatus::Bad => {
 					debug!(target: "sync", "Bad new block hash {:?}", hash);
+					io.note_reputation(peer_id, ReputationChange::BadBlock);
 					io.disable_peer(peer_id);
 					return Ok(());
 				}
@@ -1026,7 +1384,7 @@ impl ChainSync {
 			return Ok(());
 		}
 		self.clear_peer_download(peer_id);
-		if !self.reset_peer_asking(peer_id, PeerAsking::SnapshotManifest) || self.state != SyncState::SnapshotManifest {
+		if !self.reset_peer_asking(io, peer_id, PeerAsking::SnapshotManifest) || self.state != SyncState::SnapshotManifest {
 			trace!(target: "sync", "{}: Ignored unexpected/expired manifest", peer_id);
 			self.continue_sync(io);
 			return Ok(());
@@ -1049,6 +1407,11 @@ impl ChainSync {
 			return Ok(());
 		}
 		self.snapshot.reset_to(&manifest, &manifest_rlp.as_raw().sha3());
+		let completed = io.snapshot_service().completed_chunks(manifest.clone());
+		if !completed.is_empty() {
+			trace!(target: "sync", "{}: Resuming restoration with {} chunks already restored", peer_id, completed.len());
+			self.snapshot.note_completed(&completed);
+		}
 		io.snapshot_service().begin_restore(manifest);
 		self.state = SyncState::SnapshotData;
 
@@ -1066,7 +1429,7 @@ impl ChainSync {
 			return Ok(());
 		}
 		self.clear_peer_download(peer_id);
-		if !self.reset_peer_asking(peer_id, PeerAsking::SnapshotData) || (self.state != SyncState::SnapshotData && self.state != SyncState::SnapshotWaiting) {
+		if !self.reset_peer_asking(io, peer_id, PeerAsking::SnapshotData) || (self.state != SyncState::SnapshotData && self.state != SyncState::SnapshotWaiting) {
 			trace!(target: "sync", "{}: Ignored unexpected snapshot data", peer_id);
 			self.continue_sync(io);
 			return Ok(());
@@ -1098,12 +1461,15 @@ impl ChainSync {
 			}
 			Err(()) => {
 				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.note_reputation(peer_id, ReputationChange::BadBlock);
 				io.disconnect_peer(peer_id);
 				self.continue_sync(io);
 				return Ok(());
 			}
 		}
 
+		io.note_reputation(peer_id, ReputationChange::UsefulData);
+
 		if self.snapshot.is_complete() {
 			// wait for snapshot restoration process to complete
 			self.state = SyncState::SnapshotWaiting;
@@ -1122,6 +1488,8 @@ impl ChainSync {
 		if self.peers.contains_key(&peer) {
 			debug!(target: "sync", "Disconnected {}", peer);
 			self.clear_peer_download(peer);
+			self.compact_blocks.retain(|_, block| block.peer != peer);
+			self.transactions_fetch.remove_peer(peer);
 			self.peers.remove(&peer);
 			self.active_peers.remove(&peer);
 			self.continue_sync(io);
@@ -1320,11 +1688,13 @@ impl ChainSync {
 		match block_set {
 			BlockSet::NewBlocks => {
 				if self.new_blocks.collect_blocks(io, self.state == SyncState::NewBlocks) == Err(DownloaderImportError::Invalid) {
+					self.note_error(None, "Invalid blocks in the new blocks download, restarting".into());
 					self.restart(io);
 				}
 			},
 			BlockSet::OldBlocks => {
 				if self.old_blocks.as_mut().map_or(false, |downloader| { downloader.collect_blocks(io, false) == Err(DownloaderImportError::Invalid) }) {
+					self.note_error(None, "Invalid blocks in the ancient blocks download, restarting".into());
 					self.restart(io);
 				} else if self.old_blocks.as_ref().map_or(false, |downloader| { downloader.is_complete() }) {
 					trace!(target: "sync", "Background block download is complete");
@@ -1403,7 +1773,7 @@ impl ChainSync {
 	}
 
 	/// Reset peer status after request is complete.
-	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
+	fn reset_peer_asking(&mut self, io: &mut SyncIo, peer_id: PeerId, asking: PeerAsking) -> bool {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
 			peer.expired = false;
 			peer.block_set = None;
@@ -1413,6 +1783,8 @@ impl ChainSync {
 				return false;
 			} else {
 				peer.asking = PeerAsking::Nothing;
+				let latency_ms = (time::precise_time_ns() - peer.ask_time) / 1_000_000;
+				io.note_reputation(peer_id, ReputationChange::Latency(latency_ms));
 				return true;
 			}
 		}
@@ -1441,7 +1813,12 @@ impl ChainSync {
 
 	/// Generic packet sender
 	fn send_packet(&mut self, sync: &mut SyncIo, peer_id: PeerId, packet_id: PacketId, packet: Bytes) {
-		if let Err(e) = sync.send(peer_id, packet_id, packet) {
+		let result = if packet_id >= ETH_PACKET_COUNT {
+			sync.send_protocol(WARP_SYNC_PROTOCOL_ID, peer_id, packet_id, packet)
+		} else {
+			sync.send(peer_id, packet_id, packet)
+		};
+		if let Err(e) = result {
 			debug!(target:"sync", "Error sending packet: {:?}", e);
 			sync.disable_peer(peer_id);
 		}
@@ -1461,6 +1838,8 @@ impl ChainSync {
 		let mut item_count = r.item_count()?;
 		trace!(target: "sync", "{:02} -> Transactions ({} entries)", peer_id, item_count);
 		item_count = min(item_count, MAX_TX_TO_IMPORT);
+		let block_number = io.chain().chain_info().best_block_number;
+		let node_id = io.peer_session_info(peer_id).and_then(|info| info.id);
 		let mut transactions = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
 			let rlp = r.at(i)?;
@@ -1468,6 +1847,12 @@ impl ChainSync {
 				debug!("Skipped oversized transaction of {} bytes", rlp.as_raw().len());
 				continue;
 			}
+			let hash = rlp.as_raw().sha3();
+			self.transactions_fetch.received(&hash);
+			self.transactions_stats.received(hash, node_id, block_number);
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.last_sent_transactions.insert(hash);
+			}
 			let tx = rlp.as_raw().to_vec();
 			transactions.push(tx);
 		}
@@ -1475,13 +1860,61 @@ impl ChainSync {
 		Ok(())
 	}
 
+	/// Called when peer announces hashes of new transactions
+	fn on_peer_new_transaction_hashes(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		// Accept transactions only when fully synced
+		if !io.is_chain_queue_empty() || (self.state != SyncState::Idle && self.state != SyncState::NewBlocks) {
+			trace!(target: "sync", "{} Ignoring transaction hashes while syncing", peer_id);
+			return Ok(());
+		}
+		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
+			trace!(target: "sync", "{} Ignoring transaction hashes from unconfirmed/unknown peer", peer_id);
+			return Ok(());
+		}
+
+		let item_count = min(r.item_count()?, MAX_TRANSACTION_HASHES_TO_ANNOUNCE);
+		trace!(target: "sync", "{:02} -> NewTransactionHashes ({} entries)", peer_id, item_count);
+		if item_count == 0 {
+			return Ok(());
+		}
+		for i in 0 .. item_count {
+			let hash: H256 = r.val_at(i)?;
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.last_sent_transactions.insert(hash);
+			}
+			if io.chain().queued_transaction(&hash).is_some() {
+				continue;
+			}
+			if !self.transactions_fetch.announce(peer_id, hash) {
+				trace!(target: "sync", "{:02} -> Too many pending transaction announcements, ignoring the rest", peer_id);
+				break;
+			}
+		}
+		self.request_transactions(io);
+		Ok(())
+	}
+
+	/// Request announced transactions, batched per peer
+	fn request_transactions(&mut self, io: &mut SyncIo) {
+		let requests = self.transactions_fetch.requests(time::precise_time_ns(), MAX_TRANSACTIONS_TO_REQUEST);
+		for (peer_id, hashes) in requests {
+			trace!(target: "sync", "{:02} <- GetTransactions ({} entries)", peer_id, hashes.len());
+			let mut rlp = RlpStream::new_list(hashes.len());
+			for hash in &hashes {
+				rlp.append(hash);
+			}
+			self.send_packet(io, peer_id, GET_TRANSACTIONS_PACKET, rlp.out());
+		}
+	}
+
 	/// Send Status message
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), NetworkError> {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
 		let protocol = if warp_protocol { warp_protocol_version } else { PROTOCOL_VERSION_63 };
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
-		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 });
+		let with_fork_id = warp_protocol_version == PROTOCOL_VERSION_5;
+		let mut packet = RlpStream::new_list(if with_fork_id { 8 } else if warp_protocol { 7 } else { 5 });
 		let chain = io.chain().chain_info();
 		packet.append(&(protocol as u32));
 		packet.append(&self.network_id);
@@ -1498,6 +1931,10 @@ impl ChainSync {
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
 		}
+		if with_fork_id {
+			let fork_id = self.fork_filter.fork_id(chain.best_block_number);
+			packet.begin_list(2).append(&fork_id.hash).append(&fork_id.next);
+		}
 		io.respond(STATUS_PACKET, packet.out())
 	}
 
@@ -1596,6 +2033,58 @@ impl ChainSync {
 		Ok(Some((BLOCK_BODIES_PACKET, rlp)))
 	}
 
+	/// Respond to GetBlockTransactions request
+	fn return_block_transactions(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+		// Packet layout:
+		// [ hash: B_32, [ index: P, ... ] ]
+		let hash: H256 = r.val_at(0)?;
+		let indexes: Vec<usize> = r.list_at(1)?;
+		let transactions: Vec<Bytes> = match io.chain().block_body(BlockId::Hash(hash.clone())) {
+			Some(body) => {
+				let body_rlp = body.rlp();
+				let transactions_rlp = body_rlp.at(0);
+				let count = transactions_rlp.item_count();
+				let mut requested = HashSet::with_capacity(min(indexes.len(), count));
+				let valid = indexes.len() <= count && indexes.iter().all(|&i| i < count && requested.insert(i));
+				match valid {
+					true => indexes.iter().map(|&i| transactions_rlp.at(i).as_raw().to_vec()).collect(),
+					false => Vec::new(),
+				}
+			},
+			None => Vec::new(),
+		};
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append(&hash);
+		rlp.begin_list(transactions.len());
+		for tx in &transactions {
+			rlp.append_raw(tx, 1);
+		}
+		trace!(target: "sync", "{} -> GetBlockTransactions: returned {} of {} entries", peer_id, transactions.len(), indexes.len());
+		Ok(Some((BLOCK_TRANSACTIONS_PACKET, rlp)))
+	}
+
+	/// Respond to GetTransactions request with transactions found in the queue
+	fn return_transactions(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
+		let count = min(r.item_count()?, MAX_TRANSACTIONS_TO_REQUEST);
+		let mut requested = HashSet::with_capacity(count);
+		let mut transactions = Vec::new();
+		for i in 0 .. count {
+			let hash: H256 = r.val_at(i)?;
+			if !requested.insert(hash) {
+				continue;
+			}
+			if let Some(tx) = io.chain().queued_transaction(&hash) {
+				transactions.push(tx);
+			}
+		}
+		let mut rlp = RlpStream::new_list(transactions.len());
+		for tx in &transactions {
+			rlp.append(&tx.transaction);
+		}
+		trace!(target: "sync", "{} -> GetTransactions: returned {} of {} entries", peer_id, transactions.len(), count);
+		Ok(Some((TRANSACTIONS_PACKET, rlp)))
+	}
+
 	/// Respond to GetNodeData request
 	fn return_node_data(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
 		let mut count = r.item_count().unwrap_or(0);
@@ -1649,11 +2138,16 @@ impl ChainSync {
 	fn return_snapshot_manifest(io: &SyncIo, r: &UntrustedRlp, peer_id: PeerId) -> RlpResponseResult {
 		let count = r.item_count().unwrap_or(0);
 		trace!(target: "sync", "{} -> GetSnapshotManifest", peer_id);
-		if count != 0 {
-			debug!(target: "sync", "Invalid GetSnapshotManifest request, ignoring.");
-			return Ok(None);
-		}
-		let rlp = match io.snapshot_service().manifest() {
+		// an empty request asks for the latest manifest; a block number for a retained one.
+		let manifest = match count {
+			0 => io.snapshot_service().manifest(),
+			1 => io.snapshot_service().manifest_at(r.val_at(0)?),
+			_ => {
+				debug!(target: "sync", "Invalid GetSnapshotManifest request, ignoring.");
+				return Ok(None);
+			}
+		};
+		let rlp = match manifest {
 			Some(manifest) => {
 				trace!(target: "sync", "{} <- SnapshotManifest", peer_id);
 				let mut rlp = RlpStream::new_list(1);
@@ -1729,6 +2223,14 @@ impl ChainSync {
 			GET_SNAPSHOT_DATA_PACKET => ChainSync::return_rlp(io, &rlp, peer,
 				ChainSync::return_snapshot_data,
 				|e| format!("Error sending snapshot data: {:?}", e)),
+			GET_BLOCK_TRANSACTIONS_PACKET => ChainSync::return_rlp(io, &rlp, peer,
+				ChainSync::return_block_transactions,
+				|e| format!("Error sending block transactions: {:?}", e)),
+
+			GET_TRANSACTIONS_PACKET => ChainSync::return_rlp(io, &rlp, peer,
+				ChainSync::return_transactions,
+				|e| format!("Error sending transactions: {:?}", e)),
+
 			CONSENSUS_DATA_PACKET => ChainSync::on_consensus_packet(io, peer, &rlp),
 			_ => {
 				sync.write().on_packet(io, peer, packet_id, data);
@@ -1756,20 +2258,25 @@ impl ChainSync {
 			NEW_BLOCK_HASHES_PACKET => self.on_peer_new_hashes(io, peer, &rlp),
 			SNAPSHOT_MANIFEST_PACKET => self.on_snapshot_manifest(io, peer, &rlp),
 			SNAPSHOT_DATA_PACKET => self.on_snapshot_data(io, peer, &rlp),
+			COMPACT_BLOCK_PACKET => self.on_peer_compact_block(io, peer, &rlp),
+			BLOCK_TRANSACTIONS_PACKET => self.on_peer_block_transactions(io, peer, &rlp),
+			NEW_TRANSACTION_HASHES_PACKET => self.on_peer_new_transaction_hashes(io, peer, &rlp),
 			_ => {
 				debug!(target: "sync", "{}: Unknown packet {}", peer, packet_id);
 				Ok(())
 			}
 		};
-		result.unwrap_or_else(|e| {
+		if let Err(e) = result {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
-		})
+			self.note_error(Some(peer), format!("Malformed packet {}: {}", packet_id, e));
+		}
 	}
 
 	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	pub fn maintain_peers(&mut self, io: &mut SyncIo) {
 		let tick = time::precise_time_ns();
 		let mut aborting = Vec::new();
+		let mut errors = Vec::new();
 		for (peer_id, peer) in &self.peers {
 			let elapsed = (tick - peer.ask_time) / 1_000_000_000;
 			let timeout = match peer.asking {
@@ -1783,11 +2290,14 @@ impl ChainSync {
 			};
 			if timeout {
 				trace!(target:"sync", "Timeout {}", peer_id);
+				errors.push(format!("Timeout waiting for {:?}", peer.asking));
+				io.note_reputation(*peer_id, ReputationChange::Timeout);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
 		}
-		for p in aborting {
+		for (p, error) in aborting.into_iter().zip(errors) {
+			self.note_error(Some(p), error);
 			self.on_peer_aborting(io, p);
 		}
 
@@ -1796,9 +2306,29 @@ impl ChainSync {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
 			if elapsed > STATUS_TIMEOUT_SEC {
 				trace!(target:"sync", "Status timeout {}", peer);
+				io.note_reputation(*peer, ReputationChange::Timeout);
 				io.disconnect_peer(*peer);
 			}
 		}
+
+		// Request transactions that did not arrive in time from other peers that announced them
+		for peer_id in self.transactions_fetch.expire(tick, TRANSACTIONS_TIMEOUT_SEC * 1_000_000_000) {
+			trace!(target:"sync", "Transactions request timeout {}", peer_id);
+		}
+		self.request_transactions(io);
+
+		// Drop compact blocks whose transactions did not arrive in time and download them in full instead
+		let expired: Vec<_> = self.compact_blocks.iter()
+			.filter(|&(_, block)| (tick - block.received) / 1_000_000_000 > COMPACT_BLOCK_TIMEOUT_SEC)
+			.map(|(hash, block)| (hash.clone(), block.peer))
+			.collect();
+		for (hash, peer_id) in expired {
+			trace!(target:"sync", "Compact block {} timeout from {}", hash, peer_id);
+			self.compact_blocks.remove(&hash);
+			if self.state == SyncState::Idle && self.peers.contains_key(&peer_id) {
+				self.sync_peer(io, peer_id, true);
+			}
+		}
 	}
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
@@ -1873,6 +2403,22 @@ impl ChainSync {
 		)
 	}
 
+	/// creates compact block rlp with short transaction ids for the given client
+	fn create_compact_block_rlp(chain: &BlockChainClient, hash: &H256) -> Bytes {
+		let block = chain.block(BlockId::Hash(hash.clone())).expect("Block has just been imported or sealed; qed");
+		let block_rlp = block.rlp();
+		let transactions_rlp = block_rlp.at(1);
+		let mut rlp_stream = RlpStream::new_list(4);
+		rlp_stream.append_raw(block_rlp.at(0).as_raw(), 1);
+		rlp_stream.append_raw(block_rlp.at(2).as_raw(), 1);
+		rlp_stream.begin_list(transactions_rlp.item_count());
+		for tx in transactions_rlp.iter() {
+			rlp_stream.append(&H64::from(tx.as_raw().sha3()));
+		}
+		rlp_stream.append(&chain.block_total_difficulty(BlockId::Hash(hash.clone())).expect("Block has just been imported or sealed; qed"));
+		rlp_stream.out()
+	}
+
 	/// creates given hash block rlp for the given client
 	fn create_new_block_rlp(chain: &BlockChainClient, hash: &H256) -> Bytes {
 		ChainSync::create_block_rlp(
@@ -1910,7 +2456,7 @@ impl ChainSync {
 	}
 
 	fn get_consensus_peers(&self) -> Vec<PeerId> {
-		self.peers.iter().filter_map(|(id, p)| if p.protocol_version == PROTOCOL_VERSION_2 { Some(*id) } else { None }).collect()
+		self.peers.iter().filter_map(|(id, p)| if p.protocol_version == PROTOCOL_VERSION_2 || p.protocol_version == PROTOCOL_VERSION_3 || p.protocol_version == PROTOCOL_VERSION_4 || p.protocol_version == PROTOCOL_VERSION_5 { Some(*id) } else { None }).collect()
 	}
 
 	/// propagates latest block to a set of peers
@@ -1918,7 +2464,14 @@ impl ChainSync {
 		trace!(target: "sync", "Sending NewBlocks to {:?}", peers);
 		let mut sent = 0;
 		for peer_id in peers {
-			if blocks.is_empty() {
+			let compact = self.peers.get(peer_id).map_or(false, PeerInfo::supports_compact_blocks);
+			if compact {
+				let hashes = if blocks.is_empty() { vec![chain_info.best_block_hash.clone()] } else { blocks.to_vec() };
+				for h in &hashes {
+					let rlp = ChainSync::create_compact_block_rlp(io.chain(), h);
+					self.send_packet(io, *peer_id, COMPACT_BLOCK_PACKET, rlp);
+				}
+			} else if blocks.is_empty() {
 				let rlp =  ChainSync::create_latest_block_rlp(io.chain());
 				self.send_packet(io, *peer_id, NEW_BLOCK_PACKET, rlp);
 			} else {
@@ -2014,6 +2567,16 @@ impl ChainSync {
 			for tx in &transactions { packet.append(&tx.transaction); }
 			packet.out()
 		};
+		// Announcements to peers we haven't sent anything yet are capped like any other
+		let announced_hashes = all_transactions_hashes.iter()
+			.take(MAX_TRANSACTION_HASHES_TO_ANNOUNCE)
+			.cloned()
+			.collect::<HashSet<H256>>();
+		let announced_hashes_rlp = {
+			let mut packet = RlpStream::new_list(announced_hashes.len());
+			for hash in &announced_hashes { packet.append(hash); }
+			packet.out()
+		};
 
 		// Clear old transactions from stats
 		self.transactions_stats.retain(&all_transactions_hashes);
@@ -2027,21 +2590,32 @@ impl ChainSync {
 					let stats = &mut self.transactions_stats;
 					let peer_info = self.peers.get_mut(&peer_id)
 						.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
+					// Peers supporting announcements receive hashes and fetch the transactions they miss
+					let announce = peer_info.supports_transaction_hashes();
+					let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 
 					// Send all transactions
 					if peer_info.last_sent_transactions.is_empty() {
+						let sent = if announce { &announced_hashes } else { &all_transactions_hashes };
 						// update stats
-						for hash in &all_transactions_hashes {
-							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
-							stats.propagated(*hash, id, block_number);
+						for hash in sent {
+							if announce {
+								stats.announced(*hash, id, block_number);
+							} else {
+								stats.propagated(*hash, id, block_number);
+							}
 						}
-						peer_info.last_sent_transactions = all_transactions_hashes.clone();
-						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
+						peer_info.last_sent_transactions = sent.clone();
+						return Some(match announce {
+							true => (peer_id, sent.len(), NEW_TRANSACTION_HASHES_PACKET, announced_hashes_rlp.clone()),
+							false => (peer_id, sent.len(), TRANSACTIONS_PACKET, all_transactions_rlp.clone()),
+						});
 					}
 
 					// Get hashes of all transactions to send to this peer
+					let limit = if announce { MAX_TRANSACTION_HASHES_TO_ANNOUNCE } else { MAX_TRANSACTIONS_TO_PROPAGATE };
 					let to_send = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
-						.take(MAX_TRANSACTIONS_TO_PROPAGATE)
+						.take(limit)
 						.cloned()
 						.collect::<HashSet<_>>();
 					if to_send.is_empty() {
@@ -2051,11 +2625,16 @@ impl ChainSync {
 					// Construct RLP
 					let mut packet = RlpStream::new_list(to_send.len());
 					for tx in &transactions {
-						if to_send.contains(&tx.transaction.hash()) {
-							packet.append(&tx.transaction);
+						let hash = tx.transaction.hash();
+						if to_send.contains(&hash) {
 							// update stats
-							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
-							stats.propagated(tx.transaction.hash(), id, block_number);
+							if announce {
+								packet.append(&hash);
+								stats.announced(hash, id, block_number);
+							} else {
+								packet.append(&tx.transaction);
+								stats.propagated(hash, id, block_number);
+							}
 						}
 					}
 
@@ -2064,7 +2643,8 @@ impl ChainSync {
 						.chain(&to_send)
 						.cloned()
 						.collect();
-					Some((peer_id, to_send.len(), packet.out()))
+					let packet_id = if announce { NEW_TRANSACTION_HASHES_PACKET } else { TRANSACTIONS_PACKET };
+					Some((peer_id, to_send.len(), packet_id, packet.out()))
 				})
 				.collect::<Vec<_>>()
 		};
@@ -2074,10 +2654,14 @@ impl ChainSync {
 		if lucky_peers.len() > 0 {
 			let mut max_sent = 0;
 			let lucky_peers_len = lucky_peers.len();
-			for (peer_id, sent, rlp) in lucky_peers {
+			for (peer_id, sent, packet_id, rlp) in lucky_peers {
 				peers.insert(peer_id);
-				self.send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
-				trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+				self.send_packet(io, peer_id, packet_id, rlp);
+				if packet_id == NEW_TRANSACTION_HASHES_PACKET {
+					trace!(target: "sync", "{:02} <- NewTransactionHashes ({} entries)", peer_id, sent);
+				} else {
+					trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+				}
 				max_sent = max(max_sent, sent);
 			}
 			debug!(target: "sync", "Sent up to {} transactions to {} peers.", max_sent, lucky_peers_len);
@@ -2125,10 +2709,56 @@ impl ChainSync {
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.maybe_start_snapshot_sync(io);
 		self.check_resume(io);
+		self.check_stalled(io);
+	}
+
+	/// Restarts sync if the best block has not advanced for too long while peers report higher difficulty.
+	fn check_stalled(&mut self, io: &mut SyncIo) {
+		let chain_info = io.chain().chain_info();
+		let now = time::precise_time_ns();
+		if chain_info.best_block_number != self.last_progress.0 {
+			self.last_progress = (chain_info.best_block_number, now);
+			return;
+		}
+		let expects_progress = match self.state {
+			SyncState::WaitingPeers | SyncState::SnapshotManifest | SyncState::SnapshotData | SyncState::SnapshotWaiting => false,
+			_ => self.peers.values().any(|p| p.is_allowed() && p.difficulty.map_or(false, |d| d > chain_info.total_difficulty)),
+		};
+		if !expects_progress {
+			self.last_progress.1 = now;
+			return;
+		}
+		let stalled_for = (now - self.last_progress.1) / 1_000_000_000;
+		if stalled_for < STALL_TIMEOUT_SEC {
+			return;
+		}
+
+		warn!(target: "sync", "Sync stalled at block #{} for {} seconds, restarting", chain_info.best_block_number, stalled_for);
+		self.note_error(None, format!("Sync stalled at block #{} for {} seconds in state {:?}", chain_info.best_block_number, stalled_for, self.state));
+		self.stall_recoveries += 1;
+		self.last_progress.1 = now;
+
+		if io.chain().queue_info().is_full() {
+			io.chain().clear_queue();
+		}
+		self.update_targets(io.chain());
+		// Reconnect peers that were deactivated or did not answer.
+		let stuck: Vec<_> = self.peers.iter()
+			.filter(|&(id, p)| p.expired || !self.active_peers.contains(id))
+			.map(|(id, _)| *id)
+			.collect();
+		for peer_id in stuck {
+			io.disconnect_peer(peer_id);
+			self.on_peer_aborting(io, peer_id);
+		}
+		self.reset_and_continue(io);
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
-	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, _imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
+	pub fn chain_new_blocks(&mut self, io: &mut SyncIo, imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
+		for hash in imported {
+			self.compact_blocks.remove(hash);
+		}
 		let queue_info = io.chain().queue_info();
 		let is_syncing = self.status().is_syncing(queue_info);
 
@@ -2189,13 +2819,15 @@ fn accepts_service_transaction(client_id: &str) -> bool {
 #[cfg(test)]
 mod tests {
 	use std::collections::{HashSet, VecDeque};
-	use network::PeerId;
+	use network::{PeerId, ReputationChange};
 	use tests::helpers::*;
 	use tests::snapshot::TestSnapshotService;
 	use util::{Uint, U256, Address, RwLock};
 	use util::sha3::Hashable;
-	use util::hash::H256;
+	use util::hash::{H64, H256};
 	use util::bytes::Bytes;
+	use fork_id::ForkId;
+	use util::ordered_trie_root;
 	use rlp::{Rlp, RlpStream, UntrustedRlp};
 	use super::*;
 	use ::SyncConfig;
@@ -2203,8 +2835,9 @@ mod tests {
 	use ethkey;
 	use ethcore::header::*;
 	use ethcore::client::*;
-	use ethcore::transaction::UnverifiedTransaction;
+	use ethcore::transaction::{Transaction, Action, UnverifiedTransaction};
 	use ethcore::miner::MinerService;
+	use ethkey::{Random, Generator};
 
 	fn get_dummy_block(order: u32, parent_hash: H256) -> Bytes {
 		let mut header = Header::new();
@@ -2222,6 +2855,35 @@ mod tests {
 		rlp.out()
 	}
 
+	fn get_dummy_block_with_transaction(order: u32, parent_hash: H256) -> (Bytes, Bytes) {
+		let keypair = Random.generate().unwrap();
+		let tx = Transaction {
+			action: Action::Create,
+			value: U256::from(100),
+			data: Vec::new(),
+			gas: U256::from(100_000),
+			gas_price: U256::from(200_000_000_000u64),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+		let tx_rlp = ::rlp::encode(&tx).to_vec();
+
+		let mut header = Header::new();
+		header.set_gas_limit(0.into());
+		header.set_difficulty((order * 100).into());
+		header.set_timestamp((order * 10) as u64);
+		header.set_number(order as u64);
+		header.set_parent_hash(parent_hash);
+		header.set_state_root(H256::zero());
+		header.set_transactions_root(ordered_trie_root(vec![tx_rlp.clone()]));
+
+		let mut rlp = RlpStream::new_list(3);
+		rlp.append(&header);
+		rlp.begin_list(1);
+		rlp.append_raw(&tx_rlp, 1);
+		rlp.append_raw(&::rlp::EMPTY_LIST_RLP, 1);
+		(rlp.out(), tx_rlp)
+	}
+
 	fn get_dummy_blocks(order: u32, parent_hash: H256) -> Bytes {
 		let mut rlp = RlpStream::new_list(1);
 		rlp.append_raw(&get_dummy_block(order, parent_hash), 1);
@@ -2329,6 +2991,24 @@ mod tests {
 		assert_eq!(1, io.packets.len());
 	}
 
+	#[test]
+	fn return_retained_snapshot_manifest() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new_with_snapshot(4, H256::new(), 30000);
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut request = RlpStream::new_list(1);
+		request.append(&30000u64);
+		let result = ChainSync::return_snapshot_manifest(&io, &UntrustedRlp::new(&request.out()), 0).unwrap().unwrap();
+		assert_eq!(Rlp::new(&result.1.out()).item_count(), 1);
+
+		let mut request = RlpStream::new_list(1);
+		request.append(&20000u64);
+		let result = ChainSync::return_snapshot_manifest(&io, &UntrustedRlp::new(&request.out()), 0).unwrap().unwrap();
+		assert_eq!(Rlp::new(&result.1.out()).item_count(), 0);
+	}
+
 	#[test]
 	fn return_block_headers() {
 		use ethcore::views::HeaderView;
@@ -2452,6 +3132,7 @@ mod tests {
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
+				fork_id: None,
 			});
 
 	}
@@ -2526,6 +3207,22 @@ mod tests {
 		assert_eq!(0x07, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn lowers_reputation_of_timed_out_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.peers.get_mut(&0).unwrap().asking = PeerAsking::BlockHeaders;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		sync.maintain_peers(&mut io);
+
+		assert!(io.to_disconnect.contains(&0));
+		assert_eq!(io.reputation.get(&0), Some(&ReputationChange::Timeout.delta()));
+	}
+
 	#[test]
 	fn sends_sealed_block() {
 		let mut client = TestBlockChainClient::new();
@@ -2573,6 +3270,7 @@ mod tests {
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
+				fork_id: None,
 			});
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2908,6 +3606,151 @@ mod tests {
 		assert!(result.is_ok());
 	}
 
+	#[test]
+	fn sends_compact_block_to_capable_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::UncleAndTransaction);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.peers.get_mut(&0).unwrap().protocol_version = PROTOCOL_VERSION_3;
+		let chain_info = client.chain_info();
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let peers = sync.get_lagging_peers(&chain_info);
+		let peer_count = sync.propagate_blocks(&chain_info, &mut io, &[], &peers);
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(1, peer_count);
+		assert_eq!(COMPACT_BLOCK_PACKET, io.packets[0].packet_id);
+		let rlp = UntrustedRlp::new(&io.packets[0].data);
+		assert_eq!(rlp.at(2).unwrap().item_count().unwrap(), 1);
+	}
+
+	#[test]
+	fn compact_block_rlp_mutually_acceptable() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::UncleAndTransaction);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.peers.get_mut(&0).unwrap().protocol_version = PROTOCOL_VERSION_3;
+		let chain_info = client.chain_info();
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let peers = sync.get_lagging_peers(&chain_info);
+		sync.propagate_blocks(&chain_info, &mut io, &[], &peers);
+
+		let data = &io.packets[0].data.clone();
+		let result = sync.on_peer_compact_block(&mut io, 0, &UntrustedRlp::new(data));
+		assert!(result.is_ok());
+	}
+
+	#[test]
+	fn rebuilds_compact_block_with_requested_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let (block, tx) = get_dummy_block_with_transaction(11, client.chain_info().best_block_hash);
+		let block_rlp = Rlp::new(&block);
+		let hash = block_rlp.at(0).as_raw().sha3();
+
+		let mut compact = RlpStream::new_list(4);
+		compact.append_raw(block_rlp.at(0).as_raw(), 1);
+		compact.append_raw(block_rlp.at(2).as_raw(), 1);
+		compact.append_list::<H64, H64>(&[H64::from(tx.sha3())]);
+		compact.append(&U256::from(1000));
+		let compact = compact.out();
+
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		// transaction is not queued, so it has to be requested
+		assert!(sync.on_peer_compact_block(&mut io, 0, &UntrustedRlp::new(&compact)).is_ok());
+		assert_eq!(1, io.packets.len());
+		assert_eq!(GET_BLOCK_TRANSACTIONS_PACKET, io.packets[0].packet_id);
+		assert_eq!(1, sync.compact_blocks.len());
+
+		let mut response = RlpStream::new_list(2);
+		response.append(&hash);
+		response.begin_list(1);
+		response.append_raw(&tx, 1);
+		let response = response.out();
+		assert!(sync.on_peer_block_transactions(&mut io, 0, &UntrustedRlp::new(&response)).is_ok());
+
+		assert!(sync.compact_blocks.is_empty());
+		assert_eq!(io.chain.chain_info().best_block_hash, hash);
+	}
+
+	#[test]
+	fn downloads_compact_block_when_too_many_pending() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let (block, tx) = get_dummy_block_with_transaction(11, client.chain_info().best_block_hash);
+		let block_rlp = Rlp::new(&block);
+
+		let mut compact = RlpStream::new_list(4);
+		compact.append_raw(block_rlp.at(0).as_raw(), 1);
+		compact.append_raw(block_rlp.at(2).as_raw(), 1);
+		compact.append_list::<H64, H64>(&[H64::from(tx.sha3())]);
+		compact.append(&U256::from(1000));
+		let compact = compact.out();
+
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.active_peers.insert(0);
+		for i in 0..MAX_PENDING_COMPACT_BLOCKS {
+			sync.compact_blocks.insert(H256::from(i as u64), CompactBlock {
+				peer: 0,
+				header: Vec::new(),
+				uncles: Vec::new(),
+				difficulty: U256::zero(),
+				transactions: vec![None],
+				full: false,
+				received: time::precise_time_ns(),
+			});
+		}
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		assert!(sync.on_peer_compact_block(&mut io, 0, &UntrustedRlp::new(&compact)).is_ok());
+
+		assert_eq!(MAX_PENDING_COMPACT_BLOCKS, sync.compact_blocks.len());
+		assert!(io.packets.iter().all(|p| p.packet_id != GET_BLOCK_TRANSACTIONS_PACKET));
+		assert!(io.packets.iter().any(|p| p.packet_id == GET_BLOCK_HEADERS_PACKET));
+	}
+
+	#[test]
+	fn return_block_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(2, EachBlockWith::Transaction);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+		let hash = io.chain.chain_info().best_block_hash;
+
+		let request = |indexes: &[usize]| {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&hash);
+			rlp.append_list::<usize, usize>(indexes);
+			rlp.out()
+		};
+
+		let result = ChainSync::return_block_transactions(&io, &UntrustedRlp::new(&request(&[0])), 0);
+		let (packet_id, rlp) = result.unwrap().unwrap();
+		assert_eq!(BLOCK_TRANSACTIONS_PACKET, packet_id);
+		assert_eq!(1, Rlp::new(&rlp.out()).at(1).item_count());
+
+		let result = ChainSync::return_block_transactions(&io, &UntrustedRlp::new(&request(&[1])), 0);
+		let (_, rlp) = result.unwrap().unwrap();
+		assert_eq!(0, Rlp::new(&rlp.out()).at(1).item_count());
+
+		// duplicate indexes are rejected
+		let result = ChainSync::return_block_transactions(&io, &UntrustedRlp::new(&request(&[0, 0])), 0);
+		let (_, rlp) = result.unwrap().unwrap();
+		assert_eq!(0, Rlp::new(&rlp.out()).at(1).item_count());
+	}
+
 	#[test]
 	fn should_add_transactions_to_queue() {
 		fn sender(tx: &UnverifiedTransaction) -> Address {
@@ -2989,4 +3832,211 @@ mod tests {
 		assert_eq!(status.transactions_in_pending_queue, 0);
 		assert_eq!(status.transactions_in_future_queue, 0);
 	}
+
+	#[test]
+	fn restarts_stalled_sync() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.get_mut(&0).unwrap().difficulty = Some(U256::max_value());
+		sync.state = SyncState::Blocks;
+
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		// `ChainSync::new` recorded the best block, so a check right away is not a stall
+		sync.check_stalled(&mut io);
+		assert_eq!(sync.last_progress.0, 10);
+		assert_eq!(sync.stall_recoveries, 0);
+
+		sync.last_progress.1 = sync.last_progress.1.saturating_sub((STALL_TIMEOUT_SEC + 1) * 1_000_000_000);
+		sync.check_stalled(&mut io);
+
+		assert_eq!(sync.stall_recoveries, 1);
+		assert_eq!(sync.recent_errors.len(), 1);
+		assert!(io.to_disconnect.contains(&0));
+		assert!(!sync.peers.contains_key(&0));
+	}
+
+	#[test]
+	fn keeps_bounded_error_log() {
+		let client = TestBlockChainClient::new();
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		for i in 0..(MAX_RECENT_ERRORS + 5) {
+			sync.note_error(Some(i), format!("error {}", i));
+		}
+
+		let diagnostics = sync.diagnostics();
+		assert_eq!(diagnostics.recent_errors.len(), MAX_RECENT_ERRORS);
+		assert_eq!(diagnostics.recent_errors[0].peer, Some(5));
+		assert_eq!(diagnostics.stall_recoveries, 0);
+	}
+
+	#[test]
+	fn announces_transaction_hashes_to_capable_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let hash = client.insert_transaction_to_queue();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.get_mut(&0).unwrap().protocol_version = PROTOCOL_VERSION_4;
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let peer_count = sync.propagate_new_transactions(&mut io);
+
+		assert_eq!(1, peer_count);
+		assert_eq!(1, io.packets.len());
+		assert_eq!(NEW_TRANSACTION_HASHES_PACKET, io.packets[0].packet_id);
+		let hashes: Vec<H256> = UntrustedRlp::new(&io.packets[0].data).as_list().unwrap();
+		assert_eq!(hashes, vec![hash]);
+		assert!(sync.transactions_stats().contains_key(&hash));
+	}
+
+	#[test]
+	fn caps_first_announcement_to_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.get_mut(&0).unwrap().protocol_version = PROTOCOL_VERSION_4;
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let transactions = (0..MAX_TRANSACTION_HASHES_TO_ANNOUNCE + 1).map(|nonce| {
+			let tx = Transaction { nonce: (nonce as u64).into(), ..Default::default() };
+			PendingTransaction::new(tx.fake_sign(Address::from(1)), None)
+		}).collect();
+
+		sync.propagate_transactions_to_peers(&mut io, vec![0], transactions);
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(MAX_TRANSACTION_HASHES_TO_ANNOUNCE, UntrustedRlp::new(&io.packets[0].data).item_count().unwrap());
+		assert_eq!(MAX_TRANSACTION_HASHES_TO_ANNOUNCE, sync.peers[&0].last_sent_transactions.len());
+	}
+
+	#[test]
+	fn requests_announced_transactions_once() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(1));
+		sync.peers.get_mut(&0).unwrap().protocol_version = PROTOCOL_VERSION_4;
+		sync.peers.get_mut(&1).unwrap().protocol_version = PROTOCOL_VERSION_4;
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let hashes = {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(&H256::from(1));
+			rlp.append(&H256::from(2));
+			rlp.out()
+		};
+		sync.on_packet(&mut io, 0, NEW_TRANSACTION_HASHES_PACKET, &hashes);
+		sync.on_packet(&mut io, 1, NEW_TRANSACTION_HASHES_PACKET, &hashes);
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(GET_TRANSACTIONS_PACKET, io.packets[0].packet_id);
+		assert_eq!(0, io.packets[0].recipient);
+		assert_eq!(2, UntrustedRlp::new(&io.packets[0].data).item_count().unwrap());
+
+		// the other peer is asked after the first one disconnects
+		sync.on_peer_aborting(&mut io, 0);
+		sync.request_transactions(&mut io);
+		let requests: Vec<_> = io.packets.iter().filter(|p| p.packet_id == GET_TRANSACTIONS_PACKET).collect();
+		assert_eq!(2, requests.len());
+		assert_eq!(1, requests[1].recipient);
+	}
+
+	#[test]
+	fn return_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let hash = client.insert_transaction_to_queue();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&hash);
+		request.append(&H256::from(1));
+		let result = ChainSync::return_transactions(&io, &UntrustedRlp::new(&request.out()), 0);
+		let (packet_id, rlp) = result.unwrap().unwrap();
+
+		assert_eq!(TRANSACTIONS_PACKET, packet_id);
+		let transactions: Vec<UnverifiedTransaction> = UntrustedRlp::new(&rlp.out()).as_list().unwrap();
+		assert_eq!(1, transactions.len());
+		assert_eq!(hash, transactions[0].hash());
+	}
+
+	fn status_packet(client: &BlockChainClient, fork_id: ForkId) -> Bytes {
+		let chain_info = client.chain_info();
+		let mut packet = RlpStream::new_list(8);
+		packet.append(&(PROTOCOL_VERSION_5 as u32));
+		packet.append(&1u64);
+		packet.append(&chain_info.total_difficulty);
+		packet.append(&chain_info.best_block_hash);
+		packet.append(&chain_info.genesis_hash);
+		packet.append(&H256::new());
+		packet.append(&0u64);
+		packet.begin_list(2).append(&fork_id.hash).append(&fork_id.next);
+		packet.out()
+	}
+
+	#[test]
+	fn sends_fork_id_in_status() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0));
+		io.warp_protocol_version = PROTOCOL_VERSION_5;
+
+		sync.on_peer_connected(&mut io, 0);
+
+		assert_eq!(1, io.packets.len());
+		assert_eq!(STATUS_PACKET, io.packets[0].packet_id);
+		let rlp = UntrustedRlp::new(&io.packets[0].data);
+		assert_eq!(8, rlp.item_count().unwrap());
+		let fork_id = rlp.at(7).unwrap();
+		assert_eq!(sync.fork_filter.fork_id(10), ForkId { hash: fork_id.val_at(0).unwrap(), next: fork_id.val_at(1).unwrap() });
+	}
+
+	#[test]
+	fn accepts_peer_with_compatible_fork_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let status = status_packet(&client, sync.fork_filter.fork_id(10));
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0));
+		io.warp_protocol_version = PROTOCOL_VERSION_5;
+
+		sync.on_packet(&mut io, 0, STATUS_PACKET, &status);
+
+		assert!(sync.peers.get(&0).map_or(false, |p| p.fork_id.is_some()));
+		assert!(io.to_disconnect.is_empty());
+	}
+
+	#[test]
+	fn rejects_peer_with_incompatible_fork_id() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		let mut fork_id = sync.fork_filter.fork_id(10);
+		fork_id.hash ^= 1;
+		let status = status_packet(&client, fork_id);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, Some(0));
+		io.warp_protocol_version = PROTOCOL_VERSION_5;
+
+		sync.on_packet(&mut io, 0, STATUS_PACKET, &status);
+
+		assert!(!sync.peers.contains_key(&0));
+		assert!(io.to_disconnect.contains(&0));
+		assert_eq!(1, sync.recent_errors.len());
+	}
 }
@@ -0,0 +1,172 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain compatibility identifier exchanged in the status handshake.
+//!
+//! The identifier is a checksum of the genesis hash and all transitions passed at the
+//! current best block, together with the next scheduled transition. Comparing it with
+//! our own transitions tells whether a peer follows the same fork history without
+//! downloading any headers.
+
+use util::H256;
+use util::sha3::Hashable;
+use ethcore::header::BlockNumber;
+
+/// Fork identifier announced by a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// Checksum of the genesis hash and passed transitions.
+	pub hash: u32,
+	/// Next scheduled transition or 0 if none is known.
+	pub next: BlockNumber,
+}
+
+/// Computes local fork identifiers and validates remote ones.
+#[derive(Debug, Clone)]
+pub struct ForkFilter {
+	/// Transition blocks, sorted.
+	forks: Vec<BlockNumber>,
+	/// Checksum after passing the first `i` transitions.
+	hashes: Vec<u32>,
+}
+
+fn checksum(data: &[u8]) -> u32 {
+	let hash = data.sha3();
+	((hash[0] as u32) << 24) | ((hash[1] as u32) << 16) | ((hash[2] as u32) << 8) | (hash[3] as u32)
+}
+
+fn checksum_update(hash: u32, fork: BlockNumber) -> u32 {
+	let mut data = Vec::with_capacity(12);
+	data.extend((0..4).rev().map(|i| (hash >> (8 * i)) as u8));
+	data.extend((0..8).rev().map(|i| (fork >> (8 * i)) as u8));
+	checksum(&data)
+}
+
+impl ForkFilter {
+	/// Create a new filter for the chain with given genesis hash and transition blocks.
+	pub fn new(genesis: &H256, mut forks: Vec<BlockNumber>) -> Self {
+		forks.retain(|n| *n != 0);
+		forks.sort();
+		forks.dedup();
+		let mut hashes = Vec::with_capacity(forks.len() + 1);
+		let mut hash = checksum(&genesis[..]);
+		hashes.push(hash);
+		for fork in &forks {
+			hash = checksum_update(hash, *fork);
+			hashes.push(hash);
+		}
+		ForkFilter {
+			forks: forks,
+			hashes: hashes,
+		}
+	}
+
+	fn passed(&self, best_block: BlockNumber) -> usize {
+		self.forks.iter().take_while(|f| **f <= best_block).count()
+	}
+
+	/// Fork identifier at the given best block.
+	pub fn fork_id(&self, best_block: BlockNumber) -> ForkId {
+		let passed = self.passed(best_block);
+		ForkId {
+			hash: self.hashes[passed],
+			next: self.forks.get(passed).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Returns `true` if `number` is one of the transition blocks and `remote` has already passed it.
+	pub fn passed_transition(&self, remote: &ForkId, number: BlockNumber) -> bool {
+		match (self.forks.binary_search(&number), self.hashes.iter().position(|h| *h == remote.hash)) {
+			(Ok(fork), Some(passed)) => passed > fork,
+			_ => false,
+		}
+	}
+
+	/// Checks whether a peer announcing `remote` may follow our chain, given our best block.
+	pub fn is_compatible(&self, best_block: BlockNumber, remote: &ForkId) -> bool {
+		let passed = self.passed(best_block);
+		match self.hashes.iter().position(|h| *h == remote.hash) {
+			// Same transitions passed. Remote must not expect a transition we have already passed.
+			Some(i) if i == passed => remote.next == 0 || remote.next > best_block,
+			// Remote is behind. It must know about the next transition it has to pass.
+			Some(i) if i < passed => remote.next == self.forks[i],
+			// Remote is ahead and all transitions it passed are known to us.
+			Some(_) => true,
+			None => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::H256;
+	use super::{ForkFilter, ForkId};
+
+	fn filter() -> ForkFilter {
+		ForkFilter::new(&H256::from(1), vec![300, 0, 100, 200, 100])
+	}
+
+	#[test]
+	fn fork_id_changes_at_transitions() {
+		let filter = filter();
+
+		assert_eq!(filter.fork_id(0).next, 100);
+		assert_eq!(filter.fork_id(99), filter.fork_id(0));
+		assert_eq!(filter.fork_id(100).next, 200);
+		assert!(filter.fork_id(100).hash != filter.fork_id(99).hash);
+		assert_eq!(filter.fork_id(1000).next, 0);
+		assert!(ForkFilter::new(&H256::from(2), vec![100, 200, 300]).fork_id(0) != filter.fork_id(0));
+	}
+
+	#[test]
+	fn accepts_peers_on_the_same_chain() {
+		let filter = filter();
+
+		// same state
+		assert!(filter.is_compatible(150, &filter.fork_id(150)));
+		// remote is syncing
+		assert!(filter.is_compatible(150, &filter.fork_id(50)));
+		// we are syncing
+		assert!(filter.is_compatible(150, &filter.fork_id(250)));
+		// remote knows about a future transition we do not know yet
+		assert!(filter.is_compatible(1000, &ForkId { hash: filter.fork_id(1000).hash, next: 2000 }));
+	}
+
+	#[test]
+	fn detects_passed_transitions() {
+		let filter = filter();
+
+		assert!(filter.passed_transition(&filter.fork_id(250), 200));
+		assert!(!filter.passed_transition(&filter.fork_id(150), 200));
+		assert!(!filter.passed_transition(&filter.fork_id(250), 150));
+		assert!(!filter.passed_transition(&ForkId { hash: 0, next: 0 }, 100));
+	}
+
+	#[test]
+	fn rejects_peers_on_other_forks() {
+		let filter = filter();
+		let other = ForkFilter::new(&H256::from(1), vec![100, 250, 300]);
+
+		// remote did not pass a transition we passed
+		assert!(!filter.is_compatible(210, &other.fork_id(210)));
+		// remote expects a transition we passed without it
+		assert!(!filter.is_compatible(260, &ForkId { hash: filter.fork_id(200).hash, next: 250 }));
+		// remote is behind and does not know the next transition
+		assert!(!filter.is_compatible(250, &ForkId { hash: filter.fork_id(150).hash, next: 0 }));
+		// unknown genesis
+		assert!(!filter.is_compatible(0, &ForkFilter::new(&H256::from(2), vec![]).fork_id(0)));
+	}
+}
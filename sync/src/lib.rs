@@ -39,6 +39,7 @@ extern crate smallvec;
 extern crate rlp;
 
 extern crate ethcore_light as light;
+extern crate metrics;
 
 #[cfg(test)] extern crate ethcore_devtools as devtools;
 #[cfg(test)] extern crate ethkey;
@@ -58,6 +59,9 @@ mod block_sync;
 mod sync_io;
 mod snapshot;
 mod transactions_stats;
+mod transactions_fetch;
+mod fork_id;
+mod private_tx;
 
 pub mod light_sync;
 
@@ -74,8 +78,10 @@ mod api {
 mod api;
 
 pub use api::*;
-pub use chain::{SyncStatus, SyncState};
-pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError};
+pub use chain::{SyncStatus, SyncState, SyncDiagnostics, PeerSyncInfo, SyncError};
+pub use block_sync::DownloaderDiagnostics;
+pub use private_tx::PrivateTxHandler;
+pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError, ConnectionFilter, ConnectionDirection};
 
 /// IPC interfaces
 #[cfg(feature="ipc")]
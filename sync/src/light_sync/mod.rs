@@ -43,7 +43,7 @@ use light::net::{
 	Capabilities, ReqId, Status, Error as NetError,
 };
 use light::request::{self, CompleteHeadersRequest as HeadersRequest};
-use network::PeerId;
+use network::{PeerId, ReputationChange};
 use util::{U256, H256, Mutex, RwLock};
 use rand::{Rng, OsRng};
 
@@ -214,7 +214,10 @@ impl<'a> ResponseContext for ResponseCtx<'a> {
 	fn responder(&self) -> PeerId { self.peer }
 	fn req_id(&self) -> &ReqId { &self.req_id }
 	fn data(&self) -> &[encoded::Header] { self.data }
-	fn punish_responder(&self) { self.ctx.disable_peer(self.peer) }
+	fn punish_responder(&self) {
+		self.ctx.note_reputation(self.peer, ReputationChange::BadBlock);
+		self.ctx.disable_peer(self.peer)
+	}
 }
 
 /// Light client synchronization manager. See module docs for more details.
@@ -350,6 +353,10 @@ impl<L: AsLightClient + Send + Sync> Handler for LightSync<L> {
 			None => &[],
 		};
 
+		if !headers.is_empty() {
+			ctx.note_reputation(peer, ReputationChange::UsefulData);
+		}
+
 		{
 			let mut state = self.state.lock();
 
@@ -458,6 +465,7 @@ impl<L: AsLightClient> LightSync<L> {
 						AbortReason::BadScaffold(bad_peers) => {
 							debug!(target: "sync", "Disabling peers responsible for bad scaffold");
 							for peer in bad_peers {
+								ctx.note_reputation(peer, ReputationChange::BadBlock);
 								ctx.disable_peer(peer);
 							}
 						}
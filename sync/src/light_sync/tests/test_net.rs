@@ -29,7 +29,7 @@ use light::client::Client as LightClient;
 use light::net::{LightProtocol, IoContext, Capabilities, Params as LightParams};
 use light::net::request_credits::FlowParams;
 use light::provider::LightProvider;
-use network::{NodeId, PeerId};
+use network::{NodeId, PeerId, ReputationChange};
 use util::RwLock;
 
 use time::Duration;
@@ -63,6 +63,7 @@ impl<'a> IoContext for TestIoContext<'a> {
 	}
 
 	fn disable_peer(&self, peer: PeerId) { self.disconnect_peer(peer) }
+	fn note_reputation(&self, _peer: PeerId, _change: ReputationChange) { }
 	fn protocol_version(&self, _peer: PeerId) -> Option<u8> { Some(::light::net::MAX_PROTOCOL_VERSION) }
 
 	fn persistent_peer_id(&self, _peer: PeerId) -> Option<NodeId> { unimplemented!() }
@@ -0,0 +1,180 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Private transaction subprotocol. Relays encrypted private transactions and the
+//! validators' signatures between the nodes taking part in private contracts.
+//!
+//! Private transactions are sent to all peers. Signatures are only needed by the originator,
+//! so they travel back along the path the private transaction took.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use network::{NetworkProtocolHandler, NetworkContext, PeerId, PacketId};
+use parking_lot::{Mutex, RwLock};
+use rlp::UntrustedRlp;
+use util::{H256, Hashable};
+
+/// Private transaction packet.
+pub const PRIVATE_TRANSACTION_PACKET: PacketId = 0x00;
+/// Validator's signature of a private transaction.
+pub const SIGNED_PRIVATE_TRANSACTION_PACKET: PacketId = 0x01;
+/// Number of packets in the subprotocol.
+pub const PRIVATE_TX_PACKET_COUNT: u8 = 0x02;
+/// Supported subprotocol versions.
+pub const PRIVATE_TX_PROTOCOL_VERSIONS: &'static [u8] = &[1u8];
+
+/// Number of recently relayed packets remembered to avoid relaying them again.
+const MAX_SEEN_PACKETS: usize = 4096;
+/// Number of private transactions for which the peer they came from is remembered.
+const MAX_ROUTES: usize = 4096;
+
+/// Handles private transaction messages received from peers.
+/// Messages are only queued here and must be imported off the network thread.
+/// The handler is responsible for relaying the messages it imported successfully.
+pub trait PrivateTxHandler: Send + Sync {
+	/// Queue an encrypted private transaction for import. Returns its hash.
+	fn queue_private_transaction(&self, rlp: &[u8]) -> Result<H256, String>;
+
+	/// Queue a validator's signature of a private transaction for import. Returns its hash.
+	fn queue_signed_private_transaction(&self, rlp: &[u8]) -> Result<H256, String>;
+}
+
+/// Protocol handler relaying private transaction messages between peers supporting the subprotocol.
+pub struct PrivateTxProtocolHandler {
+	handler: Arc<PrivateTxHandler>,
+	peers: RwLock<HashSet<PeerId>>,
+	seen: Mutex<(HashSet<H256>, VecDeque<H256>)>,
+	/// Peer each private transaction was first received from.
+	routes: Mutex<(HashMap<H256, PeerId>, VecDeque<H256>)>,
+}
+
+impl PrivateTxProtocolHandler {
+	/// Create a new handler.
+	pub fn new(handler: Arc<PrivateTxHandler>) -> Self {
+		PrivateTxProtocolHandler {
+			handler: handler,
+			peers: RwLock::new(HashSet::new()),
+			seen: Mutex::new((HashSet::new(), VecDeque::new())),
+			routes: Mutex::new((HashMap::new(), VecDeque::new())),
+		}
+	}
+
+	/// Send a private transaction to all peers but the one it came from, or a signature
+	/// to the peer the signed private transaction came from.
+	pub fn propagate(&self, io: &NetworkContext, packet_id: PacketId, data: Vec<u8>, from: Option<PeerId>) {
+		if from.is_none() {
+			// don't import our own packets when peers relay them back.
+			self.note_seen(data.sha3());
+		}
+
+		let targets: Vec<PeerId> = {
+			let peers = self.peers.read();
+			match packet_id {
+				SIGNED_PRIVATE_TRANSACTION_PACKET => match UntrustedRlp::new(&data).val_at::<H256>(0) {
+					Ok(private_hash) => self.routes.lock().0.get(&private_hash).cloned()
+						.into_iter()
+						.filter(|peer| peers.contains(peer))
+						.collect(),
+					Err(_) => Vec::new(),
+				},
+				_ => peers.iter().cloned().collect(),
+			}
+		};
+
+		for peer in targets.into_iter().filter(|peer| Some(*peer) != from) {
+			if let Err(e) = io.send(peer, packet_id, data.clone()) {
+				debug!(target: "privatetx", "Error sending private transaction packet to peer {}: {:?}", peer, e);
+			}
+		}
+	}
+
+	/// Remember the peer a private transaction was first received from.
+	fn note_route(&self, private_hash: H256, peer: PeerId) {
+		let mut routes = self.routes.lock();
+		if routes.0.contains_key(&private_hash) {
+			return;
+		}
+
+		routes.0.insert(private_hash, peer);
+		routes.1.push_back(private_hash);
+		if routes.1.len() > MAX_ROUTES {
+			if let Some(old) = routes.1.pop_front() {
+				routes.0.remove(&old);
+			}
+		}
+	}
+
+	/// Check whether a packet hash was seen before.
+	fn is_seen(&self, hash: &H256) -> bool {
+		self.seen.lock().0.contains(hash)
+	}
+
+	/// Remember a packet hash. Returns `false` if it was seen before.
+	fn note_seen(&self, hash: H256) -> bool {
+		let mut seen = self.seen.lock();
+		if !seen.0.insert(hash) {
+			return false;
+		}
+
+		seen.1.push_back(hash);
+		if seen.1.len() > MAX_SEEN_PACKETS {
+			if let Some(old) = seen.1.pop_front() {
+				seen.0.remove(&old);
+			}
+		}
+		true
+	}
+}
+
+impl NetworkProtocolHandler for PrivateTxProtocolHandler {
+	fn read(&self, _io: &NetworkContext, peer: &PeerId, packet_id: u8, data: &[u8]) {
+		let packet_hash = data.sha3();
+		if self.is_seen(&packet_hash) {
+			return;
+		}
+
+		let result = match packet_id {
+			PRIVATE_TRANSACTION_PACKET => self.handler.queue_private_transaction(data),
+			SIGNED_PRIVATE_TRANSACTION_PACKET => self.handler.queue_signed_private_transaction(data),
+			_ => {
+				debug!(target: "privatetx", "Unknown private transaction packet {} from peer {}", packet_id, peer);
+				return;
+			}
+		};
+
+		// the message is relayed by the handler once imported. Packets which couldn't be queued
+		// aren't marked as seen, so they are accepted when received again.
+		match result {
+			Ok(hash) => {
+				self.note_seen(packet_hash);
+				if packet_id == PRIVATE_TRANSACTION_PACKET {
+					self.note_route(hash, *peer);
+				}
+				trace!(target: "privatetx", "Queued private transaction packet {} from peer {}", hash, peer);
+			},
+			Err(e) => debug!(target: "privatetx", "Error queueing private transaction packet from peer {}: {}", peer, e),
+		}
+	}
+
+	fn connected(&self, _io: &NetworkContext, peer: &PeerId) {
+		self.peers.write().insert(*peer);
+	}
+
+	fn disconnected(&self, _io: &NetworkContext, peer: &PeerId) {
+		self.peers.write().remove(peer);
+	}
+}
@@ -67,6 +67,16 @@ impl Snapshot {
 		self.snapshot_hash = Some(hash.clone());
 	}
 
+	/// Mark chunks restored by an earlier, interrupted restoration as completed
+	/// so they aren't downloaded again. Hashes not in the manifest are ignored.
+	pub fn note_completed(&mut self, hashes: &[H256]) {
+		for hash in hashes {
+			if self.pending_state_chunks.contains(hash) || self.pending_block_chunks.contains(hash) {
+				self.completed_chunks.insert(hash.clone());
+			}
+		}
+	}
+
 	/// Validate chunk and mark it as downloaded
 	pub fn validate_chunk(&mut self, chunk: &[u8]) -> Result<ChunkType, ()> {
 		let hash = chunk.sha3();
@@ -205,5 +215,22 @@ mod test {
 		assert_eq!(snapshot.done_chunks(), snapshot.total_chunks());
 		assert_eq!(snapshot.snapshot_hash(), Some(manifest.into_rlp().sha3()));
 	}
+
+	#[test]
+	fn skips_completed_chunks() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, state_chunks, _) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+
+		let mut completed = manifest.state_hashes.clone();
+		completed.push(H256::random());
+		snapshot.note_completed(&completed);
+		assert_eq!(snapshot.done_chunks(), 20);
+		assert!(snapshot.validate_chunk(&state_chunks[0]).is_err());
+
+		let requested: Vec<H256> = (0..20).map(|_| snapshot.needed_chunk().unwrap()).collect();
+		assert!(snapshot.needed_chunk().is_none());
+		assert_eq!(&requested[..], &manifest.block_hashes[..]);
+	}
 }
 
@@ -49,6 +49,8 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub to_disconnect: HashSet<PeerId>,
 	pub packets: Vec<TestPacket>,
 	pub peers_info: HashMap<PeerId, String>,
+	pub reputation: HashMap<PeerId, i32>,
+	pub warp_protocol_version: u8,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
 }
 
@@ -63,6 +65,8 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			overlay: RwLock::new(HashMap::new()),
 			packets: Vec::new(),
 			peers_info: HashMap::new(),
+			reputation: HashMap::new(),
+			warp_protocol_version: 2,
 		}
 	}
 }
@@ -82,6 +86,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.to_disconnect.insert(peer_id);
 	}
 
+	fn note_reputation(&mut self, peer_id: PeerId, change: ReputationChange) {
+		*self.reputation.entry(peer_id).or_insert(0) += change.delta();
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
@@ -131,7 +139,7 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	}
 
 	fn protocol_version(&self, protocol: &ProtocolId, peer_id: PeerId) -> u8 {
-		if protocol == &WARP_SYNC_PROTOCOL_ID { 2 } else { self.eth_protocol_version(peer_id) }
+		if protocol == &WARP_SYNC_PROTOCOL_ID { self.warp_protocol_version } else { self.eth_protocol_version(peer_id) }
 	}
 
 	fn chain_overlay(&self) -> &RwLock<HashMap<BlockNumber, Bytes>> {
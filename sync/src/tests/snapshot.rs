@@ -74,6 +74,13 @@ impl SnapshotService for TestSnapshotService {
 		self.manifest.as_ref().cloned()
 	}
 
+	fn manifest_at(&self, block_number: u64) -> Option<ManifestData> {
+		match self.manifest {
+			Some(ref m) if m.block_number == block_number => Some(m.clone()),
+			_ => None,
+		}
+	}
+
 	fn chunk(&self, hash: H256) -> Option<Bytes> {
 		self.chunks.get(&hash).cloned()
 	}
@@ -93,9 +100,12 @@ impl SnapshotService for TestSnapshotService {
 	}
 
 	fn begin_restore(&self, manifest: ManifestData) {
-		*self.restoration_manifest.lock() = Some(manifest);
-		self.state_restoration_chunks.lock().clear();
-		self.block_restoration_chunks.lock().clear();
+		let mut restoration_manifest = self.restoration_manifest.lock();
+		if restoration_manifest.as_ref() != Some(&manifest) {
+			self.state_restoration_chunks.lock().clear();
+			self.block_restoration_chunks.lock().clear();
+		}
+		*restoration_manifest = Some(manifest);
 	}
 
 	fn abort_restore(&self) {
@@ -116,6 +126,16 @@ impl SnapshotService for TestSnapshotService {
 		}
 	}
 
+	fn completed_chunks(&self, manifest: ManifestData) -> Vec<H256> {
+		match *self.restoration_manifest.lock() {
+			Some(ref m) if m == &manifest => self.state_restoration_chunks.lock().keys()
+				.chain(self.block_restoration_chunks.lock().keys())
+				.cloned()
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
+
 	fn provide_canon_hashes(&self, hashes: &[(u64, H256)]) {
 		self.canon_hashes.lock().extend(hashes.iter().cloned());
 	}
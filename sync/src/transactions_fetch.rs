@@ -0,0 +1,215 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transactions announced by hash and not yet received.
+//! Each transaction is requested from a single peer at a time; other peers that
+//! announced it are kept as fallback in case the request times out.
+
+use std::collections::HashMap;
+use network::PeerId;
+use util::H256;
+use util::hash::H256FastMap;
+
+/// Maximum number of announced transactions being tracked.
+const MAX_PENDING_ANNOUNCEMENTS: usize = 8192;
+
+#[derive(Debug)]
+struct Announcement {
+	/// Peers that announced the transaction and were not asked for it yet.
+	peers: Vec<PeerId>,
+	/// Peer the transaction is currently requested from and the request timestamp.
+	requested: Option<(PeerId, u64)>,
+}
+
+#[derive(Debug, Default)]
+pub struct TransactionsFetch {
+	announced: H256FastMap<Announcement>,
+}
+
+impl TransactionsFetch {
+	/// Notes transaction hash announced by a peer.
+	/// Returns `false` if the announcement was dropped because too many are pending.
+	pub fn announce(&mut self, peer: PeerId, hash: H256) -> bool {
+		if !self.announced.contains_key(&hash) && self.announced.len() >= MAX_PENDING_ANNOUNCEMENTS {
+			return false;
+		}
+		let announcement = self.announced.entry(hash).or_insert_with(|| Announcement {
+			peers: Vec::new(),
+			requested: None,
+		});
+		let requested_from_peer = announcement.requested.map_or(false, |(p, _)| p == peer);
+		if !requested_from_peer && !announcement.peers.contains(&peer) {
+			announcement.peers.push(peer);
+		}
+		true
+	}
+
+	/// Assigns announced transactions that are not being requested to announcing peers.
+	/// Every peer gets at most `max_per_peer` transactions in flight.
+	/// Returns a batch of hashes to request for each peer.
+	pub fn requests(&mut self, now: u64, max_per_peer: usize) -> Vec<(PeerId, Vec<H256>)> {
+		let mut in_flight: HashMap<PeerId, usize> = HashMap::new();
+		for announcement in self.announced.values() {
+			if let Some((peer, _)) = announcement.requested {
+				*in_flight.entry(peer).or_insert(0) += 1;
+			}
+		}
+
+		let mut batches: HashMap<PeerId, Vec<H256>> = HashMap::new();
+		for (hash, announcement) in &mut self.announced {
+			if announcement.requested.is_some() {
+				continue;
+			}
+			let index = announcement.peers.iter()
+				.position(|p| in_flight.get(p).map_or(true, |count| *count < max_per_peer));
+			if let Some(index) = index {
+				let peer = announcement.peers.remove(index);
+				*in_flight.entry(peer).or_insert(0) += 1;
+				announcement.requested = Some((peer, now));
+				batches.entry(peer).or_insert_with(Vec::new).push(hash.clone());
+			}
+		}
+		batches.into_iter().collect()
+	}
+
+	/// Notes received transaction.
+	pub fn received(&mut self, hash: &H256) {
+		self.announced.remove(hash);
+	}
+
+	/// Cancels requests sent before `now - timeout`, so that the transactions could be
+	/// requested from other peers. Returns peers that did not respond in time.
+	pub fn expire(&mut self, now: u64, timeout: u64) -> Vec<PeerId> {
+		let mut expired = Vec::new();
+		for announcement in self.announced.values_mut() {
+			if let Some((peer, time)) = announcement.requested {
+				if now.saturating_sub(time) > timeout {
+					announcement.requested = None;
+					if !expired.contains(&peer) {
+						expired.push(peer);
+					}
+				}
+			}
+		}
+		self.announced.retain(|_, a| a.requested.is_some() || !a.peers.is_empty());
+		expired
+	}
+
+	/// Forgets all announcements and requests of a disconnected peer.
+	pub fn remove_peer(&mut self, peer: PeerId) {
+		for announcement in self.announced.values_mut() {
+			announcement.peers.retain(|p| *p != peer);
+			if announcement.requested.map_or(false, |(p, _)| p == peer) {
+				announcement.requested = None;
+			}
+		}
+		self.announced.retain(|_, a| a.requested.is_some() || !a.peers.is_empty());
+	}
+
+	/// Returns `true` if the transaction was announced and not received yet.
+	#[cfg(test)]
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.announced.contains_key(hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::H256;
+	use super::TransactionsFetch;
+
+	#[test]
+	fn should_request_each_transaction_once() {
+		// given
+		let mut fetch = TransactionsFetch::default();
+		let hash1: H256 = 1.into();
+		let hash2: H256 = 2.into();
+		fetch.announce(1, hash1);
+		fetch.announce(2, hash1);
+		fetch.announce(2, hash2);
+
+		// when
+		let requests = fetch.requests(0, 16);
+
+		// then
+		assert_eq!(requests.iter().map(|&(_, ref h)| h.len()).sum::<usize>(), 2);
+		assert!(fetch.requests(0, 16).is_empty());
+		fetch.announce(3, hash2);
+		assert!(fetch.requests(0, 16).is_empty());
+	}
+
+	#[test]
+	fn should_limit_requests_per_peer() {
+		// given
+		let mut fetch = TransactionsFetch::default();
+		for i in 0..10u64 {
+			fetch.announce(1, i.into());
+		}
+
+		// when
+		let requests = fetch.requests(0, 4);
+
+		// then
+		assert_eq!(requests, vec![(1, requests[0].1.clone())]);
+		assert_eq!(requests[0].1.len(), 4);
+		assert_eq!(fetch.requests(0, 4).len(), 0);
+		assert!(fetch.contains(&9.into()));
+	}
+
+	#[test]
+	fn should_rerequest_from_other_peer_on_timeout() {
+		// given
+		let mut fetch = TransactionsFetch::default();
+		let hash: H256 = 1.into();
+		fetch.announce(1, hash);
+		fetch.announce(2, hash);
+		let first = fetch.requests(0, 16);
+		assert_eq!(first.len(), 1);
+
+		// when
+		let expired = fetch.expire(100, 10);
+		let second = fetch.requests(100, 16);
+
+		// then
+		assert_eq!(expired, vec![first[0].0]);
+		assert_eq!(second.len(), 1);
+		assert!(second[0].0 != first[0].0);
+		assert_eq!(second[0].1, vec![hash]);
+
+		// no more peers to ask
+		fetch.expire(200, 10);
+		assert!(!fetch.contains(&hash));
+	}
+
+	#[test]
+	fn should_forget_received_and_disconnected() {
+		// given
+		let mut fetch = TransactionsFetch::default();
+		let hash1: H256 = 1.into();
+		let hash2: H256 = 2.into();
+		fetch.announce(1, hash1);
+		fetch.announce(1, hash2);
+		fetch.announce(2, hash2);
+
+		// when
+		fetch.received(&hash1);
+		fetch.remove_peer(1);
+
+		// then
+		assert!(!fetch.contains(&hash1));
+		assert_eq!(fetch.requests(0, 16), vec![(2, vec![hash2])]);
+	}
+}
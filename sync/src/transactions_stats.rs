@@ -25,14 +25,18 @@ type BlockNumber = u64;
 #[derive(Debug, PartialEq, Clone)]
 pub struct Stats {
 	first_seen: BlockNumber,
+	received_from: Option<NodeId>,
 	propagated_to: HashMap<NodeId, usize>,
+	announced_to: HashMap<NodeId, usize>,
 }
 
 impl Stats {
 	pub fn new(number: BlockNumber) -> Self {
 		Stats {
 			first_seen: number,
+			received_from: None,
 			propagated_to: Default::default(),
+			announced_to: Default::default(),
 		}
 	}
 }
@@ -41,10 +45,15 @@ impl<'a> From<&'a Stats> for TransactionStats {
 	fn from(other: &'a Stats) -> Self {
 		TransactionStats {
 			first_seen: other.first_seen,
+			received_from: other.received_from,
 			propagated_to: other.propagated_to
 				.iter()
 				.map(|(hash, size)| (*hash, *size))
 				.collect(),
+			announced_to: other.announced_to
+				.iter()
+				.map(|(hash, size)| (*hash, *size))
+				.collect(),
 		}
 	}
 }
@@ -63,6 +72,22 @@ impl TransactionsStats {
 		*count = count.saturating_add(1);
 	}
 
+	/// Increases number of hash announcements to given `enodeid`.
+	pub fn announced(&mut self, hash: H256, enode_id: Option<NodeId>, current_block_num: BlockNumber) {
+		let enode_id = enode_id.unwrap_or_default();
+		let mut stats = self.pending_transactions.entry(hash).or_insert_with(|| Stats::new(current_block_num));
+		let mut count = stats.announced_to.entry(enode_id).or_insert(0);
+		*count = count.saturating_add(1);
+	}
+
+	/// Records the peer transaction was first received from.
+	pub fn received(&mut self, hash: H256, enode_id: Option<NodeId>, current_block_num: BlockNumber) {
+		let mut stats = self.pending_transactions.entry(hash).or_insert_with(|| Stats::new(current_block_num));
+		if stats.received_from.is_none() {
+			stats.received_from = enode_id;
+		}
+	}
+
 	/// Returns propagation stats for given hash or `None` if hash is not known.
 	#[cfg(test)]
 	pub fn get(&self, hash: &H256) -> Option<&Stats> {
@@ -109,10 +134,41 @@ mod tests {
 		let stats = stats.get(&hash);
 		assert_eq!(stats, Some(&Stats {
 			first_seen: 5,
+			received_from: None,
 			propagated_to: hash_map![
 				enodeid1 => 2,
 				enodeid2 => 1
 			],
+			announced_to: HashMap::new(),
+		}));
+	}
+
+	#[test]
+	fn should_keep_track_of_announcements_and_source() {
+		// given
+		let mut stats = TransactionsStats::default();
+		let hash = 5.into();
+		let source = 1.into();
+		let enodeid1 = 2.into();
+		let enodeid2 = 5.into();
+
+		// when
+		stats.received(hash, Some(source), 5);
+		stats.received(hash, Some(enodeid1), 6);
+		stats.announced(hash, Some(enodeid1), 10);
+		stats.propagated(hash, Some(enodeid2), 10);
+
+		// then
+		let stats = stats.get(&hash);
+		assert_eq!(stats, Some(&Stats {
+			first_seen: 5,
+			received_from: Some(source),
+			propagated_to: hash_map![
+				enodeid2 => 1
+			],
+			announced_to: hash_map![
+				enodeid1 => 1
+			],
 		}));
 	}
 
@@ -0,0 +1,309 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics registry and Prometheus text exposition.
+//!
+//! Subsystems implement `Collector` and are registered with a `Registry`,
+//! which gathers their current values whenever it's scraped.
+
+#[macro_use]
+extern crate log;
+
+mod server;
+
+use std::fmt::Write;
+use std::sync::{Arc, RwLock, Weak};
+
+pub use server::{start_server, Server};
+
+/// Default histogram buckets for latencies, in seconds.
+pub const LATENCY_BUCKETS: &'static [f64] = &[0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+/// Kind of a metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricType {
+	/// A value which only ever increases.
+	Counter,
+	/// A value which may go up and down.
+	Gauge,
+	/// Observations counted into buckets.
+	Histogram,
+}
+
+impl MetricType {
+	fn as_str(&self) -> &'static str {
+		match *self {
+			MetricType::Counter => "counter",
+			MetricType::Gauge => "gauge",
+			MetricType::Histogram => "histogram",
+		}
+	}
+}
+
+/// A single sample of a metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+	/// Full name of the sample, e.g. with a `_bucket` suffix for histograms.
+	pub name: String,
+	/// Label pairs.
+	pub labels: Vec<(String, String)>,
+	/// Current value.
+	pub value: f64,
+}
+
+/// A metric family: a name, description and all samples of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+	/// Name of the metric.
+	pub name: String,
+	/// Human-readable description.
+	pub help: String,
+	/// Kind of the metric.
+	pub kind: MetricType,
+	/// Samples, one per distinct label set.
+	pub samples: Vec<Sample>,
+}
+
+impl Metric {
+	/// Create a metric family without samples.
+	pub fn new(name: &str, help: &str, kind: MetricType) -> Self {
+		Metric {
+			name: name.into(),
+			help: help.into(),
+			kind: kind,
+			samples: Vec::new(),
+		}
+	}
+
+	/// Create a counter with a single unlabeled sample.
+	pub fn counter(name: &str, help: &str, value: f64) -> Self {
+		Metric::new(name, help, MetricType::Counter).with_sample(&[], value)
+	}
+
+	/// Create a gauge with a single unlabeled sample.
+	pub fn gauge(name: &str, help: &str, value: f64) -> Self {
+		Metric::new(name, help, MetricType::Gauge).with_sample(&[], value)
+	}
+
+	/// Add a sample with the given labels.
+	pub fn with_sample(mut self, labels: &[(&str, &str)], value: f64) -> Self {
+		let name = self.name.clone();
+		self.samples.push(Sample {
+			name: name,
+			labels: labels.iter().map(|&(k, v)| (k.into(), v.into())).collect(),
+			value: value,
+		});
+		self
+	}
+
+	/// Add the samples of a histogram with the given labels.
+	pub fn with_histogram(mut self, labels: &[(&str, &str)], histogram: &Histogram) -> Self {
+		let labels: Vec<(String, String)> = labels.iter().map(|&(k, v)| (k.into(), v.into())).collect();
+
+		let mut cumulative = 0;
+		for (bound, count) in histogram.buckets.iter().zip(histogram.counts.iter()) {
+			cumulative += *count;
+			let mut bucket_labels = labels.clone();
+			bucket_labels.push(("le".into(), format!("{}", bound)));
+			self.samples.push(Sample {
+				name: format!("{}_bucket", self.name),
+				labels: bucket_labels,
+				value: cumulative as f64,
+			});
+		}
+
+		let mut inf_labels = labels.clone();
+		inf_labels.push(("le".into(), "+Inf".into()));
+		self.samples.push(Sample { name: format!("{}_bucket", self.name), labels: inf_labels, value: histogram.count as f64 });
+		self.samples.push(Sample { name: format!("{}_sum", self.name), labels: labels.clone(), value: histogram.sum });
+		self.samples.push(Sample { name: format!("{}_count", self.name), labels: labels, value: histogram.count as f64 });
+		self
+	}
+}
+
+/// Observations counted into fixed buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+	buckets: &'static [f64],
+	counts: Vec<u64>,
+	sum: f64,
+	count: u64,
+}
+
+impl Histogram {
+	/// Create a histogram with the given upper bucket bounds, in increasing order.
+	pub fn new(buckets: &'static [f64]) -> Self {
+		Histogram {
+			buckets: buckets,
+			counts: vec![0; buckets.len()],
+			sum: 0.0,
+			count: 0,
+		}
+	}
+
+	/// Record an observation.
+	pub fn observe(&mut self, value: f64) {
+		if let Some(idx) = self.buckets.iter().position(|bound| value <= *bound) {
+			self.counts[idx] += 1;
+		}
+		self.sum += value;
+		self.count += 1;
+	}
+
+	/// Number of observations.
+	pub fn count(&self) -> u64 {
+		self.count
+	}
+
+	/// Sum of all observations.
+	pub fn sum(&self) -> f64 {
+		self.sum
+	}
+}
+
+/// A subsystem exposing metrics.
+pub trait Collector: Send + Sync {
+	/// Gather the current values of all metrics.
+	fn collect(&self) -> Vec<Metric>;
+}
+
+/// Registry of collectors. Holds weak references only, so registering
+/// doesn't keep a subsystem alive.
+#[derive(Default)]
+pub struct Registry {
+	collectors: RwLock<Vec<Weak<Collector>>>,
+}
+
+impl Registry {
+	/// Create an empty registry.
+	pub fn new() -> Self {
+		Registry::default()
+	}
+
+	/// Register a collector.
+	pub fn register(&self, collector: Arc<Collector>) {
+		self.collectors.write().expect("poisoned lock").push(Arc::downgrade(&collector));
+	}
+
+	/// Gather metrics from all live collectors, dropping dead ones.
+	pub fn gather(&self) -> Vec<Metric> {
+		let mut collectors = self.collectors.write().expect("poisoned lock");
+		collectors.retain(|c| c.upgrade().is_some());
+
+		collectors.iter()
+			.filter_map(|c| c.upgrade())
+			.flat_map(|c| c.collect())
+			.collect()
+	}
+
+	/// Encode all metrics in the Prometheus text exposition format.
+	pub fn encode(&self) -> String {
+		encode(&self.gather())
+	}
+}
+
+/// Encode metrics in the Prometheus text exposition format.
+pub fn encode(metrics: &[Metric]) -> String {
+	let mut out = String::new();
+
+	for metric in metrics {
+		let _ = writeln!(out, "# HELP {} {}", metric.name, escape(&metric.help, false));
+		let _ = writeln!(out, "# TYPE {} {}", metric.name, metric.kind.as_str());
+
+		for sample in &metric.samples {
+			out.push_str(&sample.name);
+			if !sample.labels.is_empty() {
+				let labels: Vec<_> = sample.labels.iter()
+					.map(|&(ref k, ref v)| format!("{}=\"{}\"", k, escape(v, true)))
+					.collect();
+				let _ = write!(out, "{{{}}}", labels.join(","));
+			}
+			let _ = writeln!(out, " {}", sample.value);
+		}
+	}
+
+	out
+}
+
+fn escape(s: &str, quotes: bool) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'"' if quotes => escaped.push_str("\\\""),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use super::*;
+
+	struct TestCollector;
+
+	impl Collector for TestCollector {
+		fn collect(&self) -> Vec<Metric> {
+			vec![
+				Metric::counter("test_total", "Things counted.", 3.0),
+				Metric::new("test_peers", "Peers by protocol.", MetricType::Gauge)
+					.with_sample(&[("protocol", "eth")], 2.0)
+					.with_sample(&[("protocol", "pip")], 1.0),
+			]
+		}
+	}
+
+	#[test]
+	fn encodes_text_format() {
+		let registry = Registry::new();
+		let collector = Arc::new(TestCollector);
+		registry.register(collector.clone());
+
+		assert_eq!(registry.encode(), "# HELP test_total Things counted.\n\
+			# TYPE test_total counter\n\
+			test_total 3\n\
+			# HELP test_peers Peers by protocol.\n\
+			# TYPE test_peers gauge\n\
+			test_peers{protocol=\"eth\"} 2\n\
+			test_peers{protocol=\"pip\"} 1\n");
+
+		drop(collector);
+		assert_eq!(registry.encode(), "");
+	}
+
+	#[test]
+	fn encodes_histogram() {
+		static BUCKETS: &'static [f64] = &[0.1, 1.0];
+		let mut histogram = Histogram::new(BUCKETS);
+		histogram.observe(0.0625);
+		histogram.observe(0.5);
+		histogram.observe(2.0);
+
+		let metric = Metric::new("latency_seconds", "Latency.", MetricType::Histogram)
+			.with_histogram(&[("method", "eth_call")], &histogram);
+
+		assert_eq!(encode(&[metric]), "# HELP latency_seconds Latency.\n\
+			# TYPE latency_seconds histogram\n\
+			latency_seconds_bucket{method=\"eth_call\",le=\"0.1\"} 1\n\
+			latency_seconds_bucket{method=\"eth_call\",le=\"1\"} 2\n\
+			latency_seconds_bucket{method=\"eth_call\",le=\"+Inf\"} 3\n\
+			latency_seconds_sum{method=\"eth_call\"} 2.5625\n\
+			latency_seconds_count{method=\"eth_call\"} 3\n");
+	}
+}
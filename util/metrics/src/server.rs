@@ -0,0 +1,153 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal HTTP server exposing a registry to be scraped.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use Registry;
+
+/// Path the metrics are served on.
+pub const METRICS_PATH: &'static str = "/metrics";
+
+const READ_TIMEOUT_SECS: u64 = 5;
+
+/// A running metrics server. Stops listening when dropped.
+pub struct Server {
+	addr: SocketAddr,
+	running: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+	/// The address the server is listening on.
+	pub fn addr(&self) -> &SocketAddr {
+		&self.addr
+	}
+}
+
+impl Drop for Server {
+	fn drop(&mut self) {
+		self.running.store(false, Ordering::SeqCst);
+
+		// wake the listener up so it notices.
+		let _ = TcpStream::connect(&self.addr);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
+
+/// Start serving the metrics of the registry over HTTP at the given address.
+pub fn start_server(addr: &SocketAddr, registry: Arc<Registry>) -> io::Result<Server> {
+	let listener = TcpListener::bind(addr)?;
+	let addr = listener.local_addr()?;
+	let running = Arc::new(AtomicBool::new(true));
+
+	let r = running.clone();
+	let handle = thread::Builder::new().name("metrics".into()).spawn(move || {
+		for stream in listener.incoming() {
+			if !r.load(Ordering::SeqCst) { break }
+
+			let res = stream.and_then(|stream| handle_connection(stream, &registry));
+			if let Err(e) = res {
+				debug!(target: "metrics", "Error serving metrics request: {}", e);
+			}
+		}
+	})?;
+
+	Ok(Server {
+		addr: addr,
+		running: running,
+		handle: Some(handle),
+	})
+}
+
+fn handle_connection(mut stream: TcpStream, registry: &Registry) -> io::Result<()> {
+	stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT_SECS)))?;
+
+	let request_line = {
+		let mut reader = BufReader::new(&mut stream);
+		let mut request_line = String::new();
+		reader.read_line(&mut request_line)?;
+
+		// skip the headers; nothing in them matters.
+		loop {
+			let mut line = String::new();
+			if reader.read_line(&mut line)? == 0 || line.trim().is_empty() { break }
+		}
+
+		request_line
+	};
+
+	let mut parts = request_line.split_whitespace();
+	let (status, body) = match (parts.next(), parts.next()) {
+		(Some("GET"), Some(METRICS_PATH)) => ("200 OK", registry.encode()),
+		(Some("GET"), _) => ("404 Not Found", "Not Found\n".to_owned()),
+		_ => ("405 Method Not Allowed", "Method Not Allowed\n".to_owned()),
+	};
+
+	write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+		status, body.len(), body)?;
+	stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{Read, Write};
+	use std::net::TcpStream;
+	use std::sync::Arc;
+	use {Collector, Metric, Registry};
+	use super::start_server;
+
+	struct TestCollector;
+
+	impl Collector for TestCollector {
+		fn collect(&self) -> Vec<Metric> {
+			vec![Metric::gauge("test_value", "A value.", 7.0)]
+		}
+	}
+
+	fn request(server: &super::Server, path: &str) -> String {
+		let mut stream = TcpStream::connect(server.addr()).unwrap();
+		write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+
+		let mut response = String::new();
+		stream.read_to_string(&mut response).unwrap();
+		response
+	}
+
+	#[test]
+	fn serves_metrics() {
+		let registry = Arc::new(Registry::new());
+		let collector = Arc::new(TestCollector);
+		registry.register(collector.clone());
+
+		let server = start_server(&"127.0.0.1:0".parse().unwrap(), registry).unwrap();
+
+		let response = request(&server, "/metrics");
+		assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+		assert!(response.ends_with("\r\n\r\n# HELP test_value A value.\n# TYPE test_value gauge\ntest_value 7\n"));
+
+		let response = request(&server, "/");
+		assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+	}
+}
@@ -0,0 +1,370 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bandwidth limiting and rate measurement.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use host::ProtocolId;
+
+/// Upload and download limits, in bytes per second. `None` means unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BandwidthLimit {
+	/// Upload limit.
+	pub upload: Option<u64>,
+	/// Download limit.
+	pub download: Option<u64>,
+}
+
+impl BandwidthLimit {
+	fn is_limited(&self) -> bool {
+		self.upload.is_some() || self.download.is_some()
+	}
+}
+
+/// Bandwidth limits configuration.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BandwidthConfig {
+	/// Limits for all traffic of the node.
+	pub total: BandwidthLimit,
+	/// Limits for each peer connection.
+	pub peer: BandwidthLimit,
+	/// Limits for all traffic of a subprotocol.
+	pub protocols: HashMap<ProtocolId, BandwidthLimit>,
+}
+
+impl BandwidthConfig {
+	/// Check if any limit is set.
+	pub fn is_limited(&self) -> bool {
+		self.total.is_limited() || self.peer.is_limited() || self.protocols.values().any(BandwidthLimit::is_limited)
+	}
+}
+
+/// Traffic direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+	/// Outgoing traffic.
+	Upload,
+	/// Incoming traffic.
+	Download,
+}
+
+/// Token bucket refilled at a constant rate, holding up to one second worth of tokens.
+/// Tokens may go into debt, which delays further traffic until it is paid off.
+#[derive(Debug)]
+pub struct TokenBucket {
+	rate: u64,
+	tokens: i64,
+	updated: Instant,
+}
+
+impl TokenBucket {
+	/// Create a full bucket with given rate in bytes per second.
+	pub fn new(rate: u64) -> Self {
+		TokenBucket {
+			rate: rate,
+			tokens: rate as i64,
+			updated: Instant::now(),
+		}
+	}
+
+	fn refill(&mut self, now: Instant) {
+		if now <= self.updated {
+			return;
+		}
+		let elapsed = now - self.updated;
+		let elapsed_ns = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+		let added = (self.rate as f64 * elapsed_ns as f64 / 1_000_000_000f64) as i64;
+		if added > 0 {
+			self.tokens = cmp::min(self.tokens.saturating_add(added), self.rate as i64);
+			self.updated = now;
+		}
+	}
+
+	fn available_at(&mut self, now: Instant) -> usize {
+		self.refill(now);
+		cmp::max(self.tokens, 0) as usize
+	}
+
+	/// Number of bytes that can be transferred now.
+	pub fn available(&mut self) -> usize {
+		self.available_at(Instant::now())
+	}
+
+	/// Take tokens for transferred bytes.
+	pub fn consume(&mut self, bytes: usize) {
+		self.tokens = self.tokens.saturating_sub(bytes as i64);
+	}
+}
+
+/// Measures transfer rate over one-second windows.
+#[derive(Debug)]
+struct Meter {
+	window_start: Instant,
+	bytes: u64,
+	rate: u64,
+}
+
+impl Meter {
+	fn new() -> Self {
+		Meter {
+			window_start: Instant::now(),
+			bytes: 0,
+			rate: 0,
+		}
+	}
+
+	fn roll(&mut self, now: Instant) {
+		let elapsed = now - self.window_start;
+		if elapsed >= Duration::from_secs(1) {
+			let elapsed_ms = elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000;
+			self.rate = self.bytes * 1000 / elapsed_ms;
+			self.bytes = 0;
+			self.window_start = now;
+		}
+	}
+
+	fn add(&mut self, bytes: usize) {
+		self.roll(Instant::now());
+		self.bytes += bytes as u64;
+	}
+
+	fn rate(&mut self) -> u64 {
+		self.roll(Instant::now());
+		self.rate
+	}
+}
+
+/// Rate limit and measurement of a single traffic channel.
+#[derive(Debug)]
+struct Channel {
+	bucket: Option<TokenBucket>,
+	meter: Meter,
+}
+
+impl Channel {
+	fn new(limit: Option<u64>) -> Self {
+		Channel {
+			bucket: limit.map(TokenBucket::new),
+			meter: Meter::new(),
+		}
+	}
+
+	fn quota(&mut self, wanted: usize) -> usize {
+		match self.bucket {
+			Some(ref mut bucket) => cmp::min(bucket.available(), wanted),
+			None => wanted,
+		}
+	}
+
+	fn consume(&mut self, bytes: usize) {
+		if let Some(ref mut bucket) = self.bucket {
+			bucket.consume(bytes);
+		}
+		self.meter.add(bytes);
+	}
+}
+
+/// Current transfer rates, in bytes per second.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BandwidthRates {
+	/// Total upload rate.
+	pub upload: u64,
+	/// Total download rate.
+	pub download: u64,
+	/// Upload and download rates by subprotocol.
+	pub protocols: Vec<(ProtocolId, u64, u64)>,
+}
+
+/// Node-wide bandwidth limiter, shared by all connections.
+#[derive(Debug)]
+pub struct Bandwidth {
+	config: BandwidthConfig,
+	upload: Mutex<Channel>,
+	download: Mutex<Channel>,
+	protocols: Mutex<HashMap<ProtocolId, (Channel, Channel)>>,
+}
+
+impl Default for Bandwidth {
+	fn default() -> Self {
+		Bandwidth::new(Default::default())
+	}
+}
+
+impl Bandwidth {
+	/// Create a new limiter with given limits.
+	pub fn new(config: BandwidthConfig) -> Self {
+		Bandwidth {
+			upload: Mutex::new(Channel::new(config.total.upload)),
+			download: Mutex::new(Channel::new(config.total.download)),
+			protocols: Mutex::new(HashMap::new()),
+			config: config,
+		}
+	}
+
+	/// Configured limits.
+	pub fn config(&self) -> &BandwidthConfig {
+		&self.config
+	}
+
+	fn with_protocol<F, T>(&self, protocol: ProtocolId, direction: Direction, f: F) -> T where F: FnOnce(&mut Channel) -> T {
+		let mut protocols = self.protocols.lock();
+		let limit = self.config.protocols.get(&protocol).cloned().unwrap_or_default();
+		let channels = protocols.entry(protocol).or_insert_with(|| (Channel::new(limit.upload), Channel::new(limit.download)));
+		match direction {
+			Direction::Upload => f(&mut channels.0),
+			Direction::Download => f(&mut channels.1),
+		}
+	}
+
+	fn total(&self, direction: Direction) -> &Mutex<Channel> {
+		match direction {
+			Direction::Upload => &self.upload,
+			Direction::Download => &self.download,
+		}
+	}
+
+	/// Number of bytes out of `wanted` that may be transferred now, optionally for given subprotocol.
+	pub fn quota(&self, direction: Direction, protocol: Option<ProtocolId>, wanted: usize) -> usize {
+		let quota = self.total(direction).lock().quota(wanted);
+		match protocol {
+			Some(protocol) => self.with_protocol(protocol, direction, |c| c.quota(quota)),
+			None => quota,
+		}
+	}
+
+	/// Account bytes transferred over the wire.
+	pub fn consume_total(&self, direction: Direction, bytes: usize) {
+		self.total(direction).lock().consume(bytes);
+	}
+
+	/// Account bytes transferred for a subprotocol.
+	pub fn consume_protocol(&self, direction: Direction, protocol: ProtocolId, bytes: usize) {
+		self.with_protocol(protocol, direction, |c| c.consume(bytes));
+	}
+
+	/// Current transfer rates.
+	pub fn rates(&self) -> BandwidthRates {
+		let mut protocols: Vec<_> = self.protocols.lock().iter_mut()
+			.map(|(p, &mut (ref mut up, ref mut down))| (*p, up.meter.rate(), down.meter.rate()))
+			.collect();
+		protocols.sort();
+
+		BandwidthRates {
+			upload: self.upload.lock().meter.rate(),
+			download: self.download.lock().meter.rate(),
+			protocols: protocols,
+		}
+	}
+}
+
+/// Bandwidth limiter of a single peer connection.
+#[derive(Debug)]
+pub struct PeerBandwidth {
+	upload: Option<TokenBucket>,
+	download: Option<TokenBucket>,
+}
+
+impl PeerBandwidth {
+	/// Create a new limiter with given limits.
+	pub fn new(limit: &BandwidthLimit) -> Self {
+		PeerBandwidth {
+			upload: limit.upload.map(TokenBucket::new),
+			download: limit.download.map(TokenBucket::new),
+		}
+	}
+
+	fn bucket(&mut self, direction: Direction) -> Option<&mut TokenBucket> {
+		match direction {
+			Direction::Upload => self.upload.as_mut(),
+			Direction::Download => self.download.as_mut(),
+		}
+	}
+
+	/// Number of bytes out of `wanted` that may be transferred now.
+	pub fn quota(&mut self, direction: Direction, wanted: usize) -> usize {
+		match self.bucket(direction) {
+			Some(bucket) => cmp::min(bucket.available(), wanted),
+			None => wanted,
+		}
+	}
+
+	/// Account transferred bytes.
+	pub fn consume(&mut self, direction: Direction, bytes: usize) {
+		if let Some(bucket) = self.bucket(direction) {
+			bucket.consume(bytes);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use super::{Bandwidth, BandwidthConfig, BandwidthLimit, Direction, TokenBucket, PeerBandwidth};
+
+	#[test]
+	fn bucket_refills_at_rate() {
+		let mut bucket = TokenBucket::new(1000);
+		let start = bucket.updated;
+		assert_eq!(bucket.available_at(start), 1000);
+
+		bucket.consume(1500);
+		assert_eq!(bucket.available_at(start), 0);
+		assert_eq!(bucket.available_at(start + Duration::from_millis(250)), 0);
+		assert_eq!(bucket.available_at(start + Duration::from_millis(1000)), 500);
+		// never exceeds one second worth of tokens.
+		assert_eq!(bucket.available_at(start + Duration::from_secs(10)), 1000);
+	}
+
+	#[test]
+	fn bucket_ignores_past_instants() {
+		let mut bucket = TokenBucket::new(100);
+		bucket.consume(100);
+		let past = Instant::now() - Duration::from_secs(1);
+		assert_eq!(bucket.available_at(past), 0);
+	}
+
+	#[test]
+	fn limits_total_and_protocol_traffic() {
+		let mut config = BandwidthConfig::default();
+		config.total.upload = Some(1000);
+		config.protocols.insert(*b"par", BandwidthLimit { upload: Some(100), download: None });
+		let bandwidth = Bandwidth::new(config);
+
+		assert_eq!(bandwidth.quota(Direction::Upload, None, 5000), 1000);
+		assert_eq!(bandwidth.quota(Direction::Upload, Some(*b"par"), 5000), 100);
+		assert_eq!(bandwidth.quota(Direction::Upload, Some(*b"eth"), 5000), 1000);
+		assert_eq!(bandwidth.quota(Direction::Download, Some(*b"par"), 5000), 5000);
+
+		bandwidth.consume_total(Direction::Upload, 100);
+		bandwidth.consume_protocol(Direction::Upload, *b"par", 100);
+		assert_eq!(bandwidth.quota(Direction::Upload, Some(*b"par"), 5000), 0);
+		assert!(bandwidth.quota(Direction::Upload, Some(*b"eth"), 5000) >= 900);
+		assert!(bandwidth.config().is_limited());
+		assert!(!BandwidthConfig::default().is_limited());
+	}
+
+	#[test]
+	fn limits_peer_traffic() {
+		let mut peer = PeerBandwidth::new(&BandwidthLimit { upload: None, download: Some(10) });
+		assert_eq!(peer.quota(Direction::Upload, 100), 100);
+		assert_eq!(peer.quota(Direction::Download, 100), 10);
+		peer.consume(Direction::Download, 10);
+		assert_eq!(peer.quota(Direction::Download, 100), 0);
+	}
+}
@@ -30,6 +30,8 @@ use error::*;
 use io::{IoContext, StreamToken};
 use handshake::Handshake;
 use stats::NetworkStats;
+use bandwidth::{Direction, PeerBandwidth};
+use host::ProtocolId;
 use rcrypto::blockmodes::*;
 use rcrypto::aessafe::*;
 use rcrypto::symmetriccipher::*;
@@ -56,14 +58,22 @@ pub struct GenericConnection<Socket: GenericSocket> {
 	rec_buf: Bytes,
 	/// Expected size
 	rec_size: usize,
-	/// Send out packets FIFO
-	send_queue: VecDeque<Cursor<Bytes>>,
+	/// Send out packets FIFO, with the subprotocol they belong to
+	send_queue: VecDeque<(Cursor<Bytes>, Option<ProtocolId>)>,
 	/// Event flags this connection expects
 	interest: Ready,
 	/// Shared network statistics
 	stats: Arc<NetworkStats>,
 	/// Registered flag
 	registered: AtomicBool,
+	/// Bandwidth limits of this peer
+	bandwidth: PeerBandwidth,
+	/// Subprotocol of the last received packet, holding further reads while its download limit is exceeded
+	read_protocol: Option<ProtocolId>,
+	/// Reading was paused by bandwidth limits
+	read_throttled: bool,
+	/// Writing was paused by bandwidth limits
+	write_throttled: bool,
 }
 
 impl<Socket: GenericSocket> GenericConnection<Socket> {
@@ -80,13 +90,33 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		if self.rec_size == 0 || self.rec_buf.len() >= self.rec_size {
 			return Ok(None);
 		}
+		self.read_throttled = false;
+		// The subprotocol of the incoming packet is not known until it is decoded,
+		// so subprotocol limits only apply back-pressure once the previous packet has been charged.
+		if let Some(protocol) = self.read_protocol {
+			if self.stats.bandwidth().quota(Direction::Download, Some(protocol), 1) == 0 {
+				trace!(target:"network", "{}: Reading paused by subprotocol bandwidth limit", self.token);
+				self.read_throttled = true;
+				return Ok(None);
+			}
+			self.read_protocol = None;
+		}
 		let sock_ref = <Socket as Read>::by_ref(&mut self.socket);
 		loop {
 			let max = self.rec_size - self.rec_buf.len();
+			let max = self.bandwidth.quota(Direction::Download, max);
+			let max = self.stats.bandwidth().quota(Direction::Download, None, max);
+			if max == 0 {
+				trace!(target:"network", "{}: Reading paused by bandwidth limit", self.token);
+				self.read_throttled = true;
+				return Ok(None);
+			}
 			match sock_ref.take(max as u64).try_read(unsafe { self.rec_buf.mut_bytes() }) {
 				Ok(Some(size)) if size != 0  => {
 					unsafe { self.rec_buf.advance(size); }
 					self.stats.inc_recv(size);
+					self.bandwidth.consume(Direction::Download, size);
+					self.stats.bandwidth().consume_total(Direction::Download, size);
 					trace!(target:"network", "{}: Read {} of {} bytes", self.token, self.rec_buf.len(), self.rec_size);
 					if self.rec_size != 0 && self.rec_buf.len() == self.rec_size {
 						self.rec_size = 0;
@@ -108,9 +138,14 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 
 	/// Add a packet to send queue.
 	pub fn send<Message>(&mut self, io: &IoContext<Message>, data: Bytes) where Message: Send + Clone + Sync + 'static {
+		self.send_for(io, None, data)
+	}
+
+	/// Add a packet of given subprotocol to send queue.
+	pub fn send_for<Message>(&mut self, io: &IoContext<Message>, protocol: Option<ProtocolId>, data: Bytes) where Message: Send + Clone + Sync + 'static {
 		if !data.is_empty() {
 			trace!(target:"network", "{}: Sending {} bytes", self.token, data.len());
-			self.send_queue.push_back(Cursor::new(data));
+			self.send_queue.push_back((Cursor::new(data), protocol));
 			if !self.interest.is_writable() {
 				self.interest.insert(Ready::writable());
 			}
@@ -123,11 +158,28 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 		self.interest.is_writable()
 	}
 
+	/// Check if reading was paused by bandwidth limits.
+	pub fn is_read_throttled(&self) -> bool {
+		self.read_throttled
+	}
+
+	/// Check if writing was paused by bandwidth limits.
+	pub fn is_write_throttled(&self) -> bool {
+		self.write_throttled
+	}
+
+	/// Account a received packet of given subprotocol. Further reads are held while the subprotocol is over its limit.
+	pub fn received_for(&mut self, protocol: ProtocolId, size: usize) {
+		self.read_protocol = Some(protocol);
+		self.stats.bandwidth().consume_protocol(Direction::Download, protocol, size);
+	}
+
 	/// Writable IO handler. Called when the socket is ready to send.
 	pub fn writable<Message>(&mut self, io: &IoContext<Message>) -> Result<WriteStatus, NetworkError> where Message: Send + Clone + Sync + 'static {
+		self.write_throttled = false;
 		{
-			let buf = match self.send_queue.front_mut() {
-				Some(buf) => buf,
+			let (buf, protocol) = match self.send_queue.front_mut() {
+				Some(&mut (ref mut buf, protocol)) => (buf, protocol),
 				None => return Ok(WriteStatus::Complete),
 			};
 			let send_size = buf.get_ref().len();
@@ -136,15 +188,29 @@ impl<Socket: GenericSocket> GenericConnection<Socket> {
 				warn!(target:"net", "Unexpected connection data");
 				return Ok(WriteStatus::Complete)
 			}
+			let quota = self.bandwidth.quota(Direction::Upload, send_size - pos);
+			let quota = self.stats.bandwidth().quota(Direction::Upload, protocol, quota);
+			if quota == 0 {
+				trace!(target:"network", "{}: Writing paused by bandwidth limit", self.token);
+				self.write_throttled = true;
+				return Ok(WriteStatus::Ongoing)
+			}
 			let buf = buf as &mut Buf;
-			match self.socket.try_write(buf.bytes()) {
+			let result = self.socket.try_write(&buf.bytes()[..quota]);
+			if let Ok(Some(size)) = result {
+				self.stats.inc_send(size);
+				self.bandwidth.consume(Direction::Upload, size);
+				self.stats.bandwidth().consume_total(Direction::Upload, size);
+				if let Some(protocol) = protocol {
+					self.stats.bandwidth().consume_protocol(Direction::Upload, protocol, size);
+				}
+			}
+			match result {
 				Ok(Some(size)) if (pos + size) < send_size => {
 					buf.advance(size);
-					self.stats.inc_send(size);
 					Ok(WriteStatus::Ongoing)
 				},
 				Ok(Some(size)) if (pos + size) == send_size => {
-					self.stats.inc_send(size);
 					trace!(target:"network", "{}: Wrote {} bytes", self.token, send_size);
 					Ok(WriteStatus::Complete)
 				},
@@ -178,8 +244,12 @@ impl Connection {
 			rec_buf: Bytes::new(),
 			rec_size: 0,
 			interest: Ready::hup() | Ready::readable(),
+			bandwidth: PeerBandwidth::new(&stats.bandwidth().config().peer),
 			stats: stats,
 			registered: AtomicBool::new(false),
+			read_protocol: None,
+			read_throttled: false,
+			write_throttled: false,
 		}
 	}
 
@@ -214,6 +284,10 @@ impl Connection {
 			interest: Ready::hup(),
 			stats: self.stats.clone(),
 			registered: AtomicBool::new(false),
+			bandwidth: PeerBandwidth::new(&self.stats.bandwidth().config().peer),
+			read_protocol: None,
+			read_throttled: false,
+			write_throttled: false,
 		})
 	}
 
@@ -351,8 +425,8 @@ impl EncryptedConnection {
 		Ok(enc)
 	}
 
-	/// Send a packet
-	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, payload: &[u8]) -> Result<(), NetworkError> where Message: Send + Clone + Sync + 'static {
+	/// Send a packet, optionally belonging to a subprotocol.
+	pub fn send_packet<Message>(&mut self, io: &IoContext<Message>, protocol: Option<ProtocolId>, payload: &[u8]) -> Result<(), NetworkError> where Message: Send + Clone + Sync + 'static {
 		let mut header = RlpStream::new();
 		let len = payload.len();
 		if len >= (1 << 24) {
@@ -377,7 +451,7 @@ impl EncryptedConnection {
 		self.egress_mac.update(&packet[32..(32 + len + padding)]);
 		EncryptedConnection::update_mac(&mut self.egress_mac, &mut self.mac_encoder, &[0u8; 0]);
 		self.egress_mac.clone().finalize(&mut packet[(32 + len + padding)..]);
-		self.connection.send(io, packet);
+		self.connection.send_for(io, protocol, packet);
 		Ok(())
 	}
 
@@ -507,6 +581,7 @@ mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::AtomicBool;
 	use super::super::stats::*;
+	use bandwidth::{BandwidthConfig, BandwidthLimit, PeerBandwidth};
 	use std::io::{Read, Write, Error, Cursor, ErrorKind};
 	use mio::{Ready};
 	use std::collections::VecDeque;
@@ -557,6 +632,10 @@ mod tests {
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				bandwidth: PeerBandwidth::new(&Default::default()),
+				read_protocol: None,
+				read_throttled: false,
+				write_throttled: false,
 			}
 		}
 	}
@@ -580,6 +659,10 @@ mod tests {
 				interest: Ready::hup() | Ready::readable(),
 				stats: Arc::<NetworkStats>::new(NetworkStats::new()),
 				registered: AtomicBool::new(false),
+				bandwidth: PeerBandwidth::new(&Default::default()),
+				read_protocol: None,
+				read_throttled: false,
+				write_throttled: false,
 			}
 		}
 	}
@@ -607,7 +690,7 @@ mod tests {
 	fn connection_write() {
 		let mut connection = TestConnection::new();
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.send_queue.push_back((data, None));
 
 		let status = connection.writable(&test_io());
 		assert!(status.is_ok());
@@ -620,7 +703,7 @@ mod tests {
 		let mut connection = TestConnection::new();
 		connection.socket = TestSocket::new_buf(1024);
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.send_queue.push_back((data, None));
 
 		let status = connection.writable(&test_io());
 
@@ -633,7 +716,7 @@ mod tests {
 	fn connection_write_to_broken() {
 		let mut connection = TestBrokenConnection::new();
 		let data = Cursor::new(vec![0; 10240]);
-		connection.send_queue.push_back(data);
+		connection.send_queue.push_back((data, None));
 
 		let status = connection.writable(&test_io());
 
@@ -686,4 +769,24 @@ mod tests {
 		assert!(status.is_ok());
 		assert_eq!(0, connection.socket.cursor);
 	}
+	#[test]
+	fn connection_read_held_by_exceeded_protocol_limit() {
+		let mut config = BandwidthConfig::default();
+		config.protocols.insert(*b"par", BandwidthLimit { upload: None, download: Some(100) });
+		let mut connection = TestConnection::new();
+		connection.stats = Arc::new(NetworkStats::with_bandwidth(config));
+		connection.rec_size = 2048;
+		connection.socket.read_buffer = vec![99; 2048];
+
+		// an unknown next packet is not capped by a subprotocol limit
+		connection.readable().unwrap();
+		assert_eq!(2048, connection.socket.cursor);
+
+		connection.received_for(*b"par", 2048);
+		connection.rec_size = 1024;
+		connection.socket.read_buffer.extend(vec![99; 1024]);
+		assert!(connection.readable().unwrap().is_none());
+		assert!(connection.is_read_throttled());
+		assert_eq!(2048, connection.socket.cursor);
+	}
 }
@@ -28,6 +28,8 @@ use util::hash::*;
 use rlp::*;
 use node_table::*;
 use error::NetworkError;
+use node_record::NodeRecord;
+use topic_table::{Topic, TopicTable};
 use io::{StreamToken, IoContext};
 use ethkey::{Secret, KeyPair, sign, recover};
 use AllowIP;
@@ -46,9 +48,16 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+// Node record and topic packets. Nodes that only speak v4 ignore these.
+const PACKET_RECORD_REQUEST: u8 = 5;
+const PACKET_RECORD: u8 = 6;
+const PACKET_TOPIC_REGISTER: u8 = 7;
+const PACKET_TOPIC_QUERY: u8 = 8;
+const PACKET_TOPIC_NODES: u8 = 9;
 
 const PING_TIMEOUT_MS: u64 = 300;
 const MAX_NODES_PING: usize = 32; // Max nodes to add/ping at once
+const TOPIC_NODES_PER_PACKET: usize = 3; // Records are up to 300 bytes each
 
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
@@ -80,6 +89,13 @@ impl NodeBucket {
 	}
 }
 
+#[derive(Default)]
+struct TopicSearch {
+	round: u16,
+	queried: HashSet<NodeId>,
+	results: HashMap<NodeId, NodeRecord>,
+}
+
 struct Datagramm {
 	payload: Bytes,
 	address: SocketAddr,
@@ -100,6 +116,11 @@ pub struct Discovery {
 	check_timestamps: bool,
 	adding_nodes: Vec<NodeEntry>,
 	allow_ips: AllowIP,
+	record: NodeRecord,
+	records: HashMap<NodeId, NodeRecord>,
+	topic_table: TopicTable,
+	advertised: HashSet<Topic>,
+	searches: HashMap<Topic, TopicSearch>,
 }
 
 pub struct TableUpdates {
@@ -110,6 +131,9 @@ pub struct TableUpdates {
 impl Discovery {
 	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, allow_ips: AllowIP) -> Discovery {
 		let socket = UdpSocket::bind(&listen).expect("Error binding UDP socket");
+		let mut record = NodeRecord::default();
+		record.set_endpoint(&public);
+		record.sign(key.secret()).unwrap_or_else(|e| warn!("Error signing node record: {:?}", e));
 		Discovery {
 			id: key.public().clone(),
 			id_hash: key.public().sha3(),
@@ -125,9 +149,57 @@ impl Discovery {
 			check_timestamps: true,
 			adding_nodes: Vec::new(),
 			allow_ips: allow_ips,
+			record: record,
+			records: HashMap::new(),
+			topic_table: TopicTable::default(),
+			advertised: HashSet::new(),
+			searches: HashMap::new(),
+		}
+	}
+
+	/// Local node record.
+	pub fn record(&self) -> &NodeRecord {
+		&self.record
+	}
+
+	/// Set an entry of the local node record. The record is signed again if the value has changed.
+	pub fn set_record_entry(&mut self, key: &str, value: Bytes) {
+		self.record.set_raw(key, value);
+		if !self.record.is_signed() {
+			self.record.sign(&self.secret).unwrap_or_else(|e| warn!("Error signing node record: {:?}", e));
 		}
 	}
 
+	/// Last known record of a node.
+	pub fn node_record(&self, id: &NodeId) -> Option<&NodeRecord> {
+		self.records.get(id)
+	}
+
+	/// Ask a node for its record.
+	pub fn request_record(&mut self, node: &NodeEntry) {
+		let rlp = RlpStream::new_list(0);
+		self.send_packet(PACKET_RECORD_REQUEST, &node.endpoint.udp_address(), &rlp.out());
+	}
+
+	/// Advertise the local node under a topic. Registrations are sent to the nodes closest
+	/// to the topic and renewed on every refresh.
+	pub fn register_topic(&mut self, topic: Topic) {
+		if self.advertised.insert(topic) {
+			self.send_registration(&topic);
+		}
+	}
+
+	/// Start looking for nodes advertising a topic. The search advances with discovery rounds
+	/// and restarts on every refresh.
+	pub fn search_topic(&mut self, topic: Topic) {
+		self.searches.entry(topic).or_insert_with(TopicSearch::default);
+	}
+
+	/// Records of nodes found advertising a topic.
+	pub fn topic_nodes(&self, topic: &Topic) -> Vec<NodeRecord> {
+		self.searches.get(topic).map_or_else(Vec::new, |s| s.results.values().cloned().collect())
+	}
+
 	/// Add a new node to discovery table. Pings the node.
 	pub fn add_node(&mut self, e: NodeEntry) {
 		if self.is_allowed(&e) {
@@ -230,6 +302,43 @@ impl Discovery {
 		self.discovery_round += 1;
 	}
 
+	fn send_registration(&mut self, topic: &Topic) {
+		let nearest = Discovery::nearest_node_entries_to_hash(topic, &self.node_buckets);
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append(topic);
+		rlp.append(&self.record);
+		let payload = rlp.out();
+		for n in nearest.into_iter().take(ALPHA) {
+			trace!(target: "discovery", "Sent TopicRegister {:?} to {:?}", topic, &n.endpoint);
+			self.send_packet(PACKET_TOPIC_REGISTER, &n.endpoint.udp_address(), &payload);
+		}
+	}
+
+	fn search_topics(&mut self) {
+		let mut queries = Vec::new();
+		for (topic, search) in &mut self.searches {
+			if search.round == DISCOVERY_MAX_STEPS {
+				continue;
+			}
+			let nearest = Discovery::nearest_node_entries_to_hash(topic, &self.node_buckets).into_iter();
+			let nearest = nearest.filter(|x| !search.queried.contains(&x.id)).take(ALPHA).collect::<Vec<_>>();
+			if nearest.is_empty() {
+				search.round = DISCOVERY_MAX_STEPS;
+				continue;
+			}
+			for n in nearest {
+				search.queried.insert(n.id.clone());
+				queries.push((topic.clone(), n.endpoint));
+			}
+			search.round += 1;
+		}
+		for (topic, endpoint) in queries {
+			trace!(target: "discovery", "Sent TopicQuery {:?} to {:?}", topic, &endpoint);
+			let rlp = encode_list(&(&[topic][..]));
+			self.send_packet(PACKET_TOPIC_QUERY, &endpoint.udp_address(), &rlp);
+		}
+	}
+
 	fn distance(a: &H256, b: &H256) -> u32 {
 		let d = *a ^ *b;
 		let mut ret:u32 = 0;
@@ -281,16 +390,19 @@ impl Discovery {
 		self.send_to(packet, address.clone());
 	}
 
-	#[cfg_attr(feature="dev", allow(map_clone))]
 	fn nearest_node_entries(target: &NodeId, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
+		Discovery::nearest_node_entries_to_hash(&target.sha3(), buckets)
+	}
+
+	#[cfg_attr(feature="dev", allow(map_clone))]
+	fn nearest_node_entries_to_hash(target_hash: &H256, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
 		let mut found: BTreeMap<u32, Vec<&NodeEntry>> = BTreeMap::new();
 		let mut count = 0;
-		let target_hash = target.sha3();
 
 		// Sort nodes by distance to target
 		for bucket in buckets {
 			for node in &bucket.nodes {
-				let distance = Discovery::distance(&target_hash, &node.id_hash);
+				let distance = Discovery::distance(target_hash, &node.id_hash);
 				found.entry(distance).or_insert_with(Vec::new).push(&node.address);
 				if count == BUCKET_SIZE {
 					// delete the most distant element
@@ -384,6 +496,11 @@ impl Discovery {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_RECORD_REQUEST => self.on_record_request(&rlp, &node_id, &from),
+			PACKET_RECORD => self.on_record(&rlp, &node_id, &from),
+			PACKET_TOPIC_REGISTER => self.on_topic_register(&rlp, &node_id, &from),
+			PACKET_TOPIC_QUERY => self.on_topic_query(&rlp, &node_id, &from),
+			PACKET_TOPIC_NODES => self.on_topic_nodes(&rlp, &node_id, &from),
 			_ => {
 				debug!("Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -504,6 +621,136 @@ impl Discovery {
 		Ok(Some(TableUpdates { added: added, removed: HashSet::new() }))
 	}
 
+	fn on_record_request(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got RecordRequest from {:?}", &from);
+		let timestamp: u64 = rlp.val_at(0)?;
+		self.check_timestamp(timestamp)?;
+		let mut response = RlpStream::new_list(1);
+		response.append(&self.record);
+		self.send_packet(PACKET_RECORD, from, &response.drain());
+		Ok(None)
+	}
+
+	fn on_record(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got Record from {:?}", &from);
+		let record: NodeRecord = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		if record.id() != node {
+			debug!(target: "discovery", "Record of {:?} sent by {:?}", record.id(), node);
+			return Err(NetworkError::BadProtocol);
+		}
+		self.update_record(record);
+		Ok(None)
+	}
+
+	fn on_topic_register(&mut self, rlp: &UntrustedRlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got TopicRegister from {:?}", &from);
+		let topic: Topic = rlp.val_at(0)?;
+		let record: NodeRecord = rlp.val_at(1)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		if record.id() != node {
+			debug!(target: "discovery", "Registration of {:?} sent by {:?}", record.id(), node);
+			return Err(NetworkError::BadProtocol);
+		}
+		if !record.endpoint().map_or(false, |e| e.is_valid()) {
+			debug!(target: "discovery", "Registration without a valid endpoint from {:?}", node);
+			return Ok(None);
+		}
+		// Only advertise endpoints on the address the registration came from.
+		if !record.endpoint().map_or(false, |e| e.address.ip() == from.ip()) {
+			debug!(target: "discovery", "Registration of {:?} for another address sent from {:?}", node, from);
+			return Ok(None);
+		}
+		if !self.topic_table.register(topic, record.clone(), time::get_time().sec as u64) {
+			debug!(target: "discovery", "Topic table is full, dropped registration for {:?}", topic);
+		}
+		self.update_record(record);
+		Ok(None)
+	}
+
+	fn on_topic_query(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		trace!(target: "discovery", "Got TopicQuery from {:?}", &from);
+		let topic: Topic = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(1)?;
+		self.check_timestamp(timestamp)?;
+		let mut nodes = Vec::new();
+		if self.advertised.contains(&topic) {
+			nodes.push(self.record.clone());
+		}
+		// The sender is not verified, so the reply fits in a single packet to avoid amplification.
+		// Most recent registrations come first.
+		nodes.extend(self.topic_table.nodes(&topic, time::get_time().sec as u64).into_iter().rev());
+		nodes.truncate(TOPIC_NODES_PER_PACKET);
+		if nodes.is_empty() {
+			return Ok(None);
+		}
+		let mut packets = Discovery::prepare_topic_nodes_packets(&topic, &nodes);
+		for p in packets.drain(..) {
+			self.send_packet(PACKET_TOPIC_NODES, from, &p);
+		}
+		trace!(target: "discovery", "Sent {} TopicNodes to {:?}", nodes.len(), &from);
+		Ok(None)
+	}
+
+	fn prepare_topic_nodes_packets(topic: &Topic, nodes: &[NodeRecord]) -> Vec<Bytes> {
+		nodes.chunks(TOPIC_NODES_PER_PACKET).map(|c| {
+			let mut rlp = RlpStream::new_list(2);
+			rlp.append(topic);
+			rlp.begin_list(c.len());
+			for record in c {
+				rlp.append(record);
+			}
+			rlp.out()
+		}).collect()
+	}
+
+	fn on_topic_nodes(&mut self, rlp: &UntrustedRlp, _node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, NetworkError> {
+		let topic: Topic = rlp.val_at(0)?;
+		let timestamp: u64 = rlp.val_at(2)?;
+		self.check_timestamp(timestamp)?;
+		if !self.searches.contains_key(&topic) {
+			debug!(target: "discovery", "Unexpected TopicNodes {:?} from {:?}", topic, &from);
+			return Ok(None);
+		}
+		trace!(target: "discovery", "Got {} TopicNodes from {:?}", rlp.at(1)?.item_count()?, &from);
+		let mut added = HashMap::new();
+		for r in rlp.at(1)?.iter() {
+			let record: NodeRecord = r.as_val()?;
+			if *record.id() == self.id {
+				continue;
+			}
+			let endpoint = match record.endpoint() {
+				Some(ref e) if e.is_valid() => e.clone(),
+				_ => {
+					debug!(target: "discovery", "Bad record endpoint: {:?}", record);
+					continue;
+				}
+			};
+			let entry = NodeEntry { id: record.id().clone(), endpoint: endpoint };
+			if !self.is_allowed(&entry) {
+				debug!(target: "discovery", "Address not allowed: {:?}", entry);
+				continue;
+			}
+			if let Some(search) = self.searches.get_mut(&topic) {
+				search.results.insert(entry.id.clone(), record.clone());
+			}
+			self.update_record(record);
+			added.insert(entry.id.clone(), entry.clone());
+			self.ping(&entry.endpoint);
+			self.update_node(entry);
+		}
+		Ok(Some(TableUpdates { added: added, removed: HashSet::new() }))
+	}
+
+	fn update_record(&mut self, record: NodeRecord) {
+		let newer = self.records.get(record.id()).map_or(true, |r| r.seq() < record.seq());
+		if newer {
+			self.records.insert(record.id().clone(), record);
+		}
+	}
+
 	fn check_expired(&mut self, force: bool) -> HashSet<NodeId> {
 		let now = time::precise_time_ns();
 		let mut removed: HashSet<NodeId> = HashSet::new();
@@ -521,12 +768,16 @@ impl Discovery {
 				} else { true }
 			});
 		}
+		for id in &removed {
+			self.records.remove(id);
+		}
 		removed
 	}
 
 	pub fn round(&mut self) -> Option<TableUpdates> {
 		let removed = self.check_expired(false);
 		self.discover();
+		self.search_topics();
 		if !removed.is_empty() {
 			Some(TableUpdates { added: HashMap::new(), removed: removed })
 		} else { None }
@@ -534,6 +785,15 @@ impl Discovery {
 
 	pub fn refresh(&mut self) {
 		self.start();
+		self.topic_table.expire(time::get_time().sec as u64);
+		for search in self.searches.values_mut() {
+			search.round = 0;
+			search.queried.clear();
+		}
+		let advertised: Vec<_> = self.advertised.iter().cloned().collect();
+		for topic in advertised {
+			self.send_registration(&topic);
+		}
 	}
 
 	pub fn register_socket<Host:Handler>(&self, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
@@ -615,6 +875,81 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	fn deliver(nodes: &mut [Discovery]) {
+		let mut sent = true;
+		while sent {
+			sent = false;
+			for i in 0..nodes.len() {
+				while let Some(datagramm) = nodes[i].send_queue.pop_front() {
+					sent = true;
+					let from = nodes[i].public_endpoint.udp_address();
+					if let Some(target) = nodes.iter_mut().find(|n| n.public_endpoint.udp_address() == datagramm.address) {
+						target.on_packet(&datagramm.payload, from).ok();
+					}
+				}
+			}
+		}
+	}
+
+	fn loopback_nodes(first_port: u16, count: u16) -> Vec<Discovery> {
+		(first_port..first_port + count).map(|port| {
+			let ep = NodeEndpoint { address: SocketAddr::from_str(&format!("127.0.0.1:{}", port)).unwrap(), udp_port: port };
+			Discovery::new(&Random.generate().unwrap(), ep.address.clone(), ep, 0, AllowIP::All)
+		}).collect()
+	}
+
+	#[test]
+	fn record_request() {
+		let mut nodes = loopback_nodes(40460, 2);
+		nodes[1].set_record_entry(::node_record::KEY_LES, ::rlp::encode(&100u64).to_vec());
+		let entry = NodeEntry { id: nodes[1].id.clone(), endpoint: nodes[1].public_endpoint.clone() };
+		nodes[0].request_record(&entry);
+		deliver(&mut nodes);
+
+		let record = nodes[0].node_record(&entry.id).expect("Record should have been received");
+		assert_eq!(record, nodes[1].record());
+		assert_eq!(record.get::<u64>(::node_record::KEY_LES), Some(100));
+		assert_eq!(record.endpoint().unwrap().udp_address(), entry.endpoint.udp_address());
+	}
+
+	#[test]
+	fn topic_discovery() {
+		let topic = ::topic_table::topic("pip@test");
+		let mut nodes = loopback_nodes(40470, 12);
+		let boot = NodeEntry { id: nodes[0].id.clone(), endpoint: nodes[0].public_endpoint.clone() };
+		for n in nodes.iter_mut().skip(1) {
+			n.add_node(boot.clone());
+		}
+		for i in 0..nodes.len() {
+			nodes[i].refresh();
+		}
+		for _ in 0..10 {
+			deliver(&mut nodes);
+			for n in nodes.iter_mut() {
+				n.round();
+			}
+		}
+
+		for i in 1..3 {
+			nodes[i].set_record_entry(::node_record::KEY_LES, ::rlp::encode(&100u64).to_vec());
+			nodes[i].register_topic(topic);
+		}
+		nodes[11].search_topic(topic);
+		deliver(&mut nodes);
+		for _ in 0..10 {
+			nodes[11].round();
+			deliver(&mut nodes);
+		}
+
+		let found = nodes[11].topic_nodes(&topic);
+		assert_eq!(found.len(), 2);
+		for i in 1..3 {
+			let record = found.iter().find(|r| r.id() == &nodes[i].id).expect("Advertising node should be found");
+			assert_eq!(record.get::<u64>(::node_record::KEY_LES), Some(100));
+		}
+		assert!(nodes[0].topic_nodes(&topic).is_empty());
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
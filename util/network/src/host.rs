@@ -31,6 +31,7 @@ use mio::tcp::*;
 use util::hash::*;
 use util::Hashable;
 use util::version;
+use util::bytes::Bytes;
 use rlp::*;
 use session::{Session, SessionInfo, SessionData};
 use error::*;
@@ -38,7 +39,11 @@ use io::*;
 use {NetworkProtocolHandler, NonReservedPeerMode, AllowIP, PROTOCOL_VERSION};
 use node_table::*;
 use stats::NetworkStats;
+use bandwidth::BandwidthConfig;
+use connection_filter::{ConnectionFilter, ConnectionDirection};
 use discovery::{Discovery, TableUpdates, NodeEntry};
+use node_record::NodeRecord;
+use topic_table::topic;
 use ip_utils::{map_external_address, select_public_address};
 use path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
@@ -57,6 +62,7 @@ const DISCOVERY: usize = SYS_TIMER + 3;
 const DISCOVERY_REFRESH: usize = SYS_TIMER + 4;
 const DISCOVERY_ROUND: usize = SYS_TIMER + 5;
 const NODE_TABLE: usize = SYS_TIMER + 6;
+const BANDWIDTH: usize = SYS_TIMER + 7;
 const FIRST_SESSION: usize = 0;
 const LAST_SESSION: usize = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: usize = LAST_SESSION + 256;
@@ -67,6 +73,7 @@ const MAINTENANCE_TIMEOUT: u64 = 1000;
 const DISCOVERY_REFRESH_TIMEOUT: u64 = 60_000;
 const DISCOVERY_ROUND_TIMEOUT: u64 = 300;
 const NODE_TABLE_TIMEOUT: u64 = 300_000;
+const BANDWIDTH_TIMEOUT: u64 = 100;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Network service configuration
@@ -103,6 +110,14 @@ pub struct NetworkConfiguration {
 	pub non_reserved_mode: NonReservedPeerMode,
 	/// IP filter
 	pub allow_ips: AllowIP,
+	/// Topics to advertise through discovery.
+	pub discovery_topics: Vec<String>,
+	/// Topics to look for through discovery.
+	pub discovery_searches: Vec<String>,
+	/// Additional entries of the local node record. Values are RLP-encoded.
+	pub node_record: Vec<(String, Bytes)>,
+	/// Upload and download limits.
+	pub bandwidth: BandwidthConfig,
 }
 
 impl Default for NetworkConfiguration {
@@ -131,6 +146,10 @@ impl NetworkConfiguration {
 			allow_ips: AllowIP::All,
 			reserved_nodes: Vec::new(),
 			non_reserved_mode: NonReservedPeerMode::Accept,
+			discovery_topics: Vec::new(),
+			discovery_searches: Vec::new(),
+			node_record: Vec::new(),
+			bandwidth: BandwidthConfig::default(),
 		}
 	}
 
@@ -184,6 +203,8 @@ pub enum NetworkIoMessage {
 	Disconnect(PeerId),
 	/// Disconnect and temporary disable peer.
 	DisablePeer(PeerId),
+	/// Adjust peer reputation.
+	Reputation(PeerId, ReputationChange),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
 }
@@ -281,6 +302,12 @@ impl<'s> NetworkContext<'s> {
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
+	/// Adjust peer reputation. Peers whose reputation drops too low are disconnected and banned.
+	pub fn note_reputation(&self, peer: PeerId, change: ReputationChange) {
+		self.io.message(NetworkIoMessage::Reputation(peer, change))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
 	/// Check if the session is still active.
 	pub fn is_expired(&self) -> bool {
 		self.session.as_ref().map_or(false, |s| s.lock().expired())
@@ -376,11 +403,12 @@ pub struct Host {
 	reserved_nodes: RwLock<HashSet<NodeId>>,
 	num_sessions: AtomicUsize,
 	stopping: AtomicBool,
+	filter: Option<Arc<ConnectionFilter>>,
 }
 
 impl Host {
 	/// Create a new instance
-	pub fn new(mut config: NetworkConfiguration, stats: Arc<NetworkStats>) -> Result<Host, NetworkError> {
+	pub fn new(mut config: NetworkConfiguration, stats: Arc<NetworkStats>, filter: Option<Arc<ConnectionFilter>>) -> Result<Host, NetworkError> {
 		let mut listen_address = match config.listen_address {
 			None => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), DEFAULT_PORT)),
 			Some(addr) => addr,
@@ -433,6 +461,7 @@ impl Host {
 			reserved_nodes: RwLock::new(HashSet::new()),
 			num_sessions: AtomicUsize::new(0),
 			stopping: AtomicBool::new(false),
+			filter: filter,
 		};
 
 		for n in boot_nodes {
@@ -505,6 +534,42 @@ impl Host {
 		}
 	}
 
+	/// Disconnect all peers which are no longer allowed by the connection filter.
+	pub fn disconnect_disallowed_peers(&self, io: &IoContext<NetworkIoMessage>) {
+		let filter = match self.filter {
+			Some(ref filter) => filter,
+			None => return,
+		};
+		let self_id = self.info.read().id().clone();
+		// The filter may be slow, so it is not called with the sessions locked.
+		let peers: Vec<(StreamToken, NodeId, ConnectionDirection)> = self.sessions.read().iter()
+			.filter_map(|e| {
+				let s = e.lock();
+				let direction = if s.info.originated { ConnectionDirection::Outbound } else { ConnectionDirection::Inbound };
+				s.id().map(|id| (s.token(), id.clone(), direction))
+			})
+			.collect();
+		let mut to_kill = Vec::new();
+		for (token, id, direction) in peers {
+			if filter.connection_allowed(&self_id, &id, direction) {
+				continue;
+			}
+			let session = { self.sessions.read().get(token).cloned() };
+			if let Some(session) = session {
+				let mut s = session.lock();
+				// The slot may have been reused by another peer meanwhile.
+				if s.id() == Some(&id) {
+					s.disconnect(io, DisconnectReason::UselessPeer);
+					to_kill.push(token);
+				}
+			}
+		}
+		for p in to_kill {
+			trace!(target: "network", "Disconnecting peer no longer allowed by the connection filter: {}", p);
+			self.kill_connection(p, io, false);
+		}
+	}
+
 	pub fn remove_reserved_node(&self, id: &str) -> Result<(), NetworkError> {
 		let n = Node::from_str(id)?;
 		self.reserved_nodes.write().remove(&n.id);
@@ -521,6 +586,11 @@ impl Host {
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(info.id().clone(), e.clone())))
 	}
 
+	/// Records of nodes found advertising a topic. Empty if discovery is disabled.
+	pub fn topic_nodes(&self, name: &str) -> Vec<NodeRecord> {
+		self.discovery.lock().as_ref().map_or_else(Vec::new, |d| d.topic_nodes(&topic(name)))
+	}
+
 	pub fn local_url(&self) -> String {
 		let info = self.info.read();
 		format!("{}", Node::new(info.id().clone(), info.local_endpoint.clone()))
@@ -594,7 +664,17 @@ impl Host {
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
 				let mut udp_addr = local_endpoint.address.clone();
 				udp_addr.set_port(local_endpoint.udp_port);
-				Some(Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips))
+				let mut discovery = Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips);
+				for &(ref key, ref value) in &info.config.node_record {
+					discovery.set_record_entry(key, value.clone());
+				}
+				for name in &info.config.discovery_topics {
+					discovery.register_topic(topic(name));
+				}
+				for name in &info.config.discovery_searches {
+					discovery.search_topic(topic(name));
+				}
+				Some(discovery)
 			} else { None }
 		};
 
@@ -607,6 +687,9 @@ impl Host {
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
+		if self.stats.bandwidth().config().is_limited() {
+			io.register_timer(BANDWIDTH, BANDWIDTH_TIMEOUT)?;
+		}
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
@@ -616,6 +699,26 @@ impl Host {
 		self.connect_peers(io);
 	}
 
+	/// Resume sessions paused by bandwidth limits.
+	fn resume_throttled(&self, io: &IoContext<NetworkIoMessage>) {
+		let throttled: Vec<_> = self.sessions.read().iter()
+			.map(|e| {
+				let s = e.lock();
+				(s.token(), s.is_read_throttled(), s.is_write_throttled())
+			})
+			.filter(|&(_, read, write)| read || write)
+			.collect();
+
+		for (token, read, write) in throttled {
+			if write {
+				self.session_writable(token, io);
+			}
+			if read {
+				self.session_readable(token, io);
+			}
+		}
+	}
+
 	fn have_session(&self, id: &NodeId) -> bool {
 		self.sessions.read().iter().any(|e| e.lock().info.id == Some(id.clone()))
 	}
@@ -687,7 +790,12 @@ impl Host {
 
 		let max_handshakes_per_round = max_handshakes / 2;
 		let mut started: usize = 0;
-		for id in nodes.filter(|id| !self.have_session(id) && !self.connecting_to(id) && *id != self_id)
+		for id in nodes.filter(|id|
+			!self.have_session(id) &&
+			!self.connecting_to(id) &&
+			*id != self_id &&
+			self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, id, ConnectionDirection::Outbound))
+		)
 			.take(min(max_handshakes_per_round, max_handshakes - handshake_count)) {
 			self.connect_peer(&id, io);
 			started += 1;
@@ -837,6 +945,26 @@ impl Host {
 
 							let id = s.id().expect("Ready session always has id").clone();
 
+							if let Some(ref filter) = self.filter {
+								let self_id = self.info.read().id().clone();
+								let direction = if s.info.originated { ConnectionDirection::Outbound } else { ConnectionDirection::Inbound };
+								if !filter.connection_allowed(&self_id, &id, direction) {
+									trace!(target: "network", "Connection filter rejected peer {:?}", id);
+									s.disconnect(io, DisconnectReason::UselessPeer);
+									kill = true;
+									break;
+								}
+							}
+
+							let reputation = self.nodes.read().reputation(&id);
+							if reputation <= BAN_REPUTATION && !self.reserved_nodes.read().contains(&id) {
+								trace!(target: "network", "Rejected banned peer {:?} with reputation {}", id, reputation);
+								s.disconnect(io, DisconnectReason::UselessPeer);
+								kill = true;
+								break;
+							}
+							s.info.reputation = reputation;
+
 							// Check for the session limit. session_counts accounts for the new session.
 							if reserved_only ||
 								(s.info.originated && session_count > min_peers) ||
@@ -1067,8 +1195,10 @@ impl IoHandler<NetworkIoMessage> for Host {
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
 				self.nodes.write().clear_useless();
+				self.nodes.write().decay_reputation();
 				self.nodes.write().save();
 			},
+			BANDWIDTH => self.resume_throttled(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1136,6 +1266,21 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::Reputation(ref peer, ref change) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				if let Some(session) = session {
+					let id = session.lock().id().cloned();
+					let score = id.as_ref().and_then(|id| self.nodes.write().note_reputation(id, *change));
+					if let (Some(id), Some(score)) = (id, score) {
+						session.lock().info.reputation = score;
+						if score <= BAN_REPUTATION && !self.reserved_nodes.read().contains(&id) {
+							debug!(target: "network", "Banning peer {} with reputation {}", peer, score);
+							session.lock().disconnect(io, DisconnectReason::UselessPeer);
+							self.kill_connection(*peer, io, false);
+						}
+					}
+				}
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
 			_ => {}	// ignore others.
@@ -1254,7 +1399,7 @@ fn host_client_url() {
 	let mut config = NetworkConfiguration::new_local();
 	let key = "6f7b0d801bc7b5ce7bbd930b84fd0369b3eb25d09be58d64ba811091046f3aa2".parse().unwrap();
 	config.use_secret = Some(key);
-	let host: Host = Host::new(config, Arc::new(NetworkStats::new())).unwrap();
+	let host: Host = Host::new(config, Arc::new(NetworkStats::new()), None).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
 
@@ -44,7 +44,7 @@
 //! }
 //!
 //! fn main () {
-//! 	let mut service = NetworkService::new(NetworkConfiguration::new_local()).expect("Error creating network service");
+//! 	let mut service = NetworkService::new(NetworkConfiguration::new_local(), None).expect("Error creating network service");
 //! 	service.start().expect("Error starting service");
 //! 	service.register_protocol(Arc::new(MyHandler), *b"myp", 1, &[1u8]);
 //!
@@ -88,8 +88,12 @@ mod discovery;
 mod service;
 mod error;
 mod node_table;
+mod node_record;
+mod topic_table;
 mod stats;
+mod bandwidth;
 mod ip_utils;
+mod connection_filter;
 
 #[cfg(test)]
 mod tests;
@@ -98,10 +102,13 @@ pub use host::{PeerId, PacketId, ProtocolId, NetworkContext, NetworkIoMessage, N
 pub use service::NetworkService;
 pub use error::NetworkError;
 pub use stats::NetworkStats;
+pub use bandwidth::{BandwidthConfig, BandwidthLimit, BandwidthRates};
 pub use session::SessionInfo;
+pub use connection_filter::{ConnectionFilter, ConnectionDirection};
 
 use io::TimerToken;
-pub use node_table::{is_valid_node_url, NodeId};
+pub use node_table::{is_valid_node_url, NodeId, ReputationChange, BAN_REPUTATION};
+pub use node_record::{NodeRecord, KEY_CHAIN, KEY_LES};
 
 const PROTOCOL_VERSION: u32 = 4;
 
@@ -0,0 +1,236 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signed node records.
+//!
+//! A record is a sequence-numbered set of key/value pairs describing a node, signed by the
+//! node's key. Values are stored RLP-encoded so that arbitrary metadata (chain, light
+//! protocol serving capacity, ...) can be carried without this module knowing its type.
+//! The node id is not stored in the record but recovered from the signature.
+
+use std::collections::BTreeMap;
+use util::bytes::Bytes;
+use util::hash::*;
+use util::sha3::Hashable;
+use rlp::*;
+use ethkey::{Secret, sign, recover};
+use node_table::{NodeId, NodeEndpoint};
+use error::NetworkError;
+
+/// Maximum size of an encoded record in bytes.
+pub const MAX_RECORD_SIZE: usize = 300;
+
+/// Key of the endpoint entry.
+pub const KEY_ENDPOINT: &'static str = "endpoint";
+/// Key of the chain entry. The value is the genesis hash of the chain the node follows.
+pub const KEY_CHAIN: &'static str = "chain";
+/// Key of the light protocol entry. The value is the node's serving capacity.
+pub const KEY_LES: &'static str = "les";
+
+/// Signed node record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRecord {
+	seq: u64,
+	pairs: BTreeMap<String, Bytes>,
+	signature: H520,
+	id: NodeId,
+}
+
+impl Default for NodeRecord {
+	fn default() -> Self {
+		NodeRecord {
+			seq: 0,
+			pairs: BTreeMap::new(),
+			signature: H520::default(),
+			id: NodeId::default(),
+		}
+	}
+}
+
+impl NodeRecord {
+	/// Sequence number of the record. Incremented every time the record is changed.
+	pub fn seq(&self) -> u64 {
+		self.seq
+	}
+
+	/// Id of the node that signed this record.
+	pub fn id(&self) -> &NodeId {
+		&self.id
+	}
+
+	/// Returns true if the record carries a signature.
+	pub fn is_signed(&self) -> bool {
+		!self.signature.is_zero()
+	}
+
+	/// Get a decoded value.
+	pub fn get<T>(&self, key: &str) -> Option<T> where T: Decodable {
+		self.pairs.get(key).and_then(|v| UntrustedRlp::new(v).as_val().ok())
+	}
+
+	/// Get the raw RLP value.
+	pub fn get_raw(&self, key: &str) -> Option<&[u8]> {
+		self.pairs.get(key).map(|v| &v[..])
+	}
+
+	/// Set a value.
+	pub fn set<T>(&mut self, key: &str, value: &T) where T: Encodable {
+		self.set_raw(key, encode(value).to_vec());
+	}
+
+	/// Set a raw RLP value. Invalidates the signature and bumps the sequence number
+	/// if the value has changed.
+	pub fn set_raw(&mut self, key: &str, value: Bytes) {
+		if self.pairs.get(key) == Some(&value) {
+			return;
+		}
+		self.pairs.insert(key.to_owned(), value);
+		self.seq += 1;
+		self.signature = H520::default();
+	}
+
+	/// Node endpoint stored in the record.
+	pub fn endpoint(&self) -> Option<NodeEndpoint> {
+		self.pairs.get(KEY_ENDPOINT).and_then(|v| NodeEndpoint::from_rlp(&UntrustedRlp::new(v)).ok())
+	}
+
+	/// Store node endpoint in the record.
+	pub fn set_endpoint(&mut self, endpoint: &NodeEndpoint) {
+		let mut rlp = RlpStream::new();
+		endpoint.to_rlp_list(&mut rlp);
+		self.set_raw(KEY_ENDPOINT, rlp.out());
+	}
+
+	/// Sign the record with the node's secret.
+	pub fn sign(&mut self, secret: &Secret) -> Result<(), NetworkError> {
+		let hash = self.content().sha3();
+		let signature = sign(secret, &hash)?;
+		self.id = recover(&signature, &hash)?;
+		self.signature = signature.into();
+		Ok(())
+	}
+
+	fn content(&self) -> Bytes {
+		let mut rlp = RlpStream::new_list(1 + self.pairs.len() * 2);
+		rlp.append(&self.seq);
+		self.append_pairs(&mut rlp);
+		rlp.out()
+	}
+
+	fn append_pairs(&self, rlp: &mut RlpStream) {
+		for (k, v) in &self.pairs {
+			rlp.append(k);
+			rlp.append_raw(v, 1);
+		}
+	}
+}
+
+impl Encodable for NodeRecord {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2 + self.pairs.len() * 2);
+		s.append(&self.signature);
+		s.append(&self.seq);
+		self.append_pairs(s);
+	}
+}
+
+impl Decodable for NodeRecord {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		if rlp.as_raw().len() > MAX_RECORD_SIZE {
+			return Err(DecoderError::Custom("Node record is too big"));
+		}
+		let count = rlp.item_count()?;
+		if count < 2 || count % 2 != 0 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+		let signature: H520 = rlp.val_at(0)?;
+		let mut record = NodeRecord {
+			seq: rlp.val_at(1)?,
+			pairs: BTreeMap::new(),
+			signature: signature,
+			id: NodeId::default(),
+		};
+		let mut last: Option<String> = None;
+		for i in 1 .. count / 2 {
+			let key: String = rlp.val_at(i * 2)?;
+			if last.as_ref().map_or(false, |l| l >= &key) {
+				return Err(DecoderError::Custom("Node record keys are not sorted"));
+			}
+			record.pairs.insert(key.clone(), rlp.at(i * 2 + 1)?.as_raw().to_vec());
+			last = Some(key);
+		}
+		record.id = recover(&record.signature.clone().into(), &record.content().sha3())
+			.map_err(|_| DecoderError::Custom("Invalid node record signature"))?;
+		Ok(record)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+	use ethkey::{Random, Generator};
+
+	#[test]
+	fn record_roundtrip() {
+		let key = Random.generate().unwrap();
+		let endpoint = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:30303").unwrap(), udp_port: 30301 };
+		let mut record = NodeRecord::default();
+		record.set_endpoint(&endpoint);
+		record.set(KEY_CHAIN, &H256::from(5));
+		record.set(KEY_LES, &100u64);
+		record.sign(key.secret()).unwrap();
+		assert_eq!(record.seq(), 3);
+		assert_eq!(record.id(), key.public());
+
+		let decoded: NodeRecord = decode(&encode(&record));
+		assert_eq!(decoded, record);
+		assert_eq!(decoded.get::<H256>(KEY_CHAIN), Some(H256::from(5)));
+		assert_eq!(decoded.get::<u64>(KEY_LES), Some(100));
+		assert_eq!(decoded.endpoint().unwrap().udp_address(), SocketAddr::from_str("127.0.0.1:30301").unwrap());
+	}
+
+	#[test]
+	fn rejects_tampered_record() {
+		let key = Random.generate().unwrap();
+		let mut record = NodeRecord::default();
+		record.set(KEY_LES, &100u64);
+		record.sign(key.secret()).unwrap();
+		let signature = record.signature.clone();
+		record.set(KEY_LES, &1000u64);
+		record.signature = signature;
+		record.seq -= 1;
+
+		let encoded = encode(&record);
+		let decoded = UntrustedRlp::new(&encoded).as_val::<NodeRecord>();
+		assert!(decoded.map(|r| r.id() != key.public()).unwrap_or(true));
+	}
+
+	#[test]
+	fn setting_same_value_keeps_signature() {
+		let key = Random.generate().unwrap();
+		let mut record = NodeRecord::default();
+		record.set(KEY_LES, &100u64);
+		record.sign(key.secret()).unwrap();
+		record.set(KEY_LES, &100u64);
+		assert!(record.is_signed());
+		assert_eq!(record.seq(), 1);
+		record.set(KEY_LES, &200u64);
+		assert!(!record.is_signed());
+		assert_eq!(record.seq(), 2);
+	}
+}
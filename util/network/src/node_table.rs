@@ -24,6 +24,7 @@ use std::fmt::{Display, Formatter};
 use std::path::{PathBuf};
 use std::fmt;
 use std::fs;
+use std::cmp::{min, max};
 use std::io::{Read, Write};
 use util::hash::*;
 use util::UtilError;
@@ -124,6 +125,40 @@ impl FromStr for NodeEndpoint {
 	}
 }
 
+/// Lowest possible reputation score.
+pub const MIN_REPUTATION: i32 = -1000;
+/// Highest possible reputation score.
+pub const MAX_REPUTATION: i32 = 1000;
+/// Nodes at or below this score are banned: disconnected and not selected for new connections.
+pub const BAN_REPUTATION: i32 = -500;
+
+/// Peer behaviour affecting its reputation score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationChange {
+	/// Peer sent an invalid block, header or other chain data.
+	BadBlock,
+	/// Peer did not respond to a request in time.
+	Timeout,
+	/// Peer provided useful data.
+	UsefulData,
+	/// Peer responded to a request after the given number of milliseconds.
+	Latency(u64),
+}
+
+impl ReputationChange {
+	/// Score adjustment for this change.
+	pub fn delta(&self) -> i32 {
+		match *self {
+			ReputationChange::BadBlock => -200,
+			ReputationChange::Timeout => -20,
+			ReputationChange::UsefulData => 2,
+			ReputationChange::Latency(ms) if ms < 500 => 1,
+			ReputationChange::Latency(ms) if ms < 2000 => 0,
+			ReputationChange::Latency(_) => -5,
+		}
+	}
+}
+
 #[derive(PartialEq, Eq, Copy, Clone)]
 pub enum PeerType {
 	_Required,
@@ -135,6 +170,7 @@ pub struct Node {
 	pub endpoint: NodeEndpoint,
 	pub peer_type: PeerType,
 	pub failures: u32,
+	pub score: i32,
 	pub last_attempted: Option<Tm>,
 }
 
@@ -145,9 +181,15 @@ impl Node {
 			endpoint: endpoint,
 			peer_type: PeerType::Optional,
 			failures: 0,
+			score: 0,
 			last_attempted: None,
 		}
 	}
+
+	/// Returns true if the node's reputation is too low to connect to it.
+	pub fn is_banned(&self) -> bool {
+		self.score <= BAN_REPUTATION
+	}
 }
 
 impl Display for Node {
@@ -177,6 +219,7 @@ impl FromStr for Node {
 			peer_type: PeerType::Optional,
 			last_attempted: None,
 			failures: 0,
+			score: 0,
 		})
 	}
 }
@@ -212,16 +255,20 @@ impl NodeTable {
 
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve failure counter
-		let failures = self.nodes.get(&node.id).map_or(0, |n| n.failures);
-		node.failures = failures;
+		// preserve failure counter and reputation
+		if let Some(n) = self.nodes.get(&node.id) {
+			node.failures = n.failures;
+			node.score = n.score;
+		}
 		self.nodes.insert(node.id.clone(), node);
 	}
 
-	/// Returns node ids sorted by number of failures
+	/// Returns ids of nodes that are not banned, sorted by reputation and then by number of failures
 	pub fn nodes(&self, filter: AllowIP) -> Vec<NodeId> {
-		let mut refs: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id) && n.endpoint.is_allowed(filter)).collect();
-		refs.sort_by(|a, b| a.failures.cmp(&b.failures));
+		let mut refs: Vec<&Node> = self.nodes.values()
+			.filter(|n| !self.useless_nodes.contains(&n.id) && !n.is_banned() && n.endpoint.is_allowed(filter))
+			.collect();
+		refs.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.failures.cmp(&b.failures)));
 		refs.iter().map(|n| n.id.clone()).collect()
 	}
 
@@ -256,6 +303,26 @@ impl NodeTable {
 		}
 	}
 
+	/// Adjust node reputation. Returns the new score or `None` if the node is unknown.
+	pub fn note_reputation(&mut self, id: &NodeId, change: ReputationChange) -> Option<i32> {
+		self.nodes.get_mut(id).map(|node| {
+			node.score = max(MIN_REPUTATION, min(MAX_REPUTATION, node.score + change.delta()));
+			node.score
+		})
+	}
+
+	/// Reputation score of a node. Unknown nodes have a neutral score.
+	pub fn reputation(&self, id: &NodeId) -> i32 {
+		self.nodes.get(id).map_or(0, |n| n.score)
+	}
+
+	/// Move all scores a step towards neutral, so that bans and old merits eventually expire.
+	pub fn decay_reputation(&mut self) {
+		for node in self.nodes.values_mut() {
+			node.score -= node.score / 10 + node.score.signum();
+		}
+	}
+
 	/// Mark as useless, no furter attempts to connect until next call to `clear_useless`.
 	pub fn mark_as_useless(&mut self, id: &NodeId) {
 		self.useless_nodes.insert(id.clone());
@@ -278,10 +345,11 @@ impl NodeTable {
 			let mut json = String::new();
 			json.push_str("{\n");
 			json.push_str("\"nodes\": [\n");
-			let node_ids = self.nodes(AllowIP::All);
-			for i in 0 .. node_ids.len() {
-				let node = self.nodes.get(&node_ids[i]).expect("self.nodes() only returns node IDs from self.nodes");
-				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {} }}{}\n", node, node.failures, if i == node_ids.len() - 1 {""} else {","}))
+			let mut nodes: Vec<&Node> = self.nodes.values().filter(|n| !self.useless_nodes.contains(&n.id)).collect();
+			nodes.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.failures.cmp(&b.failures)));
+			for i in 0 .. nodes.len() {
+				let node = nodes[i];
+				json.push_str(&format!("\t{{ \"url\": \"{}\", \"failures\": {}, \"score\": {} }}{}\n", node, node.failures, node.score, if i == nodes.len() - 1 {""} else {","}))
 			}
 			json.push_str("]\n");
 			json.push_str("}");
@@ -332,6 +400,9 @@ impl NodeTable {
 							if let Some(failures) = n.get("failures").and_then(|f| f.as_u64()) {
 								node.failures = failures as u32;
 							}
+							if let Some(score) = n.get("score").and_then(|f| f.as_i64()) {
+								node.score = max(MIN_REPUTATION as i64, min(MAX_REPUTATION as i64, score)) as i32;
+							}
 							nodes.insert(node.id.clone(), node);
 						}
 					}
@@ -413,6 +484,38 @@ mod tests {
 		assert_eq!(r[2][..], id1[..]);
 	}
 
+	#[test]
+	fn table_reputation_order_and_ban() {
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node3 = Node::from_str("enode://c979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let (id1, id2, id3) = (node1.id.clone(), node2.id.clone(), node3.id.clone());
+		let mut table = NodeTable::new(None);
+		table.add_node(node1);
+		table.add_node(node2);
+		table.add_node(node3);
+
+		table.note_failure(&id1);
+		table.note_reputation(&id1, ReputationChange::UsefulData);
+		table.note_reputation(&id2, ReputationChange::Timeout);
+		assert_eq!(table.nodes(AllowIP::All), vec![id1.clone(), id3.clone(), id2.clone()]);
+
+		for _ in 0..3 {
+			table.note_reputation(&id2, ReputationChange::BadBlock);
+		}
+		assert_eq!(table.reputation(&id2), -620);
+		assert_eq!(table.nodes(AllowIP::All), vec![id1.clone(), id3.clone()]);
+
+		for _ in 0..10 {
+			table.note_reputation(&id2, ReputationChange::BadBlock);
+		}
+		assert_eq!(table.reputation(&id2), MIN_REPUTATION);
+
+		table.decay_reputation();
+		assert_eq!(table.reputation(&id1), 1);
+		assert_eq!(table.reputation(&id2), -899);
+	}
+
 	#[test]
 	fn table_save_load() {
 		let temp_path = RandomTempPath::create_dir();
@@ -425,6 +528,7 @@ mod tests {
 			table.add_node(node1);
 			table.add_node(node2);
 			table.note_failure(&id2);
+			table.note_reputation(&id2, ReputationChange::BadBlock);
 		}
 
 		{
@@ -432,6 +536,7 @@ mod tests {
 			let r = table.nodes(AllowIP::All);
 			assert_eq!(r[0][..], id1[..]);
 			assert_eq!(r[1][..], id2[..]);
+			assert_eq!(table.reputation(&id2), -200);
 		}
 	}
 }
@@ -15,9 +15,11 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use {NetworkProtocolHandler, NetworkConfiguration, NonReservedPeerMode};
+use connection_filter::ConnectionFilter;
 use error::NetworkError;
 use host::{Host, NetworkContext, NetworkIoMessage, PeerId, ProtocolId};
 use stats::NetworkStats;
+use node_record::NodeRecord;
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -49,17 +51,18 @@ pub struct NetworkService {
 	panic_handler: Arc<PanicHandler>,
 	host_handler: Arc<HostHandler>,
 	config: NetworkConfiguration,
+	filter: Option<Arc<ConnectionFilter>>,
 }
 
 impl NetworkService {
 	/// Starts IO event loop
-	pub fn new(config: NetworkConfiguration) -> Result<NetworkService, NetworkError> {
+	pub fn new(config: NetworkConfiguration, filter: Option<Arc<ConnectionFilter>>) -> Result<NetworkService, NetworkError> {
 		let host_handler = Arc::new(HostHandler { public_url: RwLock::new(None) });
 		let panic_handler = PanicHandler::new_in_arc();
 		let io_service = IoService::<NetworkIoMessage>::start()?;
 		panic_handler.forward_from(&io_service);
 
-		let stats = Arc::new(NetworkStats::new());
+		let stats = Arc::new(NetworkStats::with_bandwidth(config.bandwidth.clone()));
 		let host_info = Host::client_version();
 		Ok(NetworkService {
 			io_service: io_service,
@@ -69,6 +72,7 @@ impl NetworkService {
 			host: RwLock::new(None),
 			config: config,
 			host_handler: host_handler,
+			filter: filter,
 		})
 	}
 
@@ -115,11 +119,17 @@ impl NetworkService {
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Returns records of nodes found advertising a topic.
+	pub fn topic_nodes(&self, name: &str) -> Vec<NodeRecord> {
+		let host = self.host.read();
+		host.as_ref().map_or_else(Vec::new, |h| h.topic_nodes(name))
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
 		if host.is_none() {
-			let h = Arc::new(Host::new(self.config.clone(), self.stats.clone())?);
+			let h = Arc::new(Host::new(self.config.clone(), self.stats.clone(), self.filter.clone())?);
 			self.io_service.register_handler(h.clone())?;
 			*host = Some(h);
 		}
@@ -176,6 +186,15 @@ impl NetworkService {
 		}
 	}
 
+	/// Disconnect all peers which are no longer allowed by the connection filter.
+	pub fn disconnect_disallowed_peers(&self) {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			let io_ctxt = IoContext::new(self.io_service.channel(), 0);
+			host.disconnect_disallowed_peers(&io_ctxt);
+		}
+	}
+
 	/// Executes action in the network context
 	pub fn with_context<F>(&self, protocol: ProtocolId, action: F) where F: FnOnce(&NetworkContext) {
 		let io = IoContext::new(self.io_service.channel(), 0);
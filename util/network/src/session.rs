@@ -107,6 +107,8 @@ pub struct SessionInfo {
 	pub remote_address: String,
 	/// Local endpoint address of the session
 	pub local_address: String,
+	/// Peer reputation score
+	pub reputation: i32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -193,6 +195,7 @@ impl Session {
 				originated: originated,
 				remote_address: "Handshake".to_owned(),
 				local_address: local_addr,
+				reputation: 0,
 			},
 			ping_time_ns: 0,
 			pong_time_ns: None,
@@ -245,6 +248,16 @@ impl Session {
 		}
 	}
 
+	/// Check if reading from this session was paused by bandwidth limits.
+	pub fn is_read_throttled(&self) -> bool {
+		self.connection().is_read_throttled()
+	}
+
+	/// Check if writing to this session was paused by bandwidth limits.
+	pub fn is_write_throttled(&self) -> bool {
+		self.connection().is_write_throttled()
+	}
+
 	/// Check if this session is over and there is nothing to be sent.
 	pub fn done(&self) -> bool {
 		self.expired() && !self.connection().is_sending()
@@ -347,7 +360,7 @@ impl Session {
 		let mut rlp = RlpStream::new();
 		rlp.append(&(pid as u32));
 		rlp.append_raw(data, 1);
-		self.send(io, rlp)
+		self.send_for(io, Some(protocol), rlp)
 	}
 
 	/// Keep this session alive. Returns false if ping timeout happened
@@ -435,6 +448,9 @@ impl Session {
 				// map to protocol
 				let protocol = self.info.capabilities[i].protocol;
 				let protocol_packet_id = packet_id - self.info.capabilities[i].id_offset;
+				if let State::Session(ref mut c) = self.state {
+					c.connection.received_for(protocol, packet.data.len());
+				}
 
 				match *self.protocol_states.entry(protocol).or_insert_with(|| ProtocolState::Pending(Vec::new())) {
 					ProtocolState::Connected => {
@@ -559,12 +575,16 @@ impl Session {
 	}
 
 	fn send<Message>(&mut self, io: &IoContext<Message>, rlp: RlpStream) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
+		self.send_for(io, None, rlp)
+	}
+
+	fn send_for<Message>(&mut self, io: &IoContext<Message>, protocol: Option<ProtocolId>, rlp: RlpStream) -> Result<(), NetworkError> where Message: Send + Sync + Clone {
 		match self.state {
 			State::Handshake(_) => {
 				warn!(target:"network", "Unexpected send request");
 			},
 			State::Session(ref mut s) => {
-				s.send_packet(io, &rlp.out())?
+				s.send_packet(io, protocol, &rlp.out())?
 			},
 		}
 		Ok(())
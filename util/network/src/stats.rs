@@ -16,6 +16,7 @@
 
 //! Network Statistics
 use std::sync::atomic::*;
+use bandwidth::{Bandwidth, BandwidthConfig, BandwidthRates};
 
 /// Network statistics structure
 #[derive(Default, Debug)]
@@ -26,6 +27,8 @@ pub struct NetworkStats {
 	send: AtomicUsize,
 	/// Total number of sessions created
 	sessions: AtomicUsize,
+	/// Bandwidth limits and rates
+	bandwidth: Bandwidth,
 }
 
 impl NetworkStats {
@@ -65,12 +68,29 @@ impl NetworkStats {
 		self.sessions.load(Ordering::Relaxed)
 	}
 
+	/// Get bandwidth limiter.
+	#[inline]
+	pub fn bandwidth(&self) -> &Bandwidth {
+		&self.bandwidth
+	}
+
+	/// Get current upload and download rates.
+	pub fn rates(&self) -> BandwidthRates {
+		self.bandwidth.rates()
+	}
+
 	/// Create a new empty instance.
 	pub fn new() -> NetworkStats {
+		NetworkStats::with_bandwidth(Default::default())
+	}
+
+	/// Create a new empty instance with given bandwidth limits.
+	pub fn with_bandwidth(config: BandwidthConfig) -> NetworkStats {
 		NetworkStats {
 			recv: AtomicUsize::new(0),
 			send: AtomicUsize::new(0),
 			sessions: AtomicUsize::new(0),
+			bandwidth: Bandwidth::new(config),
 		}
 	}
 }
@@ -0,0 +1,164 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Topic registrations held on behalf of other nodes.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use util::hash::H256;
+use util::sha3::Hashable;
+use node_table::NodeId;
+use node_record::NodeRecord;
+
+/// Discovery topic. Nodes advertising a topic register with the nodes closest to its hash.
+pub type Topic = H256;
+
+/// Maximum number of registrations kept per topic.
+pub const MAX_TOPIC_ENTRIES: usize = 32;
+/// Maximum number of registrations per topic from a single IP address.
+pub const MAX_TOPIC_ENTRIES_PER_IP: usize = 4;
+/// Maximum number of topics kept.
+pub const MAX_TOPICS: usize = 256;
+/// Registration lifetime in seconds.
+pub const TOPIC_EXPIRY_SEC: u64 = 300;
+
+/// Get the topic for a given name.
+pub fn topic(name: &str) -> Topic {
+	name.sha3()
+}
+
+struct TopicEntry {
+	record: NodeRecord,
+	ip: Option<IpAddr>,
+	expires: u64,
+}
+
+/// Table of topic registrations, oldest first.
+#[derive(Default)]
+pub struct TopicTable {
+	topics: HashMap<Topic, VecDeque<TopicEntry>>,
+}
+
+impl TopicTable {
+	/// Register a node for the topic. Replaces an earlier registration of the same node and
+	/// evicts the oldest one from the same IP address once it has `MAX_TOPIC_ENTRIES_PER_IP`.
+	/// Returns false if the table is full.
+	pub fn register(&mut self, topic: Topic, record: NodeRecord, now: u64) -> bool {
+		if !self.topics.contains_key(&topic) && self.topics.len() >= MAX_TOPICS {
+			return false;
+		}
+		let ip = record.endpoint().map(|e| e.address.ip());
+		let entries = self.topics.entry(topic).or_insert_with(VecDeque::new);
+		entries.retain(|e| e.record.id() != record.id());
+		let same_ip = match ip {
+			Some(_) => entries.iter().filter(|e| e.ip == ip).count(),
+			None => 0,
+		};
+		if same_ip >= MAX_TOPIC_ENTRIES_PER_IP {
+			let oldest = entries.iter().position(|e| e.ip == ip).expect("same_ip > 0; qed");
+			entries.remove(oldest);
+		} else if entries.len() >= MAX_TOPIC_ENTRIES {
+			entries.pop_front();
+		}
+		entries.push_back(TopicEntry { record: record, ip: ip, expires: now + TOPIC_EXPIRY_SEC });
+		true
+	}
+
+	/// Records of nodes registered for the topic.
+	pub fn nodes(&self, topic: &Topic, now: u64) -> Vec<NodeRecord> {
+		self.topics.get(topic).map_or_else(Vec::new, |entries| {
+			entries.iter().filter(|e| e.expires > now).map(|e| e.record.clone()).collect()
+		})
+	}
+
+	/// Check whether the node is registered for the topic.
+	pub fn contains(&self, topic: &Topic, id: &NodeId) -> bool {
+		self.topics.get(topic).map_or(false, |entries| entries.iter().any(|e| e.record.id() == id))
+	}
+
+	/// Remove expired registrations.
+	pub fn expire(&mut self, now: u64) {
+		for entries in self.topics.values_mut() {
+			entries.retain(|e| e.expires > now);
+		}
+		self.topics.retain(|_, entries| !entries.is_empty());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::SocketAddr;
+	use std::str::FromStr;
+	use ethkey::{Random, Generator};
+	use node_table::NodeEndpoint;
+
+	fn record() -> NodeRecord {
+		let mut record = NodeRecord::default();
+		record.sign(Random.generate().unwrap().secret()).unwrap();
+		record
+	}
+
+	#[test]
+	fn registrations_expire() {
+		let mut table = TopicTable::default();
+		let t = topic("les");
+		let r = record();
+		assert!(table.register(t, r.clone(), 100));
+		assert!(table.register(t, r.clone(), 200));
+		assert_eq!(table.nodes(&t, 200).len(), 1);
+		assert!(table.contains(&t, r.id()));
+		assert!(table.nodes(&t, 200 + TOPIC_EXPIRY_SEC).is_empty());
+		table.expire(200 + TOPIC_EXPIRY_SEC);
+		assert!(!table.contains(&t, r.id()));
+	}
+
+	#[test]
+	fn registrations_are_limited() {
+		let mut table = TopicTable::default();
+		let t = topic("les");
+		let first = record();
+		table.register(t, first.clone(), 0);
+		for _ in 0..MAX_TOPIC_ENTRIES {
+			table.register(t, record(), 0);
+		}
+		assert_eq!(table.nodes(&t, 0).len(), MAX_TOPIC_ENTRIES);
+		assert!(!table.contains(&t, first.id()));
+	}
+
+	#[test]
+	fn registrations_are_limited_per_ip() {
+		let mut table = TopicTable::default();
+		let t = topic("les");
+		let record_at = |address: &str| {
+			let key = Random.generate().unwrap();
+			let mut record = NodeRecord::default();
+			record.set_endpoint(&NodeEndpoint { address: SocketAddr::from_str(address).unwrap(), udp_port: 30303 });
+			record.sign(key.secret()).unwrap();
+			record
+		};
+		let other = record_at("10.0.0.2:30303");
+		let first = record_at("10.0.0.1:30303");
+		table.register(t, other.clone(), 0);
+		table.register(t, first.clone(), 0);
+		for _ in 0..MAX_TOPIC_ENTRIES_PER_IP {
+			table.register(t, record_at("10.0.0.1:30303"), 0);
+		}
+		assert_eq!(table.nodes(&t, 0).len(), MAX_TOPIC_ENTRIES_PER_IP + 1);
+		assert!(!table.contains(&t, first.id()));
+		assert!(table.contains(&t, other.id()));
+	}
+}